@@ -20,9 +20,16 @@ pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
-use eth_types::{self, Address, GethExecStep, GethExecTrace, Word};
+use eth_types::{
+    self, evm_types::OpcodeId, Address, ChainSpec, GethExecStep, GethExecTrace, ToBigEndian,
+    ToWord, Word, H256,
+};
 use ethers_providers::JsonRpcClient;
-pub use execution::{CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, NumberOrHash};
+pub use execution::{
+    CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
+    PrecompileEvent,
+};
+use futures::future::try_join_all;
 pub use input_state_ref::CircuitInputStateRef;
 use std::collections::HashMap;
 pub use transaction::{Transaction, TransactionContext};
@@ -106,7 +113,14 @@ impl<'a> CircuitInputBuilder {
             ),
         );
 
-        Transaction::new(call_id, &self.sdb, &mut self.code_db, eth_tx, is_success)
+        Transaction::new(
+            call_id,
+            &self.sdb,
+            &mut self.code_db,
+            eth_tx,
+            self.block.base_fee,
+            is_success,
+        )
     }
 
     /// Iterate over all generated CallContext RwCounterEndOfReversion
@@ -163,9 +177,10 @@ impl<'a> CircuitInputBuilder {
         // TODO: Move into gen_associated_steps with
         // - execution_state: BeginTx
         // - op: None
-        // Generate BeginTx step
-        let begin_tx_step = gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
-        tx.steps_mut().push(begin_tx_step);
+        // Generate BeginTx step, followed by the step of the precompiled
+        // contract in case of a transaction to it.
+        let begin_tx_steps = gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
+        tx.steps_mut().extend(begin_tx_steps);
 
         for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
@@ -194,9 +209,40 @@ impl<'a> CircuitInputBuilder {
 
 /// Retrieve the init_code from memory for {CREATE, CREATE2}
 pub fn get_create_init_code(step: &GethExecStep) -> Result<&[u8], Error> {
-    let offset = step.stack.nth_last(1)?;
-    let length = step.stack.nth_last(2)?;
-    Ok(&step.memory.0[offset.low_u64() as usize..(offset.low_u64() + length.low_u64()) as usize])
+    let (offset, length) = get_call_memory_offset_length(step, 1)?;
+    Ok(&step.memory.0[offset as usize..(offset + length) as usize])
+}
+
+/// Return the preimage of the keccak256 hash from which the address of a
+/// contract created by CREATE is taken, which is `rlp([sender, nonce])`.
+pub fn get_create_address_preimage(sender: Address, nonce: u64) -> Vec<u8> {
+    let nonce_rlp = match nonce {
+        0 => vec![0x80],
+        1..=0x7f => vec![nonce as u8],
+        _ => {
+            let bytes = nonce.to_be_bytes();
+            let bytes = &bytes[nonce.leading_zeros() as usize / 8..];
+            std::iter::once(0x80 + bytes.len() as u8)
+                .chain(bytes.iter().copied())
+                .collect()
+        }
+    };
+    std::iter::once(0xc0 + 21 + nonce_rlp.len() as u8)
+        .chain(std::iter::once(0x94))
+        .chain(sender.0)
+        .chain(nonce_rlp)
+        .collect()
+}
+
+/// Return the preimage of the keccak256 hash from which the address of a
+/// contract created by CREATE2 is taken, which is
+/// `0xff ++ sender ++ salt ++ init_code_hash`.
+pub fn get_create2_address_preimage(sender: Address, salt: Word, init_code_hash: H256) -> Vec<u8> {
+    std::iter::once(0xff)
+        .chain(sender.0)
+        .chain(salt.to_be_bytes())
+        .chain(init_code_hash.0)
+        .collect()
 }
 
 /// Retrieve the memory offset and length of call.
@@ -210,38 +256,76 @@ pub fn get_call_memory_offset_length(step: &GethExecStep, nth: usize) -> Result<
     }
 }
 
+/// Retrieve the return data offset and length of a call halting at `step`.
+/// Only a successful RETURN or a REVERT returns data, except for a successful
+/// contract creation whose returned bytes become the deployed code instead.
+pub fn get_return_data_offset_length(
+    call: &Call,
+    step: &GethExecStep,
+) -> Result<(u64, u64), Error> {
+    match step.op {
+        OpcodeId::RETURN | OpcodeId::REVERT
+            if step.error.is_none() && !(call.is_create() && call.is_success) =>
+        {
+            get_call_memory_offset_length(step, 0)
+        }
+        _ => Ok((0, 0)),
+    }
+}
+
 type EthBlock = eth_types::Block<eth_types::Transaction>;
 
+/// Number of the previous block hashes accessible by `BLOCKHASH`.
+const NUM_HISTORY_HASHES: u64 = 256;
+
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
 /// the necessary information and using the CircuitInputBuilder.
 pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
-    chain_id: Word,
-    history_hashes: Vec<Word>,
+    chain_spec: ChainSpec,
 }
 
 impl<P: JsonRpcClient> BuilderClient<P> {
-    /// Create a new BuilderClient
-    pub async fn new(client: GethClient<P>) -> Result<Self, Error> {
+    /// Create a new BuilderClient which processes blocks under the rules of
+    /// the given chain specification.
+    pub async fn new(client: GethClient<P>, chain_spec: ChainSpec) -> Result<Self, Error> {
         let chain_id = client.get_chain_id().await?;
+        if chain_id != chain_spec.chain_id {
+            return Err(Error::ChainIdMismatch(chain_spec.chain_id, chain_id));
+        }
 
         Ok(Self {
             cli: client,
-            chain_id: chain_id.into(),
-            // TODO: Get history hashes
-            history_hashes: Vec::new(),
+            chain_spec,
         })
     }
 
-    /// Step 1. Query geth for Block, Txs and TxExecTraces
+    /// Step 1. Query geth for Block, Txs, TxExecTraces and the hashes of the
+    /// previous blocks accessible by `BLOCKHASH`
     pub async fn get_block(
         &self,
         block_num: u64,
-    ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>), Error> {
+    ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>, Vec<Word>), Error> {
         let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
         let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
-        Ok((eth_block, geth_traces))
+        let history_hashes = self.get_history_hashes(block_num).await?;
+        Ok((eth_block, geth_traces, history_hashes))
+    }
+
+    /// Query geth for the hashes of the previous (at most 256) blocks, where
+    /// the lastest one is at the end. The blocks are requested concurrently.
+    pub async fn get_history_hashes(&self, block_num: u64) -> Result<Vec<Word>, Error> {
+        try_join_all(
+            (block_num.saturating_sub(NUM_HISTORY_HASHES)..block_num).map(|num| async move {
+                let block = self.cli.get_block_by_number(num.into()).await?;
+                block
+                    .hash
+                    .map(|hash| hash.to_word())
+                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))
+            }),
+        )
+        .await
     }
 
     /// Step 2. Get State Accesses from TxExecTraces
@@ -340,8 +424,9 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         code_db: CodeDB,
         eth_block: &EthBlock,
         geth_traces: &[eth_types::GethExecTrace],
+        history_hashes: Vec<Word>,
     ) -> Result<CircuitInputBuilder, Error> {
-        let block = Block::new(self.chain_id, self.history_hashes.clone(), eth_block)?;
+        let block = Block::new(&self.chain_spec, history_hashes, eth_block)?;
         let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
         builder.handle_block(eth_block, geth_traces)?;
         Ok(builder)
@@ -349,11 +434,17 @@ impl<P: JsonRpcClient> BuilderClient<P> {
 
     /// Perform all the steps to generate the circuit inputs
     pub async fn gen_inputs(&self, block_num: u64) -> Result<CircuitInputBuilder, Error> {
-        let (eth_block, geth_traces) = self.get_block(block_num).await?;
+        let (eth_block, geth_traces, history_hashes) = self.get_block(block_num).await?;
         let access_set = self.get_state_accesses(&eth_block, &geth_traces)?;
         let (proofs, codes) = self.get_state(block_num, access_set).await?;
         let (state_db, code_db) = self.build_state_code_db(proofs, codes);
-        let builder = self.gen_inputs_from_state(state_db, code_db, &eth_block, &geth_traces)?;
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            &geth_traces,
+            history_hashes,
+        )?;
         Ok(builder)
     }
 }
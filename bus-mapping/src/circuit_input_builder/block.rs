@@ -1,11 +1,11 @@
 //! Block-related utility module
 
-use super::{transaction::Transaction, CopyEvent};
+use super::{transaction::Transaction, CopyEvent, ExpEvent, PrecompileEvent};
 use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, Hash, Word};
+use eth_types::{Address, ChainSpec, Hardfork, Hash, Word};
 use std::collections::HashMap;
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -49,55 +49,85 @@ pub struct Block {
     pub history_hashes: Vec<Word>,
     /// coinbase
     pub coinbase: Address,
-    /// time
+    /// gas limit
     pub gas_limit: u64,
     /// number
     pub number: Word,
-    /// difficulty
+    /// timestamp
     pub timestamp: Word,
-    /// gas limit
+    /// difficulty, which is the randomness of the beacon chain (PREVRANDAO)
+    /// since the Merge
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// Hard fork whose rules the block is processed under
+    pub hardfork: Hardfork,
+    /// Maximum size of the code deployed by a contract creation
+    pub max_code_size: u64,
     /// Container of operations done in this block.
     pub container: OperationContainer,
     /// Transactions contained in the block
     pub txs: Vec<Transaction>,
     /// Copy events in this block.
     pub copy_events: Vec<CopyEvent>,
+    /// Inputs to the SHA3 opcode
+    pub sha3_inputs: Vec<Vec<u8>>,
+    /// Exponentiation events in the block.
+    pub exp_events: Vec<ExpEvent>,
+    /// Precompile events in the block.
+    pub precompile_events: Vec<PrecompileEvent>,
     code: HashMap<Hash, Vec<u8>>,
 }
 
 impl Block {
-    /// Create a new block.
+    /// Create a new block processed under the rules of its hard fork in the
+    /// chain specification.
     pub fn new<TX>(
-        chain_id: Word,
+        chain_spec: &ChainSpec,
         history_hashes: Vec<Word>,
         eth_block: &eth_types::Block<TX>,
     ) -> Result<Self, Error> {
-        if eth_block.base_fee_per_gas.is_none() {
-            // FIXME: resolve this once we have proper EIP-1559 support
+        let number = eth_block
+            .number
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+            .low_u64();
+        let hardfork = chain_spec.hardfork(number, eth_block.timestamp.low_u64());
+        if hardfork >= Hardfork::London && eth_block.base_fee_per_gas.is_none() {
             log::warn!(
                 "This does not look like a EIP-1559 block - base_fee_per_gas defaults to zero"
             );
         }
 
         Ok(Self {
-            chain_id,
+            chain_id: chain_spec.chain_id.into(),
             history_hashes,
             coinbase: eth_block.author,
             gas_limit: eth_block.gas_limit.low_u64(),
-            number: eth_block
-                .number
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                .low_u64()
-                .into(),
+            number: number.into(),
             timestamp: eth_block.timestamp,
-            difficulty: eth_block.difficulty,
-            base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            difficulty: if hardfork >= Hardfork::Merge {
+                let mix_hash = eth_block
+                    .mix_hash
+                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+                Word::from_big_endian(mix_hash.as_bytes())
+            } else {
+                eth_block.difficulty
+            },
+            // Blocks before London have no base fee, so the gas price of their
+            // transactions is fully paid to the coinbase.
+            base_fee: if hardfork >= Hardfork::London {
+                eth_block.base_fee_per_gas.unwrap_or_default()
+            } else {
+                Word::zero()
+            },
+            hardfork,
+            max_code_size: chain_spec.max_code_size,
             container: OperationContainer::new(),
             txs: Vec::new(),
             copy_events: Vec::new(),
+            sha3_inputs: Vec::new(),
+            exp_events: Vec::new(),
+            precompile_events: Vec::new(),
             code: HashMap::new(),
         })
     }
@@ -118,4 +148,14 @@ impl Block {
     pub fn add_copy_event(&mut self, copy: CopyEvent) {
         self.copy_events.push(copy);
     }
+
+    /// Push an exponentiation event to the block.
+    pub fn add_exp_event(&mut self, event: ExpEvent) {
+        self.exp_events.push(event);
+    }
+
+    /// Push a precompile event to the block.
+    pub fn add_precompile_event(&mut self, event: PrecompileEvent) {
+        self.precompile_events.push(event);
+    }
 }
@@ -1,5 +1,5 @@
 use super::CodeSource;
-use crate::{exec_trace::OperationRef, Error};
+use crate::{exec_trace::OperationRef, precompile::PrecompileCalls, Error};
 use eth_types::{evm_types::OpcodeId, Address, Hash, Word};
 
 /// Type of a *CALL*/CREATE* Function.
@@ -86,6 +86,12 @@ pub struct Call {
     pub return_data_offset: u64,
     /// Return data length
     pub return_data_length: u64,
+    /// Last callee's call id
+    pub last_callee_id: usize,
+    /// Last callee's return data offset
+    pub last_callee_return_data_offset: u64,
+    /// Last callee's return data length
+    pub last_callee_return_data_length: u64,
 }
 
 impl Call {
@@ -94,6 +100,14 @@ impl Call {
     pub fn is_create(&self) -> bool {
         self.kind.is_create()
     }
+
+    /// Return the precompiled contract this call executes, if any.
+    pub fn precompile(&self) -> Option<PrecompileCalls> {
+        match self.code_source {
+            CodeSource::Address(address) => PrecompileCalls::from_address(&address),
+            _ => None,
+        }
+    }
 }
 
 /// Context of a [`Call`].
@@ -108,6 +122,9 @@ pub struct CallContext {
     /// Call data (copy of tx input or caller's
     /// memory[call_data_offset..call_data_offset + call_data_length])
     pub call_data: Vec<u8>,
+    /// Return data of the last callee (copy of callee's
+    /// memory[return_data_offset..return_data_offset + return_data_length])
+    pub return_data: Vec<u8>,
 }
 
 /// A reversion group is the collection of calls and the operations which are
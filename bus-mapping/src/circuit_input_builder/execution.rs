@@ -1,9 +1,12 @@
 //! Execution step related module.
 
-use crate::{error::ExecError, exec_trace::OperationRef, operation::RWCounter, operation::RW};
+use crate::{
+    error::ExecError, exec_trace::OperationRef, operation::RWCounter, operation::RW,
+    precompile::PrecompileCalls,
+};
 use eth_types::{
     evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
-    GethExecStep, H256,
+    GethExecStep, Word, H256,
 };
 use gadgets::impl_expr;
 use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
@@ -97,8 +100,12 @@ pub enum ExecState {
     Op(OpcodeId),
     /// Virtual step Begin Tx
     BeginTx,
+    /// Virtual step warming up an entry of the access list of the tx
+    TxAccessList,
     /// Virtual step End Tx
     EndTx,
+    /// Virtual step of the execution of a precompiled contract
+    Precompile(PrecompileCalls),
 }
 
 impl ExecState {
@@ -150,6 +157,9 @@ pub enum CopyDataType {
     TxCalldata,
     /// When the destination for the copy event is tx's log.
     TxLog,
+    /// When the destination for the copy event is an accumulator of the random
+    /// linear combination of the copied bytes, e.g. the input of SHA3.
+    RlcAcc,
 }
 
 impl From<CopyDataType> for usize {
@@ -232,3 +242,56 @@ pub struct CopyEvent {
     /// Helper field for witness generation.
     pub pc: ProgramCounter,
 }
+
+/// Defines a single step of the square-and-multiply method used to compute
+/// the exponentiation in an [`ExpEvent`]. Every step verifies `a * b == d (mod
+/// 2^256)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpStep {
+    /// First multiplicand, the intermediate exponentiation from the previous
+    /// step (or the base for the first step).
+    pub a: Word,
+    /// Second multiplicand, equal to `a` for a squaring step and to the base
+    /// for a multiplication step.
+    pub b: Word,
+    /// Product of the multiplication, i.e. `a * b (mod 2^256)`.
+    pub d: Word,
+    /// Whether this step squares the intermediate exponentiation, otherwise it
+    /// multiplies it by the base.
+    pub is_square: bool,
+    /// The exponent accumulated after this step, such that `d == base ^
+    /// exponent (mod 2^256)`.
+    pub exponent: Word,
+}
+
+/// Defines an exponentiation event associated with the EXP opcode, which is
+/// verified by the exponentiation circuit step by step. Exponentiations by an
+/// exponent of 0 or 1 are trivial and don't need such an event.
+#[derive(Clone, Debug)]
+pub struct ExpEvent {
+    /// Identifier of the exponentiation, the read-write counter at the EXP
+    /// step.
+    pub identifier: usize,
+    /// Base of the exponentiation.
+    pub base: Word,
+    /// Exponent of the exponentiation.
+    pub exponent: Word,
+    /// Result of the exponentiation, i.e. `base ^ exponent (mod 2^256)`.
+    pub exponentiation: Word,
+    /// Represents the list of steps, starting from squaring the base and
+    /// ending with the step that results in the exponentiation.
+    pub steps: Vec<ExpStep>,
+}
+
+/// Defines a precompile event, a successful call to a precompiled contract,
+/// whose output is verified by a table dedicated to the precompiled contracts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileEvent {
+    /// The precompiled contract which is called.
+    pub precompile: PrecompileCalls,
+    /// Input of the call, the call data padded or truncated to the length the
+    /// precompiled contract operates on.
+    pub input: Vec<u8>,
+    /// Output of the call.
+    pub output: Vec<u8>,
+}
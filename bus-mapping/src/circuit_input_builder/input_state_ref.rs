@@ -1,25 +1,31 @@
 //! CircuitInput builder tooling module.
 
 use super::{
-    get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, Transaction, TransactionContext,
+    get_call_memory_offset_length, get_create_init_code, get_return_data_offset_length, Block,
+    BlockContext, Call, CallContext, CallKind, CodeSource, CopyEvent, ExecState, ExecStep,
+    ExpEvent, PrecompileEvent, Transaction, TransactionContext,
 };
 use crate::{
     error::{get_step_reported_error, ExecError},
     exec_trace::OperationRef,
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
-        RW,
+        StackOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogField, TxLogOp,
+        TxReceiptField, TxReceiptOp, RW,
     },
+    precompile::{self, PrecompileCalls},
     state_db::{CodeDB, StateDB},
     Error,
 };
 use eth_types::{
-    evm_types::{Gas, MemoryAddress, OpcodeId, StackAddress},
-    Address, GethExecStep, ToAddress, ToBigEndian, Word, H256,
+    evm_types::{
+        gas_utils::memory_expansion_gas_cost, Gas, GasCost, MemoryAddress, OpcodeId,
+        ProgramCounter, StackAddress,
+    },
+    Address, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_core::utils::{get_contract_address, get_create2_address};
+use keccak256::EMPTY_HASH;
 
 /// Reference to the internal state of the CircuitInputBuilder in a particular
 /// [`ExecStep`].
@@ -62,6 +68,43 @@ impl<'a> CircuitInputStateRef<'a> {
         }
     }
 
+    /// Create a new step warming up the entry at `index` of the access list of
+    /// the transaction, which is kept in the program counter.
+    pub fn new_tx_access_list_step(&self, index: usize, gas_left: u64) -> Result<ExecStep, Error> {
+        let call_ctx = self.tx_ctx.call_ctx()?;
+
+        Ok(ExecStep {
+            exec_state: ExecState::TxAccessList,
+            pc: ProgramCounter(index),
+            gas_left: Gas(gas_left),
+            call_index: call_ctx.index,
+            rwc: self.block_ctx.rwc,
+            reversible_write_counter: call_ctx.reversible_write_counter,
+            log_id: self.tx_ctx.log_id,
+            ..Default::default()
+        })
+    }
+
+    /// Create a new step of the execution of a precompiled contract, which is
+    /// given `gas_left` by its caller.
+    pub fn new_precompile_step(
+        &self,
+        precompile: PrecompileCalls,
+        gas_left: u64,
+    ) -> Result<ExecStep, Error> {
+        let call_ctx = self.tx_ctx.call_ctx()?;
+
+        Ok(ExecStep {
+            exec_state: ExecState::Precompile(precompile),
+            gas_left: Gas(gas_left),
+            call_index: call_ctx.index,
+            rwc: self.block_ctx.rwc,
+            reversible_write_counter: call_ctx.reversible_write_counter,
+            log_id: self.tx_ctx.log_id,
+            ..Default::default()
+        })
+    }
+
     /// Create a new EndTx step
     pub fn new_end_tx_step(&self) -> ExecStep {
         let prev_step = self
@@ -71,7 +114,12 @@ impl<'a> CircuitInputStateRef<'a> {
             .expect("steps should have at least one BeginTx step");
         ExecStep {
             exec_state: ExecState::EndTx,
-            gas_left: Gas(prev_step.gas_left.0 - prev_step.gas_cost.0),
+            gas_left: if prev_step.error.is_none() {
+                Gas(prev_step.gas_left.0 - prev_step.gas_cost.0)
+            } else {
+                // All the gas left is consumed when an error occurs.
+                Gas(0)
+            },
             rwc: self.block_ctx.rwc,
             // For tx without code execution
             reversible_write_counter: if let Some(call_ctx) = self.tx_ctx.calls().last() {
@@ -370,6 +418,60 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Push a read type [`TxAccessListAccountOp`] into the
+    /// [`OperationContainer`](crate::operation::OperationContainer) with the
+    /// next [`RWCounter`](crate::operation::RWCounter), and then
+    /// adds a reference to the stored operation ([`OperationRef`]) inside
+    /// the bus-mapping instance of the current [`ExecStep`].  Then increase
+    /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
+    pub fn tx_accesslist_account_read(
+        &mut self,
+        step: &mut ExecStep,
+        tx_id: usize,
+        address: Address,
+        is_warm: bool,
+    ) -> Result<(), Error> {
+        self.push_op(
+            step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+        Ok(())
+    }
+
+    /// Push a read type [`TxAccessListAccountStorageOp`] into the
+    /// [`OperationContainer`](crate::operation::OperationContainer) with the
+    /// next [`RWCounter`](crate::operation::RWCounter), and then
+    /// adds a reference to the stored operation ([`OperationRef`]) inside
+    /// the bus-mapping instance of the current [`ExecStep`].  Then increase
+    /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
+    pub fn tx_accesslist_account_storage_read(
+        &mut self,
+        step: &mut ExecStep,
+        tx_id: usize,
+        address: Address,
+        key: Word,
+        is_warm: bool,
+    ) -> Result<(), Error> {
+        self.push_op(
+            step,
+            RW::READ,
+            TxAccessListAccountStorageOp {
+                tx_id,
+                address,
+                key,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+        Ok(())
+    }
+
     /// Push a write type [`TxAccessListAccountOp`] into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter), and then
@@ -397,6 +499,35 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Push a write type [`TxAccessListAccountStorageOp`] into the
+    /// [`OperationContainer`](crate::operation::OperationContainer) with the
+    /// next [`RWCounter`](crate::operation::RWCounter), and then
+    /// adds a reference to the stored operation ([`OperationRef`]) inside
+    /// the bus-mapping instance of the current [`ExecStep`].  Then increase
+    /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
+    pub fn tx_accesslist_account_storage_write(
+        &mut self,
+        step: &mut ExecStep,
+        tx_id: usize,
+        address: Address,
+        key: Word,
+        is_warm: bool,
+        is_warm_prev: bool,
+    ) -> Result<(), Error> {
+        self.push_op(
+            step,
+            RW::WRITE,
+            TxAccessListAccountStorageOp {
+                tx_id,
+                address,
+                key,
+                is_warm,
+                is_warm_prev,
+            },
+        );
+        Ok(())
+    }
+
     /// Push 2 reversible [`AccountOp`] to update `sender` and `receiver`'s
     /// balance by `value`, with `sender` being extraly charged with `fee`.
     pub fn transfer_with_fee(
@@ -547,7 +678,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        precompile::is_precompiled(address)
     }
 
     // TODO: Remove unwrap() and add err handling.
@@ -568,7 +699,7 @@ impl<'a> CircuitInputStateRef<'a> {
                 step.stack.nth_last(2)?,
             ),
             CallKind::CallCode => (caller.address, caller.address, step.stack.nth_last(2)?),
-            CallKind::DelegateCall => (caller.caller_address, caller.address, Word::zero()),
+            CallKind::DelegateCall => (caller.caller_address, caller.address, caller.value),
             CallKind::StaticCall => (
                 caller.address,
                 step.stack.nth_last(1)?.to_address(),
@@ -638,6 +769,9 @@ impl<'a> CircuitInputStateRef<'a> {
             call_data_length,
             return_data_offset,
             return_data_length,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
         };
 
         Ok(call)
@@ -736,13 +870,22 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
-            OpEnum::AccountDestructed(_) => unimplemented!(),
+            OpEnum::AccountDestructed(op) => {
+                if !op.is_destructed_prev && op.is_destructed {
+                    self.sdb.destruct_account(op.address);
+                }
+                if op.is_destructed_prev && !op.is_destructed {
+                    self.sdb.undestruct_account(&op.address);
+                }
+            }
             _ => unreachable!(),
         };
     }
 
-    /// Handle a reversion group
-    fn handle_reversion(&mut self) {
+    /// Handle a reversion group. The steps which are being generated, and so
+    /// are not pushed into the transaction yet, are given in
+    /// `current_exec_steps`.
+    fn handle_reversion(&mut self, current_exec_steps: &mut [&mut ExecStep]) {
         let reversion_group = self
             .tx_ctx
             .reversion_groups
@@ -759,9 +902,13 @@ impl<'a> CircuitInputStateRef<'a> {
                     false,
                     op,
                 );
-                self.tx.steps_mut()[step_index]
-                    .bus_mapping_instance
-                    .push(rev_op_ref);
+                let steps_len = self.tx.steps().len();
+                let step = if step_index >= steps_len {
+                    &mut *current_exec_steps[step_index - steps_len]
+                } else {
+                    &mut self.tx.steps_mut()[step_index]
+                };
+                step.bus_mapping_instance.push(rev_op_ref);
             }
         }
 
@@ -774,32 +921,219 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Handle a return step caused by any opcode that causes a return to the
-    /// previous call context.
-    pub fn handle_return(&mut self, step: &GethExecStep) -> Result<(), Error> {
+    /// previous call context. The steps which are being generated, and so are
+    /// not pushed into the transaction yet, are given in `current_exec_steps`.
+    pub fn handle_return(
+        &mut self,
+        current_exec_steps: &mut [&mut ExecStep],
+        step: &GethExecStep,
+    ) -> Result<(), Error> {
         let call = self.call()?.clone();
 
-        // Store deployed code if it's a successful create
-        if call.is_create() && call.is_success {
-            let offset = step.stack.nth_last(0)?;
-            let length = step.stack.nth_last(1)?;
-            let code = step
-                .memory
-                .read_chunk(offset.low_u64().into(), length.low_u64().into());
-            let code_hash = self.code_db.insert(code);
-            let (found, callee_account) = self.sdb.get_account_mut(&call.address);
-            if !found {
-                return Err(Error::AccountNotFound(call.address));
-            }
-            callee_account.code_hash = code_hash;
-        }
+        // NOTE: The deployed code of a successful create is stored by the
+        // RETURN step, which pushes the write of the callee's code hash.
+
+        // A call to an account without code halts within the caller's step,
+        // so there is no callee context to read the return data from.
+        let last_callee_id = if step.depth as usize == call.depth {
+            call.call_id
+        } else {
+            0
+        };
+        let (return_data_offset, return_data_length) = if call.is_root {
+            (0, 0)
+        } else {
+            get_return_data_offset_length(&call, step)?
+        };
+        let return_data = step
+            .memory
+            .read_chunk(return_data_offset.into(), return_data_length.into());
+
+        self.end_call(
+            current_exec_steps,
+            last_callee_id,
+            return_data_offset,
+            return_data,
+        )
+    }
+
+    /// Handle the return of a call to a precompiled contract, whose `output`
+    /// is stored at the beginning of its memory.
+    pub fn handle_precompile_return(
+        &mut self,
+        current_exec_steps: &mut [&mut ExecStep],
+        output: Vec<u8>,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.end_call(current_exec_steps, call_id, 0, output)
+    }
+
+    /// Handle the failure of a creation transaction whose contract address
+    /// collides with an existing account, which ends the root call right in
+    /// its BeginTx step.
+    pub fn handle_tx_address_collision(
+        &mut self,
+        begin_tx_step: &mut ExecStep,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.end_call(&mut [begin_tx_step], call_id, 0, Vec::new())
+    }
+
+    /// End the current call, updating the caller's last callee information,
+    /// which is read by RETURNDATASIZE and RETURNDATACOPY.
+    fn end_call(
+        &mut self,
+        current_exec_steps: &mut [&mut ExecStep],
+        last_callee_id: usize,
+        return_data_offset: u64,
+        return_data: Vec<u8>,
+    ) -> Result<(), Error> {
+        let call = self.call()?.clone();
 
         // Handle reversion if this call doens't end successfully
-        if !self.call()?.is_success {
-            self.handle_reversion();
+        if !call.is_success {
+            self.handle_reversion(current_exec_steps);
         }
 
         self.tx_ctx.pop_call_ctx();
 
+        if !call.is_root {
+            let caller = self.call_mut()?;
+            caller.last_callee_id = last_callee_id;
+            caller.last_callee_return_data_offset = return_data_offset;
+            caller.last_callee_return_data_length = return_data.len() as u64;
+            self.call_ctx_mut()?.return_data = return_data;
+        }
+
+        Ok(())
+    }
+
+    /// Generate the operations shared by the steps which halt in exception,
+    /// which read that the call fails and, for an internal call, restore the
+    /// caller's context without returning any gas to it.
+    pub fn gen_error_halt_ops(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let call = self.call()?.clone();
+        debug_assert!(!call.is_success);
+        self.call_context_read(
+            exec_step,
+            call.call_id,
+            CallContextField::IsSuccess,
+            Word::zero(),
+        );
+
+        if !call.is_root {
+            self.gen_restore_context_ops(exec_step, geth_steps)?;
+        }
+
+        Ok(())
+    }
+
+    /// Generate the caller's context reads and last callee writes which
+    /// restore the caller's context when the current call halts, i.e.
+    /// `Instruction.step_state_transition_to_restored_context` in python spec.
+    pub fn gen_restore_context_ops(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let geth_step = &geth_steps[0];
+        let call = self.call()?.clone();
+
+        // The gas left after this step is returned back to the caller. The code
+        // deposit cost of a successful create is charged by geth after the
+        // RETURN step, so it's deducted here.
+        let code_deposit_cost =
+            if call.is_create() && call.is_success && geth_step.op == OpcodeId::RETURN {
+                let (_, length) = get_call_memory_offset_length(geth_step, 0)?;
+                GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length
+            } else {
+                0
+            };
+        let geth_step_next = &geth_steps[1];
+        // All the gas left is consumed when an error occurs.
+        let caller_gas_left = if exec_step.error.is_some() {
+            geth_step_next.gas.0
+        } else {
+            geth_step_next.gas.0 - (geth_step.gas.0 - geth_step.gas_cost.0 - code_deposit_cost)
+        };
+
+        let (return_data_offset, return_data_length) =
+            get_return_data_offset_length(&call, geth_step)?;
+        self.gen_restore_context_ops_to(
+            exec_step,
+            geth_step_next,
+            caller_gas_left,
+            call.call_id,
+            return_data_offset,
+            return_data_length,
+        )
+    }
+
+    /// Generate the caller's context reads and last callee writes which
+    /// restore the caller's context, given the caller's next step and the gas
+    /// returned back to it. It's used directly by the step of a precompiled
+    /// contract, which doesn't have a geth step of its own.
+    pub fn gen_restore_context_ops_to(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_step_next: &GethExecStep,
+        caller_gas_left: u64,
+        last_callee_id: usize,
+        return_data_offset: u64,
+        return_data_length: u64,
+    ) -> Result<(), Error> {
+        let call = self.call()?.clone();
+        let caller = self.caller()?.clone();
+        self.call_context_read(
+            exec_step,
+            call.call_id,
+            CallContextField::CallerId,
+            caller.call_id.into(),
+        );
+
+        for (field, value) in [
+            (CallContextField::IsRoot, (caller.is_root as u64).into()),
+            (
+                CallContextField::IsCreate,
+                (caller.is_create() as u64).into(),
+            ),
+            (CallContextField::CodeHash, caller.code_hash.to_word()),
+            (CallContextField::ProgramCounter, geth_step_next.pc.0.into()),
+            (
+                CallContextField::StackPointer,
+                geth_step_next.stack.stack_pointer().0.into(),
+            ),
+            (CallContextField::GasLeft, caller_gas_left.into()),
+            (
+                CallContextField::MemorySize,
+                geth_step_next.memory.word_size().into(),
+            ),
+            (
+                CallContextField::ReversibleWriteCounter,
+                self.caller_ctx()?.reversible_write_counter.into(),
+            ),
+        ] {
+            self.call_context_read(exec_step, caller.call_id, field, value);
+        }
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, last_callee_id.into()),
+            (
+                CallContextField::LastCalleeReturnDataOffset,
+                return_data_offset.into(),
+            ),
+            (
+                CallContextField::LastCalleeReturnDataLength,
+                return_data_length.into(),
+            ),
+        ] {
+            self.call_context_write(exec_step, caller.call_id, field, value);
+        }
+
         Ok(())
     }
 
@@ -808,6 +1142,16 @@ impl<'a> CircuitInputStateRef<'a> {
         self.block.add_copy_event(copy);
     }
 
+    /// Push an exponentiation event to the state.
+    pub fn push_exponentiation(&mut self, event: ExpEvent) {
+        self.block.add_exp_event(event);
+    }
+
+    /// Push a precompile event to the state.
+    pub fn push_precompile_event(&mut self, event: PrecompileEvent) {
+        self.block.add_precompile_event(event);
+    }
+
     pub(crate) fn get_step_err(
         &self,
         step: &GethExecStep,
@@ -817,7 +1161,9 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
 
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        // An opcode which isn't available in the hard fork of the block is
+        // executed as an invalid one, e.g. BASEFEE before London.
+        if !step.op.is_enabled(self.block.hardfork) {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
@@ -839,13 +1185,16 @@ impl<'a> CircuitInputStateRef<'a> {
         let call = self.call()?;
 
         // Return from a call with a failure
-        if step.depth != next_depth && next_result.is_zero() {
+        if step.depth == next_depth + 1 && next_result.is_zero() {
             if !matches!(step.op, OpcodeId::RETURN) {
                 // Without calling RETURN
                 return Ok(match step.op {
                     OpcodeId::JUMP | OpcodeId::JUMPI => Some(ExecError::InvalidJump),
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
-                    // Break write protection (CALL with value will be handled below)
+                    // Break write protection
+                    OpcodeId::CALL if call.is_static && !step.stack.nth_last(2)?.is_zero() => {
+                        Some(ExecError::WriteProtection)
+                    }
                     OpcodeId::SSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
@@ -872,14 +1221,27 @@ impl<'a> CircuitInputStateRef<'a> {
                 if !call.is_root && call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
-                    if length > Word::from(0x6000u64) {
+                    // The memory expansion of RETURN is charged along with the
+                    // code deposit.
+                    let curr_memory_word_size = step.memory.word_size() as u64;
+                    let next_memory_word_size = if length.is_zero() {
+                        curr_memory_word_size
+                    } else {
+                        curr_memory_word_size.max((offset.low_u64() + length.low_u64() + 31) / 32)
+                    };
+                    let memory_expansion_cost =
+                        memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
+                    if length > Word::from(self.block.max_code_size) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !step.memory.0.is_empty()
                         && step.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
-                    } else if Word::from(200u64) * length > Word::from(step.gas.0) {
+                    } else if Word::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64()) * length
+                        + Word::from(memory_expansion_cost)
+                        > Word::from(step.gas.0)
+                    {
                         return Ok(Some(ExecError::CodeStoreOutOfGas));
                     } else {
                         return Err(Error::UnexpectedExecStepError(
@@ -901,9 +1263,12 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Return from a call without calling RETURN or STOP and having success
         // is unexpected.
-        if step.depth != next_depth
+        if step.depth == next_depth + 1
             && next_result != Word::zero()
-            && !matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP)
+            && !matches!(
+                step.op,
+                OpcodeId::RETURN | OpcodeId::STOP | OpcodeId::SELFDESTRUCT
+            )
         {
             return Err(Error::UnexpectedExecStepError(
                 "success result without {RETURN, STOP}",
@@ -924,22 +1289,22 @@ impl<'a> CircuitInputStateRef<'a> {
         ) && next_result.is_zero()
             && next_pc != 0
         {
-            if step.depth == 1025 {
-                return Ok(Some(ExecError::Depth));
-            }
-
-            // Insufficient_balance
             let value = match step.op {
                 OpcodeId::CALL | OpcodeId::CALLCODE => step.stack.nth_last(2)?,
                 OpcodeId::CREATE | OpcodeId::CREATE2 => step.stack.nth_last(0)?,
                 _ => Word::zero(),
             };
 
-            // CALL with value
+            // CALL with value, which is checked before the call depth
             if matches!(step.op, OpcodeId::CALL) && !value.is_zero() && self.call()?.is_static {
                 return Ok(Some(ExecError::WriteProtection));
             }
 
+            if step.depth == 1025 {
+                return Ok(Some(ExecError::Depth));
+            }
+
+            // Insufficient_balance
             let sender = self.call()?.address;
             let (found, account) = self.sdb.get_account(&sender);
             if !found {
@@ -956,8 +1321,11 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::CREATE2 => self.create2_address(step)?,
                     _ => unreachable!(),
                 };
-                let (found, _) = self.sdb.get_account(&address);
-                if found {
+                let (found, account) = self.sdb.get_account(&address);
+                if found
+                    && (!account.nonce.is_zero()
+                        || account.code_hash.to_fixed_bytes() != *EMPTY_HASH)
+                {
                     return Ok(Some(ExecError::ContractAddressCollision));
                 }
             }
@@ -94,6 +94,9 @@ fn mock_internal_create() -> Call {
         call_data_length: 0,
         return_data_offset: 0,
         return_data_length: 0,
+        last_callee_id: 0,
+        last_callee_return_data_offset: 0,
+        last_callee_return_data_length: 0,
     }
 }
 
@@ -1320,6 +1323,9 @@ fn tracer_err_write_protection() {
             call_data_length: 0,
             return_data_offset: 0,
             return_data_length: 0,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
         },
         step,
     );
@@ -2,7 +2,7 @@
 
 use std::collections::BTreeMap;
 
-use eth_types::{Address, GethExecTrace, Word};
+use eth_types::{AccessList, Address, GethExecTrace, Word};
 use ethers_core::utils::get_contract_address;
 
 use crate::{
@@ -159,6 +159,7 @@ impl TransactionContext {
             index: call_idx,
             reversible_write_counter: 0,
             call_data,
+            return_data: Vec::new(),
         });
     }
 
@@ -181,8 +182,14 @@ pub struct Transaction {
     pub nonce: u64,
     /// Gas
     pub gas: u64,
-    /// Gas price
+    /// Gas price, which is the effective gas price for EIP-1559 transactions
     pub gas_price: Word,
+    /// Max fee per gas (EIP-1559), which is the gas price for legacy
+    /// transactions
+    pub max_fee_per_gas: Word,
+    /// Max priority fee per gas (EIP-1559), which is the gas price for legacy
+    /// transactions
+    pub max_priority_fee_per_gas: Word,
     /// From / Caller Address
     pub from: Address,
     /// To / Callee Address
@@ -191,6 +198,8 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>,
+    /// Access list (EIP-2930)
+    pub access_list: AccessList,
     /// Calls made in the transaction
     calls: Vec<Call>,
     /// Execution steps
@@ -204,6 +213,7 @@ impl Transaction {
         sdb: &StateDB,
         code_db: &mut CodeDB,
         eth_tx: &eth_types::Transaction,
+        base_fee: Word,
         is_success: bool,
     ) -> Result<Self, Error> {
         let (found, _) = sdb.get_account(&eth_tx.from);
@@ -252,14 +262,38 @@ impl Transaction {
             }
         };
 
+        // A transaction is treated as EIP-1559 one when its max fee per gas is
+        // specified, and the price paid is then the base fee plus the priority
+        // fee, capped by the max fee.
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match eth_tx
+            .max_fee_per_gas
+            .filter(|max_fee_per_gas| !max_fee_per_gas.is_zero())
+        {
+            Some(max_fee_per_gas) => {
+                let max_priority_fee_per_gas = eth_tx.max_priority_fee_per_gas.unwrap_or_default();
+                (
+                    max_fee_per_gas.min(base_fee + max_priority_fee_per_gas),
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                )
+            }
+            None => {
+                let gas_price = eth_tx.gas_price.unwrap_or_default();
+                (gas_price, gas_price, gas_price)
+            }
+        };
+
         Ok(Self {
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
-            gas_price: eth_tx.gas_price.unwrap_or_default(),
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             from: eth_tx.from,
             to: eth_tx.to.unwrap_or_default(),
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list: eth_tx.access_list.clone().unwrap_or_default(),
             calls: vec![call],
             steps: Vec::new(),
         })
@@ -6,8 +6,8 @@ use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
 use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_MAX_INITCODE_SIZE_EXCEEDED, GETH_ERR_OUT_OF_GAS,
+    GETH_ERR_STACK_OVERFLOW, GETH_ERR_STACK_UNDERFLOW,
 };
 
 /// Error type for any BusMapping related failure.
@@ -36,6 +36,8 @@ pub enum Error {
     EthTypeError(eth_types::Error),
     /// EVM Execution error
     ExecutionError(ExecError),
+    /// Chain id of the chain specification doesn't match the one of the node.
+    ChainIdMismatch(u64, u64),
 }
 
 impl From<eth_types::Error> for Error {
@@ -139,8 +141,15 @@ pub enum ExecError {
 
 // TODO: Move to impl block.
 pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
-    if error == GETH_ERR_OUT_OF_GAS || error == GETH_ERR_GAS_UINT_OVERFLOW {
-        // NOTE: We report a GasUintOverflow error as an OutOfGas error
+    if error.starts_with(GETH_ERR_OUT_OF_GAS)
+        || error == GETH_ERR_GAS_UINT_OVERFLOW
+        || error.starts_with(GETH_ERR_MAX_INITCODE_SIZE_EXCEEDED)
+    {
+        // NOTE: We report a GasUintOverflow error as an OutOfGas error. The
+        // errors of dynamic gas are wrapped by geth, e.g. "out of gas: not
+        // enough gas for reentrancy sentry" of SSTORE. An oversized initcode
+        // of CREATE and CREATE2 is also reported as an OutOfGas error, which
+        // the gadgets of their dynamic gas check.
         let oog_err = match op {
             OpcodeId::MLOAD | OpcodeId::MSTORE | OpcodeId::MSTORE8 => {
                 OogError::StaticMemoryExpansion
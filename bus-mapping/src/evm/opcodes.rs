@@ -1,21 +1,28 @@
 //! Definition of each opcode of the EVM.
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
-    evm::OpcodeId,
-    operation::{
-        AccountField, AccountOp, CallContextField, TxAccessListAccountOp, TxReceiptField,
-        TxRefundOp, RW,
+    circuit_input_builder::{
+        get_create_address_preimage, CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep,
+        ExecStep, NumberOrHash,
     },
+    error::{ExecError, OogError},
+    evm::OpcodeId,
+    operation::{AccountField, AccountOp, CallContextField, TxReceiptField, TxRefundOp, RW},
+    precompile::PrecompileCalls,
     Error,
 };
 use core::fmt::Debug;
 use eth_types::{
-    evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    GethExecStep, ToAddress, ToWord, Word,
+    evm_types::{
+        gas_utils::{initcode_word_gas_cost, max_refund_quotient_of_gas_used},
+        GasCost,
+    },
+    GethExecStep, Hardfork, ToWord, Word,
 };
 use keccak256::EMPTY_HASH;
 use log::warn;
 
+mod address;
+mod balance;
 mod call;
 mod calldatacopy;
 mod calldataload;
@@ -25,22 +32,57 @@ mod callvalue;
 mod chainid;
 mod codecopy;
 mod codesize;
+mod create;
 mod dup;
+mod error_contract_address_collision;
+mod error_invalid_creation_code;
+mod error_invalid_jump;
+mod error_invalid_opcode;
+mod error_max_code_size;
+mod error_oog_account_access;
+mod error_oog_call;
+mod error_oog_code_store;
+mod error_oog_constant;
+mod error_oog_create2;
+mod error_oog_dynamic_memory;
+mod error_oog_exp;
+mod error_oog_ext_code_copy;
+mod error_oog_log;
+mod error_oog_memory_copy;
+mod error_oog_self_destruct;
+mod error_oog_sha3;
+mod error_oog_sload;
+mod error_oog_sstore;
+mod error_oog_static_memory;
+mod error_precheck_failed;
+mod error_return_data_oob;
+mod error_stack;
+mod error_write_protection;
+mod exp;
+mod extcodecopy;
 mod extcodehash;
+mod extcodesize;
 mod gasprice;
 mod logs;
 mod mload;
 mod mstore;
 mod number;
 mod origin;
-mod r#return;
+mod precompile;
+mod return_revert;
+mod returndatacopy;
+mod returndatasize;
 mod selfbalance;
+mod selfdestruct;
+mod sha3;
 mod sload;
 mod sstore;
 mod stackonlyop;
 mod stop;
 mod swap;
 
+use address::Address;
+use balance::Balance;
 use call::Call;
 use calldatacopy::Calldatacopy;
 use calldataload::Calldataload;
@@ -49,15 +91,48 @@ use caller::Caller;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use codesize::Codesize;
+use create::Create;
 use dup::Dup;
+use error_contract_address_collision::ErrorContractAddressCollision;
+use error_invalid_creation_code::ErrorInvalidCreationCode;
+use error_invalid_jump::ErrorInvalidJump;
+use error_invalid_opcode::ErrorInvalidOpcode;
+use error_max_code_size::ErrorMaxCodeSizeExceeded;
+use error_oog_account_access::ErrorOOGAccountAccess;
+use error_oog_call::ErrorOOGCall;
+use error_oog_code_store::ErrorOOGCodeStore;
+use error_oog_constant::ErrorOOGConstant;
+use error_oog_create2::ErrorOOGCreate2;
+use error_oog_dynamic_memory::ErrorOOGDynamicMemory;
+use error_oog_exp::ErrorOOGExp;
+use error_oog_ext_code_copy::ErrorOOGExtCodeCopy;
+use error_oog_log::ErrorOOGLog;
+use error_oog_memory_copy::ErrorOOGMemoryCopy;
+use error_oog_self_destruct::ErrorOOGSelfDestruct;
+use error_oog_sha3::ErrorOOGSha3;
+use error_oog_sload::ErrorOOGSload;
+use error_oog_sstore::ErrorOOGSstore;
+use error_oog_static_memory::ErrorOOGStaticMemory;
+use error_precheck_failed::ErrorPrecheckFailed;
+use error_return_data_oob::ErrorReturnDataOutOfBound;
+use error_stack::ErrorStack;
+use error_write_protection::ErrorWriteProtection;
+use exp::Exponentiation;
+use extcodecopy::Extcodecopy;
 use extcodehash::Extcodehash;
+use extcodesize::Extcodesize;
 use gasprice::GasPrice;
 use logs::Log;
 use mload::Mload;
 use mstore::Mstore;
 use origin::Origin;
-use r#return::Return;
+use precompile::gen_precompile_ops;
+use return_revert::ReturnRevert;
+use returndatacopy::Returndatacopy;
+use returndatasize::Returndatasize;
 use selfbalance::Selfbalance;
+use selfdestruct::Selfdestruct;
+use sha3::Sha3;
 use sload::Sload;
 use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
@@ -107,7 +182,7 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SMOD => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::ADDMOD => StackOnlyOpcode::<3, 1>::gen_associated_ops,
         OpcodeId::MULMOD => StackOnlyOpcode::<3, 1>::gen_associated_ops,
-        OpcodeId::EXP => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        OpcodeId::EXP => Exponentiation::gen_associated_ops,
         OpcodeId::SIGNEXTEND => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::LT => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::GT => StackOnlyOpcode::<2, 1>::gen_associated_ops,
@@ -123,9 +198,9 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SHL => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::SHR => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::SAR => StackOnlyOpcode::<2, 1>::gen_associated_ops,
-        OpcodeId::SHA3 => StackOnlyOpcode::<2, 1>::gen_associated_ops,
-        OpcodeId::ADDRESS => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::BALANCE => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        OpcodeId::SHA3 => Sha3::gen_associated_ops,
+        OpcodeId::ADDRESS => Address::gen_associated_ops,
+        OpcodeId::BALANCE => Balance::gen_associated_ops,
         OpcodeId::ORIGIN => Origin::gen_associated_ops,
         OpcodeId::CALLER => Caller::gen_associated_ops,
         OpcodeId::CALLVALUE => Callvalue::gen_associated_ops,
@@ -135,10 +210,10 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::GASPRICE => GasPrice::gen_associated_ops,
         OpcodeId::CODECOPY => Codecopy::gen_associated_ops,
         OpcodeId::CODESIZE => Codesize::gen_associated_ops,
-        OpcodeId::EXTCODESIZE => StackOnlyOpcode::<1, 1>::gen_associated_ops,
-        OpcodeId::EXTCODECOPY => StackOnlyOpcode::<4, 0>::gen_associated_ops,
-        OpcodeId::RETURNDATASIZE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::RETURNDATACOPY => StackOnlyOpcode::<3, 0>::gen_associated_ops,
+        OpcodeId::EXTCODESIZE => Extcodesize::gen_associated_ops,
+        OpcodeId::EXTCODECOPY => Extcodecopy::gen_associated_ops,
+        OpcodeId::RETURNDATASIZE => Returndatasize::gen_associated_ops,
+        OpcodeId::RETURNDATACOPY => Returndatacopy::gen_associated_ops,
         OpcodeId::EXTCODEHASH => Extcodehash::gen_associated_ops,
         OpcodeId::BLOCKHASH => StackOnlyOpcode::<1, 1>::gen_associated_ops,
         OpcodeId::COINBASE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
@@ -198,34 +273,61 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::LOG2 => Log::gen_associated_ops,
         OpcodeId::LOG3 => Log::gen_associated_ops,
         OpcodeId::LOG4 => Log::gen_associated_ops,
-        // OpcodeId::CREATE => {},
-        OpcodeId::CALL => Call::gen_associated_ops,
-        // OpcodeId::CALLCODE => {},
-        // OpcodeId::RETURN => {},
-        // OpcodeId::DELEGATECALL => {},
-        // OpcodeId::CREATE2 => {},
-        // OpcodeId::STATICCALL => {},
-        // OpcodeId::REVERT => {},
-        OpcodeId::REVERT | OpcodeId::RETURN => {
+        OpcodeId::CREATE => Create::<false>::gen_associated_ops,
+        OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            Call::gen_associated_ops
+        }
+        OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
+        OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
+        OpcodeId::SELFDESTRUCT => Selfdestruct::gen_associated_ops,
+        _ => {
             warn!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
-            Return::gen_associated_ops
+            dummy_gen_associated_ops
+        }
+    }
+}
+
+fn fn_gen_error_state_associated_ops(error: &ExecError) -> Option<FnGenAssociatedOps> {
+    match error {
+        ExecError::OutOfGas(OogError::Constant) => Some(ErrorOOGConstant::gen_associated_ops),
+        ExecError::OutOfGas(OogError::StaticMemoryExpansion) => {
+            Some(ErrorOOGStaticMemory::gen_associated_ops)
         }
-        OpcodeId::SELFDESTRUCT => {
-            warn!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
-            dummy_gen_selfdestruct_ops
+        ExecError::OutOfGas(OogError::DynamicMemoryExpansion) => {
+            Some(ErrorOOGDynamicMemory::gen_associated_ops)
         }
-        OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
-            warn!("Using dummy gen_call_ops for opcode {:?}", opcode_id);
-            dummy_gen_call_ops
+        ExecError::OutOfGas(OogError::MemoryCopy) => Some(ErrorOOGMemoryCopy::gen_associated_ops),
+        ExecError::OutOfGas(OogError::AccountAccess) => {
+            Some(ErrorOOGAccountAccess::gen_associated_ops)
         }
-        OpcodeId::CREATE | OpcodeId::CREATE2 => {
-            warn!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-            dummy_gen_create_ops
+        ExecError::OutOfGas(OogError::Log) => Some(ErrorOOGLog::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Exp) => Some(ErrorOOGExp::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Sha3) => Some(ErrorOOGSha3::gen_associated_ops),
+        ExecError::OutOfGas(OogError::ExtCodeCopy) => Some(ErrorOOGExtCodeCopy::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Sload) => Some(ErrorOOGSload::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Sstore) => Some(ErrorOOGSstore::gen_associated_ops),
+        ExecError::OutOfGas(
+            OogError::Call | OogError::CallCode | OogError::DelegateCall | OogError::StaticCall,
+        ) => Some(ErrorOOGCall::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Create2) => Some(ErrorOOGCreate2::gen_associated_ops),
+        ExecError::OutOfGas(OogError::SelfDestruct) => {
+            Some(ErrorOOGSelfDestruct::gen_associated_ops)
         }
-        _ => {
-            warn!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
-            dummy_gen_associated_ops
+        ExecError::CodeStoreOutOfGas => Some(ErrorOOGCodeStore::gen_associated_ops),
+        ExecError::ContractAddressCollision => {
+            Some(ErrorContractAddressCollision::gen_associated_ops)
         }
+        ExecError::InvalidCreationCode => Some(ErrorInvalidCreationCode::gen_associated_ops),
+        ExecError::MaxCodeSizeExceeded => Some(ErrorMaxCodeSizeExceeded::gen_associated_ops),
+        ExecError::ReturnDataOutOfBounds => Some(ErrorReturnDataOutOfBound::gen_associated_ops),
+        ExecError::InvalidJump => Some(ErrorInvalidJump::gen_associated_ops),
+        ExecError::InvalidOpcode => Some(ErrorInvalidOpcode::gen_associated_ops),
+        ExecError::StackOverflow => Some(ErrorStack::<true>::gen_associated_ops),
+        ExecError::StackUnderflow => Some(ErrorStack::<false>::gen_associated_ops),
+        ExecError::WriteProtection => Some(ErrorWriteProtection::gen_associated_ops),
+        ExecError::Depth => Some(ErrorPrecheckFailed::<true>::gen_associated_ops),
+        ExecError::InsufficientBalance => Some(ErrorPrecheckFailed::<false>::gen_associated_ops),
+        _ => None,
     }
 }
 
@@ -236,11 +338,24 @@ pub fn gen_associated_ops(
     state: &mut CircuitInputStateRef,
     geth_steps: &[GethExecStep],
 ) -> Result<Vec<ExecStep>, Error> {
+    // Errors are handled by their own implementation once it exists,
+    // otherwise the opcode's implementation is still used.
+    let geth_step = &geth_steps[0];
+    if let Some(exec_error) = state.get_step_err(geth_step, geth_steps.get(1))? {
+        if let Some(fn_gen_error_ops) = fn_gen_error_state_associated_ops(&exec_error) {
+            return fn_gen_error_ops(state, geth_steps);
+        }
+        warn!(
+            "Unhandled error {:?} for opcode {:?}",
+            exec_error, opcode_id
+        );
+    }
+
     let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
     fn_gen_associated_ops(state, geth_steps)
 }
 
-pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
+pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<Vec<ExecStep>, Error> {
     let mut exec_step = state.new_begin_tx_step();
     let call = state.call()?.clone();
 
@@ -287,11 +402,19 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         .input
         .iter()
         .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 });
+    let access_list_gas_cost = state.tx.access_list.0.iter().fold(0, |acc, item| {
+        acc + GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+            + item.storage_keys.len() as u64 * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64()
+    });
+    // The initcode of a creation transaction is charged per word since
+    // Shanghai (EIP-3860).
     let intrinsic_gas_cost = if state.tx.is_create() {
         GasCost::CREATION_TX.as_u64()
+            + initcode_word_gas_cost(state.block.hardfork, state.tx.input.len() as u64)
     } else {
         GasCost::TX.as_u64()
-    } + call_data_gas_cost;
+    } + call_data_gas_cost
+        + access_list_gas_cost;
     exec_step.gas_cost = GasCost(intrinsic_gas_cost);
 
     // Transfer with fee
@@ -305,70 +428,252 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
 
     // Get code_hash of callee
     let (_, callee_account) = state.sdb.get_account(&call.address);
-    let code_hash = callee_account.code_hash;
+    let (callee_nonce, code_hash) = (callee_account.nonce, callee_account.code_hash);
+    state.account_read(
+        &mut exec_step,
+        call.address,
+        AccountField::CodeHash,
+        code_hash.to_word(),
+        code_hash.to_word(),
+    )?;
 
     // There are 4 branches from here.
-    match (
-        call.is_create(),
-        state.is_precompiled(&call.address),
-        code_hash.to_fixed_bytes() == *EMPTY_HASH,
-    ) {
-        // 1. Creation transaction.
-        (true, _, _) => {
-            warn!("Creation transaction is left unimplemented");
-            Ok(exec_step)
-        }
-        // 2. Call to precompiled.
-        (_, true, _) => {
-            warn!("Call to precompiled is left unimplemented");
-            Ok(exec_step)
-        }
-        (_, _, is_empty_code_hash) => {
-            state.account_read(
+    let precompile = PrecompileCalls::from_address(&call.address);
+    let mut is_collision = false;
+    if call.is_create() {
+        // 1. Creation transaction, whose new contract starts with nonce 1
+        // (EIP-161), unless its address collides with an account which
+        // already has a nonce or code.
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::Nonce,
+            callee_nonce,
+            callee_nonce,
+        )?;
+        is_collision = !callee_nonce.is_zero() || code_hash.to_fixed_bytes() != *EMPTY_HASH;
+        if !is_collision {
+            state.push_op_reversible(
                 &mut exec_step,
-                call.address,
-                AccountField::CodeHash,
-                code_hash.to_word(),
-                code_hash.to_word(),
+                RW::WRITE,
+                AccountOp {
+                    address: call.address,
+                    field: AccountField::Nonce,
+                    value: 1.into(),
+                    value_prev: 0.into(),
+                },
             )?;
+        }
+    } else if precompile.is_none() && code_hash.to_fixed_bytes() == *EMPTY_HASH {
+        // 3. Call to account with empty code.
+        warn!("Call to account with empty code is left unimplemented");
+        return Ok(vec![exec_step]);
+    }
 
-            // 3. Call to account with empty code.
-            if is_empty_code_hash {
-                warn!("Call to account with empty code is left unimplemented");
-                return Ok(exec_step);
-            }
+    // 2. Call to precompiled, 4. call to account with non-empty code and 1.
+    // creation transaction executing its calldata as initcode. The code hash
+    // of a precompiled contract's context is its address, which identifies the
+    // precompiled contract to execute.
+    let code_hash = match precompile {
+        Some(precompile) => precompile.address().to_word(),
+        None => call.code_hash.to_word(),
+    };
+    if !is_collision {
+        for (field, value) in [
+            (CallContextField::Depth, call.depth.into()),
+            (
+                CallContextField::CallerAddress,
+                call.caller_address.to_word(),
+            ),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            (
+                CallContextField::CallDataOffset,
+                call.call_data_offset.into(),
+            ),
+            (
+                CallContextField::CallDataLength,
+                call.call_data_length.into(),
+            ),
+            (CallContextField::Value, call.value),
+            (CallContextField::IsStatic, (call.is_static as usize).into()),
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            (CallContextField::IsRoot, 1.into()),
+            (
+                CallContextField::IsCreate,
+                (call.is_create() as usize).into(),
+            ),
+            (CallContextField::CodeHash, code_hash),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+    }
 
-            // 4. Call to account with non-empty code.
-            for (field, value) in [
-                (CallContextField::Depth, call.depth.into()),
-                (
-                    CallContextField::CallerAddress,
-                    call.caller_address.to_word(),
-                ),
-                (CallContextField::CalleeAddress, call.address.to_word()),
-                (
-                    CallContextField::CallDataOffset,
-                    call.call_data_offset.into(),
-                ),
-                (
-                    CallContextField::CallDataLength,
-                    call.call_data_length.into(),
-                ),
-                (CallContextField::Value, call.value),
-                (CallContextField::IsStatic, (call.is_static as usize).into()),
-                (CallContextField::LastCalleeId, 0.into()),
-                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                (CallContextField::LastCalleeReturnDataLength, 0.into()),
-                (CallContextField::IsRoot, 1.into()),
-                (CallContextField::IsCreate, 0.into()),
-                (CallContextField::CodeHash, code_hash.to_word()),
-            ] {
-                state.call_context_read(&mut exec_step, call.call_id, field, value);
-            }
+    // The contract address is derived from the caller's nonce before its
+    // increment, and the initcode is hashed by copying it from the calldata.
+    if call.is_create() {
+        let init_code = state.tx.input.clone();
+        state.block.sha3_inputs.push(get_create_address_preimage(
+            call.caller_address,
+            state.tx.nonce,
+        ));
+        if !is_collision {
+            state.block.sha3_inputs.push(init_code.clone());
+        }
+        if !is_collision && !init_code.is_empty() {
+            let copy_event = gen_begin_tx_copy_event(state, &exec_step, &init_code)?;
+            state.push_copy(copy_event);
+        }
+    }
+
+    // Warm up the coinbase since Shanghai (EIP-3651).
+    if state.block.hardfork >= Hardfork::Shanghai {
+        let coinbase = state.block.coinbase;
+        let is_warm_prev = !state.sdb.add_account_to_access_list(coinbase);
+        state.tx_accesslist_account_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            coinbase,
+            true,
+            is_warm_prev,
+        )?;
+    }
+
+    // The collision consumes all the gas of the transaction, which fails
+    // without executing its initcode, so its reversions are done right after
+    // BeginTx.
+    if is_collision {
+        exec_step.gas_cost = GasCost(state.tx.gas);
+        state.handle_tx_address_collision(&mut exec_step)?;
+        return Ok(vec![exec_step]);
+    }
 
-            Ok(exec_step)
+    // Warm up the accounts and storage keys in the access list (EIP-2930).
+    let gas_left = state.tx.gas - exec_step.gas_cost.as_u64();
+    let mut steps = gen_tx_access_list_ops(state, gas_left)?;
+
+    // The precompiled contract is executed in a step of its own right after
+    // BeginTx and the access list, since there are no geth steps for it.
+    if let Some(precompile) = precompile {
+        let precompile_step =
+            gen_precompile_ops(state, &mut exec_step, precompile, gas_left, None)?;
+        steps.push(precompile_step);
+    }
+
+    steps.insert(0, exec_step);
+    Ok(steps)
+}
+
+/// Generate a TxAccessList step for each entry of the access list of the
+/// transaction, which are its addresses followed by its storage keys.
+fn gen_tx_access_list_ops(
+    state: &mut CircuitInputStateRef,
+    gas_left: u64,
+) -> Result<Vec<ExecStep>, Error> {
+    let access_list = state.tx.access_list.0.clone();
+    let entries =
+        access_list
+            .iter()
+            .map(|item| (item.address, None))
+            .chain(access_list.iter().flat_map(|item| {
+                item.storage_keys
+                    .iter()
+                    .map(|key| (item.address, Some(key.to_word())))
+            }));
+
+    let mut steps = Vec::new();
+    for (index, (address, key)) in entries.enumerate() {
+        let mut exec_step = state.new_tx_access_list_step(index, gas_left)?;
+        let call_id = state.call()?.call_id;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            state.tx_ctx.id().into(),
+        );
+        match key {
+            None => {
+                let is_warm_prev = !state.sdb.add_account_to_access_list(address);
+                state.tx_accesslist_account_write(
+                    &mut exec_step,
+                    state.tx_ctx.id(),
+                    address,
+                    true,
+                    is_warm_prev,
+                )?;
+            }
+            Some(key) => {
+                let is_warm_prev = !state.sdb.add_account_storage_to_access_list((address, key));
+                state.tx_accesslist_account_storage_write(
+                    &mut exec_step,
+                    state.tx_ctx.id(),
+                    address,
+                    key,
+                    true,
+                    is_warm_prev,
+                )?;
+            }
         }
+        steps.push(exec_step);
     }
+    Ok(steps)
+}
+
+/// Generate the copy event of hashing the calldata of a creation transaction
+/// as its initcode, which doesn't read or write anything in the rw table.
+fn gen_begin_tx_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &ExecStep,
+    init_code: &[u8],
+) -> Result<CopyEvent, Error> {
+    let length = init_code.len() as u64;
+    let rwc = state.block_ctx.rwc;
+    let steps = init_code
+        .iter()
+        .copied()
+        .enumerate()
+        .flat_map(|(idx, value)| {
+            [
+                CopyStep {
+                    addr: idx as u64,
+                    tag: CopyDataType::TxCalldata,
+                    rw: RW::READ,
+                    value,
+                    is_code: None,
+                    is_pad: false,
+                    rwc,
+                    rwc_inc_left: 0,
+                },
+                CopyStep {
+                    addr: idx as u64,
+                    tag: CopyDataType::RlcAcc,
+                    rw: RW::WRITE,
+                    value,
+                    is_code: None,
+                    is_pad: false,
+                    rwc,
+                    rwc_inc_left: 0,
+                },
+            ]
+        })
+        .collect();
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::TxCalldata,
+        src_id: NumberOrHash::Number(state.tx_ctx.id()),
+        src_addr: 0,
+        src_addr_end: length,
+        dst_type: CopyDataType::RlcAcc,
+        dst_id: NumberOrHash::Number(state.call()?.call_id),
+        dst_addr: 0,
+        log_id: None,
+        length,
+        steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: state.call()?.call_id,
+        pc: exec_step.pc,
+    })
 }
 
 pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
@@ -399,8 +704,12 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         },
     );
 
-    let effective_refund =
-        refund.min((state.tx.gas - exec_step.gas_left.0) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    // The refund is capped at gas_used / 5 since London (EIP-3529), and at
+    // gas_used / 2 before.
+    let effective_refund = refund.min(
+        (state.tx.gas - exec_step.gas_left.0)
+            / max_refund_quotient_of_gas_used(state.block.hardfork),
+    );
     let (found, caller_account) = state.sdb.get_account_mut(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
@@ -478,150 +787,3 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
 
     Ok(exec_step)
 }
-
-fn dummy_gen_call_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-
-    let tx_id = state.tx_ctx.id();
-    let call = state.parse_call(geth_step)?;
-
-    let (_, account) = state.sdb.get_account(&call.address);
-    let callee_code_hash = account.code_hash;
-
-    let is_warm = state.sdb.check_account_in_access_list(&call.address);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        TxAccessListAccountOp {
-            tx_id,
-            address: call.address,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    state.push_call(call.clone(), geth_step);
-
-    match (
-        state.is_precompiled(&call.address),
-        callee_code_hash.to_fixed_bytes() == *EMPTY_HASH,
-    ) {
-        // 1. Call to precompiled.
-        (true, _) => Ok(vec![exec_step]),
-        // 2. Call to account with empty code.
-        (_, true) => {
-            state.handle_return(geth_step)?;
-            Ok(vec![exec_step])
-        }
-        // 3. Call to account with non-empty code.
-        (_, false) => Ok(vec![exec_step]),
-    }
-}
-
-fn dummy_gen_create_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-
-    let tx_id = state.tx_ctx.id();
-    let call = state.parse_call(geth_step)?;
-
-    // Increase caller's nonce
-    let nonce_prev = state.sdb.get_nonce(&call.caller_address);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        AccountOp {
-            address: call.caller_address,
-            field: AccountField::Nonce,
-            value: (nonce_prev + 1).into(),
-            value_prev: nonce_prev.into(),
-        },
-    )?;
-
-    // Add callee into access list
-    let is_warm = state.sdb.check_account_in_access_list(&call.address);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        TxAccessListAccountOp {
-            tx_id,
-            address: call.address,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    state.push_call(call.clone(), geth_step);
-
-    // Increase callee's nonce
-    let nonce_prev = state.sdb.get_nonce(&call.address);
-    debug_assert!(nonce_prev == 0);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        AccountOp {
-            address: call.address,
-            field: AccountField::Nonce,
-            value: 1.into(),
-            value_prev: 0.into(),
-        },
-    )?;
-
-    state.transfer(
-        &mut exec_step,
-        call.caller_address,
-        call.address,
-        call.value,
-    )?;
-
-    if call.code_hash.to_fixed_bytes() == *EMPTY_HASH {
-        // 1. Create with empty initcode.
-        state.handle_return(geth_step)?;
-        Ok(vec![exec_step])
-    } else {
-        // 2. Create with non-empty initcode.
-        Ok(vec![exec_step])
-    }
-}
-
-fn dummy_gen_selfdestruct_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-    let sender = state.call()?.address;
-    let receiver = geth_step.stack.last()?.to_address();
-
-    let is_warm = state.sdb.check_account_in_access_list(&receiver);
-    state.push_op_reversible(
-        &mut exec_step,
-        RW::WRITE,
-        TxAccessListAccountOp {
-            tx_id: state.tx_ctx.id(),
-            address: receiver,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    let (found, receiver_account) = state.sdb.get_account(&receiver);
-    if !found {
-        return Err(Error::AccountNotFound(receiver));
-    }
-    let value = receiver_account.balance;
-    state.transfer(&mut exec_step, sender, receiver, value)?;
-
-    if state.call()?.is_persistent {
-        state.sdb.destruct_account(sender);
-    }
-
-    Ok(vec![exec_step])
-}
@@ -1,21 +1,22 @@
-use super::Opcode;
+use super::{precompile::gen_precompile_ops, Opcode};
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CallKind, CircuitInputStateRef, ExecStep},
     operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    precompile::PrecompileCalls,
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
-        GasCost,
+        GasCost, GAS_STIPEND_CALL_WITH_VALUE,
     },
-    GethExecStep, ToWord,
+    GethExecStep, ToAddress, ToWord,
 };
 use keccak256::EMPTY_HASH;
-use log::warn;
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
-/// corresponding to the `OpcodeId::CALL` `OpcodeId`.
+/// corresponding to the `OpcodeId::CALL`, `OpcodeId::CALLCODE`,
+/// `OpcodeId::DELEGATECALL` and `OpcodeId::STATICCALL` `OpcodeId`s.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct Call;
 
@@ -54,7 +55,23 @@ impl Opcode for Call {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        for i in 0..7 {
+        // DELEGATECALL keeps the caller and value of the current call.
+        if call.kind == CallKind::DelegateCall {
+            for (field, value) in [
+                (
+                    CallContextField::CallerAddress,
+                    current_call.caller_address.to_word(),
+                ),
+                (CallContextField::Value, current_call.value),
+            ] {
+                state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+            }
+        }
+
+        // Only CALL and CALLCODE have the value on stack.
+        let has_value_on_stack = matches!(call.kind, CallKind::Call | CallKind::CallCode);
+        let n_pop = if has_value_on_stack { 7 } else { 6 };
+        for i in 0..n_pop {
             state.stack_read(
                 &mut exec_step,
                 geth_step.stack.nth_last_filled(i),
@@ -64,17 +81,20 @@ impl Opcode for Call {
 
         state.stack_write(
             &mut exec_step,
-            geth_step.stack.nth_last_filled(6),
+            geth_step.stack.nth_last_filled(n_pop - 1),
             (call.is_success as u64).into(),
         )?;
 
-        let is_warm = state.sdb.check_account_in_access_list(&call.address);
+        // CALLCODE and DELEGATECALL execute the code of `code_address` within
+        // the current account, so it's `code_address` that gets accessed.
+        let code_address = geth_step.stack.nth_last(1)?.to_address();
+        let is_warm = state.sdb.check_account_in_access_list(&code_address);
         state.push_op_reversible(
             &mut exec_step,
             RW::WRITE,
             TxAccessListAccountOp {
                 tx_id,
-                address: call.address,
+                address: code_address,
                 is_warm: true,
                 is_warm_prev: is_warm,
             },
@@ -93,14 +113,18 @@ impl Opcode for Call {
             state.call_context_read(&mut exec_step, call.call_id, field, value);
         }
 
-        state.transfer(
-            &mut exec_step,
-            call.caller_address,
-            call.address,
-            call.value,
-        )?;
+        // Only CALL and CALLCODE transfer value, where CALLCODE transfers it
+        // from the current account to itself.
+        if has_value_on_stack {
+            state.transfer(
+                &mut exec_step,
+                current_call.address,
+                call.address,
+                call.value,
+            )?;
+        }
 
-        let (_, callee_account) = state.sdb.get_account(&call.address);
+        let (_, callee_account) = state.sdb.get_account(&code_address);
         let is_account_empty = callee_account.is_empty();
         let callee_nonce = callee_account.nonce;
         let callee_code_hash = callee_account.code_hash;
@@ -108,7 +132,7 @@ impl Opcode for Call {
             (AccountField::Nonce, callee_nonce),
             (AccountField::CodeHash, callee_code_hash.to_word()),
         ] {
-            state.account_read(&mut exec_step, call.address, field, value, value)?;
+            state.account_read(&mut exec_step, code_address, field, value, value)?;
         }
 
         // Calculate next_memory_word_size and callee_gas_left manually in case
@@ -121,14 +145,14 @@ impl Opcode for Call {
         .into_iter()
         .max()
         .unwrap();
-        let has_value = !call.value.is_zero();
+        let has_value = has_value_on_stack && !call.value.is_zero();
         let gas_cost = if is_warm {
             GasCost::WARM_ACCESS.as_u64()
         } else {
             GasCost::COLD_ACCOUNT_ACCESS.as_u64()
         } + if has_value {
             GasCost::CALL_WITH_VALUE.as_u64()
-                + if is_account_empty {
+                + if call.kind == CallKind::Call && is_account_empty {
                     GasCost::NEW_ACCOUNT.as_u64()
                 } else {
                     0
@@ -142,91 +166,107 @@ impl Opcode for Call {
         let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, geth_step.stack.last()?);
 
         // There are 3 branches from here.
-        match (
-            state.is_precompiled(&call.address),
-            callee_code_hash.to_fixed_bytes() == *EMPTY_HASH,
-        ) {
-            // 1. Call to precompiled.
-            (true, _) => {
-                warn!("Call to precompiled is left unimplemented");
-                Ok(vec![exec_step])
-            }
-            // 2. Call to account with empty code.
-            (_, true) => {
-                for (field, value) in [
-                    (CallContextField::LastCalleeId, 0.into()),
-                    (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                    (CallContextField::LastCalleeReturnDataLength, 0.into()),
-                ] {
-                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
-                }
-                state.handle_return(geth_step)?;
-                Ok(vec![exec_step])
+        let precompile = PrecompileCalls::from_address(&code_address);
+
+        // 1. Call to account with empty code.
+        if precompile.is_none() && callee_code_hash.to_fixed_bytes() == *EMPTY_HASH {
+            for (field, value) in [
+                (CallContextField::LastCalleeId, 0.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
             }
-            // 3. Call to account with non-empty code.
-            (_, false) => {
-                for (field, value) in [
-                    (
-                        CallContextField::ProgramCounter,
-                        (geth_step.pc.0 + 1).into(),
-                    ),
-                    (
-                        CallContextField::StackPointer,
-                        (geth_step.stack.stack_pointer().0 + 6).into(),
-                    ),
-                    (
-                        CallContextField::GasLeft,
-                        (geth_step.gas.0 - gas_cost - callee_gas_left).into(),
-                    ),
-                    (CallContextField::MemorySize, next_memory_word_size.into()),
-                    (
-                        CallContextField::ReversibleWriteCounter,
-                        (exec_step.reversible_write_counter + 1).into(),
-                    ),
-                ] {
-                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
-                }
+            state.handle_return(&mut [&mut exec_step], geth_step)?;
+            return Ok(vec![exec_step]);
+        }
 
-                for (field, value) in [
-                    (CallContextField::CallerId, current_call.call_id.into()),
-                    (CallContextField::TxId, tx_id.into()),
-                    (CallContextField::Depth, call.depth.into()),
-                    (
-                        CallContextField::CallerAddress,
-                        call.caller_address.to_word(),
-                    ),
-                    (CallContextField::CalleeAddress, call.address.to_word()),
-                    (
-                        CallContextField::CallDataOffset,
-                        call.call_data_offset.into(),
-                    ),
-                    (
-                        CallContextField::CallDataLength,
-                        call.call_data_length.into(),
-                    ),
-                    (
-                        CallContextField::ReturnDataOffset,
-                        call.return_data_offset.into(),
-                    ),
-                    (
-                        CallContextField::ReturnDataLength,
-                        call.return_data_length.into(),
-                    ),
-                    (CallContextField::Value, call.value),
-                    (CallContextField::IsSuccess, (call.is_success as u64).into()),
-                    (CallContextField::IsStatic, (call.is_static as u64).into()),
-                    (CallContextField::LastCalleeId, 0.into()),
-                    (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                    (CallContextField::LastCalleeReturnDataLength, 0.into()),
-                    (CallContextField::IsRoot, 0.into()),
-                    (CallContextField::IsCreate, 0.into()),
-                    (CallContextField::CodeHash, call.code_hash.to_word()),
-                ] {
-                    state.call_context_read(&mut exec_step, call.call_id, field, value);
-                }
+        // 2. Call to precompiled and 3. call to account with non-empty code,
+        // which both switch to the callee's context.
+        for (field, value) in [
+            (
+                CallContextField::ProgramCounter,
+                (geth_step.pc.0 + 1).into(),
+            ),
+            (
+                CallContextField::StackPointer,
+                (geth_step.stack.stack_pointer().0 + n_pop - 1).into(),
+            ),
+            (
+                CallContextField::GasLeft,
+                (geth_step.gas.0 - gas_cost - callee_gas_left).into(),
+            ),
+            (CallContextField::MemorySize, next_memory_word_size.into()),
+            (
+                CallContextField::ReversibleWriteCounter,
+                (exec_step.reversible_write_counter + 1).into(),
+            ),
+        ] {
+            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+        }
 
-                Ok(vec![exec_step])
-            }
+        // The code hash of a precompiled contract's context is its address,
+        // which identifies the precompiled contract to execute.
+        let code_hash = match precompile {
+            Some(precompile) => precompile.address().to_word(),
+            None => call.code_hash.to_word(),
+        };
+        for (field, value) in [
+            (CallContextField::CallerId, current_call.call_id.into()),
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::Depth, call.depth.into()),
+            (
+                CallContextField::CallerAddress,
+                call.caller_address.to_word(),
+            ),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            (
+                CallContextField::CallDataOffset,
+                call.call_data_offset.into(),
+            ),
+            (
+                CallContextField::CallDataLength,
+                call.call_data_length.into(),
+            ),
+            (
+                CallContextField::ReturnDataOffset,
+                call.return_data_offset.into(),
+            ),
+            (
+                CallContextField::ReturnDataLength,
+                call.return_data_length.into(),
+            ),
+            (CallContextField::Value, call.value),
+            (CallContextField::IsSuccess, (call.is_success as u64).into()),
+            (CallContextField::IsStatic, (call.is_static as u64).into()),
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            (CallContextField::IsRoot, 0.into()),
+            (CallContextField::IsCreate, 0.into()),
+            (CallContextField::CodeHash, code_hash),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
         }
+
+        // The precompiled contract is executed in a step of its own, which
+        // halts right away.
+        if let Some(precompile) = precompile {
+            let stipend = if has_value {
+                GAS_STIPEND_CALL_WITH_VALUE
+            } else {
+                0
+            };
+            let precompile_step = gen_precompile_ops(
+                state,
+                &mut exec_step,
+                precompile,
+                callee_gas_left + stipend,
+                geth_steps.get(1),
+            )?;
+            return Ok(vec![exec_step, precompile_step]);
+        }
+
+        Ok(vec![exec_step])
     }
 }
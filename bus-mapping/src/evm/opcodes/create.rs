@@ -0,0 +1,601 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{
+        get_call_memory_offset_length, get_create2_address_preimage, get_create_address_preimage,
+        get_create_init_code, CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep, ExecStep,
+        NumberOrHash,
+    },
+    operation::{AccountField, AccountOp, CallContextField, MemoryOp, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{
+    evm_types::{
+        gas_utils::{initcode_word_gas_cost, memory_expansion_gas_cost},
+        GasCost,
+    },
+    GethExecStep, ToWord, Word, H256,
+};
+use ethers_core::utils::keccak256;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CREATE`](crate::evm::OpcodeId::CREATE) and
+/// [`OpcodeId::CREATE2`](crate::evm::OpcodeId::CREATE2) `OpcodeId`s.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Create<const IS_CREATE2: bool>;
+
+impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let tx_id = state.tx_ctx.id();
+        let current_call = state.call()?.clone();
+        // The contract address is derived from the caller's nonce before its
+        // increment.
+        let nonce_prev = state.sdb.get_nonce(&current_call.address);
+        let call = state.parse_call(geth_step)?;
+
+        gen_caller_ops::<IS_CREATE2>(
+            state,
+            &mut exec_step,
+            geth_step,
+            if call.is_success {
+                call.address.to_word()
+            } else {
+                Word::zero()
+            },
+        )?;
+
+        // Increase caller's nonce
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.caller_address,
+                field: AccountField::Nonce,
+                value: (nonce_prev + 1).into(),
+                value_prev: nonce_prev.into(),
+            },
+        )?;
+
+        // Add callee into access list
+        let is_warm = state.sdb.check_account_in_access_list(&call.address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: call.address,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Switch to callee's call context
+        state.push_call(call.clone(), geth_step);
+
+        // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a placeholder,
+        // and later set the proper value in
+        // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+        for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                (call.is_persistent as u64).into(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        // The callee must not have any code, otherwise it's an address
+        // collision.
+        let (_, callee_account) = state.sdb.get_account(&call.address);
+        let callee_code_hash = callee_account.code_hash.to_word();
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::CodeHash,
+            callee_code_hash,
+            callee_code_hash,
+        )?;
+
+        // Increase callee's nonce
+        let callee_nonce_prev = state.sdb.get_nonce(&call.address);
+        debug_assert!(callee_nonce_prev == 0);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Nonce,
+                value: 1.into(),
+                value_prev: 0.into(),
+            },
+        )?;
+
+        state.transfer(
+            &mut exec_step,
+            call.caller_address,
+            call.address,
+            call.value,
+        )?;
+
+        let init_code = get_create_init_code(geth_step)?.to_vec();
+        state.block.sha3_inputs.push(if IS_CREATE2 {
+            get_create2_address_preimage(
+                call.caller_address,
+                geth_step.stack.nth_last(3)?,
+                H256(keccak256(&init_code)),
+            )
+        } else {
+            get_create_address_preimage(call.caller_address, nonce_prev)
+        });
+
+        // Calculate next_memory_word_size and callee_gas_left manually in case
+        // there isn't next geth_step (e.g. initcode is empty). The offset is
+        // ignored when the initcode is empty, so it could be any word.
+        let (offset, length) = get_call_memory_offset_length(geth_step, 1)?;
+        let next_memory_word_size = if length == 0 {
+            geth_step.memory.word_size() as u64
+        } else {
+            (geth_step.memory.word_size() as u64).max((offset + length + 31) / 32)
+        };
+        let gas_cost = GasCost::CREATE.as_u64()
+            + if IS_CREATE2 {
+                GasCost::COPY_SHA3.as_u64() * ((length + 31) / 32)
+            } else {
+                0
+            }
+            + memory_expansion_gas_cost(geth_step.memory.word_size() as u64, next_memory_word_size)
+            + initcode_word_gas_cost(state.block.hardfork, length);
+        let gas_left = geth_step.gas.0 - gas_cost;
+        let callee_gas_left = gas_left - gas_left / 64;
+
+        if length == 0 {
+            // 1. Create with empty initcode.
+            for (field, value) in [
+                (CallContextField::LastCalleeId, 0.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            }
+            state.handle_return(&mut [&mut exec_step], geth_step)?;
+        } else {
+            // 2. Create with non-empty initcode.
+            let n_pop = if IS_CREATE2 { 4 } else { 3 };
+            for (field, value) in [
+                (
+                    CallContextField::ProgramCounter,
+                    (geth_step.pc.0 + 1).into(),
+                ),
+                (
+                    CallContextField::StackPointer,
+                    (geth_step.stack.stack_pointer().0 + n_pop - 1).into(),
+                ),
+                (
+                    CallContextField::GasLeft,
+                    (gas_left - callee_gas_left).into(),
+                ),
+                (CallContextField::MemorySize, next_memory_word_size.into()),
+                (
+                    CallContextField::ReversibleWriteCounter,
+                    (exec_step.reversible_write_counter + 2).into(),
+                ),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            }
+
+            for (field, value) in [
+                (CallContextField::CallerId, current_call.call_id.into()),
+                (CallContextField::TxId, tx_id.into()),
+                (CallContextField::Depth, call.depth.into()),
+                (
+                    CallContextField::CallerAddress,
+                    call.caller_address.to_word(),
+                ),
+                (CallContextField::CalleeAddress, call.address.to_word()),
+                (CallContextField::CallDataOffset, 0.into()),
+                (CallContextField::CallDataLength, 0.into()),
+                (CallContextField::ReturnDataOffset, 0.into()),
+                (CallContextField::ReturnDataLength, 0.into()),
+                (CallContextField::Value, call.value),
+                (CallContextField::IsSuccess, (call.is_success as u64).into()),
+                (CallContextField::IsStatic, 0.into()),
+                (CallContextField::LastCalleeId, 0.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+                (CallContextField::IsRoot, 0.into()),
+                (CallContextField::IsCreate, 1.into()),
+                (CallContextField::CodeHash, call.code_hash.to_word()),
+            ] {
+                state.call_context_read(&mut exec_step, call.call_id, field, value);
+            }
+
+            // The initcode is hashed by copying it from the caller's memory.
+            let copy_event = gen_copy_event(
+                state,
+                &mut exec_step,
+                current_call.call_id,
+                offset,
+                &init_code,
+            )?;
+            state.push_copy(copy_event);
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+/// Generate the operations of a CREATE or CREATE2 step that happen in the
+/// caller's call context before a callee is derived, which are shared with
+/// the step failing with an address collision: the call context reads, the
+/// stack pops and the push of `address`, and the keccak256 of the initcode.
+pub(crate) fn gen_caller_ops<const IS_CREATE2: bool>(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    geth_step: &GethExecStep,
+    address: Word,
+) -> Result<(), Error> {
+    let current_call = state.call()?.clone();
+
+    // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a placeholder,
+    // and later set the proper value in
+    // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+    for (field, value) in [
+        (CallContextField::TxId, state.tx_ctx.id().into()),
+        (CallContextField::RwCounterEndOfReversion, 0.into()),
+        (
+            CallContextField::IsPersistent,
+            (current_call.is_persistent as u64).into(),
+        ),
+        (
+            CallContextField::CalleeAddress,
+            current_call.address.to_word(),
+        ),
+        (
+            CallContextField::IsStatic,
+            (current_call.is_static as u64).into(),
+        ),
+        (CallContextField::Depth, current_call.depth.into()),
+    ] {
+        state.call_context_read(exec_step, current_call.call_id, field, value);
+    }
+
+    // value, offset, length, and salt for CREATE2
+    let n_pop = if IS_CREATE2 { 4 } else { 3 };
+    for i in 0..n_pop {
+        state.stack_read(
+            exec_step,
+            geth_step.stack.nth_last_filled(i),
+            geth_step.stack.nth_last(i)?,
+        )?;
+    }
+    state.stack_write(
+        exec_step,
+        geth_step.stack.nth_last_filled(n_pop - 1),
+        address,
+    )?;
+
+    state
+        .block
+        .sha3_inputs
+        .push(get_create_init_code(geth_step)?.to_vec());
+
+    Ok(())
+}
+
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    caller_id: usize,
+    src_addr: u64,
+    bytes: &[u8],
+) -> Vec<CopyStep> {
+    let mut steps = Vec::with_capacity(2 * bytes.len());
+    for (idx, value) in bytes.iter().copied().enumerate() {
+        let addr = src_addr + idx as u64;
+        let rwc = state.block_ctx.rwc;
+        // The current call has already been switched to the callee, so the
+        // read is pushed with the caller's call id explicitly.
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(caller_id, addr.into(), value),
+        );
+        // Read
+        steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Memory,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        steps.push(CopyStep {
+            addr: idx as u64,
+            tag: CopyDataType::RlcAcc,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+    }
+    steps
+}
+
+pub(crate) fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    caller_id: usize,
+    offset: u64,
+    bytes: &[u8],
+) -> Result<CopyEvent, Error> {
+    let length = bytes.len() as u64;
+
+    let mut steps = gen_copy_steps(state, exec_step, caller_id, offset, bytes);
+    for cs in steps.iter_mut() {
+        cs.rwc_inc_left = state.block_ctx.rwc.0 as u64 - cs.rwc.0 as u64;
+    }
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(caller_id),
+        src_addr: offset,
+        src_addr_end: offset + length,
+        dst_type: CopyDataType::RlcAcc,
+        dst_id: NumberOrHash::Number(caller_id),
+        dst_addr: 0,
+        log_id: None,
+        length,
+        steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: caller_id,
+        pc: exec_step.pc,
+    })
+}
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        ToBigEndian, Word,
+    };
+    use ethers_core::utils::{get_contract_address, get_create2_address};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    // RETURN the 4 bytes 0x6001600a of the deployed code, which are stored at
+    // the end of the first memory word.
+    fn initcode() -> Vec<u8> {
+        bytecode! {
+            PUSH4(0x6001600a)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04)
+            PUSH1(0x1c)
+            RETURN
+        }
+        .to_vec()
+    }
+
+    fn test_ok(is_create2: bool) {
+        let initcode = initcode();
+        let salt = Word::from(0xcafe);
+        let value = Word::from(0x10);
+
+        // Store the initcode at the end of the first memory word, and create
+        // the contract from it.
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+        };
+        if is_create2 {
+            code.push(32, salt);
+        }
+        code.append(&bytecode! {
+            PUSH1(initcode.len())
+            PUSH1(32 - initcode.len())
+            PUSH1(value)
+        });
+        code.write_op(if is_create2 {
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        });
+        code.append(&bytecode! { STOP });
+
+        let creator = address!("0x0000000000000000000000000000000000000010");
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(creator)
+                    .balance(Word::from(1u64 << 20))
+                    .code(code.clone());
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let call_id = transaction.calls()[0].call_id;
+        let opcode = if is_create2 {
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        };
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(opcode))
+            .unwrap();
+        let container = &builder.block.container;
+
+        let address = if is_create2 {
+            get_create2_address(creator, salt.to_be_bytes().to_vec(), initcode.clone())
+        } else {
+            get_contract_address(creator, Word::zero())
+        };
+        let callee = &transaction.calls()[1];
+        assert_eq!(callee.address, address);
+        assert!(callee.is_create());
+        assert_eq!(
+            callee.code_hash.to_word(),
+            Word::from_big_endian(&keccak256(&initcode))
+        );
+
+        // Stack write of the created address
+        let n_pop = if is_create2 { 4 } else { 3 };
+        assert_eq!(
+            {
+                let operation = &container.stack[step.bus_mapping_instance[6 + n_pop].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &StackOp::new(call_id, StackAddress::from(1023), address.to_word())
+            )
+        );
+
+        // Caller's nonce is increased, and the callee starts with nonce 1.
+        assert_eq!(
+            {
+                let operation = &container.account[step.bus_mapping_instance[7 + n_pop].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &AccountOp {
+                    address: creator,
+                    field: AccountField::Nonce,
+                    value: Word::one(),
+                    value_prev: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &container.account[step.bus_mapping_instance[12 + n_pop].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &AccountOp {
+                    address,
+                    field: AccountField::Nonce,
+                    value: Word::one(),
+                    value_prev: Word::zero(),
+                }
+            )
+        );
+
+        // The deployed code is stored in the code hash of the callee by the
+        // RETURN step.
+        let deployed_code = vec![0x60, 0x01, 0x60, 0x0a];
+        let return_step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURN))
+            .unwrap();
+        assert_eq!(
+            {
+                let operation = &container.account[return_step.bus_mapping_instance[6].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &AccountOp {
+                    address,
+                    field: AccountField::CodeHash,
+                    value: Word::from_big_endian(&keccak256(&deployed_code)),
+                    value_prev: Word::from_big_endian(&keccak256(&[])),
+                }
+            )
+        );
+        assert_eq!(
+            builder.code_db.0.get(&keccak256(&deployed_code).into()),
+            Some(&deployed_code)
+        );
+
+        // The initcode is copied from the caller's memory to be hashed, and so
+        // is the deployed code from the callee's memory.
+        let copy_events = &builder.block.copy_events;
+        assert_eq!(copy_events.len(), 2);
+        assert_eq!(copy_events[0].src_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_events[0].src_type, CopyDataType::Memory);
+        assert_eq!(copy_events[0].src_addr as usize, 32 - initcode.len());
+        assert_eq!(copy_events[0].dst_type, CopyDataType::RlcAcc);
+        assert_eq!(copy_events[0].length as usize, initcode.len());
+        assert!(builder.block.sha3_inputs.contains(&initcode));
+        assert_eq!(copy_events[1].src_type, CopyDataType::Memory);
+        assert_eq!(copy_events[1].dst_type, CopyDataType::RlcAcc);
+        assert_eq!(copy_events[1].length as usize, deployed_code.len());
+        assert!(builder.block.sha3_inputs.contains(&deployed_code));
+    }
+
+    #[test]
+    fn create_opcode_impl() {
+        test_ok(false);
+    }
+
+    #[test]
+    fn create2_opcode_impl() {
+        test_ok(true);
+    }
+
+    #[test]
+    fn create_address_preimage() {
+        let sender = address!("0x0000000000000000000000000000000000000010");
+        for nonce in [0, 1, 0x7f, 0x80, 0xff, 0x100, u64::MAX] {
+            assert_eq!(
+                get_contract_address(sender, nonce),
+                eth_types::Address::from_slice(
+                    &keccak256(&get_create_address_preimage(sender, nonce))[12..]
+                )
+            );
+        }
+        let salt = Word::from(0xcafe);
+        let initcode = initcode();
+        assert_eq!(
+            get_create2_address(sender, salt.to_be_bytes().to_vec(), initcode.clone()),
+            eth_types::Address::from_slice(
+                &keccak256(&get_create2_address_preimage(
+                    sender,
+                    salt,
+                    H256(keccak256(&initcode))
+                ))[12..]
+            )
+        );
+    }
+}
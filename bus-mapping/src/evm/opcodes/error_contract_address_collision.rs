@@ -0,0 +1,232 @@
+use super::{
+    create::{gen_caller_ops, gen_copy_event},
+    Opcode,
+};
+use crate::{
+    circuit_input_builder::{
+        get_create2_address_preimage, get_create_address_preimage, get_create_init_code,
+        CircuitInputStateRef, ExecStep,
+    },
+    error::ExecError,
+    evm::OpcodeId,
+    operation::{AccountField, AccountOp, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word, H256};
+use ethers_core::utils::keccak256;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CREATE`] and [`OpcodeId::CREATE2`]
+/// `OpcodeId`s deriving the address of an account which already has a nonce
+/// or code.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorContractAddressCollision;
+
+impl Opcode for ErrorContractAddressCollision {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::ContractAddressCollision);
+
+        let is_create2 = geth_step.op == OpcodeId::CREATE2;
+        let tx_id = state.tx_ctx.id();
+        let current_call = state.call()?.clone();
+        let nonce_prev = state.sdb.get_nonce(&current_call.address);
+        let call = state.parse_call(geth_step)?;
+
+        if is_create2 {
+            gen_caller_ops::<true>(state, &mut exec_step, geth_step, Word::zero())?;
+        } else {
+            gen_caller_ops::<false>(state, &mut exec_step, geth_step, Word::zero())?;
+        }
+
+        // The caller's nonce is increased and the colliding address is added
+        // into access list before the collision is detected.
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.caller_address,
+                field: AccountField::Nonce,
+                value: (nonce_prev + 1).into(),
+                value_prev: nonce_prev.into(),
+            },
+        )?;
+        let is_warm = state.sdb.check_account_in_access_list(&call.address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: call.address,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Read the nonce and code hash of the colliding account.
+        let (_, account) = state.sdb.get_account(&call.address);
+        let (nonce, code_hash) = (account.nonce, account.code_hash.to_word());
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::Nonce,
+            nonce,
+            nonce,
+        )?;
+        state.account_read(
+            &mut exec_step,
+            call.address,
+            AccountField::CodeHash,
+            code_hash,
+            code_hash,
+        )?;
+
+        let init_code = get_create_init_code(geth_step)?.to_vec();
+        state.block.sha3_inputs.push(if is_create2 {
+            get_create2_address_preimage(
+                call.caller_address,
+                geth_step.stack.nth_last(3)?,
+                H256(keccak256(&init_code)),
+            )
+        } else {
+            get_create_address_preimage(call.caller_address, nonce_prev)
+        });
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+        ] {
+            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        // The initcode is still hashed to derive the address of CREATE2.
+        if is_create2 && !init_code.is_empty() {
+            let copy_event = gen_copy_event(
+                state,
+                &mut exec_step,
+                current_call.call_id,
+                geth_step.stack.nth_last(1)?.as_u64(),
+                &init_code,
+            )?;
+            state.push_copy(copy_event);
+        }
+
+        // The failed callee is pushed and popped right away, since its code is
+        // never executed.
+        state.push_call(call, geth_step);
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_contract_address_collision_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::StackOp};
+    use eth_types::{
+        address, bytecode, evm_types::StackAddress, geth_types::GethData, ToBigEndian,
+    };
+    use ethers_core::utils::get_create2_address;
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_contract_address_collision_create2() {
+        // Creating twice the same contract with the same salt makes the second
+        // CREATE2 collide with the first one.
+        let initcode = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            RETURN
+        }
+        .to_vec();
+        let salt = Word::from(0xcafe);
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+        };
+        for _ in 0..2 {
+            code.append(&bytecode! {
+                PUSH32(salt)
+                PUSH1(initcode.len())
+                PUSH1(32 - initcode.len())
+                PUSH1(0x00)
+                CREATE2
+                POP
+            });
+        }
+        code.append(&bytecode! { STOP });
+
+        let creator = address!("0x0000000000000000000000000000000000000010");
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(creator)
+                    .balance(Word::from(1u64 << 20))
+                    .code(code.clone());
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let call_id = transaction.calls()[0].call_id;
+        let step = transaction
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::CREATE2))
+            .nth(1)
+            .unwrap();
+        let container = &builder.block.container;
+        assert_eq!(step.error, Some(ExecError::ContractAddressCollision));
+
+        // A zero is pushed as the result.
+        assert_eq!(
+            {
+                let operation = &container.stack[step.bus_mapping_instance[10].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &StackOp::new(call_id, StackAddress::from(1023), Word::zero())
+            )
+        );
+
+        let address = get_create2_address(creator, salt.to_be_bytes().to_vec(), initcode);
+        assert_eq!(
+            {
+                let operation = &container.account[step.bus_mapping_instance[13].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &AccountOp {
+                    address,
+                    field: AccountField::Nonce,
+                    value: Word::one(),
+                    value_prev: Word::one(),
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN)
+/// `OpcodeId` of a create returning code which starts with the byte `0xef`
+/// (EIP-3541).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorInvalidCreationCode;
+
+impl Opcode for ErrorInvalidCreationCode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::InvalidCreationCode);
+
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::IsCreate,
+            1.into(),
+        );
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        // Read the first byte of the returned code.
+        let byte = geth_step.memory.0[offset.as_usize()];
+        state.memory_read(&mut exec_step, offset.as_u64().into(), byte)?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_invalid_creation_code_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{MemoryOp, RW},
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{MemoryAddress, OpcodeId},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_invalid_creation_code_opcode_impl() {
+        // The initcode returns the single byte 0xef as the code to deploy.
+        let initcode = bytecode! {
+            PUSH1(0xef)
+            PUSH1(0x00)
+            MSTORE8
+            PUSH1(0x01)
+            PUSH1(0x00)
+            RETURN
+        }
+        .to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(initcode.len())
+            PUSH1(32 - initcode.len())
+            PUSH1(0x00)
+            CREATE
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let callee = &transaction.calls()[1];
+        assert!(!callee.is_success);
+
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURN))
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::InvalidCreationCode));
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.memory[step.bus_mapping_instance[3].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &MemoryOp::new(callee.call_id, MemoryAddress::from(0), 0xef)
+            )
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::JUMP`](crate::evm::OpcodeId::JUMP) and
+/// [`OpcodeId::JUMPI`](crate::evm::OpcodeId::JUMPI) `OpcodeId`s jumping to a
+/// destination which is not a `JUMPDEST`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorInvalidJump;
+
+impl Opcode for ErrorInvalidJump {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::InvalidJump);
+
+        // destination, and condition for JUMPI
+        let n_pops = if geth_step.op == OpcodeId::JUMPI {
+            2
+        } else {
+            1
+        };
+        for i in 0..n_pops {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_invalid_jump_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_invalid_jump_opcode_impl() {
+        let code = bytecode! {
+            PUSH1(0x01) // condition
+            PUSH1(0x05) // destination is the STOP below
+            JUMPI
+            STOP
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::JUMPI))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::InvalidJump));
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // Both stack items are popped before the root call fails.
+        assert_eq!(step.bus_mapping_instance.len(), 3);
+        assert_eq!(
+            [0, 1]
+                .map(|idx| &builder.block.container.stack
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), Word::from(0x05))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), Word::from(0x01))
+                ),
+            ]
+        );
+        let operation =
+            &builder.block.container.call_context[step.bus_mapping_instance[2].as_usize()];
+        assert_eq!(
+            (operation.rw(), operation.op()),
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::IsSuccess,
+                    value: Word::zero(),
+                }
+            )
+        );
+    }
+}
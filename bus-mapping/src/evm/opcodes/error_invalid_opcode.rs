@@ -0,0 +1,91 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to a byte of the bytecode which is not a defined opcode, or
+/// the designated invalid instruction
+/// [`OpcodeId::INVALID`](crate::evm::OpcodeId::INVALID).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorInvalidOpcode;
+
+impl Opcode for ErrorInvalidOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::InvalidOpcode);
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_invalid_opcode_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, RW},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_invalid_opcode_opcode_impl() {
+        let mut code = bytecode! {
+            PUSH1(0x01)
+        };
+        code.write(0x0f, true);
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::INVALID(0x0f)))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::InvalidOpcode));
+        // The root call only reads its failure before ending the transaction.
+        assert_eq!(step.bus_mapping_instance.len(), 1);
+        let operation =
+            &builder.block.container.call_context[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(
+            (operation.rw(), operation.op()),
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id: builder.block.txs()[0].calls()[0].call_id,
+                    field: CallContextField::IsSuccess,
+                    value: Word::zero(),
+                }
+            )
+        );
+    }
+}
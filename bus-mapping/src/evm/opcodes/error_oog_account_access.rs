@@ -0,0 +1,125 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to [`OpcodeId::BALANCE`](crate::evm::OpcodeId::BALANCE),
+/// [`OpcodeId::EXTCODESIZE`](crate::evm::OpcodeId::EXTCODESIZE) or
+/// [`OpcodeId::EXTCODEHASH`](crate::evm::OpcodeId::EXTCODEHASH) running out of
+/// gas due to the account access.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGAccountAccess;
+
+impl Opcode for ErrorOOGAccountAccess {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::AccountAccess));
+
+        let address_word = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), address_word)?;
+
+        // The account is not added to the access list when running out of
+        // gas, so whether it's warm is only read.
+        let address = address_word.to_address();
+        let tx_id = state.tx_ctx.id();
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+        let is_warm = state.sdb.check_account_in_access_list(&address);
+        state.tx_accesslist_account_read(&mut exec_step, tx_id, address, is_warm)?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_oog_account_access_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+        operation::{TxAccessListAccountOp, RW},
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_oog_account_access_opcode_impl() {
+        let cold_address = address!("0x0000000000000000000000000000000000c01d01");
+        let code = bytecode! {
+            PUSH20(cold_address.to_word())
+            BALANCE
+            STOP
+        };
+
+        // 21000 for the tx, 3 for the push, and not enough for the 2600 of
+        // the cold account access.
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000020"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(21_003 + 2_599));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BALANCE))
+            .unwrap();
+
+        assert_eq!(
+            step.error,
+            Some(ExecError::OutOfGas(OogError::AccountAccess))
+        );
+        let op = &builder.block.container.tx_access_list_account
+            [step.bus_mapping_instance[2].as_usize()];
+        assert_eq!(
+            (op.rw(), op.op()),
+            (
+                RW::READ,
+                &TxAccessListAccountOp {
+                    tx_id: 1,
+                    address: cold_address,
+                    is_warm: false,
+                    is_warm_prev: false,
+                }
+            )
+        );
+    }
+}
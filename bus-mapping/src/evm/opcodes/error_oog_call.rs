@@ -0,0 +1,164 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    evm::OpcodeId,
+    operation::{AccountField, CallContextField},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to [`OpcodeId::CALL`], [`OpcodeId::CALLCODE`],
+/// [`OpcodeId::DELEGATECALL`] or [`OpcodeId::STATICCALL`] running out of gas
+/// before the callee's gas is deducted.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGCall;
+
+impl Opcode for ErrorOOGCall {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(match geth_step.op {
+            OpcodeId::CALL => OogError::Call,
+            OpcodeId::CALLCODE => OogError::CallCode,
+            OpcodeId::DELEGATECALL => OogError::DelegateCall,
+            OpcodeId::STATICCALL => OogError::StaticCall,
+            _ => unreachable!(),
+        }));
+
+        let tx_id = state.tx_ctx.id();
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+
+        // The gas to forward doesn't affect the gas cost, so everything but
+        // it is read. Only CALL and CALLCODE have the value on stack.
+        let has_value_on_stack = matches!(geth_step.op, OpcodeId::CALL | OpcodeId::CALLCODE);
+        let n_stack_reads = 5 + has_value_on_stack as usize;
+        for i in 1..=n_stack_reads {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let callee_address = geth_step.stack.nth_last(1)?.to_address();
+        let is_warm = state.sdb.check_account_in_access_list(&callee_address);
+        state.tx_accesslist_account_read(&mut exec_step, tx_id, callee_address, is_warm)?;
+
+        // Only CALL might create a new account, which depends on whether the
+        // callee is empty.
+        if geth_step.op == OpcodeId::CALL {
+            let (_, callee_account) = state.sdb.get_account(&callee_address);
+            let callee_account = callee_account.clone();
+            for (field, value) in [
+                (AccountField::Nonce, callee_account.nonce),
+                (AccountField::Balance, callee_account.balance),
+                (AccountField::CodeHash, callee_account.code_hash.to_word()),
+            ] {
+                state.account_read(&mut exec_step, callee_address, field, value, value)?;
+            }
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_oog_call_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+        operation::{AccountField, AccountOp, RW},
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use keccak256::EMPTY_HASH;
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_oog_call_new_account_opcode_impl() {
+        let callee = address!("0x0000000000000000000000000000000000c01d01");
+        let code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x01) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        // 21000 for the tx, 21 for the pushes, and enough for the cold access
+        // and the value transfer, but not for creating the callee.
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000020"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(21_021 + 2_600 + 9_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::OutOfGas(OogError::Call)));
+        // After the tx id, the 6 stack reads and the access list read
+        assert_eq!(
+            [8, 9, 10]
+                .map(|idx| &builder.block.container.account
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op().clone())),
+            [
+                (AccountField::Nonce, Word::zero()),
+                (AccountField::Balance, Word::zero()),
+                (AccountField::CodeHash, Word::from_big_endian(&*EMPTY_HASH)),
+            ]
+            .map(|(field, value)| (
+                RW::READ,
+                AccountOp {
+                    address: callee,
+                    field,
+                    value,
+                    value_prev: value,
+                }
+            ))
+        );
+    }
+}
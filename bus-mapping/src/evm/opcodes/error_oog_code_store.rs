@@ -0,0 +1,47 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN)
+/// `OpcodeId` of a create running out of gas to deposit the returned code.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGCodeStore;
+
+impl Opcode for ErrorOOGCodeStore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::CodeStoreOutOfGas);
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        // Read the first byte of the returned code, which is checked to be
+        // valid before the code deposit is charged. The memory might not be
+        // expanded yet, which reads as zero.
+        if !length.is_zero() {
+            let byte = geth_step
+                .memory
+                .0
+                .get(offset.as_usize())
+                .copied()
+                .unwrap_or(0);
+            state.memory_read(&mut exec_step, offset.as_u64().into(), byte)?;
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,42 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    evm::OpcodeId,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to [`OpcodeId::CREATE`](crate::evm::OpcodeId::CREATE),
+/// [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN) or
+/// [`OpcodeId::REVERT`](crate::evm::OpcodeId::REVERT) running out of gas due
+/// to the memory expansion of the offset and size on stack.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGDynamicMemory;
+
+impl Opcode for ErrorOOGDynamicMemory {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::DynamicMemoryExpansion));
+
+        // CREATE has the value on top of the offset and size
+        let first = (geth_step.op == OpcodeId::CREATE) as usize;
+        for i in first..first + 2 {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
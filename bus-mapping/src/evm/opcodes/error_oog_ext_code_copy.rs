@@ -0,0 +1,52 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODECOPY`](crate::evm::OpcodeId::EXTCODECOPY) `OpcodeId`
+/// running out of gas.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGExtCodeCopy;
+
+impl Opcode for ErrorOOGExtCodeCopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::ExtCodeCopy));
+
+        // The address, the memory offset and the size, since the code offset
+        // doesn't affect the gas cost.
+        for i in [0, 1, 3] {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let address = geth_step.stack.nth_last(0)?.to_address();
+        let tx_id = state.tx_ctx.id();
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+        let is_warm = state.sdb.check_account_in_access_list(&address);
+        state.tx_accesslist_account_read(&mut exec_step, tx_id, address, is_warm)?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
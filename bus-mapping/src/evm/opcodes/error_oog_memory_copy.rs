@@ -0,0 +1,41 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to
+/// [`OpcodeId::CALLDATACOPY`](crate::evm::OpcodeId::CALLDATACOPY), [`OpcodeId::
+/// CODECOPY`](crate::evm::OpcodeId::CODECOPY) or [`OpcodeId::
+/// RETURNDATACOPY`](crate::evm::OpcodeId::RETURNDATACOPY) running out of gas
+/// due to the memory expansion and the copying.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGMemoryCopy;
+
+impl Opcode for ErrorOOGMemoryCopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::MemoryCopy));
+
+        // The memory offset and the size
+        for i in [0, 2] {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,76 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::{AccountField, CallContextField},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`
+/// running out of gas.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGSelfDestruct;
+
+impl Opcode for ErrorOOGSelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::SelfDestruct));
+
+        let beneficiary_word = geth_step.stack.last()?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        let call_id = state.call()?.call_id;
+        let callee_address = state.call()?.address;
+        let tx_id = state.tx_ctx.id();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, callee_address.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+
+        let beneficiary = beneficiary_word.to_address();
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.tx_accesslist_account_read(&mut exec_step, tx_id, beneficiary, is_warm)?;
+
+        // Creating the beneficiary costs NEW_ACCOUNT when there is balance to
+        // transfer.
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        let beneficiary_account = beneficiary_account.clone();
+        for (field, value) in [
+            (AccountField::Nonce, beneficiary_account.nonce),
+            (AccountField::Balance, beneficiary_account.balance),
+            (
+                AccountField::CodeHash,
+                beneficiary_account.code_hash.to_word(),
+            ),
+        ] {
+            state.account_read(&mut exec_step, beneficiary, field, value, value)?;
+        }
+        let (_, callee_account) = state.sdb.get_account(&callee_address);
+        let balance = callee_account.balance;
+        state.account_read(
+            &mut exec_step,
+            callee_address,
+            AccountField::Balance,
+            balance,
+            balance,
+        )?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,56 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SLOAD`](crate::evm::OpcodeId::SLOAD)
+/// `OpcodeId` running out of gas.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGSload;
+
+impl Opcode for ErrorOOGSload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::Sload));
+
+        let key = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), key)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+        let tx_id = state.tx_ctx.id();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, contract_addr.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+
+        // The slot is not added to the access list when running out of gas,
+        // so whether it's warm is only read.
+        let is_warm = state
+            .sdb
+            .check_account_storage_in_access_list(&(contract_addr, key));
+        state.tx_accesslist_account_storage_read(
+            &mut exec_step,
+            tx_id,
+            contract_addr,
+            key,
+            is_warm,
+        )?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,154 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::{CallContextField, StorageOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SSTORE`](crate::evm::OpcodeId::SSTORE)
+/// `OpcodeId` running out of gas, either for its gas cost or for not having
+/// more gas left than the sentry of EIP-2200.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGSstore;
+
+impl Opcode for ErrorOOGSstore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::Sstore));
+
+        let key = geth_step.stack.nth_last(0)?;
+        let value = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), key)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), value)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+        let tx_id = state.tx_ctx.id();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, contract_addr.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+
+        // Nothing is written when running out of gas, but the current and
+        // the original values and whether the slot is warm determine the gas
+        // cost.
+        let (_, value_prev) = state.sdb.get_storage(&contract_addr, &key);
+        let value_prev = *value_prev;
+        let (_, committed_value) = state.sdb.get_committed_storage(&contract_addr, &key);
+        let committed_value = *committed_value;
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            StorageOp::new(
+                contract_addr,
+                key,
+                value_prev,
+                value_prev,
+                tx_id,
+                committed_value,
+            ),
+        );
+
+        let is_warm = state
+            .sdb
+            .check_account_storage_in_access_list(&(contract_addr, key));
+        state.tx_accesslist_account_storage_read(
+            &mut exec_step,
+            tx_id,
+            contract_addr,
+            key,
+            is_warm,
+        )?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_oog_sstore_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+        operation::{StorageOp, RW},
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_oog_sstore_sentry_opcode_impl() {
+        let (key, value) = (Word::from(0x01), Word::from(0x02));
+        let code = bytecode! {
+            PUSH1(value)
+            PUSH1(key)
+            SSTORE
+            STOP
+        };
+
+        // 21000 for the tx, 6 for the pushes, and not more than the sentry of
+        // 2300 left for the SSTORE.
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code)
+                    .storage(vec![(key, Word::from(0x03))].into_iter());
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000020"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(21_006 + 2_300));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::OutOfGas(OogError::Sstore)));
+        let op = &builder.block.container.storage[step.bus_mapping_instance[4].as_usize()];
+        assert_eq!(
+            (op.rw(), op.op()),
+            (
+                RW::READ,
+                &StorageOp::new(
+                    address!("0x0000000000000000000000000000000000000010"),
+                    key,
+                    Word::from(0x03),
+                    Word::from(0x03),
+                    1,
+                    Word::from(0x03),
+                )
+            )
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to [`OpcodeId::MLOAD`](crate::evm::OpcodeId::MLOAD),
+/// [`OpcodeId::MSTORE`](crate::evm::OpcodeId::MSTORE) or
+/// [`OpcodeId::MSTORE8`](crate::evm::OpcodeId::MSTORE8) running out of gas due
+/// to the memory expansion.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGStaticMemory;
+
+impl Opcode for ErrorOOGStaticMemory {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::StaticMemoryExpansion));
+
+        // The memory address
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            geth_step.stack.last()?,
+        )?;
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
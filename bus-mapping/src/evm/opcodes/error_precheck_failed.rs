@@ -0,0 +1,234 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CALL`], [`OpcodeId::CALLCODE`],
+/// [`OpcodeId::DELEGATECALL`], [`OpcodeId::STATICCALL`], [`OpcodeId::CREATE`]
+/// and [`OpcodeId::CREATE2`] `OpcodeId`s failing before the callee is entered,
+/// which is by exceeding the maximum call depth when `IS_DEPTH` and by
+/// transferring more than the caller's balance otherwise.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorPrecheckFailed<const IS_DEPTH: bool>;
+
+impl<const IS_DEPTH: bool> Opcode for ErrorPrecheckFailed<IS_DEPTH> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(if IS_DEPTH {
+            ExecError::Depth
+        } else {
+            ExecError::InsufficientBalance
+        });
+
+        let tx_id = state.tx_ctx.id();
+        let current_call = state.call()?.clone();
+        let call = state.parse_call(geth_step)?;
+
+        // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a placeholder,
+        // and later set the proper value in
+        // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                (current_call.is_persistent as u64).into(),
+            ),
+            (
+                CallContextField::CalleeAddress,
+                current_call.address.to_word(),
+            ),
+            (
+                CallContextField::IsStatic,
+                (current_call.is_static as u64).into(),
+            ),
+            (CallContextField::Depth, current_call.depth.into()),
+        ] {
+            state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        let n_pop = match geth_step.op {
+            OpcodeId::CALL | OpcodeId::CALLCODE => 7,
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => 6,
+            OpcodeId::CREATE => 3,
+            OpcodeId::CREATE2 => 4,
+            _ => unreachable!(),
+        };
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(n_pop - 1),
+            Word::zero(),
+        )?;
+
+        // The caller's balance is read in both cases, for the layout of both
+        // failures to be the same.
+        let caller_balance = state.sdb.get_account(&current_call.address).1.balance;
+        state.account_read(
+            &mut exec_step,
+            current_call.address,
+            AccountField::Balance,
+            caller_balance,
+            caller_balance,
+        )?;
+
+        // The callee of a call is added into access list when the gas cost is
+        // charged, which is before the failure.
+        if !call.is_create() {
+            let code_address = geth_step.stack.nth_last(1)?.to_address();
+            let is_warm = state.sdb.check_account_in_access_list(&code_address);
+            state.push_op_reversible(
+                &mut exec_step,
+                RW::WRITE,
+                TxAccessListAccountOp {
+                    tx_id,
+                    address: code_address,
+                    is_warm: true,
+                    is_warm_prev: is_warm,
+                },
+            )?;
+
+            // Only CALL might create a new account, which is charged when the
+            // callee is empty.
+            if geth_step.op == OpcodeId::CALL {
+                let callee_account = state.sdb.get_account(&code_address).1.clone();
+                for (field, value) in [
+                    (AccountField::Nonce, callee_account.nonce),
+                    (AccountField::Balance, callee_account.balance),
+                    (AccountField::CodeHash, callee_account.code_hash.to_word()),
+                ] {
+                    state.account_read(&mut exec_step, code_address, field, value, value)?;
+                }
+            }
+        }
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, 0.into()),
+            (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+            (CallContextField::LastCalleeReturnDataLength, 0.into()),
+        ] {
+            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+        }
+
+        // The failed callee is pushed and popped right away, since its code is
+        // never executed.
+        state.push_call(call, geth_step);
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_precheck_failed_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{AccountOp, StackOp},
+    };
+    use eth_types::{address, bytecode, evm_types::StackAddress, geth_types::GethData};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_insufficient_balance_call() {
+        let caller = address!("0x0000000000000000000000000000000000000010");
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH2(0x101) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(caller)
+                    .balance(Word::from(0x100))
+                    .code(code);
+                accs[1].address(callee).code(bytecode! { STOP });
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let call_id = transaction.calls()[0].call_id;
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+        let container = &builder.block.container;
+        assert_eq!(step.error, Some(ExecError::InsufficientBalance));
+
+        // A zero is pushed as the result.
+        assert_eq!(
+            {
+                let operation = &container.stack[step.bus_mapping_instance[13].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &StackOp::new(call_id, StackAddress::from(1023), Word::zero())
+            )
+        );
+
+        // The caller's balance is read.
+        assert_eq!(
+            {
+                let operation = &container.account[step.bus_mapping_instance[14].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &AccountOp {
+                    address: caller,
+                    field: AccountField::Balance,
+                    value: Word::from(0x100),
+                    value_prev: Word::from(0x100),
+                }
+            )
+        );
+
+        // The caller frame keeps running without any return data.
+        assert_eq!(transaction.calls()[0].last_callee_id, 0);
+        assert!(!transaction.calls()[1].is_success);
+    }
+}
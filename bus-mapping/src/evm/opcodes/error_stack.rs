@@ -0,0 +1,136 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to any `OpcodeId` executed with a stack pointer out of its
+/// valid range, which is a stack overflow when `IS_OVERFLOW` and a stack
+/// underflow otherwise.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorStack<const IS_OVERFLOW: bool>;
+
+impl<const IS_OVERFLOW: bool> Opcode for ErrorStack<IS_OVERFLOW> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(if IS_OVERFLOW {
+            ExecError::StackOverflow
+        } else {
+            ExecError::StackUnderflow
+        });
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_stack_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, RW},
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_stack_underflow_opcode_impl() {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let callee_code = bytecode! {
+            SWAP5
+        };
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(callee_code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SWAP5))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::StackUnderflow));
+        // The failure of the callee is read, then the caller's context is
+        // restored, where all the gas given to the callee is consumed.
+        let call_context_ops = step
+            .bus_mapping_instance
+            .iter()
+            .map(|op_ref| {
+                let operation = &builder.block.container.call_context[op_ref.as_usize()];
+                (
+                    operation.rw(),
+                    operation.op().field.clone(),
+                    operation.op().value,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(call_context_ops.len(), 13);
+        assert_eq!(
+            call_context_ops[0],
+            (RW::READ, CallContextField::IsSuccess, Word::zero())
+        );
+        assert_eq!(
+            call_context_ops[1],
+            (
+                RW::READ,
+                CallContextField::CallerId,
+                builder.block.txs()[0].calls()[0].call_id.into()
+            )
+        );
+        let caller_gas_left = block.geth_traces[0]
+            .struct_logs
+            .iter()
+            .find(|step| step.op == OpcodeId::STOP)
+            .unwrap()
+            .gas
+            .0;
+        assert_eq!(
+            call_context_ops[7],
+            (RW::READ, CallContextField::GasLeft, caller_gas_left.into())
+        );
+    }
+}
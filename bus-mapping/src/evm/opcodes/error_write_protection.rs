@@ -0,0 +1,185 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to a state-modifying `OpcodeId` executed in a static call,
+/// i.e. SSTORE, LOG*, CREATE*, SELFDESTRUCT, and CALL with a non-zero value.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorWriteProtection;
+
+impl Opcode for ErrorWriteProtection {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::WriteProtection);
+
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::IsStatic,
+            (state.call()?.is_static as u64).into(),
+        );
+
+        // CALL is only write protected with a non-zero value
+        if geth_step.op == OpcodeId::CALL {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(2),
+                geth_step.stack.nth_last(2)?,
+            )?;
+        }
+
+        state.gen_error_halt_ops(&mut exec_step, geth_steps)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_write_protection_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+    use eth_types::{
+        address, bytecode,
+        bytecode::Bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        ToWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    fn static_call_block(callee_code: Bytecode) -> GethData {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            STATICCALL
+            STOP
+        };
+
+        TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1]
+                    .address(callee)
+                    .code(callee_code)
+                    .balance(Word::from(1u64 << 20));
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn error_write_protection_sstore() {
+        let block = static_call_block(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+        });
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::WriteProtection));
+        let call_id = builder.block.txs()[0].calls()[1].call_id;
+        assert_eq!(
+            [0, 1]
+                .map(|idx| &builder.block.container.call_context
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::IsStatic,
+                        value: Word::one(),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::IsSuccess,
+                        value: Word::zero(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_write_protection_call_with_value() {
+        let block = static_call_block(bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x01) // value
+            PUSH1(0x00) // address
+            PUSH2(0xffff) // gas
+            CALL
+        });
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::WriteProtection));
+        let call_id = builder.block.txs()[0].calls()[1].call_id;
+        let operation = &builder.block.container.stack[step.bus_mapping_instance[1].as_usize()];
+        assert_eq!(
+            (operation.rw(), operation.op()),
+            (
+                RW::READ,
+                &StackOp::new(call_id, StackAddress::from(1019), Word::one())
+            )
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, ExpEvent, ExpStep},
+    Error,
+};
+use eth_types::{GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::EXP`](crate::evm::OpcodeId::EXP)
+/// `OpcodeId`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Exponentiation;
+
+impl Opcode for Exponentiation {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let base = geth_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), base)?;
+        let exponent = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), exponent)?;
+
+        let exponentiation = geth_steps[1].stack.nth_last(0)?;
+        state.stack_write(
+            &mut exec_step,
+            geth_steps[1].stack.nth_last_filled(0),
+            exponentiation,
+        )?;
+
+        // Exponentiations by an exponent of 0 or 1 are verified directly in the
+        // EVM circuit, so only the others are recorded.
+        if exponent > Word::one() {
+            let steps = gen_exp_steps(base, exponent);
+            debug_assert_eq!(steps.last().map(|step| step.d), Some(exponentiation));
+            state.push_exponentiation(ExpEvent {
+                identifier: exec_step.rwc.0,
+                base,
+                exponent,
+                exponentiation,
+                steps,
+            });
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+/// Generate the square-and-multiply steps of `base ^ exponent (mod 2^256)`,
+/// going through the bits of the exponent from the most significant one. The
+/// exponent is expected to be greater than 1.
+fn gen_exp_steps(base: Word, exponent: Word) -> Vec<ExpStep> {
+    let mut steps = Vec::new();
+    let (mut acc, mut acc_exponent) = (base, Word::one());
+    for i in (0..exponent.bits() - 1).rev() {
+        let (d, _) = acc.overflowing_mul(acc);
+        acc_exponent = acc_exponent << 1;
+        steps.push(ExpStep {
+            a: acc,
+            b: acc,
+            d,
+            is_square: true,
+            exponent: acc_exponent,
+        });
+        acc = d;
+
+        if exponent.bit(i) {
+            let (d, _) = acc.overflowing_mul(base);
+            acc_exponent = acc_exponent + 1;
+            steps.push(ExpStep {
+                a: acc,
+                b: base,
+                d,
+                is_square: false,
+                exponent: acc_exponent,
+            });
+            acc = d;
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod exp_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+
+    fn test_ok(base: Word, exponent: Word, exponentiation: Word) {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(base)
+            EXP
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::EXP))
+            .unwrap();
+
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1022), base),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1023), exponent),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(1, StackAddress::from(1023), exponentiation),
+                ),
+            ]
+        );
+
+        let exp_events = builder.block.exp_events;
+        if exponent <= Word::one() {
+            assert!(exp_events.is_empty());
+            return;
+        }
+        assert_eq!(exp_events.len(), 1);
+        assert_eq!(exp_events[0].identifier, step.rwc.0);
+        assert_eq!(exp_events[0].base, base);
+        assert_eq!(exp_events[0].exponent, exponent);
+        assert_eq!(exp_events[0].exponentiation, exponentiation);
+        let first_step = exp_events[0].steps.first().unwrap();
+        assert_eq!((first_step.a, first_step.b), (base, base));
+        let last_step = exp_events[0].steps.last().unwrap();
+        assert_eq!(
+            (last_step.d, last_step.exponent),
+            (exponentiation, exponent)
+        );
+        for step in exp_events[0].steps.iter() {
+            assert_eq!(step.a.overflowing_mul(step.b).0, step.d);
+        }
+    }
+
+    #[test]
+    fn exp_opcode_impl() {
+        test_ok(2.into(), 0.into(), 1.into());
+        test_ok(3.into(), 1.into(), 3.into());
+        test_ok(
+            3.into(),
+            101.into(),
+            Word::from(3).overflowing_pow(101.into()).0,
+        );
+        test_ok(
+            Word::MAX,
+            Word::MAX,
+            Word::MAX, // (-1) ^ odd == -1 (mod 2^256)
+        );
+        test_ok(2.into(), 256.into(), 0.into());
+    }
+}
@@ -0,0 +1,384 @@
+use super::{extcodesize::external_code, Opcode};
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep, ExecStep, NumberOrHash,
+    },
+    operation::{CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{Bytecode, GethExecStep, ToAddress, ToWord, H256, U256};
+use ethers_core::utils::keccak256;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Extcodecopy;
+
+impl Opcode for Extcodecopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let external_address = geth_step.stack.nth_last(0)?.to_address();
+        let dst_offset = geth_step.stack.nth_last(1)?;
+        let code_offset = geth_step.stack.nth_last(2)?;
+        let length = geth_step.stack.nth_last(3)?;
+
+        // stack reads
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(0),
+            external_address.to_word(),
+        )?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(1),
+            dst_offset,
+        )?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(2),
+            code_offset,
+        )?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(3), length)?;
+
+        // Read transaction id, rw_counter_end_of_reversion, and is_persistent from call
+        // context
+        for (field, value) in [
+            (CallContextField::TxId, U256::from(state.tx_ctx.id())),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                U256::from(state.call()?.rw_counter_end_of_reversion as u64),
+            ),
+            (
+                CallContextField::IsPersistent,
+                U256::from(state.call()?.is_persistent as u64),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, state.call()?.call_id, field, value);
+        }
+
+        // Update transaction access list for external_address
+        let is_warm = state.sdb.check_account_in_access_list(&external_address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: external_address,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Read the code hash of the external account, whose code is copied
+        // to memory.
+        let code = external_code(state, &mut exec_step, external_address)?;
+
+        let length = length.as_u64();
+        if length > 0 {
+            // Only zeros are copied from beyond the code, so the code offset is
+            // capped to the code size, which also keeps it within u64.
+            let code_offset = code_offset.min(U256::from(code.len())).as_u64();
+            let copy_event = gen_copy_event(
+                state,
+                &mut exec_step,
+                code_offset,
+                dst_offset.as_u64(),
+                length,
+                code,
+            )?;
+            state.push_copy(copy_event);
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    dst_addr: u64,
+    bytes_left: u64,
+    src_addr_end: u64,
+    bytecode: &Bytecode,
+) -> Result<Vec<CopyStep>, Error> {
+    let mut steps = Vec::with_capacity(2 * bytes_left as usize);
+    for idx in 0..bytes_left {
+        let addr = src_addr + idx;
+        let (value, is_code, is_pad) = if addr < src_addr_end {
+            bytecode
+                .get(addr as usize)
+                .map_or((0, None, true), |e| (e.value, Some(e.is_code), false))
+        } else {
+            (0, None, true)
+        };
+        // Read
+        steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Bytecode,
+            rw: RW::READ,
+            value,
+            is_code,
+            is_pad,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: bytes_left - idx,
+        });
+        // Write
+        steps.push(CopyStep {
+            addr: dst_addr + idx,
+            tag: CopyDataType::Memory,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: bytes_left - idx,
+        });
+        state.memory_write(exec_step, (dst_addr + idx).into(), value)?;
+    }
+    Ok(steps)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    code_offset: u64,
+    dst_offset: u64,
+    length: u64,
+    code: Vec<u8>,
+) -> Result<CopyEvent, Error> {
+    let code_hash = H256(keccak256(&code));
+    let bytecode: Bytecode = code.into();
+    let src_addr_end = bytecode.to_vec().len() as u64;
+
+    let copy_steps = gen_copy_steps(
+        state,
+        exec_step,
+        code_offset,
+        dst_offset,
+        length,
+        src_addr_end,
+        &bytecode,
+    )?;
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Bytecode,
+        src_id: NumberOrHash::Hash(code_hash),
+        src_addr: code_offset,
+        src_addr_end,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(state.call()?.call_id),
+        dst_addr: dst_offset,
+        log_id: None,
+        length,
+        steps: copy_steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: state.call()?.call_id,
+        pc: exec_step.pc,
+    })
+}
+
+#[cfg(test)]
+mod extcodecopy_tests {
+    use super::*;
+    use crate::circuit_input_builder::ExecState;
+    use crate::mock::BlockData;
+    use crate::operation::{AccountField, AccountOp, MemoryOp, StackOp};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cold_empty_account() {
+        test_ok(false, false, 0x00, 0x00, 0x20);
+    }
+
+    #[test]
+    fn warm_existing_account() {
+        test_ok(true, true, 0x00, 0x00, 0x20);
+    }
+
+    #[test]
+    fn cold_existing_account_out_of_bound() {
+        test_ok(true, false, 0x20, 0x04, 0x40);
+    }
+
+    fn test_ok(exists: bool, is_warm: bool, dst_offset: usize, code_offset: usize, size: usize) {
+        let external_address = address!("0xaabbccddee000000000000000000000000000000");
+
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                PUSH20(external_address.to_word())
+                EXTCODESIZE
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH32(size)
+            PUSH32(code_offset)
+            PUSH32(dst_offset)
+            PUSH20(external_address.to_word())
+            EXTCODECOPY
+            STOP
+        });
+        let external_code = if exists {
+            bytecode! {
+                PUSH1(0x01)
+                PUSH1(0x02)
+                ADD
+                STOP
+            }
+        } else {
+            Bytecode::default()
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code.clone());
+                if exists {
+                    accs[1]
+                        .address(external_address)
+                        .code(external_code.clone());
+                } else {
+                    accs[1]
+                        .address(address!("0x0000000000000000000000000000000000000020"))
+                        .balance(Word::from(1u64 << 20));
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_id = 1;
+        let transaction = &builder.block.txs()[tx_id - 1];
+        let call_id = transaction.calls()[0].call_id;
+
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::EXTCODECOPY))
+            .unwrap();
+        let container = &builder.block.container;
+
+        assert_eq!(
+            [0, 1, 2, 3]
+                .map(|idx| &container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(
+                        call_id,
+                        StackAddress::from(1020),
+                        external_address.to_word()
+                    ),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1021), Word::from(dst_offset)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), Word::from(code_offset)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), Word::from(size)),
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &container.tx_access_list_account[step.bus_mapping_instance[7].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &TxAccessListAccountOp {
+                    tx_id,
+                    address: external_address,
+                    is_warm: true,
+                    is_warm_prev: is_warm
+                }
+            )
+        );
+        let code_hash = Word::from(keccak256(&external_code.to_vec()));
+        assert_eq!(
+            {
+                let operation = &container.account[step.bus_mapping_instance[8].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &AccountOp {
+                    address: external_address,
+                    field: AccountField::CodeHash,
+                    value: code_hash,
+                    value_prev: code_hash,
+                }
+            )
+        );
+
+        // RW table memory writes.
+        let code_bytes = external_code.to_vec();
+        assert_eq!(
+            (0..size)
+                .map(|idx| &container.memory[step.bus_mapping_instance[9 + idx].as_usize()])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, MemoryOp)>>(),
+            (0..size)
+                .map(|idx| {
+                    (
+                        RW::WRITE,
+                        MemoryOp::new(
+                            call_id,
+                            MemoryAddress::from(dst_offset + idx),
+                            code_bytes.get(code_offset + idx).copied().unwrap_or(0),
+                        ),
+                    )
+                })
+                .collect::<Vec<(RW, MemoryOp)>>(),
+        );
+
+        let copy_events = &builder.block.copy_events;
+        assert_eq!(copy_events.len(), 1);
+        assert_eq!(copy_events[0].steps.len(), 2 * size);
+        assert_eq!(
+            copy_events[0].src_id,
+            NumberOrHash::Hash(H256(keccak256(&code_bytes)))
+        );
+        assert_eq!(copy_events[0].src_type, CopyDataType::Bytecode);
+        assert_eq!(copy_events[0].src_addr as usize, code_offset);
+        assert_eq!(copy_events[0].src_addr_end as usize, code_bytes.len());
+        assert_eq!(copy_events[0].dst_type, CopyDataType::Memory);
+        assert_eq!(copy_events[0].dst_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_events[0].dst_addr as usize, dst_offset);
+        assert_eq!(copy_events[0].length as usize, size);
+    }
+}
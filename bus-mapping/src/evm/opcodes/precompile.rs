@@ -0,0 +1,501 @@
+use crate::{
+    circuit_input_builder::{
+        Call, CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep, ExecStep, NumberOrHash,
+        PrecompileEvent,
+    },
+    operation::{CallContextField, MemoryOp, RW},
+    precompile::PrecompileCalls,
+    Error,
+};
+use eth_types::{evm_types::GasCost, GethExecStep};
+
+/// Generate the step of the execution of a precompiled contract, which follows
+/// `call_step`, the step of the call to the precompiled contract or the BeginTx
+/// step of a transaction to it. `geth_step_next` is the caller's step after the
+/// call, which is `None` for a transaction to the precompiled contract.
+pub(crate) fn gen_precompile_ops(
+    state: &mut CircuitInputStateRef,
+    call_step: &mut ExecStep,
+    precompile: PrecompileCalls,
+    gas_left: u64,
+    geth_step_next: Option<&GethExecStep>,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_precompile_step(precompile, gas_left)?;
+    let call = state.call()?.clone();
+
+    // The call data is padded with zeros or truncated to the length of the
+    // input the precompiled contract operates on.
+    let mut input = state.call_ctx()?.call_data.clone();
+    input.resize(precompile.input_length(call.call_data_length) as usize, 0);
+
+    // A failed call, e.g. running out of gas or with an invalid input,
+    // consumes all the gas given to it and has no output.
+    let output = if call.is_success {
+        exec_step.gas_cost = GasCost(precompile.gas_cost(&input));
+        precompile
+            .execute(&input)
+            .ok_or(Error::InvalidGethExecTrace(
+                "successful call to a precompiled contract with an invalid input",
+            ))?
+    } else {
+        exec_step.gas_cost = GasCost(gas_left);
+        Vec::new()
+    };
+
+    for (field, value) in [
+        (CallContextField::TxId, state.tx_ctx.id().into()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+        (CallContextField::IsSuccess, (call.is_success as u64).into()),
+    ] {
+        state.call_context_read(&mut exec_step, call.call_id, field, value);
+    }
+
+    // Restore caller's context
+    if !call.is_root {
+        for (field, value) in [
+            (
+                CallContextField::ReturnDataOffset,
+                call.return_data_offset.into(),
+            ),
+            (
+                CallContextField::ReturnDataLength,
+                call.return_data_length.into(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let geth_step_next =
+            geth_step_next.expect("caller of precompiled should have a step after the call");
+        let caller_gas_left = geth_step_next.gas.0 - (exec_step.gas_left.0 - exec_step.gas_cost.0);
+        state.gen_restore_context_ops_to(
+            &mut exec_step,
+            geth_step_next,
+            caller_gas_left,
+            call.call_id,
+            0,
+            output.len() as u64,
+        )?;
+    }
+
+    // The copy steps are done after all the other operations of this step.
+    if call.is_success {
+        if !input.is_empty() {
+            let copy_event = gen_input_copy_event(state, &mut exec_step, &call, &input)?;
+            state.push_copy(copy_event);
+        }
+        if !output.is_empty() {
+            let copy_event = gen_output_copy_event(state, &mut exec_step, &call, &output)?;
+            state.push_copy(copy_event);
+        }
+        let copy_length = (call.return_data_length as usize).min(output.len());
+        if !call.is_root && copy_length > 0 {
+            let copy_event =
+                gen_return_copy_event(state, &mut exec_step, &call, &output[..copy_length])?;
+            state.push_copy(copy_event);
+        }
+
+        state.push_precompile_event(PrecompileEvent {
+            precompile,
+            input,
+            output: output.clone(),
+        });
+    }
+
+    state.handle_precompile_return(&mut [call_step, &mut exec_step], output)?;
+    Ok(exec_step)
+}
+
+/// Generate the copy event of the input of the precompiled contract into the
+/// accumulator of its random linear combination. The input is read from the
+/// caller's memory, or the transaction's call data for a root call, and padded
+/// with zeros.
+fn gen_input_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    input: &[u8],
+) -> Result<CopyEvent, Error> {
+    let (src_type, src_id) = if call.is_root {
+        (
+            CopyDataType::TxCalldata,
+            NumberOrHash::Number(state.tx_ctx.id()),
+        )
+    } else {
+        (CopyDataType::Memory, NumberOrHash::Number(call.caller_id))
+    };
+    let src_addr = call.call_data_offset;
+    let src_addr_end = call.call_data_offset + call.call_data_length;
+
+    let mut copy_steps = Vec::with_capacity(2 * input.len());
+    for (idx, value) in input.iter().copied().enumerate() {
+        let addr = src_addr + idx as u64;
+        let rwc = state.block_ctx.rwc;
+        let is_pad = addr >= src_addr_end;
+        if !is_pad && !call.is_root {
+            state.push_op(
+                exec_step,
+                RW::READ,
+                MemoryOp::new(call.caller_id, addr.into(), value),
+            );
+        }
+        // Read
+        copy_steps.push(CopyStep {
+            addr,
+            tag: src_type,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        copy_steps.push(CopyStep {
+            addr: idx as u64,
+            tag: CopyDataType::RlcAcc,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+    }
+
+    Ok(gen_copy_event(
+        state,
+        exec_step,
+        call,
+        (src_type, src_id, src_addr, src_addr_end),
+        (CopyDataType::RlcAcc, NumberOrHash::Number(call.call_id), 0),
+        copy_steps,
+    ))
+}
+
+/// Generate the copy event of the output of the precompiled contract from the
+/// accumulator of its random linear combination into the precompiled
+/// contract's memory, which is the return data of the call.
+fn gen_output_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    output: &[u8],
+) -> Result<CopyEvent, Error> {
+    let mut copy_steps = Vec::with_capacity(2 * output.len());
+    for (idx, value) in output.iter().copied().enumerate() {
+        let addr = idx as u64;
+        // Read
+        copy_steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::RlcAcc,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        copy_steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Memory,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+        state.memory_write(exec_step, addr.into(), value)?;
+    }
+
+    Ok(gen_copy_event(
+        state,
+        exec_step,
+        call,
+        (
+            CopyDataType::RlcAcc,
+            NumberOrHash::Number(call.call_id),
+            0,
+            output.len() as u64,
+        ),
+        (CopyDataType::Memory, NumberOrHash::Number(call.call_id), 0),
+        copy_steps,
+    ))
+}
+
+/// Generate the copy event of the return data of the precompiled contract from
+/// its memory into the caller's memory.
+fn gen_return_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    bytes: &[u8],
+) -> Result<CopyEvent, Error> {
+    let dst_addr = call.return_data_offset;
+
+    let mut copy_steps = Vec::with_capacity(2 * bytes.len());
+    for (idx, value) in bytes.iter().copied().enumerate() {
+        let addr = idx as u64;
+        let rwc = state.block_ctx.rwc;
+        state.memory_read(exec_step, addr.into(), value)?;
+        // Read
+        copy_steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Memory,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        copy_steps.push(CopyStep {
+            addr: dst_addr + addr,
+            tag: CopyDataType::Memory,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+        // The write is pushed with the caller's call id explicitly, since the
+        // current call is still the precompiled contract.
+        state.push_op(
+            exec_step,
+            RW::WRITE,
+            MemoryOp::new(call.caller_id, (dst_addr + addr).into(), value),
+        );
+    }
+
+    Ok(gen_copy_event(
+        state,
+        exec_step,
+        call,
+        (
+            CopyDataType::Memory,
+            NumberOrHash::Number(call.call_id),
+            0,
+            bytes.len() as u64,
+        ),
+        (
+            CopyDataType::Memory,
+            NumberOrHash::Number(call.caller_id),
+            dst_addr,
+        ),
+        copy_steps,
+    ))
+}
+
+/// Generate a copy event of the precompiled contract's step, given its source
+/// `(type, id, addr, addr_end)`, destination `(type, id, addr)` and steps.
+fn gen_copy_event(
+    state: &CircuitInputStateRef,
+    exec_step: &ExecStep,
+    call: &Call,
+    (src_type, src_id, src_addr, src_addr_end): (CopyDataType, NumberOrHash, u64, u64),
+    (dst_type, dst_id, dst_addr): (CopyDataType, NumberOrHash, u64),
+    mut steps: Vec<CopyStep>,
+) -> CopyEvent {
+    for cs in steps.iter_mut() {
+        cs.rwc_inc_left = state.block_ctx.rwc.0 as u64 - cs.rwc.0 as u64;
+    }
+
+    CopyEvent {
+        src_type,
+        src_id,
+        src_addr,
+        src_addr_end,
+        dst_type,
+        dst_id,
+        dst_addr,
+        log_id: None,
+        length: steps.len() as u64 / 2,
+        steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: call.call_id,
+        pc: exec_step.pc,
+    }
+}
+
+#[cfg(test)]
+mod precompile_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn precompile_identity_copies_return_data() {
+        let caller_code = bytecode! {
+            PUSH32(Word::from(0xdeadbeefu64))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04) // return data length
+            PUSH1(0x20) // return data offset
+            PUSH1(0x04) // call data length
+            PUSH1(0x1c) // call data offset
+            PUSH1(0x00) // value
+            PUSH1(0x04) // address of identity
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(PrecompileCalls::Identity.address());
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let call_idx = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+        let step = &steps[call_idx + 1];
+        assert_eq!(
+            step.exec_state,
+            ExecState::Precompile(PrecompileCalls::Identity)
+        );
+        assert_eq!(
+            step.gas_cost.as_u64(),
+            GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64()
+        );
+
+        let data = vec![0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            builder.block.precompile_events,
+            vec![PrecompileEvent {
+                precompile: PrecompileCalls::Identity,
+                input: data.clone(),
+                output: data,
+            }]
+        );
+
+        // The input is copied into the accumulator, the output into the memory
+        // of the precompiled contract and then into the caller's memory.
+        let copy_events = &builder.block.copy_events;
+        assert_eq!(
+            copy_events
+                .iter()
+                .map(|copy_event| (
+                    copy_event.src_type,
+                    copy_event.src_addr,
+                    copy_event.dst_type,
+                    copy_event.dst_addr,
+                    copy_event.length
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (CopyDataType::Memory, 0x1c, CopyDataType::RlcAcc, 0, 4),
+                (CopyDataType::RlcAcc, 0, CopyDataType::Memory, 0, 4),
+                (CopyDataType::Memory, 0, CopyDataType::Memory, 0x20, 4),
+            ]
+        );
+
+        let caller = &builder.block.txs()[0].calls()[0];
+        assert_eq!(caller.last_callee_return_data_length, 4);
+    }
+
+    #[test]
+    fn precompile_sha256_returns_hash() {
+        let caller_code = bytecode! {
+            PUSH1(0x20) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x20) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH1(0x02) // address of sha256
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(PrecompileCalls::Sha256.address());
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let call_idx = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+        let step = &steps[call_idx + 1];
+        assert_eq!(
+            step.exec_state,
+            ExecState::Precompile(PrecompileCalls::Sha256)
+        );
+        assert_eq!(
+            step.gas_cost.as_u64(),
+            GasCost::PRECOMPILE_SHA256_BASE.as_u64() + GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64()
+        );
+
+        // SHA-256 of 32 zero bytes
+        let input = vec![0; 32];
+        let output =
+            hex::decode("66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925")
+                .unwrap();
+        assert_eq!(
+            builder.block.precompile_events,
+            vec![PrecompileEvent {
+                precompile: PrecompileCalls::Sha256,
+                input,
+                output,
+            }]
+        );
+
+        let caller = &builder.block.txs()[0].calls()[0];
+        assert_eq!(caller.last_callee_return_data_length, 32);
+    }
+}
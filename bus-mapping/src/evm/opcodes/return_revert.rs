@@ -0,0 +1,309 @@
+use super::{create, Opcode};
+use crate::circuit_input_builder::{
+    get_call_memory_offset_length, CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep,
+    ExecStep, NumberOrHash,
+};
+use crate::operation::{AccountField, AccountOp, CallContextField, MemoryOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN)
+/// and [`OpcodeId::REVERT`](crate::evm::OpcodeId::REVERT) `OpcodeId`s.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReturnRevert;
+
+impl Opcode for ReturnRevert {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        let call = state.call()?.clone();
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::IsSuccess,
+            (call.is_success as u64).into(),
+        );
+
+        let (offset, length) = get_call_memory_offset_length(geth_step, 0)?;
+        let return_data = geth_step.memory.read_chunk(offset.into(), length.into());
+
+        // Store the deployed code of a successful create
+        let is_contract_deployment = call.is_create() && call.is_success;
+        if is_contract_deployment {
+            for (field, value) in [
+                (CallContextField::CalleeAddress, call.address.to_word()),
+                (
+                    CallContextField::RwCounterEndOfReversion,
+                    call.rw_counter_end_of_reversion.into(),
+                ),
+                (
+                    CallContextField::IsPersistent,
+                    (call.is_persistent as u64).into(),
+                ),
+            ] {
+                state.call_context_read(&mut exec_step, call.call_id, field, value);
+            }
+
+            let code_hash = state.code_db.insert(return_data.clone());
+            let (_, callee_account) = state.sdb.get_account(&call.address);
+            let code_hash_prev = callee_account.code_hash;
+            state.push_op_reversible(
+                &mut exec_step,
+                RW::WRITE,
+                AccountOp {
+                    address: call.address,
+                    field: AccountField::CodeHash,
+                    value: code_hash.to_word(),
+                    value_prev: code_hash_prev.to_word(),
+                },
+            )?;
+            state.block.sha3_inputs.push(return_data.clone());
+
+            // The deployed code must not start with 0xef (EIP-3541).
+            if length > 0 {
+                state.memory_read(&mut exec_step, offset.into(), return_data[0])?;
+            }
+        }
+
+        // Copy the return data into caller's memory
+        let is_return_data_copy = !call.is_create() && !call.is_root;
+        if is_return_data_copy {
+            for (field, value) in [
+                (
+                    CallContextField::ReturnDataOffset,
+                    call.return_data_offset.into(),
+                ),
+                (
+                    CallContextField::ReturnDataLength,
+                    call.return_data_length.into(),
+                ),
+            ] {
+                state.call_context_read(&mut exec_step, call.call_id, field, value);
+            }
+        }
+
+        // Restore caller's context
+        if !call.is_root {
+            state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        }
+
+        // The copy steps are done after all the other operations of this step.
+        if is_contract_deployment && length > 0 {
+            let copy_event =
+                create::gen_copy_event(state, &mut exec_step, call.call_id, offset, &return_data)?;
+            state.push_copy(copy_event);
+        }
+        if is_return_data_copy {
+            let copy_length = length.min(call.return_data_length) as usize;
+            if copy_length > 0 {
+                let copy_event = gen_copy_event(
+                    state,
+                    &mut exec_step,
+                    offset,
+                    call.return_data_offset,
+                    &return_data[..copy_length],
+                )?;
+                state.push_copy(copy_event);
+            }
+        }
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    dst_addr: u64,
+    bytes: &[u8],
+) -> Result<Vec<CopyStep>, Error> {
+    let caller_id = state.call()?.caller_id;
+
+    let mut copy_steps = Vec::with_capacity(2 * bytes.len());
+    for (idx, value) in bytes.iter().copied().enumerate() {
+        let idx = idx as u64;
+        let rwc = state.block_ctx.rwc;
+        state.memory_read(exec_step, (src_addr + idx).into(), value)?;
+        // Read
+        copy_steps.push(CopyStep {
+            addr: src_addr + idx,
+            tag: CopyDataType::Memory,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        copy_steps.push(CopyStep {
+            addr: dst_addr + idx,
+            tag: CopyDataType::Memory,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+        // The write is pushed with the caller's call id explicitly, since the
+        // current call is still the callee.
+        state.push_op(
+            exec_step,
+            RW::WRITE,
+            MemoryOp::new(caller_id, (dst_addr + idx).into(), value),
+        );
+    }
+
+    for cs in copy_steps.iter_mut() {
+        cs.rwc_inc_left = state.block_ctx.rwc.0 as u64 - cs.rwc.0 as u64;
+    }
+
+    Ok(copy_steps)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    dst_addr: u64,
+    bytes: &[u8],
+) -> Result<CopyEvent, Error> {
+    let length = bytes.len() as u64;
+    let copy_steps = gen_copy_steps(state, exec_step, src_addr, dst_addr, bytes)?;
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(state.call()?.call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(state.call()?.caller_id),
+        dst_addr,
+        log_id: None,
+        length,
+        steps: copy_steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: state.call()?.call_id,
+        pc: exec_step.pc,
+    })
+}
+
+#[cfg(test)]
+mod return_revert_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::Target};
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn return_revert_copy_to_caller_memory() {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let callee_code = bytecode! {
+            PUSH32(Word::from(0xdeadbeefu64))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x08)
+            PUSH1(0x1c)
+            REVERT
+        };
+        let caller_code = bytecode! {
+            PUSH1(0x04) // return data length
+            PUSH1(0x20) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(callee_code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::REVERT))
+            .unwrap();
+        let callee_call = &builder.block.txs()[0].calls()[1];
+        let caller_call = &builder.block.txs()[0].calls()[0];
+
+        // Only the first 4 bytes of the 8 returned bytes are copied into the
+        // caller's memory, as requested by the caller.
+        let memory_ops = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Memory)
+            .map(|op_ref| {
+                let operation = &builder.block.container.memory[op_ref.as_usize()];
+                (operation.rw(), operation.op().clone())
+            })
+            .collect::<Vec<_>>();
+        let return_data = [0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            memory_ops,
+            return_data
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, value)| [
+                    (
+                        RW::READ,
+                        MemoryOp::new(callee_call.call_id, (0x1c + idx).into(), *value)
+                    ),
+                    (
+                        RW::WRITE,
+                        MemoryOp::new(caller_call.call_id, (0x20 + idx).into(), *value)
+                    ),
+                ])
+                .collect::<Vec<_>>()
+        );
+
+        let copy_events = builder.block.copy_events;
+        assert_eq!(copy_events.len(), 1);
+        assert_eq!(
+            copy_events[0].src_id,
+            NumberOrHash::Number(callee_call.call_id)
+        );
+        assert_eq!(
+            copy_events[0].dst_id,
+            NumberOrHash::Number(caller_call.call_id)
+        );
+        assert_eq!(copy_events[0].length, 4);
+    }
+}
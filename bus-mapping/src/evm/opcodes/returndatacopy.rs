@@ -0,0 +1,347 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep, ExecStep, NumberOrHash,
+};
+use crate::operation::{CallContextField, MemoryOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Returndatacopy;
+
+impl Opcode for Returndatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let dst_offset = geth_step.stack.nth_last(0)?;
+        let data_offset = geth_step.stack.nth_last(1)?;
+        let length = geth_step.stack.nth_last(2)?;
+
+        // stack reads
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(0),
+            dst_offset,
+        )?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(1),
+            data_offset,
+        )?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
+
+        // Read the last callee information from call context
+        let call = state.call()?.clone();
+        for (field, value) in [
+            (CallContextField::LastCalleeId, call.last_callee_id.into()),
+            (
+                CallContextField::LastCalleeReturnDataOffset,
+                call.last_callee_return_data_offset.into(),
+            ),
+            (
+                CallContextField::LastCalleeReturnDataLength,
+                call.last_callee_return_data_length.into(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let length = length.as_u64();
+        if length > 0 {
+            let copy_event = gen_copy_event(
+                state,
+                &mut exec_step,
+                dst_offset.as_u64(),
+                data_offset.as_u64(),
+                length,
+            )?;
+            state.push_copy(copy_event);
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    dst_addr: u64,
+    bytes_left: u64,
+) -> Result<Vec<CopyStep>, Error> {
+    let last_callee_id = state.call()?.last_callee_id;
+    let return_data_offset = state.call()?.last_callee_return_data_offset;
+
+    let mut copy_steps = Vec::with_capacity(2 * bytes_left as usize);
+    for idx in 0..bytes_left {
+        let addr = src_addr + idx;
+        let rwc = state.block_ctx.rwc;
+        // RETURNDATACOPY never reads out of bound of the return data, which
+        // is checked in advance, so there is no padding.
+        let value = state.call_ctx()?.return_data[(addr - return_data_offset) as usize];
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(last_callee_id, addr.into(), value),
+        );
+        // Read
+        copy_steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Memory,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        copy_steps.push(CopyStep {
+            addr: dst_addr + idx,
+            tag: CopyDataType::Memory,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+        state.memory_write(exec_step, (dst_addr + idx).into(), value)?;
+    }
+
+    for cs in copy_steps.iter_mut() {
+        cs.rwc_inc_left = state.block_ctx.rwc.0 as u64 - cs.rwc.0 as u64;
+    }
+
+    Ok(copy_steps)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    dst_offset: u64,
+    data_offset: u64,
+    length: u64,
+) -> Result<CopyEvent, Error> {
+    let return_data_offset = state.call()?.last_callee_return_data_offset;
+    let return_data_length = state.call()?.last_callee_return_data_length;
+    let (src_addr, src_addr_end) = (
+        return_data_offset + data_offset,
+        return_data_offset + return_data_length,
+    );
+
+    let copy_steps = gen_copy_steps(state, exec_step, src_addr, dst_offset, length)?;
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(state.call()?.last_callee_id),
+        src_addr,
+        src_addr_end,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(state.call()?.call_id),
+        dst_addr: dst_offset,
+        log_id: None,
+        length,
+        steps: copy_steps,
+        tx_id: state.tx_ctx.id(),
+        call_id: state.call()?.call_id,
+        pc: exec_step.pc,
+    })
+}
+
+#[cfg(test)]
+mod returndatacopy_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextOp, StackOp},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        ToWord, Word,
+    };
+    use mock::test_ctx::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returndatacopy_opcode_impl() {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        // code B returns 0x20 bytes of its memory, starting at 0x10.
+        let return_data_offset = 0x10usize;
+        let return_data_size = 0x20usize;
+        let pushdata = hex::decode("1234567890abcdef").unwrap();
+        let memory_b = std::iter::repeat(0)
+            .take(24)
+            .chain(pushdata.clone())
+            .chain(std::iter::repeat(0).take(0x20))
+            .collect::<Vec<u8>>();
+        let code_b = bytecode! {
+            PUSH8(Word::from_big_endian(&pushdata))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(return_data_size)
+            PUSH1(return_data_offset)
+            RETURN
+        };
+
+        // code A calls code B and copies part of the returned data.
+        let dst_offset = 0x00usize;
+        let offset = 0x04usize;
+        let copy_size = 0x10usize;
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH1(copy_size)  // size
+            PUSH1(offset)     // offset
+            PUSH1(dst_offset) // dst_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURNDATACOPY))
+            .unwrap();
+
+        let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+        let callee_id = builder.block.txs()[0].calls()[1].call_id;
+        let container = &builder.block.container;
+
+        // 3 stack reads + 3 call context reads + copy_size memory reads and writes.
+        assert_eq!(step.bus_mapping_instance.len(), 6 + 2 * copy_size);
+
+        // 3 stack reads.
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1020), Word::from(dst_offset))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1021), Word::from(offset))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), Word::from(copy_size))
+                ),
+            ]
+        );
+
+        // 3 call context reads.
+        assert_eq!(
+            [3, 4, 5]
+                .map(|idx| &container.call_context[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::LastCalleeId,
+                        value: Word::from(callee_id),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::LastCalleeReturnDataOffset,
+                        value: Word::from(return_data_offset),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::LastCalleeReturnDataLength,
+                        value: Word::from(return_data_size),
+                    }
+                ),
+            ]
+        );
+
+        // Memory reads from the callee and writes to the caller.
+        let src_addr = return_data_offset + offset;
+        assert_eq!(
+            (0..copy_size)
+                .map(|idx| {
+                    [6 + 2 * idx, 7 + 2 * idx]
+                        .map(|i| &container.memory[step.bus_mapping_instance[i].as_usize()])
+                        .map(|op| (op.rw(), op.op().clone()))
+                })
+                .collect::<Vec<_>>(),
+            (0..copy_size)
+                .map(|idx| {
+                    let value = memory_b[src_addr + idx];
+                    [
+                        (
+                            RW::READ,
+                            MemoryOp::new(callee_id, MemoryAddress::from(src_addr + idx), value),
+                        ),
+                        (
+                            RW::WRITE,
+                            MemoryOp::new(call_id, MemoryAddress::from(dst_offset + idx), value),
+                        ),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let copy_events = &builder.block.copy_events;
+        assert_eq!(copy_events.len(), 1);
+        assert_eq!(copy_events[0].src_type, CopyDataType::Memory);
+        assert_eq!(copy_events[0].src_id, NumberOrHash::Number(callee_id));
+        assert_eq!(copy_events[0].src_addr as usize, src_addr);
+        assert_eq!(
+            copy_events[0].src_addr_end as usize,
+            return_data_offset + return_data_size
+        );
+        assert_eq!(copy_events[0].dst_type, CopyDataType::Memory);
+        assert_eq!(copy_events[0].dst_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_events[0].dst_addr as usize, dst_offset);
+        assert_eq!(copy_events[0].length as usize, copy_size);
+        assert_eq!(copy_events[0].steps.len(), 2 * copy_size);
+    }
+}
@@ -0,0 +1,257 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{
+        AccountDestructedOp, AccountField, AccountOp, CallContextField, TxAccessListAccountOp,
+        TxRefundOp, RW,
+    },
+    Error,
+};
+use eth_types::{evm_types::gas_utils::selfdestruct_refund, GethExecStep, ToAddress, ToWord, U256};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+/// The whole balance is transferred to the beneficiary, and the account is
+/// marked as destructed, which is applied at the end of the transaction.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let call = state.call()?.clone();
+        let tx_id = state.tx_ctx.id();
+
+        // Pop beneficiary address off stack
+        let beneficiary = geth_step.stack.last()?.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary.to_word(),
+        )?;
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::IsStatic, (call.is_static as u64).into()),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                (call.rw_counter_end_of_reversion as u64).into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                (call.is_persistent as u64).into(),
+            ),
+            (CallContextField::IsSuccess, 1.into()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        // Add beneficiary into access list
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Read nonce and code hash of beneficiary to check if it's empty
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1.clone();
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::Nonce,
+            beneficiary_account.nonce,
+            beneficiary_account.nonce,
+        )?;
+        let code_hash = beneficiary_account.code_hash.to_word();
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            code_hash,
+            code_hash,
+        )?;
+
+        // Transfer the whole balance to beneficiary. The beneficiary is
+        // credited before the balance of the destructed account is cleared,
+        // so the balance is burnt when the beneficiary is the account itself.
+        let value = state.sdb.get_account(&call.address).1.balance;
+        let beneficiary_balance_prev = beneficiary_account.balance;
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: beneficiary,
+                field: AccountField::Balance,
+                value: beneficiary_balance_prev + value,
+                value_prev: beneficiary_balance_prev,
+            },
+        )?;
+        let balance_prev = state.sdb.get_account(&call.address).1.balance;
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Balance,
+                value: U256::zero(),
+                value_prev: balance_prev,
+            },
+        )?;
+
+        // Mark the account as destructed
+        let is_destructed_prev = state.sdb.check_account_destructed(&call.address);
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            AccountDestructedOp {
+                tx_id,
+                address: call.address,
+                is_destructed: true,
+                is_destructed_prev,
+            },
+        )?;
+
+        // Refund is only given for the first destruction of the account
+        let refund_prev = state.sdb.refund();
+        let refund = if is_destructed_prev {
+            refund_prev
+        } else {
+            refund_prev + selfdestruct_refund(state.block.hardfork)
+        };
+        state.push_op_reversible(
+            &mut exec_step,
+            RW::WRITE,
+            TxRefundOp {
+                tx_id,
+                value: refund,
+                value_prev: refund_prev,
+            },
+        )?;
+
+        if !call.is_root {
+            state.gen_restore_context_ops(&mut exec_step, geth_steps)?;
+        }
+
+        state.handle_return(&mut [&mut exec_step], geth_step)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, state_db::Account};
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn selfdestruct_opcode_impl() {
+        let beneficiary = address!("0x00000000000000000000000000000000000000be");
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        };
+        let contract = address!("0x0000000000000000000000000000000000000010");
+
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(contract)
+                    .balance(Word::from(0xcafe))
+                    .code(code.clone());
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+                txs[1]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .nonce(Word::one());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+        let container = &builder.block.container;
+
+        assert_eq!(
+            [10, 11]
+                .map(|idx| &container.account[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op().clone())),
+            [
+                (
+                    RW::WRITE,
+                    AccountOp {
+                        address: beneficiary,
+                        field: AccountField::Balance,
+                        value: Word::from(0xcafe),
+                        value_prev: Word::zero(),
+                    }
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp {
+                        address: contract,
+                        field: AccountField::Balance,
+                        value: Word::zero(),
+                        value_prev: Word::from(0xcafe),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &container.account_destructed[step.bus_mapping_instance[12].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &AccountDestructedOp {
+                    tx_id: 1,
+                    address: contract,
+                    is_destructed: true,
+                    is_destructed_prev: false,
+                }
+            )
+        );
+
+        // The contract has no code anymore in the second transaction.
+        assert!(builder.block.txs()[1]
+            .steps()
+            .iter()
+            .all(|step| step.exec_state != ExecState::Op(OpcodeId::SELFDESTRUCT)));
+        assert_eq!(
+            builder.sdb.get_account(&contract).1.code_hash,
+            Account::zero().code_hash
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, CopyStep, ExecStep, NumberOrHash,
+    },
+    operation::RW,
+    Error,
+};
+use eth_types::GethExecStep;
+
+use super::Opcode;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Sha3;
+
+impl Opcode for Sha3 {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let size = geth_step.stack.nth_last(1)?;
+        let digest = geth_steps[1].stack.last()?;
+
+        // stack reads
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), size)?;
+        // stack write of the digest
+        state.stack_write(&mut exec_step, geth_step.stack.nth_last_filled(1), digest)?;
+
+        // The offset is ignored when the size is 0, so it could be beyond u64.
+        let size = size.as_u64();
+        let offset = if size > 0 { offset.as_u64() } else { 0 };
+        let sha3_input = geth_step.memory.read_chunk(offset.into(), size.into());
+        if size > 0 {
+            let copy_event = gen_copy_event(state, &mut exec_step, offset, &sha3_input)?;
+            state.push_copy(copy_event);
+        }
+        state.block.sha3_inputs.push(sha3_input);
+
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    src_addr: u64,
+    bytes: &[u8],
+) -> Result<Vec<CopyStep>, Error> {
+    let mut steps = Vec::with_capacity(2 * bytes.len());
+    for (idx, value) in bytes.iter().copied().enumerate() {
+        let addr = src_addr + idx as u64;
+        let rwc = state.block_ctx.rwc;
+        state.memory_read(exec_step, addr.into(), value)?;
+        // Read
+        steps.push(CopyStep {
+            addr,
+            tag: CopyDataType::Memory,
+            rw: RW::READ,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc,
+            rwc_inc_left: 0,
+        });
+        // Write
+        steps.push(CopyStep {
+            addr: idx as u64,
+            tag: CopyDataType::RlcAcc,
+            rw: RW::WRITE,
+            value,
+            is_code: None,
+            is_pad: false,
+            rwc: state.block_ctx.rwc,
+            rwc_inc_left: 0,
+        });
+    }
+    Ok(steps)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    offset: u64,
+    bytes: &[u8],
+) -> Result<CopyEvent, Error> {
+    let call_id = state.call()?.call_id;
+    let length = bytes.len() as u64;
+
+    let mut steps = gen_copy_steps(state, exec_step, offset, bytes)?;
+    for cs in steps.iter_mut() {
+        cs.rwc_inc_left = state.block_ctx.rwc.0 as u64 - cs.rwc.0 as u64;
+    }
+
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr: offset,
+        src_addr_end: offset + length,
+        dst_type: CopyDataType::RlcAcc,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr: 0,
+        log_id: None,
+        length,
+        steps,
+        tx_id: state.tx_ctx.id(),
+        call_id,
+        pc: exec_step.pc,
+    })
+}
+
+#[cfg(test)]
+mod sha3_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use ethers_core::utils::keccak256;
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        mock::BlockData,
+        operation::{MemoryOp, StackOp, RW},
+    };
+
+    #[test]
+    fn sha3_opcode_impl() {
+        test_ok(0x00, 0x00);
+        test_ok(0x10, 0x20);
+        test_ok(0x24, 0x43);
+    }
+
+    fn test_ok(offset: usize, size: usize) {
+        let mem = Word::from_big_endian(&[0xabu8; 32]);
+        let code = bytecode! {
+            PUSH32(mem)
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(mem)
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(size)
+            PUSH32(offset)
+            SHA3
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SHA3))
+            .unwrap();
+
+        let expected_call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+
+        // memory contents before SHA3, zero padded for memory expansion
+        let memory = (0..offset + size)
+            .map(|idx| if idx < 0x40 { 0xab } else { 0 })
+            .collect::<Vec<u8>>();
+        let input = memory[offset..].to_vec();
+        let digest = Word::from_big_endian(&keccak256(&input));
+
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1022), Word::from(offset)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1023), Word::from(size)),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(1, StackAddress::from(1023), digest),
+                ),
+            ]
+        );
+
+        // RW table memory reads, following the two MSTOREs.
+        assert_eq!(
+            (0..size)
+                .map(|idx| &builder.block.container.memory[64 + idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, MemoryOp)>>(),
+            (0..size)
+                .map(|idx| {
+                    (
+                        RW::READ,
+                        MemoryOp::new(1, MemoryAddress::from(offset + idx), input[idx]),
+                    )
+                })
+                .collect::<Vec<(RW, MemoryOp)>>(),
+        );
+
+        assert_eq!(builder.block.sha3_inputs, vec![input]);
+
+        let copy_events = builder.block.copy_events.clone();
+        if size == 0 {
+            assert!(copy_events.is_empty());
+            return;
+        }
+        assert_eq!(copy_events.len(), 1);
+        assert_eq!(copy_events[0].steps.len(), 2 * size);
+        assert_eq!(
+            copy_events[0].src_id,
+            NumberOrHash::Number(expected_call_id)
+        );
+        assert_eq!(copy_events[0].src_addr as usize, offset);
+        assert_eq!(copy_events[0].src_addr_end as usize, offset + size);
+        assert_eq!(copy_events[0].src_type, CopyDataType::Memory);
+        assert_eq!(copy_events[0].dst_type, CopyDataType::RlcAcc);
+        assert!(copy_events[0].log_id.is_none());
+        assert_eq!(copy_events[0].length as usize, size);
+        for (idx, copy_rw_pair) in copy_events[0].steps.chunks(2).enumerate() {
+            assert_eq!(copy_rw_pair[0].value, input[idx]);
+            assert_eq!(copy_rw_pair[1].value, input[idx]);
+            assert_eq!(copy_rw_pair[1].addr as usize, idx);
+            assert_eq!(copy_rw_pair[0].rwc_inc_left as usize, size - idx);
+            assert_eq!(copy_rw_pair[1].rwc_inc_left as usize, size - idx - 1);
+        }
+    }
+}
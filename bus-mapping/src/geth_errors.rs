@@ -6,3 +6,6 @@ pub const GETH_ERR_STACK_UNDERFLOW: &str = "stack underflow";
 pub const GETH_ERR_OUT_OF_GAS: &str = "out of gas";
 /// Geth error message for gas uint64 overflow
 pub const GETH_ERR_GAS_UINT_OVERFLOW: &str = "gas uint64 overflow";
+/// Geth error message for CREATE and CREATE2 with an initcode larger than
+/// the limit since Shanghai (EIP-3860)
+pub const GETH_ERR_MAX_INITCODE_SIZE_EXCEEDED: &str = "max initcode size exceeded";
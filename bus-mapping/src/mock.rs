@@ -4,7 +4,8 @@ use crate::{
     circuit_input_builder::{Block, CircuitInputBuilder},
     state_db::{self, CodeDB, StateDB},
 };
-use eth_types::{geth_types::GethData, Word};
+use eth_types::{geth_types::GethData, ChainSpec, Word};
+use ethers_core::utils::get_contract_address;
 
 /// BlockData is a type that contains all the information from a block required
 /// to build the circuit inputs.
@@ -14,8 +15,8 @@ pub struct BlockData {
     pub sdb: StateDB,
     /// CodeDB
     pub code_db: CodeDB,
-    /// chain id
-    pub chain_id: Word,
+    /// Chain specification
+    pub chain_spec: ChainSpec,
     /// history hashes contains most recent 256 block hashes in history, where
     /// the lastest one is at history_hashes[history_hashes.len() - 1].
     pub history_hashes: Vec<Word>,
@@ -32,7 +33,12 @@ impl BlockData {
         CircuitInputBuilder::new(
             self.sdb.clone(),
             self.code_db.clone(),
-            Block::new(self.chain_id, self.history_hashes.clone(), &self.eth_block).unwrap(),
+            Block::new(
+                &self.chain_spec,
+                self.history_hashes.clone(),
+                &self.eth_block,
+            )
+            .unwrap(),
         )
     }
 
@@ -44,8 +50,12 @@ impl BlockData {
         sdb.set_account(&geth_data.eth_block.author, state_db::Account::zero());
         for tx in geth_data.eth_block.transactions.iter() {
             sdb.set_account(&tx.from, state_db::Account::zero());
-            if let Some(to) = tx.to.as_ref() {
-                sdb.set_account(to, state_db::Account::zero());
+            match tx.to.as_ref() {
+                Some(to) => sdb.set_account(to, state_db::Account::zero()),
+                None => sdb.set_account(
+                    &get_contract_address(tx.from, tx.nonce),
+                    state_db::Account::zero(),
+                ),
             }
         }
 
@@ -65,7 +75,7 @@ impl BlockData {
         Self {
             sdb,
             code_db,
-            chain_id: geth_data.chain_id,
+            chain_spec: ChainSpec::new(geth_data.chain_id.as_u64(), geth_data.hardfork),
             history_hashes: geth_data.history_hashes,
             eth_block: geth_data.eth_block,
             geth_traces: geth_data.geth_traces,
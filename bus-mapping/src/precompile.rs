@@ -0,0 +1,488 @@
+//! Precompiled contracts, the contracts at the addresses 0x01 to 0x09 whose
+//! execution is built into the EVM instead of being defined by bytecode.
+
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use eth_types::{evm_types::GasCost, Address, ToBigEndian, Word, H256};
+use ethers_core::types::Signature;
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+lazy_static! {
+    /// Order of the secp256k1 curve, the upper bound of `r` and `s` of a valid
+    /// signature.
+    static ref SECP256K1_N: Word = Word::from_str_radix(
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap();
+}
+
+/// Precompiled contracts, identified by their address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrecompileCalls {
+    /// Elliptic curve public key recovery
+    ECRecover = 0x01,
+    /// SHA2-256 hash function
+    Sha256 = 0x02,
+    /// RIPEMD-160 hash function
+    Ripemd160 = 0x03,
+    /// Identity function, which returns its input
+    Identity = 0x04,
+    /// Modular exponentiation (EIP-198)
+    Modexp = 0x05,
+    /// Point addition on the alt_bn128 curve (EIP-196)
+    Bn128Add = 0x06,
+    /// Scalar multiplication on the alt_bn128 curve (EIP-196)
+    Bn128Mul = 0x07,
+    /// Pairing check on the alt_bn128 curve (EIP-197)
+    Bn128Pairing = 0x08,
+    /// Compression function F of the BLAKE2 hash function (EIP-152)
+    Blake2F = 0x09,
+}
+
+impl PrecompileCalls {
+    /// Return the precompiled contract at `address`, if any.
+    pub fn from_address(address: &Address) -> Option<Self> {
+        if address.0[0..19] != [0u8; 19] {
+            return None;
+        }
+        Some(match address.0[19] {
+            0x01 => Self::ECRecover,
+            0x02 => Self::Sha256,
+            0x03 => Self::Ripemd160,
+            0x04 => Self::Identity,
+            0x05 => Self::Modexp,
+            0x06 => Self::Bn128Add,
+            0x07 => Self::Bn128Mul,
+            0x08 => Self::Bn128Pairing,
+            0x09 => Self::Blake2F,
+            _ => return None,
+        })
+    }
+
+    /// Return the address of the precompiled contract.
+    pub fn address(&self) -> Address {
+        Address::from_low_u64_be(*self as u64)
+    }
+
+    /// Return the length of the input the precompiled contract operates on.
+    /// The call data is padded with zeros or truncated to this length, unless
+    /// the contract operates on inputs of any length.
+    pub fn input_length(&self, call_data_length: u64) -> u64 {
+        match self {
+            Self::ECRecover => 128,
+            _ => call_data_length,
+        }
+    }
+
+    /// Return the gas cost of calling the precompiled contract with `input`.
+    pub fn gas_cost(&self, input: &[u8]) -> u64 {
+        let words = (input.len() as u64 + 31) / 32;
+        match self {
+            Self::ECRecover => GasCost::PRECOMPILE_ECRECOVER.as_u64(),
+            Self::Sha256 => {
+                GasCost::PRECOMPILE_SHA256_BASE.as_u64()
+                    + GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64() * words
+            }
+            Self::Ripemd160 => {
+                GasCost::PRECOMPILE_RIPEMD160_BASE.as_u64()
+                    + GasCost::PRECOMPILE_RIPEMD160_PER_WORD.as_u64() * words
+            }
+            Self::Identity => {
+                GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                    + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * words
+            }
+            Self::Modexp => modexp_gas_cost(input),
+            Self::Bn128Add => GasCost::PRECOMPILE_BN128_ADD.as_u64(),
+            Self::Bn128Mul => GasCost::PRECOMPILE_BN128_MUL.as_u64(),
+            Self::Bn128Pairing => {
+                GasCost::PRECOMPILE_BN128_PAIRING_BASE.as_u64()
+                    + GasCost::PRECOMPILE_BN128_PAIRING_PER_POINT.as_u64()
+                        * (input.len() as u64 / 192)
+            }
+            Self::Blake2F => {
+                let rounds = if input.len() == 213 {
+                    u32::from_be_bytes(input[0..4].try_into().unwrap()) as u64
+                } else {
+                    0
+                };
+                GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.as_u64() * rounds
+            }
+        }
+    }
+
+    /// Return the output of the call to the precompiled contract with `input`,
+    /// or `None` if the call fails because `input` is invalid, like a point
+    /// which isn't on the alt_bn128 curve.
+    pub fn execute(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::ECRecover => Some(ecrecover(input)),
+            Self::Sha256 => Some(Sha256::digest(input).to_vec()),
+            Self::Ripemd160 => {
+                let mut output = vec![0u8; 12];
+                output.extend_from_slice(&Ripemd160::digest(input));
+                Some(output)
+            }
+            Self::Identity => Some(input.to_vec()),
+            Self::Modexp => Some(modexp(input)),
+            Self::Bn128Add => bn128_add(input),
+            Self::Bn128Mul => bn128_mul(input),
+            Self::Bn128Pairing => bn128_pairing(input),
+            Self::Blake2F => blake2f(input),
+        }
+    }
+}
+
+/// Check if address is a precompiled contract or not.
+pub fn is_precompiled(address: &Address) -> bool {
+    PrecompileCalls::from_address(address).is_some()
+}
+
+/// Return the word in `input` at `offset`, padding `input` with zeros.
+fn read_word(input: &[u8], offset: usize) -> Word {
+    let mut bytes = [0u8; 32];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = input.get(offset + idx).copied().unwrap_or_default();
+    }
+    Word::from_big_endian(&bytes)
+}
+
+/// Return the `length` bytes in `input` at `offset`, padding `input` with
+/// zeros.
+fn read_bytes(input: &[u8], offset: usize, length: usize) -> Vec<u8> {
+    (offset..offset + length)
+        .map(|idx| input.get(idx).copied().unwrap_or_default())
+        .collect()
+}
+
+/// Return the word in `input` at `offset` saturated to u64.
+fn read_u64(input: &[u8], offset: usize) -> u64 {
+    let word = read_word(input, offset);
+    if word > Word::from(u64::MAX) {
+        u64::MAX
+    } else {
+        word.as_u64()
+    }
+}
+
+/// Recover the address which signed the message hash, which is returned left
+/// padded to 32 bytes. The output is empty for an invalid signature.
+fn ecrecover(input: &[u8]) -> Vec<u8> {
+    let msg_hash = H256(read_word(input, 0).to_be_bytes());
+    let (v, r, s) = (
+        read_word(input, 32),
+        read_word(input, 64),
+        read_word(input, 96),
+    );
+
+    let is_valid_v = v == Word::from(27) || v == Word::from(28);
+    let is_valid_rs = [r, s]
+        .iter()
+        .all(|value| !value.is_zero() && *value < *SECP256K1_N);
+    if !is_valid_v || !is_valid_rs {
+        return Vec::new();
+    }
+
+    let signature = Signature {
+        r,
+        s,
+        v: v.as_u64(),
+    };
+    match signature.recover(msg_hash) {
+        Ok(address) => {
+            let mut output = vec![0u8; 12];
+            output.extend_from_slice(&address.0);
+            output
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return the gas cost of the modexp precompile as defined by EIP-2565.
+fn modexp_gas_cost(input: &[u8]) -> u64 {
+    let (base_length, exp_length, mod_length) =
+        (read_u64(input, 0), read_u64(input, 32), read_u64(input, 64));
+
+    let words = (base_length.max(mod_length).saturating_add(7)) / 8;
+    let multiplication_complexity = words.saturating_mul(words);
+
+    // Only the first 32 bytes of the exponent affect the iteration count.
+    let exp_head = if exp_length == 0 || base_length > input.len() as u64 {
+        Word::zero()
+    } else {
+        let exp_head = read_word(input, 96 + base_length as usize);
+        if exp_length < 32 {
+            exp_head >> (8 * (32 - exp_length))
+        } else {
+            exp_head
+        }
+    };
+    let iteration_count = if exp_length <= 32 {
+        (exp_head.bits() as u64).saturating_sub(1)
+    } else {
+        (8u64.saturating_mul(exp_length - 32))
+            .saturating_add((exp_head.bits() as u64).saturating_sub(1))
+    };
+
+    (multiplication_complexity.saturating_mul(iteration_count.max(1)) / 3)
+        .max(GasCost::PRECOMPILE_MODEXP_MIN.as_u64())
+}
+
+/// Return `base ** exp % mod` left padded to the length of `mod`, where the
+/// lengths of `base`, `exp` and `mod` are the first three words of `input`.
+/// The lengths are bounded by the gas cost of a successful call.
+fn modexp(input: &[u8]) -> Vec<u8> {
+    let (base_length, exp_length, mod_length) = (
+        read_u64(input, 0) as usize,
+        read_u64(input, 32) as usize,
+        read_u64(input, 64) as usize,
+    );
+    let base = BigUint::from_bytes_be(&read_bytes(input, 96, base_length));
+    let exp = BigUint::from_bytes_be(&read_bytes(input, 96 + base_length, exp_length));
+    let modulus = BigUint::from_bytes_be(&read_bytes(
+        input,
+        96 + base_length + exp_length,
+        mod_length,
+    ));
+
+    let mut output = vec![0u8; mod_length];
+    if modulus != BigUint::from(0u64) {
+        let result = base.modpow(&exp, &modulus).to_bytes_be();
+        output[mod_length - result.len()..].copy_from_slice(&result);
+    }
+    output
+}
+
+/// Return the element of the base field of alt_bn128 in `input` at `offset`,
+/// or `None` if it's not lower than the modulus.
+fn read_fq(input: &[u8], offset: usize) -> Option<Fq> {
+    Fq::from_slice(&read_word(input, offset).to_be_bytes()).ok()
+}
+
+/// Return the point of G1 in `input` at `offset`, where (0, 0) is the point
+/// at infinity, or `None` if it's not on the curve.
+fn read_g1(input: &[u8], offset: usize) -> Option<G1> {
+    let (x, y) = (read_fq(input, offset)?, read_fq(input, offset + 32)?);
+    if x.is_zero() && y.is_zero() {
+        Some(G1::zero())
+    } else {
+        AffineG1::new(x, y).ok().map(G1::from)
+    }
+}
+
+/// Return the point of G2 in `input` at `offset`, where every coordinate is
+/// encoded as its imaginary part followed by its real part, and (0, 0) is the
+/// point at infinity, or `None` if it's not in G2.
+fn read_g2(input: &[u8], offset: usize) -> Option<G2> {
+    let x = Fq2::new(read_fq(input, offset + 32)?, read_fq(input, offset)?);
+    let y = Fq2::new(read_fq(input, offset + 96)?, read_fq(input, offset + 64)?);
+    if x.is_zero() && y.is_zero() {
+        Some(G2::zero())
+    } else {
+        AffineG2::new(x, y).ok().map(G2::from)
+    }
+}
+
+/// Return the encoding of a point of G1 as its two coordinates, where the
+/// point at infinity is (0, 0).
+fn write_g1(point: G1) -> Vec<u8> {
+    let mut output = vec![0u8; 64];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point
+            .x()
+            .to_big_endian(&mut output[..32])
+            .expect("slice of 32 bytes");
+        point
+            .y()
+            .to_big_endian(&mut output[32..])
+            .expect("slice of 32 bytes");
+    }
+    output
+}
+
+/// Return the sum of the two points of G1 in `input`.
+fn bn128_add(input: &[u8]) -> Option<Vec<u8>> {
+    Some(write_g1(read_g1(input, 0)? + read_g1(input, 64)?))
+}
+
+/// Return the product of the point of G1 in `input` by the scalar after it.
+fn bn128_mul(input: &[u8]) -> Option<Vec<u8>> {
+    let point = read_g1(input, 0)?;
+    let scalar = Fr::from_slice(&read_word(input, 64).to_be_bytes()).ok()?;
+    Some(write_g1(point * scalar))
+}
+
+/// Return 1 as a word if the product of the pairings of the pairs of points of
+/// G1 and G2 in `input` is 1, and 0 otherwise.
+fn bn128_pairing(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 192 != 0 {
+        return None;
+    }
+    let pairs = (0..input.len() / 192)
+        .map(|idx| Some((read_g1(input, idx * 192)?, read_g2(input, idx * 192 + 64)?)))
+        .collect::<Option<Vec<_>>>()?;
+    let is_one = bn::pairing_batch(&pairs) == Gt::one();
+    Some(Word::from(is_one as u64).to_be_bytes().to_vec())
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Mixing function G of BLAKE2b.
+fn blake2b_g(v: &mut [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Return the state vector compressed by the compression function F of
+/// BLAKE2b, whose number of rounds, state vector, message block, offset
+/// counters and final block flag are encoded in `input` as defined by EIP-152.
+fn blake2f(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() != 213 || input[212] > 1 {
+        return None;
+    }
+    let read_u64_le =
+        |offset: usize| u64::from_le_bytes(input[offset..offset + 8].try_into().unwrap());
+    let rounds = u32::from_be_bytes(input[0..4].try_into().unwrap());
+    let mut h = [0u64; 8];
+    for (idx, word) in h.iter_mut().enumerate() {
+        *word = read_u64_le(4 + idx * 8);
+    }
+    let mut m = [0u64; 16];
+    for (idx, word) in m.iter_mut().enumerate() {
+        *word = read_u64_le(68 + idx * 8);
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= read_u64_le(196);
+    v[13] ^= read_u64_le(204);
+    if input[212] == 1 {
+        v[14] = !v[14];
+    }
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        blake2b_g(&mut v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        blake2b_g(&mut v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        blake2b_g(&mut v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        blake2b_g(&mut v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        blake2b_g(&mut v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        blake2b_g(&mut v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        blake2b_g(&mut v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        blake2b_g(&mut v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+    }
+
+    Some(
+        h.iter()
+            .enumerate()
+            .flat_map(|(idx, word)| (word ^ v[idx] ^ v[idx + 8]).to_le_bytes())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod precompile_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn modexp_output() {
+        // 3 ** 5 % 7 == 5, left padded to the length of the modulus
+        let mut input = Vec::new();
+        for length in [1u64, 1, 2] {
+            input.extend_from_slice(&Word::from(length).to_be_bytes());
+        }
+        input.extend_from_slice(&[3, 5, 0, 7]);
+        assert_eq!(PrecompileCalls::Modexp.execute(&input), Some(vec![0, 5]));
+        // The output is zero when the modulus is zero
+        input.truncate(99);
+        assert_eq!(PrecompileCalls::Modexp.execute(&input), Some(vec![0, 0]));
+    }
+
+    #[test]
+    fn bn128_output() {
+        let mut generator = Word::from(1).to_be_bytes().to_vec();
+        generator.extend_from_slice(&Word::from(2).to_be_bytes());
+        let generator_double = hex::decode(
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+             15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+        )
+        .unwrap();
+
+        let input = [generator.clone(), generator.clone()].concat();
+        assert_eq!(
+            PrecompileCalls::Bn128Add.execute(&input),
+            Some(generator_double.clone())
+        );
+        let input = [generator.clone(), Word::from(2).to_be_bytes().to_vec()].concat();
+        assert_eq!(
+            PrecompileCalls::Bn128Mul.execute(&input),
+            Some(generator_double)
+        );
+        // (1, 1) is not on the curve
+        let input = [Word::from(1).to_be_bytes(), Word::from(1).to_be_bytes()].concat();
+        assert_eq!(PrecompileCalls::Bn128Add.execute(&input), None);
+
+        // The empty product of pairings is 1
+        assert_eq!(
+            PrecompileCalls::Bn128Pairing.execute(&[]),
+            Some(Word::from(1).to_be_bytes().to_vec())
+        );
+        assert_eq!(PrecompileCalls::Bn128Pairing.execute(&generator), None);
+    }
+
+    #[test]
+    fn blake2f_output() {
+        // EIP-152 test vector 5, the compression of "abc" into its BLAKE2b-512
+        // hash
+        let input = hex::decode(
+            "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+             d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300\
+             000000000000000000000000000000000000000000000000000000000000000000000000\
+             000000000000000000000000000000000000000000000000000000000000000000000000\
+             000000000000000000000000000000000000000000000000000000000000000000000000\
+             000000000000000000000000000000000300000000000000000000000000000001",
+        )
+        .unwrap();
+        let output = hex::decode(
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        )
+        .unwrap();
+        assert_eq!(PrecompileCalls::Blake2F.execute(&input), Some(output));
+        // The final block flag is neither 0 nor 1
+        let mut input = input;
+        input[212] = 2;
+        assert_eq!(PrecompileCalls::Blake2F.execute(&input), None);
+    }
+}
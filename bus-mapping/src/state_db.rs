@@ -83,8 +83,9 @@ pub struct StateDB {
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
     dirty_storage: HashMap<(Address, Word), Word>,
-    // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
-    // `true`. These accounts will be reset once `commit_tx` is called.
+    // Accounts that have been through `SELFDESTRUCT` in current transaction, which are removed
+    // again if the `SELFDESTRUCT` gets reverted. These accounts will be reset once `commit_tx` is
+    // called.
     destructed_account: HashSet<Address>,
     refund: u64,
 }
@@ -230,6 +231,17 @@ impl StateDB {
         self.destructed_account.insert(addr);
     }
 
+    /// Check whether `addr` has been self destructed in current transaction.
+    pub fn check_account_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
+    /// Unset account as self destructed, in case of reversion.
+    pub fn undestruct_account(&mut self, addr: &Address) {
+        let exist = self.destructed_account.remove(addr);
+        debug_assert!(exist);
+    }
+
     /// Retrieve refund.
     pub fn refund(&self) -> u64 {
         self.refund
@@ -251,10 +263,10 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
-        for addr in self.destructed_account.clone() {
-            let (_, account) = self.get_account_mut(&addr);
-            *account = ACCOUNT_ZERO.clone();
+        for addr in self.destructed_account.iter() {
+            self.state.remove(addr);
         }
+        self.destructed_account = HashSet::new();
         self.refund = 0;
     }
 }
@@ -317,5 +329,14 @@ mod statedb_tests {
         let (found, value) = statedb.get_storage(&addr_b, &Word::from(3));
         assert!(found);
         assert_eq!(value, &Word::from(102));
+
+        // Destructed account is gone after the transaction is committed
+        statedb.destruct_account(addr_a);
+        assert!(statedb.check_account_destructed(&addr_a));
+        statedb.commit_tx();
+        assert!(!statedb.check_account_destructed(&addr_a));
+        let (found, acc) = statedb.get_account(&addr_a);
+        assert!(!found);
+        assert_eq!(acc, &Account::zero());
     }
 }
@@ -25,7 +25,10 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
         let rw_table = [(); 11].map(|_| meta.advice_column());
         let bytecode_table = [(); 5].map(|_| meta.advice_column());
         let block_table = [(); 3].map(|_| meta.advice_column());
-        let copy_table = [(); 11].map(|_| meta.advice_column());
+        let copy_table = [(); 12].map(|_| meta.advice_column());
+        let keccak_table = [(); 4].map(|_| meta.advice_column());
+        let exp_table = [(); 8].map(|_| meta.advice_column());
+        let precompile_table = [(); 5].map(|_| meta.advice_column());
         // Use constant expression to mock constant instance column for a more
         // reasonable benchmark.
         let power_of_randomness = [(); 31].map(|_| Expression::Constant(F::one()));
@@ -38,6 +41,9 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
             &bytecode_table,
             &block_table,
             &copy_table,
+            &keccak_table,
+            &exp_table,
+            &precompile_table,
         )
     }
 
@@ -74,7 +80,16 @@ mod evm_circ_benches {
             .parse()
             .expect("Cannot parse DEGREE env var as u32");
 
-        let circuit = TestCircuit::<Fr>::default();
+        // An empty block padded to the capacity of the circuit, whose total
+        // number of read/write and total number of txs are 0.
+        let circuit = TestCircuit::<Fr> {
+            block: Block {
+                evm_circuit_pad_to: (1 << degree) - 64,
+                ..Default::default()
+            },
+        };
+        let total_rws = vec![Fr::from(0)];
+        let num_txs = vec![Fr::from(0)];
         let rng = XorShiftRng::from_seed([
             0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
             0xbc, 0xe5,
@@ -99,7 +114,7 @@ mod evm_circ_benches {
             &general_params,
             &pk,
             &[circuit],
-            &[&[]],
+            &[&[&total_rws, &num_txs]],
             rng,
             &mut transcript,
         )
@@ -118,7 +133,7 @@ mod evm_circ_benches {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[]],
+            &[&[&total_rws, &num_txs]],
             &mut verifier_transcript,
         )
         .unwrap();
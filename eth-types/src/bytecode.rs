@@ -66,12 +66,12 @@ impl Bytecode {
         self
     }
 
-    /// Push
+    /// Push, where `PUSH0` is written when `n` is 0
     pub fn push(&mut self, n: usize, value: Word) -> &mut Self {
-        debug_assert!((1..=32).contains(&n), "invalid push");
+        debug_assert!((0..=32).contains(&n), "invalid push");
 
         // Write the op code
-        self.write_op_internal(OpcodeId::PUSH1.as_u8() + ((n - 1) as u8));
+        self.write_op_internal(OpcodeId::PUSH0.as_u8() + n as u8);
 
         let mut bytes = [0u8; 32];
         value.to_little_endian(&mut bytes);
@@ -155,8 +155,7 @@ impl From<Vec<u8>> for Bytecode {
             if let Ok(op) = OpcodeId::try_from(*byte) {
                 code.write_op(op);
                 if op.is_push() {
-                    let n = (op.as_u8() - OpcodeId::PUSH1.as_u8() + 1) as usize;
-                    for _ in 0..n {
+                    for _ in 0..op.push_size() {
                         match input_iter.next() {
                             Some(v) => {
                                 code.write(*v, false);
@@ -196,10 +195,13 @@ macro_rules! bytecode_internal {
     // PUSHX op codes
     ($code:ident, $x:ident ($v:expr) $($rest:tt)*) => {{
         debug_assert!($crate::evm_types::OpcodeId::$x.is_push(), "invalid push");
-        let n = $crate::evm_types::OpcodeId::$x.as_u8()
-            - $crate::evm_types::OpcodeId::PUSH1.as_u8()
-            + 1;
-        $code.push(n as usize, $v.into());
+        let n = $crate::evm_types::OpcodeId::$x.push_size();
+        $code.push(n, $v.into());
+        $crate::bytecode_internal!($code, $($rest)*);
+    }};
+    // PUSH0 op code without any push data
+    ($code:ident, PUSH0 $($rest:tt)*) => {{
+        $code.push(0, $crate::Word::zero());
         $crate::bytecode_internal!($code, $($rest)*);
     }};
     // Default opcode without any inputs
@@ -229,6 +231,8 @@ mod tests {
         let code = bytecode! {
             PUSH8(0x123)
             POP
+            PUSH0
+            POP
             PUSH24(0x321)
             PUSH32(0x432)
             MUL
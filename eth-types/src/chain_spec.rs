@@ -0,0 +1,138 @@
+//! Chain specification, which decides the rules a block is processed under.
+//! A custom chain, like an L2, is described by the hard forks it activates and
+//! its maximum code size. The gas costs and the gas refund of each hard fork
+//! are the ones of Ethereum, and can't be tuned per chain.
+
+use crate::evm_types::{MAX_CODE_SIZE, MAX_INITCODE_SIZE_MULTIPLIER};
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumCount, EnumIter};
+
+/// Hard forks of Ethereum which change the rules of the EVM, in the order of
+/// their activation. Blocks before Berlin are not supported.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    EnumCount,
+    Serialize,
+    Deserialize,
+)]
+pub enum Hardfork {
+    /// Berlin, which introduces the access lists (EIP-2929, EIP-2930)
+    Berlin,
+    /// London, which introduces the base fee (EIP-1559) and reduces the gas
+    /// refund (EIP-3529)
+    London,
+    /// The Merge, which replaces DIFFICULTY by PREVRANDAO (EIP-4399)
+    Merge,
+    /// Shanghai, which introduces PUSH0 (EIP-3855), limits the initcode
+    /// (EIP-3860) and warms up the coinbase (EIP-3651)
+    Shanghai,
+}
+
+/// London is the default hard fork, like for a geth dev node and the external
+/// tracer when no hard fork is specified.
+impl Default for Hardfork {
+    fn default() -> Self {
+        Self::London
+    }
+}
+
+/// Specification of a chain, which contains the activation of each hard fork
+/// and the maximum code size, the only limit which a custom chain could tweak.
+/// The specification must match the chain it's used with instead of being
+/// guessed from the chain id.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSpec {
+    /// Chain id
+    pub chain_id: u64,
+    /// Number of the first block processed under London
+    pub london_block: Option<u64>,
+    /// Number of the first block processed under the Merge
+    pub merge_block: Option<u64>,
+    /// Timestamp of the first block processed under Shanghai
+    pub shanghai_time: Option<u64>,
+    /// Maximum size of the code deployed by a contract creation (EIP-170)
+    pub max_code_size: u64,
+}
+
+impl ChainSpec {
+    /// Create a chain which runs the given hard fork since genesis, like a
+    /// custom L2 chain or a geth dev node.
+    pub fn new(chain_id: u64, hardfork: Hardfork) -> Self {
+        let activation = |fork: Hardfork| (hardfork >= fork).then(|| 0);
+        Self {
+            chain_id,
+            london_block: activation(Hardfork::London),
+            merge_block: activation(Hardfork::Merge),
+            shanghai_time: activation(Hardfork::Shanghai),
+            max_code_size: MAX_CODE_SIZE,
+        }
+    }
+
+    /// Ethereum mainnet
+    pub fn mainnet() -> Self {
+        Self {
+            chain_id: 1,
+            london_block: Some(12_965_000),
+            merge_block: Some(15_537_394),
+            shanghai_time: Some(1_681_338_455),
+            max_code_size: MAX_CODE_SIZE,
+        }
+    }
+
+    /// Maximum size of the initcode of a contract creation since Shanghai
+    /// (EIP-3860), which is twice the maximum code size.
+    pub fn max_initcode_size(&self) -> u64 {
+        MAX_INITCODE_SIZE_MULTIPLIER * self.max_code_size
+    }
+
+    /// Return the hard fork which the block with the given number and
+    /// timestamp is processed under.
+    pub fn hardfork(&self, number: u64, timestamp: u64) -> Hardfork {
+        let is_activated =
+            |activation: Option<u64>, value: u64| activation.map_or(false, |start| value >= start);
+
+        if is_activated(self.shanghai_time, timestamp) {
+            Hardfork::Shanghai
+        } else if is_activated(self.merge_block, number) {
+            Hardfork::Merge
+        } else if is_activated(self.london_block, number) {
+            Hardfork::London
+        } else {
+            Hardfork::Berlin
+        }
+    }
+}
+
+#[cfg(test)]
+mod chain_spec_tests {
+    use super::*;
+
+    #[test]
+    fn mainnet_hardfork() {
+        let spec = ChainSpec::mainnet();
+        assert_eq!(spec.hardfork(12_964_999, 1_628_166_812), Hardfork::Berlin);
+        assert_eq!(spec.hardfork(12_965_000, 1_628_166_822), Hardfork::London);
+        assert_eq!(spec.hardfork(15_537_394, 1_663_224_179), Hardfork::Merge);
+        assert_eq!(spec.hardfork(17_034_869, 1_681_338_443), Hardfork::Merge);
+        assert_eq!(spec.hardfork(17_034_870, 1_681_338_455), Hardfork::Shanghai);
+    }
+
+    #[test]
+    fn custom_chain_hardfork() {
+        let spec = ChainSpec::new(1337, Hardfork::London);
+        assert_eq!(spec.hardfork(0, 0), Hardfork::London);
+        assert_eq!(spec.hardfork(u64::MAX, u64::MAX), Hardfork::London);
+        assert_eq!(
+            ChainSpec::new(1337, Hardfork::Berlin).hardfork(1, 1),
+            Hardfork::Berlin
+        );
+    }
+}
@@ -62,10 +62,17 @@ impl fmt::Debug for Gas {
     }
 }
 
-/// Quotient for max refund of gas used
+/// Quotient for max refund of gas used since London (EIP-3529)
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
+/// Quotient for max refund of gas used before London
+pub const MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_LONDON: usize = 2;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Maximum size of the code deployed by a contract creation (EIP-170).
+pub const MAX_CODE_SIZE: u64 = 0x6000;
+/// Quotient of the maximum size of the initcode by the maximum code size since
+/// Shanghai (EIP-3860).
+pub const MAX_INITCODE_SIZE_MULTIPLIER: u64 = 2;
 
 /// Defines the gas consumption.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -96,12 +103,27 @@ impl GasCost {
     pub const EXT: Self = Self(20);
     /// Constant cost for SHA3
     pub const SHA3: Self = Self(30);
+    /// Constant cost for every byte of the exponent in EXP
+    pub const EXP_BYTE_COST: Self = Self(50);
     /// Constant cost for SELFDESTRUCT
     pub const SELFDESTRUCT: Self = Self(5000);
+    /// Refund for SELFDESTRUCT since London, which EIP-3529 removed
+    pub const SELFDESTRUCT_REFUND: Self = Self(0);
+    /// Refund for SELFDESTRUCT before London
+    pub const SELFDESTRUCT_REFUND_PRE_LONDON: Self = Self(24000);
     /// Constant cost for CREATE
     pub const CREATE: Self = Self(32000);
+    /// Constant cost for every byte of the code deposited by a contract
+    /// creation
+    pub const CODE_DEPOSIT_BYTE_COST: Self = Self(200);
     /// Constant cost for copying every word
     pub const COPY: Self = Self(3);
+    /// Constant cost for copying every word, specifically in the case of SHA3
+    /// opcode.
+    pub const COPY_SHA3: Self = Self(6);
+    /// Constant cost for every word of the initcode of a contract creation
+    /// since Shanghai (EIP-3860)
+    pub const INITCODE_WORD_COST: Self = Self(2);
     /// Constant cost for accessing account or storage key
     pub const WARM_ACCESS: Self = Self(100);
     /// Constant cost for a cold SLOAD
@@ -112,13 +134,23 @@ impl GasCost {
     pub const SSTORE_SET: Self = Self(20000);
     /// Constant cost for a storage reset
     pub const SSTORE_RESET: Self = Self(2900);
-    /// Constant cost for a storage clear. EIP-3529 changed it to 4800 from
-    /// 15000.
+    /// Refund for a storage clear since London (EIP-3529)
     pub const SSTORE_CLEARS_SCHEDULE: Self = Self(4800);
+    /// Refund for a storage clear before London
+    pub const SSTORE_CLEARS_SCHEDULE_PRE_LONDON: Self = Self(15000);
+    /// Minimum gas left for a storage write, which has to be exceeded to
+    /// prevent reentrancy (EIP-2200).
+    pub const SSTORE_SENTRY: Self = Self(2300);
     /// Constant cost for a non-creation transaction
     pub const TX: Self = Self(21000);
     /// Constant cost for a creation transaction
     pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for every address in the access list of a transaction
+    /// (EIP-2930)
+    pub const ACCESS_LIST_PER_ADDRESS: Self = Self(2400);
+    /// Constant cost for every storage key in the access list of a
+    /// transaction (EIP-2930)
+    pub const ACCESS_LIST_PER_STORAGE_KEY: Self = Self(1900);
     /// Constant cost for calling with non-zero value
     pub const CALL_WITH_VALUE: Self = Self(9000);
     /// Constant cost for turning empty account into non-empty account
@@ -129,6 +161,32 @@ impl GasCost {
     pub const MEMORY_EXPANSION_LINEAR_COEFF: Self = Self(3);
     /// constant gas for logs op codes
     pub const LOG: Self = Self(375);
+    /// Constant cost for the ecrecover precompile
+    pub const PRECOMPILE_ECRECOVER: Self = Self(3000);
+    /// Base cost for the sha256 precompile
+    pub const PRECOMPILE_SHA256_BASE: Self = Self(60);
+    /// Cost for every word of the input of the sha256 precompile
+    pub const PRECOMPILE_SHA256_PER_WORD: Self = Self(12);
+    /// Base cost for the ripemd160 precompile
+    pub const PRECOMPILE_RIPEMD160_BASE: Self = Self(600);
+    /// Cost for every word of the input of the ripemd160 precompile
+    pub const PRECOMPILE_RIPEMD160_PER_WORD: Self = Self(120);
+    /// Base cost for the identity precompile
+    pub const PRECOMPILE_IDENTITY_BASE: Self = Self(15);
+    /// Cost for every word of the input of the identity precompile
+    pub const PRECOMPILE_IDENTITY_PER_WORD: Self = Self(3);
+    /// Minimum cost for the modexp precompile, introduced by EIP-2565
+    pub const PRECOMPILE_MODEXP_MIN: Self = Self(200);
+    /// Constant cost for the bn128 addition precompile
+    pub const PRECOMPILE_BN128_ADD: Self = Self(150);
+    /// Constant cost for the bn128 scalar multiplication precompile
+    pub const PRECOMPILE_BN128_MUL: Self = Self(6000);
+    /// Base cost for the bn128 pairing precompile
+    pub const PRECOMPILE_BN128_PAIRING_BASE: Self = Self(45000);
+    /// Cost for every pair of points of the bn128 pairing precompile
+    pub const PRECOMPILE_BN128_PAIRING_PER_POINT: Self = Self(34000);
+    /// Cost for every round of the blake2f precompile
+    pub const PRECOMPILE_BLAKE2F_PER_ROUND: Self = Self(1);
 }
 
 impl GasCost {
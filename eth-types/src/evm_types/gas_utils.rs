@@ -1,7 +1,7 @@
 //! Utility functions to help calculate gas
 
-use super::GasCost;
-use crate::Word;
+use super::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED, MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_LONDON};
+use crate::{Hardfork, Word};
 
 /// Calculate memory expansion gas cost by current and next memory word size.
 pub fn memory_expansion_gas_cost(curr_memory_word_size: u64, next_memory_word_size: u64) -> u64 {
@@ -40,3 +40,42 @@ pub fn eip150_gas(gas_left: u64, gas_specified: Word) -> u64 {
 
     capped_gas
 }
+
+/// Return the refund for clearing a storage slot, which EIP-3529 reduced since
+/// London.
+pub fn sstore_clears_schedule(hardfork: Hardfork) -> u64 {
+    if hardfork >= Hardfork::London {
+        GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+    } else {
+        GasCost::SSTORE_CLEARS_SCHEDULE_PRE_LONDON.as_u64()
+    }
+}
+
+/// Return the refund for a SELFDESTRUCT, which EIP-3529 removed since London.
+pub fn selfdestruct_refund(hardfork: Hardfork) -> u64 {
+    if hardfork >= Hardfork::London {
+        GasCost::SELFDESTRUCT_REFUND.as_u64()
+    } else {
+        GasCost::SELFDESTRUCT_REFUND_PRE_LONDON.as_u64()
+    }
+}
+
+/// Return the gas cost for every word of the initcode of a contract creation,
+/// which EIP-3860 introduced since Shanghai.
+pub fn initcode_word_gas_cost(hardfork: Hardfork, initcode_size: u64) -> u64 {
+    if hardfork >= Hardfork::Shanghai {
+        GasCost::INITCODE_WORD_COST.as_u64() * ((initcode_size + 31) / 32)
+    } else {
+        0
+    }
+}
+
+/// Return the quotient of gas used which caps the gas refund of a transaction,
+/// which EIP-3529 raised since London.
+pub fn max_refund_quotient_of_gas_used(hardfork: Hardfork) -> u64 {
+    if hardfork >= Hardfork::London {
+        MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+    } else {
+        MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_LONDON as u64
+    }
+}
@@ -1,5 +1,5 @@
 //! Doc this
-use crate::{error::Error, evm_types::GasCost};
+use crate::{error::Error, evm_types::GasCost, Hardfork};
 use core::fmt::Debug;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -95,6 +95,8 @@ pub enum OpcodeId {
     JUMPDEST,
 
     // PUSHn
+    /// `PUSH0`, which pushes 0 without any push data (EIP-3855)
+    PUSH0,
     /// `PUSH1`
     PUSH1,
     /// `PUSH2`
@@ -269,7 +271,7 @@ pub enum OpcodeId {
     TIMESTAMP,
     /// `NUMBER`
     NUMBER,
-    /// `DIFFICULTY`
+    /// `DIFFICULTY`, which is `PREVRANDAO` since the Merge (EIP-4399)
     DIFFICULTY,
     /// `GASLIMIT`
     GASLIMIT,
@@ -315,9 +317,19 @@ pub enum OpcodeId {
 }
 
 impl OpcodeId {
-    /// Returns `true` if the `OpcodeId` is a `PUSHn`.
+    /// `PREVRANDAO`, which replaces `DIFFICULTY` since the Merge and returns
+    /// the randomness of the beacon chain (EIP-4399).
+    pub const PREVRANDAO: Self = Self::DIFFICULTY;
+
+    /// Returns `true` if the `OpcodeId` is a `PUSHn`, including `PUSH0`.
     pub fn is_push(&self) -> bool {
-        self.as_u8() >= Self::PUSH1.as_u8() && self.as_u8() <= Self::PUSH32.as_u8()
+        self.as_u8() >= Self::PUSH0.as_u8() && self.as_u8() <= Self::PUSH32.as_u8()
+    }
+
+    /// Returns the number of bytes pushed by a `PUSHn`, which is `n`.
+    pub fn push_size(&self) -> usize {
+        debug_assert!(self.is_push(), "{:?} is not a push", self);
+        (self.as_u8() - Self::PUSH0.as_u8()) as usize
     }
 
     /// Returns `true` if the `OpcodeId` is a `DUPn`.
@@ -333,6 +345,17 @@ impl OpcodeId {
     pub fn is_log(&self) -> bool {
         self.as_u8() >= Self::LOG0.as_u8() && self.as_u8() <= Self::LOG4.as_u8()
     }
+
+    /// Returns `true` if the `OpcodeId` is available under the rules of the
+    /// hard fork.
+    pub fn is_enabled(&self, hardfork: Hardfork) -> bool {
+        match self {
+            Self::BASEFEE => hardfork >= Hardfork::London,
+            Self::PUSH0 => hardfork >= Hardfork::Shanghai,
+            Self::INVALID(_) => false,
+            _ => true,
+        }
+    }
 }
 
 impl OpcodeId {
@@ -379,6 +402,7 @@ impl OpcodeId {
             OpcodeId::PC => 0x58u8,
             OpcodeId::MSIZE => 0x59u8,
             OpcodeId::JUMPDEST => 0x5bu8,
+            OpcodeId::PUSH0 => 0x5fu8,
             OpcodeId::PUSH1 => 0x60u8,
             OpcodeId::PUSH2 => 0x61u8,
             OpcodeId::PUSH3 => 0x62u8,
@@ -557,6 +581,7 @@ impl OpcodeId {
             OpcodeId::MSIZE => GasCost::QUICK,
             OpcodeId::GAS => GasCost::QUICK,
             OpcodeId::JUMPDEST => GasCost::ONE,
+            OpcodeId::PUSH0 => GasCost::QUICK,
             OpcodeId::PUSH1 => GasCost::FASTEST,
             OpcodeId::PUSH2 => GasCost::FASTEST,
             OpcodeId::PUSH3 => GasCost::FASTEST,
@@ -638,6 +663,195 @@ impl OpcodeId {
             OpcodeId::SELFDESTRUCT => GasCost::SELFDESTRUCT,
         }
     }
+
+    /// Returns the number of stack items the `OpcodeId` requires and the number
+    /// of stack items it leaves in their place, from which the stack pointers
+    /// it can be executed with are derived.
+    pub const fn stack_io(&self) -> (u32, u32) {
+        match self {
+            OpcodeId::STOP => (0, 0),
+            OpcodeId::ADD => (2, 1),
+            OpcodeId::MUL => (2, 1),
+            OpcodeId::SUB => (2, 1),
+            OpcodeId::DIV => (2, 1),
+            OpcodeId::SDIV => (2, 1),
+            OpcodeId::MOD => (2, 1),
+            OpcodeId::SMOD => (2, 1),
+            OpcodeId::ADDMOD => (3, 1),
+            OpcodeId::MULMOD => (3, 1),
+            OpcodeId::EXP => (2, 1),
+            OpcodeId::SIGNEXTEND => (2, 1),
+            OpcodeId::LT => (2, 1),
+            OpcodeId::GT => (2, 1),
+            OpcodeId::SLT => (2, 1),
+            OpcodeId::SGT => (2, 1),
+            OpcodeId::EQ => (2, 1),
+            OpcodeId::ISZERO => (1, 1),
+            OpcodeId::AND => (2, 1),
+            OpcodeId::OR => (2, 1),
+            OpcodeId::XOR => (2, 1),
+            OpcodeId::NOT => (1, 1),
+            OpcodeId::BYTE => (2, 1),
+            OpcodeId::SHL => (2, 1),
+            OpcodeId::SHR => (2, 1),
+            OpcodeId::SAR => (2, 1),
+            OpcodeId::SHA3 => (2, 1),
+            OpcodeId::ADDRESS => (0, 1),
+            OpcodeId::BALANCE => (1, 1),
+            OpcodeId::ORIGIN => (0, 1),
+            OpcodeId::CALLER => (0, 1),
+            OpcodeId::CALLVALUE => (0, 1),
+            OpcodeId::CALLDATALOAD => (1, 1),
+            OpcodeId::CALLDATASIZE => (0, 1),
+            OpcodeId::CALLDATACOPY => (3, 0),
+            OpcodeId::CODESIZE => (0, 1),
+            OpcodeId::CODECOPY => (3, 0),
+            OpcodeId::GASPRICE => (0, 1),
+            OpcodeId::EXTCODESIZE => (1, 1),
+            OpcodeId::EXTCODECOPY => (4, 0),
+            OpcodeId::RETURNDATASIZE => (0, 1),
+            OpcodeId::RETURNDATACOPY => (3, 0),
+            OpcodeId::EXTCODEHASH => (1, 1),
+            OpcodeId::BLOCKHASH => (1, 1),
+            OpcodeId::COINBASE => (0, 1),
+            OpcodeId::TIMESTAMP => (0, 1),
+            OpcodeId::NUMBER => (0, 1),
+            OpcodeId::DIFFICULTY => (0, 1),
+            OpcodeId::GASLIMIT => (0, 1),
+            OpcodeId::CHAINID => (0, 1),
+            OpcodeId::SELFBALANCE => (0, 1),
+            OpcodeId::BASEFEE => (0, 1),
+            OpcodeId::POP => (1, 0),
+            OpcodeId::MLOAD => (1, 1),
+            OpcodeId::MSTORE => (2, 0),
+            OpcodeId::MSTORE8 => (2, 0),
+            OpcodeId::SLOAD => (1, 1),
+            OpcodeId::SSTORE => (2, 0),
+            OpcodeId::JUMP => (1, 0),
+            OpcodeId::JUMPI => (2, 0),
+            OpcodeId::PC => (0, 1),
+            OpcodeId::MSIZE => (0, 1),
+            OpcodeId::GAS => (0, 1),
+            OpcodeId::JUMPDEST => (0, 0),
+            OpcodeId::PUSH0 => (0, 1),
+            OpcodeId::PUSH1 => (0, 1),
+            OpcodeId::PUSH2 => (0, 1),
+            OpcodeId::PUSH3 => (0, 1),
+            OpcodeId::PUSH4 => (0, 1),
+            OpcodeId::PUSH5 => (0, 1),
+            OpcodeId::PUSH6 => (0, 1),
+            OpcodeId::PUSH7 => (0, 1),
+            OpcodeId::PUSH8 => (0, 1),
+            OpcodeId::PUSH9 => (0, 1),
+            OpcodeId::PUSH10 => (0, 1),
+            OpcodeId::PUSH11 => (0, 1),
+            OpcodeId::PUSH12 => (0, 1),
+            OpcodeId::PUSH13 => (0, 1),
+            OpcodeId::PUSH14 => (0, 1),
+            OpcodeId::PUSH15 => (0, 1),
+            OpcodeId::PUSH16 => (0, 1),
+            OpcodeId::PUSH17 => (0, 1),
+            OpcodeId::PUSH18 => (0, 1),
+            OpcodeId::PUSH19 => (0, 1),
+            OpcodeId::PUSH20 => (0, 1),
+            OpcodeId::PUSH21 => (0, 1),
+            OpcodeId::PUSH22 => (0, 1),
+            OpcodeId::PUSH23 => (0, 1),
+            OpcodeId::PUSH24 => (0, 1),
+            OpcodeId::PUSH25 => (0, 1),
+            OpcodeId::PUSH26 => (0, 1),
+            OpcodeId::PUSH27 => (0, 1),
+            OpcodeId::PUSH28 => (0, 1),
+            OpcodeId::PUSH29 => (0, 1),
+            OpcodeId::PUSH30 => (0, 1),
+            OpcodeId::PUSH31 => (0, 1),
+            OpcodeId::PUSH32 => (0, 1),
+            OpcodeId::DUP1 => (1, 2),
+            OpcodeId::DUP2 => (2, 3),
+            OpcodeId::DUP3 => (3, 4),
+            OpcodeId::DUP4 => (4, 5),
+            OpcodeId::DUP5 => (5, 6),
+            OpcodeId::DUP6 => (6, 7),
+            OpcodeId::DUP7 => (7, 8),
+            OpcodeId::DUP8 => (8, 9),
+            OpcodeId::DUP9 => (9, 10),
+            OpcodeId::DUP10 => (10, 11),
+            OpcodeId::DUP11 => (11, 12),
+            OpcodeId::DUP12 => (12, 13),
+            OpcodeId::DUP13 => (13, 14),
+            OpcodeId::DUP14 => (14, 15),
+            OpcodeId::DUP15 => (15, 16),
+            OpcodeId::DUP16 => (16, 17),
+            OpcodeId::SWAP1 => (2, 2),
+            OpcodeId::SWAP2 => (3, 3),
+            OpcodeId::SWAP3 => (4, 4),
+            OpcodeId::SWAP4 => (5, 5),
+            OpcodeId::SWAP5 => (6, 6),
+            OpcodeId::SWAP6 => (7, 7),
+            OpcodeId::SWAP7 => (8, 8),
+            OpcodeId::SWAP8 => (9, 9),
+            OpcodeId::SWAP9 => (10, 10),
+            OpcodeId::SWAP10 => (11, 11),
+            OpcodeId::SWAP11 => (12, 12),
+            OpcodeId::SWAP12 => (13, 13),
+            OpcodeId::SWAP13 => (14, 14),
+            OpcodeId::SWAP14 => (15, 15),
+            OpcodeId::SWAP15 => (16, 16),
+            OpcodeId::SWAP16 => (17, 17),
+            OpcodeId::LOG0 => (2, 0),
+            OpcodeId::LOG1 => (3, 0),
+            OpcodeId::LOG2 => (4, 0),
+            OpcodeId::LOG3 => (5, 0),
+            OpcodeId::LOG4 => (6, 0),
+            OpcodeId::CREATE => (3, 1),
+            OpcodeId::CALL => (7, 1),
+            OpcodeId::CALLCODE => (7, 1),
+            OpcodeId::RETURN => (2, 0),
+            OpcodeId::DELEGATECALL => (6, 1),
+            OpcodeId::CREATE2 => (4, 1),
+            OpcodeId::STATICCALL => (6, 1),
+            OpcodeId::REVERT => (2, 0),
+            OpcodeId::INVALID(_) => (0, 0),
+            OpcodeId::SELFDESTRUCT => (1, 0),
+        }
+    }
+
+    /// Returns the inclusive range of stack pointers with which the `OpcodeId`
+    /// is executed without a stack underflow nor a stack overflow.
+    pub const fn valid_stack_ptr_range(&self) -> (u32, u32) {
+        let (pops, pushes) = self.stack_io();
+        (pushes.saturating_sub(pops), 1024 - pops)
+    }
+
+    /// Returns the stack pointers with which the `OpcodeId` causes a stack
+    /// overflow.
+    pub fn stack_overflow_ptrs(&self) -> Vec<u32> {
+        (0..self.valid_stack_ptr_range().0).collect()
+    }
+
+    /// Returns the stack pointers with which the `OpcodeId` causes a stack
+    /// underflow.
+    pub fn stack_underflow_ptrs(&self) -> Vec<u32> {
+        (self.valid_stack_ptr_range().1 + 1..=1024).collect()
+    }
+
+    /// Returns all the opcodes which are defined in any hard fork.
+    pub fn valid_opcodes() -> Vec<Self> {
+        (0..=u8::MAX)
+            .filter_map(|byte| Self::try_from(byte).ok())
+            .filter(|opcode| !matches!(opcode, Self::INVALID(_)))
+            .collect()
+    }
+
+    /// Returns all the bytes which are not an opcode available under the rules
+    /// of the hard fork, including the designated invalid instruction `0xfe`.
+    pub fn invalid_opcodes(hardfork: Hardfork) -> Vec<u8> {
+        (0..=u8::MAX)
+            .filter(|byte| {
+                Self::try_from(*byte).map_or(true, |opcode| !opcode.is_enabled(hardfork))
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<u8> for OpcodeId {
@@ -685,6 +899,7 @@ impl TryFrom<u8> for OpcodeId {
             0x58u8 => OpcodeId::PC,
             0x59u8 => OpcodeId::MSIZE,
             0x5bu8 => OpcodeId::JUMPDEST,
+            0x5fu8 => OpcodeId::PUSH0,
             0x60u8 => OpcodeId::PUSH1,
             0x61u8 => OpcodeId::PUSH2,
             0x62u8 => OpcodeId::PUSH3,
@@ -838,6 +1053,7 @@ impl FromStr for OpcodeId {
             "PC" => OpcodeId::PC,
             "MSIZE" => OpcodeId::MSIZE,
             "JUMPDEST" => OpcodeId::JUMPDEST,
+            "PUSH0" => OpcodeId::PUSH0,
             "PUSH1" => OpcodeId::PUSH1,
             "PUSH2" => OpcodeId::PUSH2,
             "PUSH3" => OpcodeId::PUSH3,
@@ -922,7 +1138,7 @@ impl FromStr for OpcodeId {
             "COINBASE" => OpcodeId::COINBASE,
             "TIMESTAMP" => OpcodeId::TIMESTAMP,
             "NUMBER" => OpcodeId::NUMBER,
-            "DIFFICULTY" => OpcodeId::DIFFICULTY,
+            "DIFFICULTY" | "PREVRANDAO" => OpcodeId::DIFFICULTY,
             "GASLIMIT" => OpcodeId::GASLIMIT,
             "SLOAD" => OpcodeId::SLOAD,
             "SSTORE" => OpcodeId::SSTORE,
@@ -1,7 +1,7 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
-    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, Word, U64,
+    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hardfork, Hash, ToBigEndian, Word, U64,
 };
 use serde::{Serialize, Serializer};
 use std::collections::HashMap;
@@ -45,6 +45,8 @@ pub struct BlockConstants {
     pub number: U64,
     /// difficulty
     pub difficulty: Word,
+    /// mix hash, which is the randomness of the beacon chain since the Merge
+    pub mix_hash: Hash,
     /// gas limit
     pub gas_limit: Word,
     /// base fee
@@ -60,6 +62,7 @@ impl<TX> TryFrom<&Block<TX>> for BlockConstants {
             timestamp: block.timestamp,
             number: block.number.ok_or(Error::IncompleteBlock)?,
             difficulty: block.difficulty,
+            mix_hash: block.mix_hash.unwrap_or_default(),
             gas_limit: block.gas_limit,
             base_fee: block.base_fee_per_gas.ok_or(Error::IncompleteBlock)?,
         })
@@ -73,6 +76,7 @@ impl BlockConstants {
         timestamp: Word,
         number: U64,
         difficulty: Word,
+        mix_hash: Hash,
         gas_limit: Word,
         base_fee: Word,
     ) -> BlockConstants {
@@ -81,6 +85,7 @@ impl BlockConstants {
             timestamp,
             number,
             difficulty,
+            mix_hash,
             gas_limit,
             base_fee,
         }
@@ -131,8 +136,8 @@ impl Transaction {
             gas_limit: tx.gas,
             value: tx.value,
             gas_price: tx.gas_price.unwrap_or_default(),
-            gas_fee_cap: tx.max_priority_fee_per_gas.unwrap_or_default(),
-            gas_tip_cap: tx.max_fee_per_gas.unwrap_or_default(),
+            gas_fee_cap: tx.max_fee_per_gas.unwrap_or_default(),
+            gas_tip_cap: tx.max_priority_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
             v: tx.v.as_u64(),
@@ -147,6 +152,8 @@ impl Transaction {
 pub struct GethData {
     /// chain id
     pub chain_id: Word,
+    /// Hard fork whose rules the block is processed under
+    pub hardfork: Hardfork,
     /// history hashes contains most recent 256 block hashes in history, where
     /// the lastest one is at history_hashes[history_hashes.len() - 1].
     pub history_hashes: Vec<Word>,
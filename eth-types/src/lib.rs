@@ -18,10 +18,12 @@ pub mod macros;
 pub mod error;
 #[macro_use]
 pub mod bytecode;
+pub mod chain_spec;
 pub mod evm_types;
 pub mod geth_types;
 
 pub use bytecode::Bytecode;
+pub use chain_spec::{ChainSpec, Hardfork};
 pub use error::Error;
 use halo2_proofs::{
     arithmetic::{Field as Halo2Field, FieldExt},
@@ -36,7 +38,10 @@ use crate::evm_types::{Gas, GasCost, OpcodeId, ProgramCounter};
 pub use ethers_core::abi::ethereum_types::U512;
 use ethers_core::types;
 pub use ethers_core::types::{
-    transaction::{eip2930::AccessList, response::Transaction},
+    transaction::{
+        eip2930::{AccessList, AccessListItem},
+        response::Transaction,
+    },
     Address, Block, Bytes, H160, H256, U256, U64,
 };
 
@@ -2,7 +2,7 @@
 
 use eth_types::{
     geth_types::{Account, BlockConstants, Transaction},
-    Address, Error, GethExecTrace, Word,
+    Address, Error, GethExecTrace, Hardfork, Word,
 };
 use serde::Serialize;
 use std::collections::HashMap;
@@ -12,6 +12,8 @@ use std::collections::HashMap;
 pub struct TraceConfig {
     /// chain id
     pub chain_id: Word,
+    /// hard fork whose rules the transactions are traced under
+    pub hardfork: Hardfork,
     /// history hashes contains most recent 256 block hashes in history, where
     /// the lastest one is at history_hashes[history_hashes.len() - 1].
     pub history_hashes: Vec<Word>,
@@ -1,5 +1,8 @@
 //! Utility traits, functions used in the crate.
-use eth_types::evm_types::{GasCost, OpcodeId};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Hardfork,
+};
 use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
 
 /// Returns the sum of the passed in cells
@@ -149,6 +152,7 @@ impl_expr!(u64);
 impl_expr!(usize);
 impl_expr!(OpcodeId, OpcodeId::as_u8);
 impl_expr!(GasCost, GasCost::as_u64);
+impl_expr!(Hardfork);
 
 impl<F: FieldExt> Expr<F> for Expression<F> {
     #[inline]
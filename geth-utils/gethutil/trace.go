@@ -86,6 +86,7 @@ type Block struct {
 	Timestamp  *hexutil.Big   `json:"timestamp"`
 	Number     *hexutil.Big   `json:"number"`
 	Difficulty *hexutil.Big   `json:"difficulty"`
+	MixHash    common.Hash    `json:"mix_hash"`
 	GasLimit   *hexutil.Big   `json:"gas_limit"`
 	BaseFee    *hexutil.Big   `json:"base_fee"`
 }
@@ -114,7 +115,8 @@ type Transaction struct {
 }
 
 type TraceConfig struct {
-	ChainID *hexutil.Big `json:"chain_id"`
+	ChainID  *hexutil.Big `json:"chain_id"`
+	Hardfork string       `json:"hardfork"`
 	// HistoryHashes contains most recent 256 block hashes in history,
 	// where the lastest one is at HistoryHashes[len(HistoryHashes)-1].
 	HistoryHashes []*hexutil.Big             `json:"history_hashes"`
@@ -139,17 +141,44 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		IstanbulBlock:       big.NewInt(0),
 		MuirGlacierBlock:    big.NewInt(0),
 		BerlinBlock:         big.NewInt(0),
-		LondonBlock:         big.NewInt(0),
+	}
+
+	// Hard fork defaults to London when it's not specified. The merge rules
+	// are enabled by the randomness of the beacon chain in the block context,
+	// which DIFFICULTY returns instead of the difficulty (EIP-4399). Shanghai
+	// is activated by timestamp, so it's activated since the genesis time.
+	var random *common.Hash
+	switch config.Hardfork {
+	case "Berlin":
+	case "", "London":
+		chainConfig.LondonBlock = big.NewInt(0)
+	case "Merge":
+		chainConfig.LondonBlock = big.NewInt(0)
+		random = &config.Block.MixHash
+	case "Shanghai":
+		chainConfig.LondonBlock = big.NewInt(0)
+		chainConfig.ShanghaiTime = new(uint64)
+		random = &config.Block.MixHash
+	default:
+		return nil, fmt.Errorf("unsupported hard fork: %s", config.Hardfork)
 	}
 
 	var txsGasLimit uint64
 	blockGasLimit := toBigInt(config.Block.GasLimit).Uint64()
-	messages := make([]types.Message, len(config.Transactions))
+	messages := make([]core.Message, len(config.Transactions))
 	for i, tx := range config.Transactions {
-		// If gas price is specified directly, the tx is treated as legacy type.
-		if tx.GasPrice != nil {
+		// If fee cap is not specified, the tx is treated as legacy type with gas
+		// price specified directly, otherwise the gas price is the effective one
+		// of EIP-1559.
+		if tx.GasFeeCap == nil || tx.GasFeeCap.ToInt().Sign() == 0 {
 			tx.GasFeeCap = tx.GasPrice
 			tx.GasTipCap = tx.GasPrice
+		} else {
+			gasPrice := new(big.Int).Add(toBigInt(config.Block.BaseFee), toBigInt(tx.GasTipCap))
+			if gasPrice.Cmp(toBigInt(tx.GasFeeCap)) > 0 {
+				gasPrice = toBigInt(tx.GasFeeCap)
+			}
+			tx.GasPrice = (*hexutil.Big)(gasPrice)
 		}
 
 		txAccessList := make(types.AccessList, len(tx.AccessList))
@@ -157,19 +186,19 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 			txAccessList[i].Address = accessList.Address
 			txAccessList[i].StorageKeys = accessList.StorageKeys
 		}
-		messages[i] = types.NewMessage(
-			tx.From,
-			tx.To,
-			uint64(tx.Nonce),
-			toBigInt(tx.Value),
-			uint64(tx.GasLimit),
-			toBigInt(tx.GasPrice),
-			toBigInt(tx.GasFeeCap),
-			toBigInt(tx.GasTipCap),
-			tx.CallData,
-			txAccessList,
-			false,
-		)
+		messages[i] = core.Message{
+			From:              tx.From,
+			To:                tx.To,
+			Nonce:             uint64(tx.Nonce),
+			Value:             toBigInt(tx.Value),
+			GasLimit:          uint64(tx.GasLimit),
+			GasPrice:          toBigInt(tx.GasPrice),
+			GasFeeCap:         toBigInt(tx.GasFeeCap),
+			GasTipCap:         toBigInt(tx.GasTipCap),
+			Data:              tx.CallData,
+			AccessList:        txAccessList,
+			SkipAccountChecks: false,
+		}
 
 		txsGasLimit += uint64(tx.GasLimit)
 	}
@@ -190,10 +219,11 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		},
 		Coinbase:    config.Block.Coinbase,
 		BlockNumber: toBigInt(config.Block.Number),
-		Time:        toBigInt(config.Block.Timestamp),
+		Time:        toBigInt(config.Block.Timestamp).Uint64(),
 		Difficulty:  toBigInt(config.Block.Difficulty),
 		BaseFee:     toBigInt(config.Block.BaseFee),
 		GasLimit:    blockGasLimit,
+		Random:      random,
 	}
 
 	// Setup state db with accounts from argument
@@ -212,11 +242,12 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 
 	// Run the transactions with tracing enabled.
 	executionResults := make([]*ExecutionResult, len(config.Transactions))
-	for i, message := range messages {
+	for i := range messages {
+		message := &messages[i]
 		tracer := logger.NewStructLogger(&logger.Config{EnableMemory: true})
 		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(message), stateDB, &chainConfig, vm.Config{Debug: true, Tracer: tracer, NoBaseFee: true})
 
-		result, err := core.ApplyMessage(evm, message, new(core.GasPool).AddGas(message.Gas()))
+		result, err := core.ApplyMessage(evm, message, new(core.GasPool).AddGas(message.GasLimit))
 		if err != nil {
 			return nil, fmt.Errorf("Failed to apply config.Transactions[%d]: %w", i, err)
 		}
@@ -1,10 +1,10 @@
 module main
 
-go 1.16
+go 1.19
 
 require (
-	github.com/ethereum/go-ethereum v1.10.18
-	github.com/holiman/uint256 v1.2.0
+	github.com/ethereum/go-ethereum v1.11.6
+	github.com/holiman/uint256 v1.2.2
 )
 
 // Uncomment for debugging
@@ -5,7 +5,7 @@
 
 use bus_mapping::rpc::GethClient;
 use env_logger::Env;
-use eth_types::Address;
+use eth_types::{Address, ChainSpec, Hardfork};
 use ethers::{
     abi,
     core::k256::ecdsa::SigningKey,
@@ -76,6 +76,12 @@ pub async fn get_chain_id() -> u64 {
     client.get_chain_id().await.unwrap()
 }
 
+/// Get the chain specification of the geth dev node, which runs London since
+/// genesis.
+pub async fn get_chain_spec() -> ChainSpec {
+    ChainSpec::new(get_chain_id().await, Hardfork::London)
+}
+
 const PHRASE: &str =
     "work man father plunge mystery proud hollow address reunion sauce theory bonus";
 
@@ -1,7 +1,8 @@
 #![cfg(feature = "circuit_input_builder")]
 
-use bus_mapping::circuit_input_builder::BuilderClient;
-use integration_tests::{get_client, log_init, GenDataOutput};
+use bus_mapping::{circuit_input_builder::BuilderClient, operation::TxReceiptField};
+use ethers::providers::Middleware;
+use integration_tests::{get_chain_spec, get_client, get_provider, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
 
@@ -11,10 +12,12 @@ lazy_static! {
 
 async fn test_circuit_input_builder_block(block_num: u64) {
     let cli = get_client();
-    let cli = BuilderClient::new(cli).await.unwrap();
+    let cli = BuilderClient::new(cli, get_chain_spec().await)
+        .await
+        .unwrap();
 
-    // 1. Query geth for Block, Txs and TxExecTraces
-    let (eth_block, geth_trace) = cli.get_block(block_num).await.unwrap();
+    // 1. Query geth for Block, Txs, TxExecTraces and history hashes
+    let (eth_block, geth_trace, history_hashes) = cli.get_block(block_num).await.unwrap();
 
     // 2. Get State Accesses from TxExecTraces
     let access_set = cli.get_state_accesses(&eth_block, &geth_trace).unwrap();
@@ -30,10 +33,31 @@ async fn test_circuit_input_builder_block(block_num: u64) {
     // 5. For each step in TxExecTraces, gen the associated ops and state
     // circuit inputs
     let builder = cli
-        .gen_inputs_from_state(state_db, code_db, &eth_block, &geth_trace)
+        .gen_inputs_from_state(state_db, code_db, &eth_block, &geth_trace, history_hashes)
         .unwrap();
 
     trace!("CircuitInputBuilder: {:#?}", builder);
+
+    // 6. Check the cumulative gas used of each tx, which accounts for the
+    // capped gas refund, against the receipts from geth
+    let prov = get_provider();
+    for (idx, tx) in eth_block.transactions.iter().enumerate() {
+        let receipt = prov
+            .get_transaction_receipt(tx.hash)
+            .await
+            .unwrap()
+            .expect("receipt of tx in block");
+        let cumulative_gas_used = builder
+            .block
+            .container
+            .tx_receipt
+            .iter()
+            .map(|op| op.op())
+            .find(|op| op.tx_id == idx + 1 && op.field == TxReceiptField::CumulativeGasUsed)
+            .unwrap()
+            .value;
+        assert_eq!(receipt.cumulative_gas_used, cumulative_gas_used.into());
+    }
 }
 
 macro_rules! declare_tests {
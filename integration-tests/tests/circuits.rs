@@ -3,7 +3,7 @@
 use bus_mapping::circuit_input_builder::BuilderClient;
 use bus_mapping::operation::OperationContainer;
 use halo2_proofs::dev::MockProver;
-use integration_tests::{get_client, log_init, GenDataOutput};
+use integration_tests::{get_chain_spec, get_client, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
 use zkevm_circuits::evm_circuit::witness::RwMap;
@@ -19,7 +19,9 @@ lazy_static! {
 async fn test_evm_circuit_block(block_num: u64) {
     log::info!("test evm circuit, block number: {}", block_num);
     let cli = get_client();
-    let cli = BuilderClient::new(cli).await.unwrap();
+    let cli = BuilderClient::new(cli, get_chain_spec().await)
+        .await
+        .unwrap();
     let builder = cli.gen_inputs(block_num).await.unwrap();
 
     let block = block_convert(&builder.block, &builder.code_db);
@@ -32,7 +34,9 @@ async fn test_state_circuit_block(block_num: u64) {
 
     log::info!("test state circuit, block number: {}", block_num);
     let cli = get_client();
-    let cli = BuilderClient::new(cli).await.unwrap();
+    let cli = BuilderClient::new(cli, get_chain_spec().await)
+        .await
+        .unwrap();
     let builder = cli.gen_inputs(block_num).await.unwrap();
 
     // Generate state proof
@@ -1,7 +1,7 @@
 //! Mock Block definition and builder related methods.
 
 use crate::{MockTransaction, MOCK_CHAIN_ID};
-use eth_types::{Address, Block, Bytes, Hash, Transaction, Word, U64};
+use eth_types::{Address, Block, Bytes, Hardfork, Hash, Transaction, Word, U64};
 use ethbloom::Bloom;
 
 #[derive(Clone, Debug)]
@@ -35,6 +35,8 @@ pub struct MockBlock {
     // Also, the field is stored in the block_table since we don't have a chain_config
     // structure/table.
     pub(crate) chain_id: Word,
+    // Hard fork whose rules the block is traced and processed under.
+    pub(crate) hardfork: Hardfork,
 }
 
 impl Default for MockBlock {
@@ -63,6 +65,7 @@ impl Default for MockBlock {
             mix_hash: Hash::zero(),
             nonce: U64::zero(),
             chain_id: *MOCK_CHAIN_ID,
+            hardfork: Hardfork::default(),
         }
     }
 }
@@ -272,6 +275,12 @@ impl MockBlock {
         self
     }
 
+    /// Set hardfork field for the MockBlock.
+    pub fn hardfork(&mut self, hardfork: Hardfork) -> &mut Self {
+        self.hardfork = hardfork;
+        self
+    }
+
     /// Finalizes the current MockBlock under construction returning a new
     /// instance to it.
     pub fn build(&mut self) -> Self {
@@ -3,7 +3,7 @@
 use crate::{eth, MockAccount, MockBlock, MockTransaction};
 use eth_types::{
     geth_types::{Account, BlockConstants, GethData},
-    Block, Bytecode, Error, GethExecTrace, Transaction, Word,
+    Block, Bytecode, Error, GethExecTrace, Hardfork, Transaction, Word,
 };
 use external_tracer::{trace, TraceConfig};
 use helpers::*;
@@ -78,6 +78,8 @@ use itertools::Itertools;
 pub struct TestContext<const NACC: usize, const NTX: usize> {
     /// chain id
     pub chain_id: Word,
+    /// Hard fork whose rules the block is traced under
+    pub hardfork: Hardfork,
     /// Account list
     pub accounts: [Account; NACC],
     /// history hashes contains most recent 256 block hashes in history, where
@@ -93,6 +95,7 @@ impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethD
     fn from(ctx: TestContext<NACC, NTX>) -> GethData {
         GethData {
             chain_id: ctx.chain_id,
+            hardfork: ctx.hardfork,
             history_hashes: ctx.history_hashes,
             eth_block: ctx.eth_block,
             geth_traces: ctx.geth_traces.to_vec(),
@@ -162,6 +165,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
         func_block(&mut block, transactions).build();
 
         let chain_id = block.chain_id;
+        let hardfork = block.hardfork;
         let block = Block::<Transaction>::from(block);
         let accounts: [Account; NACC] = accounts
             .iter()
@@ -171,10 +175,16 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             .try_into()
             .expect("Mismatched acc len");
 
-        let geth_traces = gen_geth_traces(block.clone(), accounts.clone(), history_hashes.clone())?;
+        let geth_traces = gen_geth_traces(
+            block.clone(),
+            hardfork,
+            accounts.clone(),
+            history_hashes.clone(),
+        )?;
 
         Ok(Self {
             chain_id,
+            hardfork,
             accounts,
             history_hashes: history_hashes.unwrap_or_default(),
             eth_block: block,
@@ -201,11 +211,13 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
 /// Block
 fn gen_geth_traces<const NACC: usize, const NTX: usize>(
     block: Block<Transaction>,
+    hardfork: Hardfork,
     accounts: [Account; NACC],
     history_hashes: Option<Vec<Word>>,
 ) -> Result<[GethExecTrace; NTX], Error> {
     let trace_config = TraceConfig {
         chain_id: block.transactions[0].chain_id.unwrap_or_default(),
+        hardfork,
         history_hashes: history_hashes.unwrap_or_default(),
         block_constants: BlockConstants::try_from(&block)?,
         accounts: accounts
@@ -1,4 +1,5 @@
 use env_logger::Env;
+use eth_types::ChainSpec;
 use halo2_proofs::pairing::bn256::G1Affine;
 use halo2_proofs::poly::commitment::Params;
 use std::env::var;
@@ -12,6 +13,8 @@ use prover::compute_proof::compute_proof;
 /// - BLOCK_NUM - the block number to generate the proof for
 /// - RPC_URL - a geth http rpc that supports the debug namespace
 /// - PARAMS_PATH - a path to a file generated with the gen_params tool
+/// - CHAIN_SPEC - the json encoded chain specification of the chain behind
+///   RPC_URL
 #[tokio::main]
 async fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
@@ -28,13 +31,16 @@ async fn main() {
         .expect("PARAMS_PATH env var")
         .parse()
         .expect("Cannot parse PARAMS_PATH env var");
+    let chain_spec: ChainSpec =
+        serde_json::from_str(&var("CHAIN_SPEC").expect("CHAIN_SPEC env var"))
+            .expect("Cannot parse CHAIN_SPEC env var");
 
     // load polynomial commitment parameters
     let params_fs = File::open(&params_path).expect("couldn't open params");
     let params: Params<G1Affine> =
         Params::read::<_>(&mut BufReader::new(params_fs)).expect("Failed to read params");
 
-    let result = compute_proof(&params, &block_num, &rpc_url)
+    let result = compute_proof(&params, &block_num, &rpc_url, &chain_spec)
         .await
         .expect("compute_proof");
 
@@ -1,5 +1,6 @@
 use bus_mapping::circuit_input_builder::BuilderClient;
 use bus_mapping::rpc::GethClient;
+use eth_types::ChainSpec;
 use ethers_providers::Http;
 use halo2_proofs::{
     pairing::bn256::{Fr, G1Affine},
@@ -23,17 +24,19 @@ use crate::structs::Proofs;
 
 /// Gathers debug trace(s) from `rpc_url` for block `block_num` with `params`
 /// created via the `gen_params` tool.
-/// Expects a go-ethereum node with debug & archive capabilities on `rpc_url`.
+/// Expects a go-ethereum node with debug & archive capabilities on `rpc_url`,
+/// which runs the chain described by `chain_spec`.
 pub async fn compute_proof(
     params: &Params<G1Affine>,
     block_num: &u64,
     rpc_url: &str,
+    chain_spec: &ChainSpec,
 ) -> Result<Proofs, Box<dyn std::error::Error>> {
     // request & build the inputs for the circuits
     let time_started = Instant::now();
     let url = Http::from_str(rpc_url)?;
     let geth_client = GethClient::new(url);
-    let builder = BuilderClient::new(geth_client).await?;
+    let builder = BuilderClient::new(geth_client, chain_spec.clone()).await?;
     let builder = builder.gen_inputs(*block_num).await?;
 
     // TODO: only {evm,state}_proof are implemented right now
@@ -124,6 +124,7 @@ impl SharedState {
                     param.as_ref(),
                     &pending_task_copy.options.block,
                     &pending_task_copy.options.rpc,
+                    &pending_task_copy.options.chain_spec,
                 )
                 .await;
 
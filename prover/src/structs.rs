@@ -32,12 +32,14 @@ pub struct JsonRpcRequest<T: serde::Serialize> {
     pub params: T,
 }
 
-#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct ProofRequestOptions {
     /// the block number
     pub block: u64,
     /// the rpc url
     pub rpc: String,
+    /// the specification of the chain behind the rpc url
+    pub chain_spec: eth_types::ChainSpec,
     /// retry proof computation if error
     pub retry: bool,
     /// parameter file to use
@@ -46,6 +48,9 @@ pub struct ProofRequestOptions {
 
 impl PartialEq for ProofRequestOptions {
     fn eq(&self, other: &Self) -> bool {
-        self.block == other.block && self.rpc == other.rpc && self.param == other.param
+        self.block == other.block
+            && self.rpc == other.rpc
+            && self.chain_spec == other.chain_spec
+            && self.param == other.param
     }
 }
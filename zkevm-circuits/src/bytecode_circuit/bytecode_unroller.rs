@@ -566,7 +566,7 @@ impl<F: Field> Config<F> {
         bytecodes: &[UnrolledBytecode<F>],
     ) -> Result<(), Error> {
         // push table: BYTE -> NUM_PUSHED:
-        // [0, OpcodeId::PUSH1[ -> 0
+        // [0, OpcodeId::PUSH1[ -> 0, including OpcodeId::PUSH0 which has no data
         // [OpcodeId::PUSH1, OpcodeId::PUSH32] -> [1..32]
         // ]OpcodeId::PUSH32, 256[ -> 0
         layouter.assign_region(
@@ -876,6 +876,8 @@ mod tests {
                     ],
                     r,
                 ),
+                // PUSH0 has no push data, so the following byte is code.
+                unroll(vec![OpcodeId::PUSH0.as_u8(), OpcodeId::ADD.as_u8()], r),
             ],
             true,
         );
@@ -35,7 +35,7 @@ pub struct CopyCircuit<F> {
     pub is_last: Column<Advice>,
     /// The relevant ID for the read-write row, represented as a random linear
     /// combination. The ID may be one of the below:
-    /// 1. Call ID/Caller ID for CopyDataType::Memory
+    /// 1. Call ID/Caller ID/Last callee ID for CopyDataType::Memory
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
     pub id: Column<Advice>,
@@ -56,9 +56,17 @@ pub struct CopyCircuit<F> {
     pub rw_counter: Column<Advice>,
     /// Decrementing counter denoting reverse read-write counter.
     pub rwc_inc_left: Column<Advice>,
+    /// Random linear combination accumulator of the copied bytes, assigned on
+    /// write rows of copy events whose source or destination is
+    /// CopyDataType::RlcAcc.
+    pub value_acc: Column<Advice>,
+    /// Random linear combination of all the copied bytes in the copy event in
+    /// case of the source or destination being CopyDataType::RlcAcc. It stays
+    /// the same across all rows of the copy event, and is 0 otherwise.
+    pub rlc_acc: Column<Advice>,
     /// Binary chip to constrain the copy table conditionally depending on the
-    /// current row's tag, whether it is Bytecode, Memory, TxCalldata or
-    /// TxLog.
+    /// current row's tag, whether it is Bytecode, Memory, TxCalldata, TxLog
+    /// or RlcAcc.
     pub tag: BinaryNumberConfig<CopyDataType, 3>,
     /// Lt chip to check: src_addr < src_addr_end.
     /// Since `src_addr` and `src_addr_end` are u64, 8 bytes are sufficient for
@@ -78,6 +86,7 @@ impl<F: Field> LookupTable<F> for CopyCircuit<F> {
             meta.query_advice(self.src_addr_end, Rotation::cur()), // src_addr_end
             meta.query_advice(self.addr, Rotation::next()), // dst_addr
             meta.query_advice(self.bytes_left, Rotation::cur()), // length
+            meta.query_advice(self.rlc_acc, Rotation::cur()), // rlc_acc
             meta.query_advice(self.rw_counter, Rotation::cur()), // rw_counter
             meta.query_advice(self.rwc_inc_left, Rotation::cur()), // rwc_inc_left
         ]
@@ -92,6 +101,7 @@ impl<F: Field> CopyCircuit<F> {
         tx_table: &dyn LookupTable<F>,
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
+        randomness: Expression<F>,
     ) -> Self {
         let q_enable = meta.fixed_column();
         let q_step = meta.complex_selector();
@@ -106,6 +116,8 @@ impl<F: Field> CopyCircuit<F> {
         let is_pad = meta.advice_column();
         let rw_counter = meta.advice_column();
         let rwc_inc_left = meta.advice_column();
+        let value_acc = meta.advice_column();
+        let rlc_acc = meta.advice_column();
 
         let tag = BinaryNumberChip::configure(meta, q_enable);
 
@@ -188,6 +200,11 @@ impl<F: Field> CopyCircuit<F> {
                         meta.query_advice(rwc_inc_left, Rotation::cur()) - rw_diff.clone(),
                         meta.query_advice(rwc_inc_left, Rotation::next()),
                     );
+                    cb.require_equal(
+                        "rows[0].rlc_acc == rows[1].rlc_acc",
+                        meta.query_advice(rlc_acc, Rotation::cur()),
+                        meta.query_advice(rlc_acc, Rotation::next()),
+                    );
                 },
             );
             cb.condition(meta.query_advice(is_last, Rotation::cur()), |cb| {
@@ -243,6 +260,50 @@ impl<F: Field> CopyCircuit<F> {
                 meta.query_advice(is_pad, Rotation::next()),
             );
 
+            // The bytes read from an accumulator aren't looked up anywhere, they
+            // are constrained by the accumulated random linear combination.
+            let is_rlc_acc = or::expr([
+                tag.value_equals(CopyDataType::RlcAcc, Rotation::cur())(meta),
+                tag.value_equals(CopyDataType::RlcAcc, Rotation::next())(meta),
+            ]);
+            cb.condition(
+                and::expr([
+                    is_rlc_acc.clone(),
+                    meta.query_advice(is_first, Rotation::cur()),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "value_acc == value for the first write row",
+                        meta.query_advice(value_acc, Rotation::next()),
+                        meta.query_advice(value, Rotation::next()),
+                    );
+                },
+            );
+            cb.condition(
+                and::expr([
+                    is_rlc_acc.clone(),
+                    not::expr(meta.query_advice(is_last, Rotation::next())),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "value_acc_next == value_acc * randomness + value_next for non-last step",
+                        meta.query_advice(value_acc, Rotation(3)),
+                        meta.query_advice(value_acc, Rotation::next()) * randomness.clone()
+                            + meta.query_advice(value, Rotation(3)),
+                    );
+                },
+            );
+            cb.condition(
+                and::expr([is_rlc_acc, meta.query_advice(is_last, Rotation::next())]),
+                |cb| {
+                    cb.require_equal(
+                        "rlc_acc == value_acc for last step",
+                        meta.query_advice(rlc_acc, Rotation::next()),
+                        meta.query_advice(value_acc, Rotation::next()),
+                    );
+                },
+            );
+
             cb.gate(meta.query_selector(q_step))
         });
 
@@ -338,6 +399,8 @@ impl<F: Field> CopyCircuit<F> {
             is_pad,
             rw_counter,
             rwc_inc_left,
+            value_acc,
+            rlc_acc,
             tag,
             addr_lt_addr_end,
         }
@@ -356,8 +419,26 @@ impl<F: Field> CopyCircuit<F> {
             || "assign copy table",
             |mut region| {
                 let mut offset = 0;
-                for copy_event in block.copy_events.values() {
+                for copy_event in block.copy_events.iter() {
+                    let is_rlc_acc = copy_event.src_type == CopyDataType::RlcAcc
+                        || copy_event.dst_type == CopyDataType::RlcAcc;
+                    let rlc_acc = if is_rlc_acc {
+                        copy_event
+                            .steps
+                            .iter()
+                            .filter(|step| step.rw.is_write())
+                            .fold(F::zero(), |acc, step| {
+                                acc * block.randomness + F::from(step.value as u64)
+                            })
+                    } else {
+                        F::zero()
+                    };
+                    let mut value_acc = F::zero();
                     for (step_idx, copy_step) in copy_event.steps.iter().enumerate() {
+                        if copy_step.rw.is_write() && is_rlc_acc {
+                            value_acc =
+                                value_acc * block.randomness + F::from(copy_step.value as u64);
+                        }
                         self.assign_step(
                             &mut region,
                             offset,
@@ -365,6 +446,8 @@ impl<F: Field> CopyCircuit<F> {
                             copy_event,
                             step_idx,
                             copy_step,
+                            value_acc,
+                            rlc_acc,
                             &tag_chip,
                             &lt_chip,
                         )?;
@@ -390,6 +473,8 @@ impl<F: Field> CopyCircuit<F> {
         copy_event: &CopyEvent,
         step_idx: usize,
         copy_step: &CopyStep,
+        value_acc: F,
+        rlc_acc: F,
         tag_chip: &BinaryNumberChip<F, CopyDataType, 3>,
         lt_chip: &LtChip<F, 8>,
     ) -> Result<(), Error> {
@@ -406,6 +491,8 @@ impl<F: Field> CopyCircuit<F> {
             &copy_event.dst_id
         };
         let bytes_left = copy_event.length - step_idx as u64 / 2;
+        let is_rlc_acc = copy_event.src_type == CopyDataType::RlcAcc
+            || copy_event.dst_type == CopyDataType::RlcAcc;
 
         // is_first
         region.assign_advice(
@@ -502,6 +589,26 @@ impl<F: Field> CopyCircuit<F> {
             offset,
             || Ok(F::from(copy_step.rwc_inc_left)),
         )?;
+        // value_acc
+        region.assign_advice(
+            || format!("assign value_acc {}", offset),
+            self.value_acc,
+            offset,
+            || {
+                Ok(if copy_step.rw.is_write() && is_rlc_acc {
+                    value_acc
+                } else {
+                    F::zero()
+                })
+            },
+        )?;
+        // rlc_acc
+        region.assign_advice(
+            || format!("assign rlc_acc {}", offset),
+            self.rlc_acc,
+            offset,
+            || Ok(rlc_acc),
+        )?;
         // tag binary number chip
         tag_chip.assign(region, offset, &copy_step.tag)?;
         // assignment for read steps
@@ -616,6 +723,20 @@ impl<F: Field> CopyCircuit<F> {
             offset,
             || Ok(F::zero()),
         )?;
+        // value_acc
+        region.assign_advice(
+            || format!("assign value_acc {}", offset),
+            self.value_acc,
+            offset,
+            || Ok(F::zero()),
+        )?;
+        // rlc_acc
+        region.assign_advice(
+            || format!("assign rlc_acc {}", offset),
+            self.rlc_acc,
+            offset,
+            || Ok(F::zero()),
+        )?;
         // tag
         tag_chip.assign(region, offset, &CopyDataType::default())?;
         Ok(())
@@ -629,11 +750,12 @@ mod tests {
         mock::BlockData,
         operation::RWCounter,
     };
-    use eth_types::{bytecode, geth_types::GethData, Field, Word};
+    use eth_types::{bytecode, geth_types::GethData, Field, ToWord, Word};
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner},
         dev::{MockProver, VerifyFailure},
         plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+        poly::Rotation,
     };
     use itertools::Itertools;
     use mock::TestContext;
@@ -642,6 +764,7 @@ mod tests {
     use crate::{
         evm_circuit::witness::{block_convert, Block, Bytecode, RwMap, Transaction},
         rw_table::RwTable,
+        util::Expr,
     };
 
     use super::CopyCircuit;
@@ -793,7 +916,17 @@ mod tests {
             let tx_table = [(); 4].map(|_| meta.advice_column());
             let rw_table = RwTable::construct(meta);
             let bytecode_table = [(); 5].map(|_| meta.advice_column());
-            let copy_table = CopyCircuit::configure(meta, &tx_table, &rw_table, &bytecode_table);
+            let randomness = {
+                let column = meta.instance_column();
+                let mut randomness = None;
+                meta.create_gate("", |meta| {
+                    randomness = Some(meta.query_instance(column, Rotation::cur()));
+                    [0.expr()]
+                });
+                randomness.unwrap()
+            };
+            let copy_table =
+                CopyCircuit::configure(meta, &tx_table, &rw_table, &bytecode_table, randomness);
 
             MyConfig {
                 tx_table,
@@ -820,8 +953,9 @@ mod tests {
     }
 
     fn run_circuit<F: Field>(k: u32, block: Block<F>) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = vec![block.randomness; (1 << k) - 64];
         let circuit = MyCircuit::<F>::new(block);
-        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        let prover = MockProver::<F>::run(k, &circuit, vec![randomness]).unwrap();
         prover.verify()
     }
 
@@ -859,6 +993,70 @@ mod tests {
         builder
     }
 
+    fn gen_sha3_data() -> CircuitInputBuilder {
+        let code = bytecode! {
+            PUSH32(Word::from(0x20))
+            PUSH32(Word::from(0x00))
+            SHA3
+            STOP
+        };
+        let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+        let block: GethData = test_ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder
+    }
+
+    fn gen_returndatacopy_data() -> CircuitInputBuilder {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+        let code_b = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH32(Word::from(0x00))
+            MSTORE
+            PUSH32(Word::from(0x20))
+            PUSH32(Word::from(0x00))
+            RETURN
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(Word::from(0x20))
+            PUSH32(Word::from(0x00))
+            PUSH32(Word::from(0x00))
+            RETURNDATACOPY
+            STOP
+        };
+        let test_ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        let block: GethData = test_ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder
+    }
+
     #[test]
     fn copy_circuit_valid_calldatacopy() {
         let builder = gen_calldatacopy_data();
@@ -873,6 +1071,20 @@ mod tests {
         assert!(run_circuit(10, block).is_ok());
     }
 
+    #[test]
+    fn copy_circuit_valid_sha3() {
+        let builder = gen_sha3_data();
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert!(run_circuit(10, block).is_ok());
+    }
+
+    #[test]
+    fn copy_circuit_valid_returndatacopy() {
+        let builder = gen_returndatacopy_data();
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert!(run_circuit(10, block).is_ok());
+    }
+
     fn perturb_tag(block: &mut bus_mapping::circuit_input_builder::Block, tag: CopyDataType) {
         debug_assert!(!block.copy_events.is_empty());
         debug_assert!(!block.copy_events[0].steps.is_empty());
@@ -915,4 +1127,23 @@ mod tests {
         let block = block_convert(&builder.block, &builder.code_db);
         assert!(run_circuit(10, block).is_err());
     }
+
+    #[test]
+    fn copy_circuit_invalid_sha3() {
+        let mut builder = gen_sha3_data();
+        match rand::thread_rng().gen_bool(0.5) {
+            true => perturb_tag(&mut builder.block, CopyDataType::Memory),
+            false => perturb_tag(&mut builder.block, CopyDataType::RlcAcc),
+        }
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert!(run_circuit(10, block).is_err());
+    }
+
+    #[test]
+    fn copy_circuit_invalid_returndatacopy() {
+        let mut builder = gen_returndatacopy_data();
+        perturb_tag(&mut builder.block, CopyDataType::Memory);
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert!(run_circuit(10, block).is_err());
+    }
 }
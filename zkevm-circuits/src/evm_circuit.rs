@@ -27,6 +27,7 @@ pub struct EvmCircuit<F> {
 
 impl<F: Field> EvmCircuit<F> {
     /// Configure EvmCircuit
+    #[allow(clippy::too_many_arguments)]
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         power_of_randomness: [Expression<F>; 31],
@@ -35,6 +36,9 @@ impl<F: Field> EvmCircuit<F> {
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
         copy_table: &dyn LookupTable<F>,
+        keccak_table: &dyn LookupTable<F>,
+        exp_table: &dyn LookupTable<F>,
+        precompile_table: &dyn LookupTable<F>,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let byte_table = [(); 1].map(|_| meta.fixed_column());
@@ -48,6 +52,9 @@ impl<F: Field> EvmCircuit<F> {
             bytecode_table,
             block_table,
             copy_table,
+            keccak_table,
+            exp_table,
+            precompile_table,
         ));
 
         Self {
@@ -99,7 +106,8 @@ impl<F: Field> EvmCircuit<F> {
         )
     }
 
-    /// Assign block
+    /// Assign block, whose steps are padded with EndBlock to
+    /// `block.evm_circuit_pad_to` rows
     pub fn assign_block(
         &self,
         layouter: &mut impl Layouter<F>,
@@ -138,7 +146,7 @@ impl<F: Field> EvmCircuit<F> {
                 num_rows += self.execution.get_step_height(step.execution_state);
             }
         }
-        num_rows
+        num_rows.max(block.evm_circuit_pad_to + 1)
     }
 }
 
@@ -148,12 +156,17 @@ pub mod test {
         copy_circuit::CopyCircuit,
         evm_circuit::{
             table::FixedTableTag,
-            witness::{Block, BlockContext, Bytecode, RwMap, Transaction},
+            witness::{
+                keccak_table_assignments, precompile_table_assignments, Block, BlockContext,
+                Bytecode, RwMap, Transaction,
+            },
             EvmCircuit,
         },
+        exp_circuit::ExpCircuit,
         rw_table::RwTable,
         util::Expr,
     };
+    use bus_mapping::circuit_input_builder::PrecompileEvent;
     use eth_types::{Field, Word};
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner},
@@ -195,6 +208,9 @@ pub mod test {
         bytecode_table: [Column<Advice>; 5],
         block_table: [Column<Advice>; 3],
         copy_table: CopyCircuit<F>,
+        keccak_table: [Column<Advice>; 4],
+        exp_table: ExpCircuit<F>,
+        precompile_table: [Column<Advice>; 5],
         evm_circuit: EvmCircuit<F>,
     }
 
@@ -349,6 +365,83 @@ pub mod test {
                 },
             )
         }
+
+        fn load_keccaks(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            inputs: &[Vec<u8>],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "keccak table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.keccak_table {
+                        region.assign_advice(
+                            || "keccak table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for input in inputs.iter() {
+                        for row in keccak_table_assignments(input, randomness) {
+                            for (column, value) in self.keccak_table.iter().zip_eq(row) {
+                                region.assign_advice(
+                                    || format!("keccak table row {}", offset),
+                                    *column,
+                                    offset,
+                                    || Ok(value),
+                                )?;
+                            }
+                            offset += 1;
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_precompiles(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            events: &[PrecompileEvent],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "precompile table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.precompile_table {
+                        region.assign_advice(
+                            || "precompile table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for event in events.iter() {
+                        let row = precompile_table_assignments(event, randomness);
+                        for (column, value) in self.precompile_table.iter().zip_eq(row) {
+                            region.assign_advice(
+                                || format!("precompile table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
     }
 
     #[derive(Default)]
@@ -379,7 +472,8 @@ pub mod test {
             let rw_table = RwTable::construct(meta);
             let bytecode_table = [(); 5].map(|_| meta.advice_column());
             let block_table = [(); 3].map(|_| meta.advice_column());
-            let copy_table = CopyCircuit::configure(meta, &tx_table, &rw_table, &bytecode_table);
+            let keccak_table = [(); 4].map(|_| meta.advice_column());
+            let precompile_table = [(); 5].map(|_| meta.advice_column());
 
             // This gate is used just to get the array of expressions from the power of
             // randomness instance column, so that later on we don't need to query
@@ -399,12 +493,24 @@ pub mod test {
                 power_of_randomness.unwrap()
             };
 
+            let copy_table = CopyCircuit::configure(
+                meta,
+                &tx_table,
+                &rw_table,
+                &bytecode_table,
+                power_of_randomness[0].clone(),
+            );
+            let exp_table = ExpCircuit::configure(meta);
+
             Self::Config {
                 tx_table,
                 rw_table,
                 bytecode_table,
                 block_table,
                 copy_table,
+                keccak_table,
+                exp_table,
+                precompile_table,
                 evm_circuit: EvmCircuit::configure(
                     meta,
                     power_of_randomness,
@@ -413,6 +519,9 @@ pub mod test {
                     &bytecode_table,
                     &block_table,
                     &copy_table,
+                    &keccak_table,
+                    &exp_table,
+                    &precompile_table,
                 ),
             }
         }
@@ -435,9 +544,24 @@ pub mod test {
             )?;
             config.load_block(&mut layouter, &self.block.context, self.block.randomness)?;
             config.copy_table.assign_block(&mut layouter, &self.block)?;
-            config
-                .evm_circuit
-                .assign_block_exact(&mut layouter, &self.block)
+            config.load_keccaks(
+                &mut layouter,
+                &self.block.sha3_inputs,
+                self.block.randomness,
+            )?;
+            config.exp_table.assign_block(&mut layouter, &self.block)?;
+            config.load_precompiles(
+                &mut layouter,
+                &self.block.precompile_events,
+                self.block.randomness,
+            )?;
+            if self.block.evm_circuit_pad_to == 0 {
+                config
+                    .evm_circuit
+                    .assign_block_exact(&mut layouter, &self.block)
+            } else {
+                config.evm_circuit.assign_block(&mut layouter, &self.block)
+            }
         }
     }
 
@@ -479,12 +603,21 @@ pub mod test {
         let k = k.max(log2_ceil(64 + num_rows_required_for_steps));
         log::debug!("evm circuit uses k = {}", k);
 
-        let power_of_randomness = (1..32)
+        let mut instance: Vec<_> = (1..32)
             .map(|exp| vec![block.randomness.pow(&[exp, 0, 0, 0]); (1 << k) - 64])
             .collect();
+        // The total number of read/write is taken from the last step, which
+        // is EndBlock.
+        let total_rws = block
+            .txs
+            .last()
+            .and_then(|tx| tx.steps.last())
+            .map_or(0, |step| step.rw_counter.saturating_sub(1));
+        instance.push(vec![F::from(total_rws as u64)]);
+        instance.push(vec![F::from(block.txs.len() as u64)]);
         let (active_gate_rows, active_lookup_rows) = TestCircuit::get_active_rows(&block);
         let circuit = TestCircuit::<F>::new(block, fixed_table_tags);
-        let prover = MockProver::<F>::run(k, &circuit, power_of_randomness).unwrap();
+        let prover = MockProver::<F>::run(k, &circuit, instance).unwrap();
         prover.verify_at_rows(active_gate_rows.into_iter(), active_lookup_rows.into_iter())
     }
 
@@ -504,6 +637,7 @@ pub mod test {
                 FixedTableTag::Range1024,
                 FixedTableTag::SignByte,
                 FixedTableTag::ResponsibleOpcode,
+                FixedTableTag::InvalidOpcode,
                 FixedTableTag::Pow2,
             ],
         )
@@ -16,7 +16,9 @@ use eth_types::Field;
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{Layouter, Region},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector, VirtualCells,
+    },
     poly::Rotation,
 };
 use std::{collections::HashMap, convert::TryInto, iter};
@@ -24,9 +26,12 @@ use strum::IntoEnumIterator;
 
 mod add_sub;
 mod addmod;
+mod address;
+mod balance;
 mod begin_tx;
 mod bitwise;
 mod block_ctx;
+mod blockhash;
 mod byte;
 mod call;
 mod calldatacopy;
@@ -38,12 +43,39 @@ mod chainid;
 mod codecopy;
 mod codesize;
 mod comparator;
+mod create;
 mod dummy;
 mod dup;
 mod end_block;
 mod end_tx;
+mod error_contract_address_collision;
+mod error_invalid_creation_code;
+mod error_invalid_jump;
+mod error_invalid_opcode;
+mod error_max_code_size;
+mod error_oog_account_access;
+mod error_oog_call;
+mod error_oog_code_store;
+mod error_oog_constant;
+mod error_oog_create2;
+mod error_oog_dynamic_memory;
+mod error_oog_exp;
+mod error_oog_ext_code_copy;
+mod error_oog_log;
+mod error_oog_memory_copy;
+mod error_oog_self_destruct;
+mod error_oog_sha3;
+mod error_oog_sload;
+mod error_oog_sstore;
 mod error_oog_static_memory;
+mod error_precheck_failed;
+mod error_return_data_oob;
+mod error_stack;
+mod error_write_protection;
+mod exp;
+mod extcodecopy;
 mod extcodehash;
+mod extcodesize;
 mod gas;
 mod gasprice;
 mod is_zero;
@@ -59,10 +91,17 @@ mod not;
 mod origin;
 mod pc;
 mod pop;
+mod precompile;
 mod push;
-mod r#return;
+mod return_revert;
+mod returndatacopy;
+mod returndatasize;
+mod sar;
 mod sdiv_smod;
 mod selfbalance;
+mod selfdestruct;
+mod sha3;
+mod shl;
 mod shr;
 mod signed_comparator;
 mod signextend;
@@ -70,12 +109,17 @@ mod sload;
 mod sstore;
 mod stop;
 mod swap;
+mod tx_access_list;
 
+use self::sha3::Sha3Gadget;
 use add_sub::AddSubGadget;
 use addmod::AddModGadget;
+use address::AddressGadget;
+use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
+use blockhash::BlockHashGadget;
 use byte::ByteGadget;
 use call::CallGadget;
 use calldatacopy::CallDataCopyGadget;
@@ -87,12 +131,38 @@ use chainid::ChainIdGadget;
 use codecopy::CodeCopyGadget;
 use codesize::CodesizeGadget;
 use comparator::ComparatorGadget;
-use dummy::DummyGadget;
+use create::CreateGadget;
 use dup::DupGadget;
 use end_block::EndBlockGadget;
 use end_tx::EndTxGadget;
+use error_contract_address_collision::ErrorContractAddressCollisionGadget;
+use error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
+use error_invalid_jump::ErrorInvalidJumpGadget;
+use error_invalid_opcode::ErrorInvalidOpcodeGadget;
+use error_max_code_size::ErrorMaxCodeSizeExceededGadget;
+use error_oog_account_access::ErrorOOGAccountAccessGadget;
+use error_oog_call::ErrorOOGCallGadget;
+use error_oog_code_store::ErrorOOGCodeStoreGadget;
+use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_create2::ErrorOOGCreate2Gadget;
+use error_oog_dynamic_memory::ErrorOOGDynamicMemoryGadget;
+use error_oog_exp::ErrorOOGExpGadget;
+use error_oog_ext_code_copy::ErrorOOGExtCodeCopyGadget;
+use error_oog_log::ErrorOOGLogGadget;
+use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+use error_oog_self_destruct::ErrorOOGSelfDestructGadget;
+use error_oog_sha3::ErrorOOGSha3Gadget;
+use error_oog_sload::ErrorOOGSloadGadget;
+use error_oog_sstore::ErrorOOGSstoreGadget;
 use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
+use error_precheck_failed::ErrorPrecheckFailedGadget;
+use error_return_data_oob::ErrorReturnDataOutOfBoundGadget;
+use error_stack::ErrorStackGadget;
+use error_write_protection::ErrorWriteProtectionGadget;
+use exp::ExponentiationGadget;
+use extcodecopy::ExtcodecopyGadget;
 use extcodehash::ExtcodehashGadget;
+use extcodesize::ExtcodesizeGadget;
 use gas::GasGadget;
 use gasprice::GasPriceGadget;
 use is_zero::IsZeroGadget;
@@ -108,10 +178,16 @@ use not::NotGadget;
 use origin::OriginGadget;
 use pc::PcGadget;
 use pop::PopGadget;
+use precompile::PrecompileGadget;
 use push::PushGadget;
-use r#return::ReturnGadget;
+use return_revert::ReturnRevertGadget;
+use returndatacopy::ReturnDataCopyGadget;
+use returndatasize::ReturnDataSizeGadget;
+use sar::SarGadget;
 use sdiv_smod::SignedDivModGadget;
 use selfbalance::SelfbalanceGadget;
+use selfdestruct::SelfdestructGadget;
+use shl::ShlGadget;
 use shr::ShrGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -119,6 +195,7 @@ use sload::SloadGadget;
 use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
+use tx_access_list::TxAccessListGadget;
 
 pub(crate) trait ExecutionGadget<F: FieldExt> {
     const NAME: &'static str;
@@ -146,17 +223,22 @@ pub(crate) struct ExecutionConfig<F> {
     num_rows_inv: Column<Advice>,
     q_step_first: Selector,
     q_step_last: Selector,
+    total_rws: Column<Instance>,
+    num_txs: Column<Instance>,
     advices: [Column<Advice>; STEP_WIDTH],
     step: Step<F>,
     height_map: HashMap<ExecutionState, usize>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
     // internal state gadgets
     begin_tx_gadget: BeginTxGadget<F>,
+    tx_access_list_gadget: TxAccessListGadget<F>,
     end_block_gadget: EndBlockGadget<F>,
     end_tx_gadget: EndTxGadget<F>,
     // opcode gadgets
     add_sub_gadget: AddSubGadget<F>,
     addmod_gadget: AddModGadget<F>,
+    address_gadget: AddressGadget<F>,
+    balance_gadget: BalanceGadget<F>,
     bitwise_gadget: BitwiseGadget<F>,
     byte_gadget: ByteGadget<F>,
     call_gadget: CallGadget<F>,
@@ -169,8 +251,13 @@ pub(crate) struct ExecutionConfig<F> {
     codecopy_gadget: CodeCopyGadget<F>,
     codesize_gadget: CodesizeGadget<F>,
     comparator_gadget: ComparatorGadget<F>,
+    create_gadget: CreateGadget<F, false>,
+    create2_gadget: CreateGadget<F, true>,
     dup_gadget: DupGadget<F>,
+    exp_gadget: ExponentiationGadget<F>,
+    extcodecopy_gadget: ExtcodecopyGadget<F>,
     extcodehash_gadget: ExtcodehashGadget<F>,
+    extcodesize_gadget: ExtcodesizeGadget<F>,
     gas_gadget: GasGadget<F>,
     gasprice_gadget: GasPriceGadget<F>,
     iszero_gadget: IsZeroGadget<F>,
@@ -186,28 +273,18 @@ pub(crate) struct ExecutionConfig<F> {
     origin_gadget: OriginGadget<F>,
     pc_gadget: PcGadget<F>,
     pop_gadget: PopGadget<F>,
+    precompile_gadget: PrecompileGadget<F>,
     push_gadget: PushGadget<F>,
-    return_gadget: ReturnGadget<F>,
+    return_revert_gadget: ReturnRevertGadget<F>,
+    returndatacopy_gadget: ReturnDataCopyGadget<F>,
+    returndatasize_gadget: ReturnDataSizeGadget<F>,
+    sar_gadget: SarGadget<F>,
     sdiv_smod_gadget: SignedDivModGadget<F>,
     selfbalance_gadget: SelfbalanceGadget<F>,
+    selfdestruct_gadget: SelfdestructGadget<F>,
+    sha3_gadget: Sha3Gadget<F>,
+    shl_gadget: ShlGadget<F>,
     shr_gadget: ShrGadget<F>,
-    sha3_gadget: DummyGadget<F, 2, 1, { ExecutionState::SHA3 }>,
-    address_gadget: DummyGadget<F, 0, 1, { ExecutionState::ADDRESS }>,
-    balance_gadget: DummyGadget<F, 1, 1, { ExecutionState::BALANCE }>,
-    blockhash_gadget: DummyGadget<F, 1, 1, { ExecutionState::BLOCKHASH }>,
-    exp_gadget: DummyGadget<F, 2, 1, { ExecutionState::EXP }>,
-    shl_gadget: DummyGadget<F, 2, 1, { ExecutionState::SHL }>,
-    sar_gadget: DummyGadget<F, 2, 1, { ExecutionState::SAR }>,
-    extcodesize_gadget: DummyGadget<F, 1, 1, { ExecutionState::EXTCODESIZE }>,
-    extcodecopy_gadget: DummyGadget<F, 4, 0, { ExecutionState::EXTCODECOPY }>,
-    returndatasize_gadget: DummyGadget<F, 0, 1, { ExecutionState::RETURNDATASIZE }>,
-    returndatacopy_gadget: DummyGadget<F, 3, 0, { ExecutionState::RETURNDATACOPY }>,
-    create_gadget: DummyGadget<F, 3, 1, { ExecutionState::CREATE }>,
-    callcode_gadget: DummyGadget<F, 7, 1, { ExecutionState::CALLCODE }>,
-    delegatecall_gadget: DummyGadget<F, 6, 1, { ExecutionState::DELEGATECALL }>,
-    create2_gadget: DummyGadget<F, 4, 1, { ExecutionState::CREATE2 }>,
-    staticcall_gadget: DummyGadget<F, 6, 1, { ExecutionState::STATICCALL }>,
-    selfdestruct_gadget: DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>,
     signed_comparator_gadget: SignedComparatorGadget<F>,
     signextend_gadget: SignextendGadget<F>,
     sload_gadget: SloadGadget<F>,
@@ -217,8 +294,38 @@ pub(crate) struct ExecutionConfig<F> {
     block_ctx_u64_gadget: BlockCtxU64Gadget<F>,
     block_ctx_u160_gadget: BlockCtxU160Gadget<F>,
     block_ctx_u256_gadget: BlockCtxU256Gadget<F>,
+    blockhash_gadget: BlockHashGadget<F>,
     // error gadgets
     error_oog_static_memory_gadget: ErrorOOGStaticMemoryGadget<F>,
+    error_contract_address_collision_gadget: ErrorContractAddressCollisionGadget<F>,
+    error_invalid_creation_code_gadget: ErrorInvalidCreationCodeGadget<F>,
+    error_max_code_size_gadget: ErrorMaxCodeSizeExceededGadget<F>,
+    error_oog_exp_gadget: ErrorOOGExpGadget<F>,
+    error_oog_account_access_gadget: ErrorOOGAccountAccessGadget<F>,
+    error_oog_call_gadget: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasCALL }>,
+    error_oog_callcode_gadget: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasCALLCODE }>,
+    error_oog_delegatecall_gadget:
+        ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasDELEGATECALL }>,
+    error_oog_staticcall_gadget: ErrorOOGCallGadget<F, { ExecutionState::ErrorOutOfGasSTATICCALL }>,
+    error_oog_code_store_gadget: ErrorOOGCodeStoreGadget<F>,
+    error_oog_constant_gadget: ErrorOOGConstantGadget<F>,
+    error_oog_create2_gadget: ErrorOOGCreate2Gadget<F>,
+    error_oog_dynamic_memory_gadget: ErrorOOGDynamicMemoryGadget<F>,
+    error_oog_ext_code_copy_gadget: ErrorOOGExtCodeCopyGadget<F>,
+    error_oog_log_gadget: ErrorOOGLogGadget<F>,
+    error_oog_memory_copy_gadget: ErrorOOGMemoryCopyGadget<F>,
+    error_oog_self_destruct_gadget: ErrorOOGSelfDestructGadget<F>,
+    error_oog_sha3_gadget: ErrorOOGSha3Gadget<F>,
+    error_oog_sload_gadget: ErrorOOGSloadGadget<F>,
+    error_oog_sstore_gadget: ErrorOOGSstoreGadget<F>,
+    error_depth_gadget: ErrorPrecheckFailedGadget<F, true>,
+    error_insufficient_balance_gadget: ErrorPrecheckFailedGadget<F, false>,
+    error_return_data_oob_gadget: ErrorReturnDataOutOfBoundGadget<F>,
+    error_invalid_jump_gadget: ErrorInvalidJumpGadget<F>,
+    error_invalid_opcode_gadget: ErrorInvalidOpcodeGadget<F>,
+    error_stack_overflow_gadget: ErrorStackGadget<F, true>,
+    error_stack_underflow_gadget: ErrorStackGadget<F, false>,
+    error_write_protection_gadget: ErrorWriteProtectionGadget<F>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
@@ -233,6 +340,9 @@ impl<F: Field> ExecutionConfig<F> {
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
         copy_table: &dyn LookupTable<F>,
+        keccak_table: &dyn LookupTable<F>,
+        exp_table: &dyn LookupTable<F>,
+        precompile_table: &dyn LookupTable<F>,
     ) -> Self {
         let q_usable = meta.complex_selector();
         let q_step = meta.advice_column();
@@ -240,6 +350,12 @@ impl<F: Field> ExecutionConfig<F> {
         let num_rows_inv = meta.advice_column();
         let q_step_first = meta.complex_selector();
         let q_step_last = meta.complex_selector();
+        // The total number of read/write and the total number of txs in the
+        // block, which are the public inputs of the EVM circuit.
+        let total_rws = meta.instance_column();
+        meta.enable_equality(total_rws);
+        let num_txs = meta.instance_column();
+        meta.enable_equality(num_txs);
         let advices = [(); STEP_WIDTH].map(|_| meta.advice_column());
 
         let step_curr = Step::new(meta, advices, 0);
@@ -269,16 +385,17 @@ impl<F: Field> ExecutionConfig<F> {
                 )
             });
 
-            let _first_step_check = {
-                let begin_tx_selector =
-                    step_curr.execution_state_selector([ExecutionState::BeginTx]);
+            // A block without any transaction starts with EndBlock.
+            let first_step_check = {
+                let begin_tx_end_block_selector = step_curr
+                    .execution_state_selector([ExecutionState::BeginTx, ExecutionState::EndBlock]);
                 iter::once((
-                    "First step should be BeginTx",
-                    q_step_first * (1.expr() - begin_tx_selector),
+                    "First step should be BeginTx or EndBlock",
+                    q_step_first * (1.expr() - begin_tx_end_block_selector),
                 ))
             };
 
-            let _last_step_check = {
+            let last_step_check = {
                 let end_block_selector =
                     step_curr.execution_state_selector([ExecutionState::EndBlock]);
                 iter::once((
@@ -289,15 +406,15 @@ impl<F: Field> ExecutionConfig<F> {
 
             iter::once(sum_to_one)
                 .chain(bool_checks)
+                .chain(first_step_check)
+                .chain(last_step_check)
                 .map(move |(name, poly)| (name, q_usable.clone() * q_step.clone() * poly))
-            // TODO: Enable these after incomplete trace is no longer necessary.
-            // .chain(first_step_check)
-            // .chain(last_step_check)
         });
 
         meta.create_gate("q_step", |meta| {
             let q_usable = meta.query_selector(q_usable);
             let q_step_first = meta.query_selector(q_step_first);
+            let q_step_last = meta.query_selector(q_step_last);
             let q_step = meta.query_advice(q_step, Rotation::cur());
             let num_rows_left_cur = meta.query_advice(num_rows_until_next_step, Rotation::cur());
             let num_rows_left_next = meta.query_advice(num_rows_until_next_step, Rotation::next());
@@ -308,6 +425,10 @@ impl<F: Field> ExecutionConfig<F> {
             cb.condition(q_step_first, |cb| {
                 cb.require_equal("q_step == 1", q_step.clone(), 1.expr());
             });
+            // q_step needs to be enabled on the last step
+            cb.condition(q_step_last, |cb| {
+                cb.require_equal("q_step == 1", q_step.clone(), 1.expr());
+            });
             // Except when step is enabled, the step counter needs to decrease by 1
             cb.condition(1.expr() - q_step.clone(), |cb| {
                 cb.require_equal(
@@ -360,14 +481,19 @@ impl<F: Field> ExecutionConfig<F> {
             num_rows_inv,
             q_step_first,
             q_step_last,
+            total_rws,
+            num_txs,
             advices,
             // internal states
             begin_tx_gadget: configure_gadget!(),
+            tx_access_list_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
             end_tx_gadget: configure_gadget!(),
             // opcode gadgets
             add_sub_gadget: configure_gadget!(),
             addmod_gadget: configure_gadget!(),
+            address_gadget: configure_gadget!(),
+            balance_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             call_gadget: configure_gadget!(),
@@ -380,8 +506,13 @@ impl<F: Field> ExecutionConfig<F> {
             codecopy_gadget: configure_gadget!(),
             codesize_gadget: configure_gadget!(),
             comparator_gadget: configure_gadget!(),
+            create_gadget: configure_gadget!(),
+            create2_gadget: configure_gadget!(),
             dup_gadget: configure_gadget!(),
+            exp_gadget: configure_gadget!(),
+            extcodecopy_gadget: configure_gadget!(),
             extcodehash_gadget: configure_gadget!(),
+            extcodesize_gadget: configure_gadget!(),
             gas_gadget: configure_gadget!(),
             gasprice_gadget: configure_gadget!(),
             iszero_gadget: configure_gadget!(),
@@ -397,27 +528,17 @@ impl<F: Field> ExecutionConfig<F> {
             origin_gadget: configure_gadget!(),
             pc_gadget: configure_gadget!(),
             pop_gadget: configure_gadget!(),
+            precompile_gadget: configure_gadget!(),
             push_gadget: configure_gadget!(),
-            return_gadget: configure_gadget!(),
+            return_revert_gadget: configure_gadget!(),
+            returndatacopy_gadget: configure_gadget!(),
+            returndatasize_gadget: configure_gadget!(),
+            sar_gadget: configure_gadget!(),
             sdiv_smod_gadget: configure_gadget!(),
             selfbalance_gadget: configure_gadget!(),
+            selfdestruct_gadget: configure_gadget!(),
             sha3_gadget: configure_gadget!(),
-            address_gadget: configure_gadget!(),
-            balance_gadget: configure_gadget!(),
-            blockhash_gadget: configure_gadget!(),
-            exp_gadget: configure_gadget!(),
             shl_gadget: configure_gadget!(),
-            sar_gadget: configure_gadget!(),
-            extcodesize_gadget: configure_gadget!(),
-            extcodecopy_gadget: configure_gadget!(),
-            returndatasize_gadget: configure_gadget!(),
-            returndatacopy_gadget: configure_gadget!(),
-            create_gadget: configure_gadget!(),
-            callcode_gadget: configure_gadget!(),
-            delegatecall_gadget: configure_gadget!(),
-            create2_gadget: configure_gadget!(),
-            staticcall_gadget: configure_gadget!(),
-            selfdestruct_gadget: configure_gadget!(),
             shr_gadget: configure_gadget!(),
             signed_comparator_gadget: configure_gadget!(),
             signextend_gadget: configure_gadget!(),
@@ -428,8 +549,37 @@ impl<F: Field> ExecutionConfig<F> {
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
+            blockhash_gadget: configure_gadget!(),
             // error gadgets
             error_oog_static_memory_gadget: configure_gadget!(),
+            error_contract_address_collision_gadget: configure_gadget!(),
+            error_invalid_creation_code_gadget: configure_gadget!(),
+            error_max_code_size_gadget: configure_gadget!(),
+            error_oog_exp_gadget: configure_gadget!(),
+            error_oog_account_access_gadget: configure_gadget!(),
+            error_oog_call_gadget: configure_gadget!(),
+            error_oog_callcode_gadget: configure_gadget!(),
+            error_oog_delegatecall_gadget: configure_gadget!(),
+            error_oog_staticcall_gadget: configure_gadget!(),
+            error_oog_code_store_gadget: configure_gadget!(),
+            error_oog_constant_gadget: configure_gadget!(),
+            error_oog_create2_gadget: configure_gadget!(),
+            error_oog_dynamic_memory_gadget: configure_gadget!(),
+            error_oog_ext_code_copy_gadget: configure_gadget!(),
+            error_oog_log_gadget: configure_gadget!(),
+            error_oog_memory_copy_gadget: configure_gadget!(),
+            error_oog_self_destruct_gadget: configure_gadget!(),
+            error_oog_sha3_gadget: configure_gadget!(),
+            error_oog_sload_gadget: configure_gadget!(),
+            error_oog_sstore_gadget: configure_gadget!(),
+            error_depth_gadget: configure_gadget!(),
+            error_insufficient_balance_gadget: configure_gadget!(),
+            error_return_data_oob_gadget: configure_gadget!(),
+            error_invalid_jump_gadget: configure_gadget!(),
+            error_invalid_opcode_gadget: configure_gadget!(),
+            error_stack_overflow_gadget: configure_gadget!(),
+            error_stack_underflow_gadget: configure_gadget!(),
+            error_write_protection_gadget: configure_gadget!(),
             // step and presets
             step: step_curr,
             height_map,
@@ -445,6 +595,9 @@ impl<F: Field> ExecutionConfig<F> {
             bytecode_table,
             block_table,
             copy_table,
+            keccak_table,
+            exp_table,
+            precompile_table,
             &power_of_randomness,
             &cell_manager,
         );
@@ -484,8 +637,12 @@ impl<F: Field> ExecutionConfig<F> {
                 G::EXECUTION_STATE,
             );
             G::configure(&mut cb);
-            let (_, _, _, height) = cb.build();
-            height
+            let (_, _, _, _, height) = cb.build();
+            // Round the height up to a multiple of the height of the step
+            // state, which is the height of EndBlock, so that any leftover
+            // rows can be padded with EndBlock steps.
+            let step_state_height = step_curr.cell_manager.get_height();
+            (height + step_state_height - 1) / step_state_height * step_state_height
         };
 
         // Now actually configure the gadget with the correct minimal height
@@ -512,7 +669,8 @@ impl<F: Field> ExecutionConfig<F> {
             (height - 1).expr(),
         );
 
-        let (constraints, constraints_first_step, stored_expressions, _) = cb.build();
+        let (constraints, constraints_first_step, constraints_not_last_step, stored_expressions, _) =
+            cb.build();
         debug_assert!(
             !height_map.contains_key(&G::EXECUTION_STATE),
             "execution state already configured"
@@ -529,9 +687,14 @@ impl<F: Field> ExecutionConfig<F> {
             &|meta| meta.query_advice(q_step, Rotation::cur());
         let q_steps_first: &dyn Fn(&mut VirtualCells<F>) -> Expression<F> =
             &|meta| meta.query_selector(q_step_first);
+        let q_steps_not_last: &dyn Fn(&mut VirtualCells<F>) -> Expression<F> = &|meta| {
+            meta.query_advice(q_step, Rotation::cur())
+                * (1.expr() - meta.query_selector(q_step_last))
+        };
         for (selector, constraints) in [
             (q_steps, constraints),
             (q_steps_first, constraints_first_step),
+            (q_steps_not_last, constraints_not_last_step),
         ] {
             if !constraints.is_empty() {
                 meta.create_gate(G::NAME, |meta| {
@@ -576,11 +739,16 @@ impl<F: Field> ExecutionConfig<F> {
                             vec![ExecutionState::EndTx],
                         ),
                         (
-                            "Only ExecutionState which halts or BeginTx can transit to EndTx",
+                            "Only BeginTx or TxAccessList can transit to TxAccessList",
+                            ExecutionState::TxAccessList,
+                            vec![ExecutionState::BeginTx, ExecutionState::TxAccessList],
+                        ),
+                        (
+                            "Only ExecutionState which halts, BeginTx or TxAccessList can transit to EndTx",
                             ExecutionState::EndTx,
                             ExecutionState::iter()
                                 .filter(ExecutionState::halts)
-                                .chain(iter::once(ExecutionState::BeginTx))
+                                .chain([ExecutionState::BeginTx, ExecutionState::TxAccessList])
                                 .collect(),
                         ),
                         (
@@ -617,6 +785,9 @@ impl<F: Field> ExecutionConfig<F> {
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
         copy_table: &dyn LookupTable<F>,
+        keccak_table: &dyn LookupTable<F>,
+        exp_table: &dyn LookupTable<F>,
+        precompile_table: &dyn LookupTable<F>,
         power_of_randomness: &[Expression<F>; 31],
         cell_manager: &CellManager<F>,
     ) {
@@ -632,6 +803,9 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Block => block_table,
                         Table::Byte => byte_table,
                         Table::Copy => copy_table,
+                        Table::Keccak => keccak_table,
+                        Table::Exp => exp_table,
+                        Table::Precompile => precompile_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -645,12 +819,14 @@ impl<F: Field> ExecutionConfig<F> {
 
     /// Assign block
     /// When exact is enabled, assign exact steps in block without padding for
-    /// unit test purpose
+    /// unit test purpose. Otherwise pad the steps with EndBlock to
+    /// `block.evm_circuit_pad_to` rows, so that the layout of the circuit
+    /// doesn't depend on the block.
     pub fn assign_block(
         &self,
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
-        _exact: bool,
+        exact: bool,
     ) -> Result<(), Error> {
         let power_of_randomness = (1..32)
             .map(|exp| block.randomness.pow(&[exp, 0, 0, 0]))
@@ -658,7 +834,18 @@ impl<F: Field> ExecutionConfig<F> {
             .try_into()
             .unwrap();
 
-        layouter.assign_region(
+        // A block without any transaction only has EndBlock steps.
+        let dummy_tx = Transaction {
+            calls: vec![Call::default()],
+            ..Default::default()
+        };
+        let dummy_end_block = ExecStep {
+            rw_counter: 1,
+            execution_state: ExecutionState::EndBlock,
+            ..Default::default()
+        };
+
+        let public_inputs = layouter.assign_region(
             || "Execution step",
             |mut region| {
                 let mut offset = 0;
@@ -670,8 +857,45 @@ impl<F: Field> ExecutionConfig<F> {
                     .txs
                     .iter()
                     .flat_map(|tx| tx.steps.iter().map(move |step| (tx, step)))
-                    .peekable();
+                    .collect::<Vec<_>>();
+
+                if !exact {
+                    let end_block = if steps.last().map(|(_, step)| step.execution_state)
+                        == Some(ExecutionState::EndBlock)
+                    {
+                        steps.pop().unwrap()
+                    } else {
+                        (&dummy_tx, &dummy_end_block)
+                    };
+
+                    // Every step height is a multiple of the one of EndBlock,
+                    // so the last EndBlock is always at the same offset.
+                    let end_block_height = self.get_step_height(ExecutionState::EndBlock);
+                    let num_rows = steps
+                        .iter()
+                        .map(|(_, step)| self.get_step_height(step.execution_state))
+                        .sum::<usize>();
+                    // The leftover rows which don't fit a whole EndBlock are
+                    // never usable, whatever the block is, so no constraint
+                    // applies to them. Only the first one is assigned, since
+                    // it's queried as the next row of the last step.
+                    let capacity = block.evm_circuit_pad_to / end_block_height * end_block_height;
+                    if num_rows + end_block_height > capacity {
+                        log::error!(
+                            "evm circuit steps need {} rows, but only {} rows are available",
+                            num_rows + end_block_height,
+                            block.evm_circuit_pad_to
+                        );
+                        return Err(Error::Synthesis);
+                    }
+                    debug_assert_eq!(num_rows % end_block_height, 0);
+                    steps.extend(
+                        iter::repeat(end_block).take((capacity - num_rows) / end_block_height),
+                    );
+                }
 
+                let last_step = steps.last().copied();
+                let mut steps = steps.into_iter().peekable();
                 let mut last_height = 0;
                 while let Some((transaction, step)) = steps.next() {
                     let call = &transaction.calls[step.call_index];
@@ -738,14 +962,40 @@ impl<F: Field> ExecutionConfig<F> {
                     || Ok(F::zero()),
                 )?;
 
-                // If not exact:
-                // TODO: Pad leftover region to the desired capacity
-                // TODO: Enable q_step_last
-                self.q_step_last.enable(&mut region, offset - last_height)?;
+                let last_offset = offset - last_height;
+                self.q_step_last.enable(&mut region, last_offset)?;
 
-                Ok(())
+                // The total number of read/write and the total number of txs
+                // are taken from the last step, which should be EndBlock.
+                match last_step {
+                    Some((_, step)) if step.execution_state == ExecutionState::EndBlock => {
+                        let region = &mut CachedRegion::<'_, '_, F>::new(
+                            &mut region,
+                            power_of_randomness,
+                            STEP_WIDTH,
+                            MAX_STEP_HEIGHT,
+                            self.advices[0].index(),
+                            last_offset,
+                        );
+                        let total_rws =
+                            self.end_block_gadget
+                                .assign_total_rws(region, last_offset, step)?;
+                        let num_txs =
+                            self.end_block_gadget
+                                .assign_num_txs(region, last_offset, block)?;
+                        Ok(Some((total_rws, num_txs)))
+                    }
+                    _ => Ok(None),
+                }
             },
-        )
+        )?;
+
+        if let Some((total_rws, num_txs)) = public_inputs {
+            layouter.constrain_instance(total_rws.cell(), self.total_rws, 0)?;
+            layouter.constrain_instance(num_txs.cell(), self.num_txs, 0)?;
+        }
+
+        Ok(())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -813,11 +1063,14 @@ impl<F: Field> ExecutionConfig<F> {
         match step.execution_state {
             // internal states
             ExecutionState::BeginTx => assign_exec_step!(self.begin_tx_gadget),
+            ExecutionState::TxAccessList => assign_exec_step!(self.tx_access_list_gadget),
             ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
             ExecutionState::EndBlock => assign_exec_step!(self.end_block_gadget),
             // opcode
             ExecutionState::ADD_SUB => assign_exec_step!(self.add_sub_gadget),
             ExecutionState::ADDMOD => assign_exec_step!(self.addmod_gadget),
+            ExecutionState::ADDRESS => assign_exec_step!(self.address_gadget),
+            ExecutionState::BALANCE => assign_exec_step!(self.balance_gadget),
             ExecutionState::BITWISE => assign_exec_step!(self.bitwise_gadget),
             ExecutionState::BYTE => assign_exec_step!(self.byte_gadget),
             ExecutionState::CALL => assign_exec_step!(self.call_gadget),
@@ -830,8 +1083,13 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::CODECOPY => assign_exec_step!(self.codecopy_gadget),
             ExecutionState::CODESIZE => assign_exec_step!(self.codesize_gadget),
             ExecutionState::CMP => assign_exec_step!(self.comparator_gadget),
+            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
+            ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
             ExecutionState::DUP => assign_exec_step!(self.dup_gadget),
+            ExecutionState::EXP => assign_exec_step!(self.exp_gadget),
+            ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
             ExecutionState::EXTCODEHASH => assign_exec_step!(self.extcodehash_gadget),
+            ExecutionState::EXTCODESIZE => assign_exec_step!(self.extcodesize_gadget),
             ExecutionState::GAS => assign_exec_step!(self.gas_gadget),
             ExecutionState::GASPRICE => assign_exec_step!(self.gasprice_gadget),
             ExecutionState::ISZERO => assign_exec_step!(self.iszero_gadget),
@@ -847,33 +1105,22 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ORIGIN => assign_exec_step!(self.origin_gadget),
             ExecutionState::PC => assign_exec_step!(self.pc_gadget),
             ExecutionState::POP => assign_exec_step!(self.pop_gadget),
+            ExecutionState::PRECOMPILE => assign_exec_step!(self.precompile_gadget),
             ExecutionState::PUSH => assign_exec_step!(self.push_gadget),
-            ExecutionState::RETURN => assign_exec_step!(self.return_gadget),
+            ExecutionState::RETURN_REVERT => assign_exec_step!(self.return_revert_gadget),
+            ExecutionState::RETURNDATACOPY => assign_exec_step!(self.returndatacopy_gadget),
+            ExecutionState::RETURNDATASIZE => assign_exec_step!(self.returndatasize_gadget),
             ExecutionState::SCMP => assign_exec_step!(self.signed_comparator_gadget),
             ExecutionState::SDIV_SMOD => assign_exec_step!(self.sdiv_smod_gadget),
             ExecutionState::BLOCKCTXU64 => assign_exec_step!(self.block_ctx_u64_gadget),
             ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
             ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
+            ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
-            // dummy gadgets
+            ExecutionState::SELFDESTRUCT => assign_exec_step!(self.selfdestruct_gadget),
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
-            ExecutionState::ADDRESS => assign_exec_step!(self.address_gadget),
-            ExecutionState::BALANCE => assign_exec_step!(self.balance_gadget),
-            ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
-            ExecutionState::EXP => assign_exec_step!(self.exp_gadget),
-            ExecutionState::SHL => assign_exec_step!(self.shl_gadget),
             ExecutionState::SAR => assign_exec_step!(self.sar_gadget),
-            ExecutionState::EXTCODESIZE => assign_exec_step!(self.extcodesize_gadget),
-            ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
-            ExecutionState::RETURNDATASIZE => assign_exec_step!(self.returndatasize_gadget),
-            ExecutionState::RETURNDATACOPY => assign_exec_step!(self.returndatacopy_gadget),
-            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
-            ExecutionState::CALLCODE => assign_exec_step!(self.callcode_gadget),
-            ExecutionState::DELEGATECALL => assign_exec_step!(self.delegatecall_gadget),
-            ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
-            ExecutionState::STATICCALL => assign_exec_step!(self.staticcall_gadget),
-            ExecutionState::SELFDESTRUCT => assign_exec_step!(self.selfdestruct_gadget),
-            // end of dummy gadgets
+            ExecutionState::SHL => assign_exec_step!(self.shl_gadget),
             ExecutionState::SHR => assign_exec_step!(self.shr_gadget),
             ExecutionState::SIGNEXTEND => assign_exec_step!(self.signextend_gadget),
             ExecutionState::SLOAD => assign_exec_step!(self.sload_gadget),
@@ -884,6 +1131,88 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
                 assign_exec_step!(self.error_oog_static_memory_gadget)
             }
+            ExecutionState::ErrorContractAddressCollision => {
+                assign_exec_step!(self.error_contract_address_collision_gadget)
+            }
+            ExecutionState::ErrorInvalidCreationCode => {
+                assign_exec_step!(self.error_invalid_creation_code_gadget)
+            }
+            ExecutionState::ErrorMaxCodeSizeExceeded => {
+                assign_exec_step!(self.error_max_code_size_gadget)
+            }
+            ExecutionState::ErrorOutOfGasEXP => assign_exec_step!(self.error_oog_exp_gadget),
+            ExecutionState::ErrorOutOfGasAccountAccess => {
+                assign_exec_step!(self.error_oog_account_access_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCALL => {
+                assign_exec_step!(self.error_oog_call_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCALLCODE => {
+                assign_exec_step!(self.error_oog_callcode_gadget)
+            }
+            ExecutionState::ErrorOutOfGasDELEGATECALL => {
+                assign_exec_step!(self.error_oog_delegatecall_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSTATICCALL => {
+                assign_exec_step!(self.error_oog_staticcall_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCodeStore => {
+                assign_exec_step!(self.error_oog_code_store_gadget)
+            }
+            ExecutionState::ErrorOutOfGasConstant => {
+                assign_exec_step!(self.error_oog_constant_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCREATE2 => {
+                assign_exec_step!(self.error_oog_create2_gadget)
+            }
+            ExecutionState::ErrorOutOfGasDynamicMemoryExpansion => {
+                assign_exec_step!(self.error_oog_dynamic_memory_gadget)
+            }
+            ExecutionState::ErrorOutOfGasEXTCODECOPY => {
+                assign_exec_step!(self.error_oog_ext_code_copy_gadget)
+            }
+            ExecutionState::ErrorOutOfGasLOG => {
+                assign_exec_step!(self.error_oog_log_gadget)
+            }
+            ExecutionState::ErrorOutOfGasMemoryCopy => {
+                assign_exec_step!(self.error_oog_memory_copy_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSELFDESTRUCT => {
+                assign_exec_step!(self.error_oog_self_destruct_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSHA3 => {
+                assign_exec_step!(self.error_oog_sha3_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSLOAD => {
+                assign_exec_step!(self.error_oog_sload_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSSTORE => {
+                assign_exec_step!(self.error_oog_sstore_gadget)
+            }
+            ExecutionState::ErrorDepth => {
+                assign_exec_step!(self.error_depth_gadget)
+            }
+            ExecutionState::ErrorInsufficientBalance => {
+                assign_exec_step!(self.error_insufficient_balance_gadget)
+            }
+            ExecutionState::ErrorReturnDataOutOfBound => {
+                assign_exec_step!(self.error_return_data_oob_gadget)
+            }
+            ExecutionState::ErrorInvalidJump => {
+                assign_exec_step!(self.error_invalid_jump_gadget)
+            }
+            ExecutionState::ErrorInvalidOpcode => {
+                assign_exec_step!(self.error_invalid_opcode_gadget)
+            }
+            ExecutionState::ErrorStackOverflow => {
+                assign_exec_step!(self.error_stack_overflow_gadget)
+            }
+            ExecutionState::ErrorStackUnderflow => {
+                assign_exec_step!(self.error_stack_underflow_gadget)
+            }
+            ExecutionState::ErrorWriteProtection => {
+                assign_exec_step!(self.error_write_protection_gadget)
+            }
             _ => unimplemented!("unimplemented ExecutionState: {:?}", step.execution_state),
         }
 
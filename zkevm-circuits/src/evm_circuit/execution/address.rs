@@ -0,0 +1,113 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes, CachedRegion, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct AddressGadget<F> {
+    same_context: SameContextGadget<F>,
+    // Using RLC to match against rw_table->stack_op value
+    address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+}
+
+impl<F: Field> ExecutionGadget<F> for AddressGadget<F> {
+    const NAME: &'static str = "ADDRESS";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ADDRESS;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let address = cb.query_rlc();
+
+        // Lookup callee address in call context.
+        cb.call_context_lookup(
+            false.expr(),
+            None, // cb.curr.state.call_id
+            CallContextFieldTag::CalleeAddress,
+            from_bytes::expr(&address.cells),
+        );
+
+        // Push the value to the stack
+        cb.stack_push(address.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::ADDRESS.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            address,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let address = block.rws[step.rw_indices[1]].stack_value();
+
+        self.address.assign(
+            region,
+            offset,
+            Some(
+                address.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+    use mock::TestContext;
+
+    #[test]
+    fn address_gadget_test() {
+        let bytecode = bytecode! {
+            ADDRESS
+            STOP
+        };
+
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+}
@@ -1,24 +1,36 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_GAS,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_CALLDATASIZE, N_BYTES_GAS},
         step::ExecutionState,
-        table::{AccountFieldTag, CallContextFieldTag, TxContextFieldTag},
+        table::{
+            AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, CopyDataType,
+            TxContextFieldTag,
+        },
         util::{
-            common_gadget::TransferWithGasFeeGadget,
+            common_gadget::{ContractAddressGadget, HardforkGadget, TransferWithGasFeeGadget},
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{MulWordByU64Gadget, RangeCheckGadget},
-            select, CachedRegion, Cell, RandomLinearCombination, Word,
+            math_gadget::{
+                AddWordsGadget, IsEqualGadget, IsZeroGadget, LtGadget, LtWordGadget,
+                MulWordByU64Gadget, RangeCheckGadget,
+            },
+            memory_gadget::MemoryWordSizeGadget,
+            or, select, CachedRegion, Cell, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use eth_types::{
+    evm_types::{gas_utils::initcode_word_gas_cost, GasCost, MAX_INITCODE_SIZE_MULTIPLIER},
+    Field, Hardfork, ToLittleEndian, ToScalar, U256,
+};
+use ethers_core::utils::keccak256;
 use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
 
 #[derive(Clone, Debug)]
 pub(crate) struct BeginTxGadget<F> {
@@ -26,17 +38,43 @@ pub(crate) struct BeginTxGadget<F> {
     tx_nonce: Cell<F>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
+    tx_max_fee_per_gas: Word<F>,
+    tx_max_priority_fee_per_gas: Word<F>,
+    sub_max_fee_by_base_fee: AddWordsGadget<F, 2, true>,
+    max_fee_lt_max_priority_fee: LtWordGadget<F>,
+    max_priority_fee_lt_max_fee_minus_base_fee: LtWordGadget<F>,
+    add_effective_tip_to_base_fee: AddWordsGadget<F, 2, true>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
+    hardfork: HardforkGadget<F>,
+    initcode_word_size: MemoryWordSizeGadget<F>,
+    max_code_size: Cell<F>,
+    initcode_size_exceeded: LtGadget<F, N_BYTES_CALLDATASIZE>,
     tx_caller_address: Cell<F>,
     tx_callee_address: Cell<F>,
     tx_is_create: Cell<F>,
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
     tx_call_data_gas_cost: Cell<F>,
+    tx_access_list_addresses_len: Cell<F>,
+    tx_access_list_storage_keys_len: Cell<F>,
     reversion_info: ReversionInfo<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
     code_hash: Cell<F>,
+    callee_nonce: Cell<F>,
+    is_callee_nonce_zero: IsZeroGadget<F>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    is_collision: Cell<F>,
+    contract_address: ContractAddressGadget<F>,
+    init_code_hash: Word<F>,
+    call_data_length_is_zero: IsZeroGadget<F>,
+    rlc_acc: Cell<F>,
+    callee_address_is_zero: IsZeroGadget<F>,
+    callee_address_lt_precompiles_end: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    access_list_is_empty: IsZeroGadget<F>,
+    coinbase: Cell<F>,
+    is_coinbase_caller: IsEqualGadget<F>,
+    is_coinbase_callee: IsEqualGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -51,7 +89,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let tx_id = cb.call_context(Some(call_id.expr()), CallContextFieldTag::TxId);
         let mut reversion_info = cb.reversion_info(None);
 
-        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost] =
+        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost, tx_access_list_addresses_len, tx_access_list_storage_keys_len] =
             [
                 TxContextFieldTag::Nonce,
                 TxContextFieldTag::Gas,
@@ -60,10 +98,17 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 TxContextFieldTag::IsCreate,
                 TxContextFieldTag::CallDataLength,
                 TxContextFieldTag::CallDataGasCost,
+                TxContextFieldTag::AccessListAddressesLen,
+                TxContextFieldTag::AccessListStorageKeysLen,
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
-        let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
-            .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+        let [tx_gas_price, tx_max_fee_per_gas, tx_max_priority_fee_per_gas, tx_value] = [
+            TxContextFieldTag::GasPrice,
+            TxContextFieldTag::MaxFeePerGas,
+            TxContextFieldTag::MaxPriorityFeePerGas,
+            TxContextFieldTag::Value,
+        ]
+        .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
         // Add first step constraint to have both rw_counter and tx_id to be 1
         cb.add_constraint_first_step(
@@ -82,24 +127,109 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         );
 
-        // TODO: Implement EIP 1559 (currently it only supports legacy
-        // transaction format)
+        // Constrain the gas price to be the effective one of EIP 1559, which is
+        // base_fee + min(max_priority_fee_per_gas, max_fee_per_gas - base_fee).
+        // Legacy transactions have both max fees set to their gas price.
+        let base_fee = cb.query_word();
+        cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
+        // max_fee_per_gas must be sufficient to pay the base fee.
+        let max_fee_minus_base_fee = cb.query_word();
+        let sub_max_fee_by_base_fee = AddWordsGadget::construct(
+            cb,
+            [max_fee_minus_base_fee.clone(), base_fee.clone()],
+            tx_max_fee_per_gas.clone(),
+        );
+        let max_fee_lt_max_priority_fee =
+            LtWordGadget::construct(cb, &tx_max_fee_per_gas, &tx_max_priority_fee_per_gas);
+        cb.require_zero(
+            "max_priority_fee_per_gas <= max_fee_per_gas",
+            max_fee_lt_max_priority_fee.expr(),
+        );
+        let max_priority_fee_lt_max_fee_minus_base_fee =
+            LtWordGadget::construct(cb, &tx_max_priority_fee_per_gas, &max_fee_minus_base_fee);
+        let effective_tip = cb.query_word();
+        cb.require_equal(
+            "effective_tip == min(max_priority_fee_per_gas, max_fee_per_gas - base_fee)",
+            effective_tip.expr(),
+            select::expr(
+                max_priority_fee_lt_max_fee_minus_base_fee.expr(),
+                tx_max_priority_fee_per_gas.expr(),
+                max_fee_minus_base_fee.expr(),
+            ),
+        );
+        let add_effective_tip_to_base_fee =
+            AddWordsGadget::construct(cb, [effective_tip, base_fee], tx_gas_price.clone());
+
         // Calculate transaction gas fee
         let mul_gas_fee_by_gas =
             MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.expr());
 
-        // TODO: Take gas cost of access list (EIP 2930) into consideration.
-        // Use intrinsic gas
+        let hardfork = HardforkGadget::construct(cb);
+        let is_shanghai = hardfork.is_activated(Hardfork::Shanghai);
+
+        // The initcode of a creation transaction is charged per word, and its
+        // size is limited to twice the maximum code size since Shanghai (EIP
+        // 3860).
+        let initcode_word_size = MemoryWordSizeGadget::construct(cb, tx_call_data_length.expr());
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let initcode_size_exceeded = LtGadget::construct(
+            cb,
+            MAX_INITCODE_SIZE_MULTIPLIER.expr() * max_code_size.expr(),
+            tx_call_data_length.expr(),
+        );
+        cb.condition(tx_is_create.expr() * is_shanghai.clone(), |cb| {
+            cb.require_zero(
+                "initcode size <= max_initcode_size",
+                initcode_size_exceeded.expr(),
+            );
+        });
+
+        // Use intrinsic gas, which includes the gas cost of access list (EIP
+        // 2930)
         let intrinsic_gas_cost = select::expr(
             tx_is_create.expr(),
             GasCost::CREATION_TX.expr(),
             GasCost::TX.expr(),
-        ) + tx_call_data_gas_cost.expr();
+        ) + tx_call_data_gas_cost.expr()
+            + tx_access_list_addresses_len.expr() * GasCost::ACCESS_LIST_PER_ADDRESS.expr()
+            + tx_access_list_storage_keys_len.expr() * GasCost::ACCESS_LIST_PER_STORAGE_KEY.expr()
+            + tx_is_create.expr()
+                * is_shanghai.clone()
+                * GasCost::INITCODE_WORD_COST.expr()
+                * initcode_word_size.expr();
 
         // Check gas_left is sufficient
         let gas_left = tx_gas.expr() - intrinsic_gas_cost;
         let sufficient_gas_left = RangeCheckGadget::construct(cb, gas_left.clone());
 
+        // The callee of a creation transaction is the contract address derived
+        // from the caller's address and nonce.
+        let contract_address = cb.condition(tx_is_create.expr(), |cb| {
+            let contract_address =
+                ContractAddressGadget::construct(cb, 0.expr(), 0.expr(), 0.expr());
+            cb.require_equal(
+                "contract address is derived from tx caller address",
+                contract_address.caller_address(),
+                tx_caller_address.expr(),
+            );
+            cb.require_equal(
+                "contract address is derived from tx nonce",
+                contract_address.nonce(),
+                tx_nonce.expr(),
+            );
+            contract_address
+        });
+        let callee_address = select::expr(
+            tx_is_create.expr(),
+            contract_address.address(),
+            tx_callee_address.expr(),
+        );
+
         // Prepare access list of caller and callee
         cb.account_access_list_write(
             tx_id.expr(),
@@ -110,7 +240,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         );
         cb.account_access_list_write(
             tx_id.expr(),
-            tx_callee_address.expr(),
+            callee_address.clone(),
             1.expr(),
             0.expr(),
             None,
@@ -120,75 +250,226 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let transfer_with_gas_fee = TransferWithGasFeeGadget::construct(
             cb,
             tx_caller_address.expr(),
-            tx_callee_address.expr(),
+            callee_address.clone(),
             tx_value.clone(),
             mul_gas_fee_by_gas.product().clone(),
             &mut reversion_info,
         );
 
-        // TODO: Handle creation transaction
-        // TODO: Handle precompiled
-
         // Read code_hash of callee
         let code_hash = cb.query_cell();
         cb.account_read(
-            tx_callee_address.expr(),
+            callee_address.clone(),
             AccountFieldTag::CodeHash,
             code_hash.expr(),
         );
 
-        // Setup next call's context.
-        for (field_tag, value) in [
-            (CallContextFieldTag::Depth, 1.expr()),
-            (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
-            (CallContextFieldTag::CalleeAddress, tx_callee_address.expr()),
-            (CallContextFieldTag::CallDataOffset, 0.expr()),
-            (
-                CallContextFieldTag::CallDataLength,
-                tx_call_data_length.expr(),
+        // The contract address collides when the callee of a creation
+        // transaction already has a nonce or code, which fails the transaction
+        // consuming all its gas without executing the initcode.
+        let callee_nonce = cb.query_cell();
+        cb.condition(tx_is_create.expr(), |cb| {
+            cb.account_read(
+                callee_address.clone(),
+                AccountFieldTag::Nonce,
+                callee_nonce.expr(),
+            );
+        });
+        let is_callee_nonce_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
             ),
-            (CallContextFieldTag::Value, tx_value.expr()),
-            (CallContextFieldTag::IsStatic, 0.expr()),
-            (CallContextFieldTag::LastCalleeId, 0.expr()),
-            (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
-            (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
-            (CallContextFieldTag::IsRoot, 1.expr()),
-            (CallContextFieldTag::IsCreate, 0.expr()),
-            (CallContextFieldTag::CodeHash, code_hash.expr()),
-        ] {
-            cb.call_context_lookup(false.expr(), Some(call_id.expr()), field_tag, value);
-        }
+        );
+        let is_collision = cb.query_bool();
+        cb.require_equal(
+            "is_collision == tx_is_create && (callee_nonce != 0 || code_hash != EMPTY_HASH)",
+            is_collision.expr(),
+            tx_is_create.expr()
+                * (1.expr() - is_callee_nonce_zero.expr() * is_empty_code_hash.expr()),
+        );
+        cb.condition(is_collision.expr(), |cb| {
+            cb.require_zero(
+                "Transaction fails when the contract address collides",
+                reversion_info.is_persistent(),
+            );
+        });
 
-        cb.require_step_state_transition(StepStateTransition {
-            // 19 read/write including:
-            //   - Read CallContext TxId
-            //   - Read CallContext RwCounterEndOfReversion
-            //   - Read CallContext IsPersistent
-            //   - Write Account Nonce
-            //   - Write TxAccessListAccount
-            //   - Write TxAccessListAccount
-            //   - Write Account Balance
-            //   - Write Account Balance
-            //   - Read Account CodeHash
-            //   - Read CallContext Depth
-            //   - Read CallContext CallerAddress
-            //   - Read CallContext CalleeAddress
-            //   - Read CallContext CallDataOffset
-            //   - Read CallContext CallDataLength
-            //   - Read CallContext Value
-            //   - Read CallContext IsStatic
-            //   - Read CallContext LastCalleeId
-            //   - Read CallContext LastCalleeReturnDataOffset
-            //   - Read CallContext LastCalleeReturnDataLength
-            rw_counter: Delta(22.expr()),
-            call_id: To(call_id.expr()),
-            is_root: To(true.expr()),
-            is_create: To(false.expr()),
-            code_hash: To(code_hash.expr()),
-            gas_left: To(gas_left),
-            reversible_write_counter: To(2.expr()),
-            log_id: To(0.expr()),
-            ..StepStateTransition::new_context()
+        // Otherwise the nonce of the new contract is set to 1 (EIP 161). The
+        // initcode is the calldata of the transaction, and is hashed by copying
+        // it from the tx table.
+        let is_deploying = tx_is_create.expr() - is_collision.expr();
+        let init_code_hash = cb.query_word();
+        let call_data_length_is_zero = IsZeroGadget::construct(cb, tx_call_data_length.expr());
+        let rlc_acc = cb.query_cell();
+        cb.condition(is_deploying.clone(), |cb| {
+            cb.account_write(
+                callee_address.clone(),
+                AccountFieldTag::Nonce,
+                1.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
+            cb.keccak_table_lookup(
+                rlc_acc.expr(),
+                tx_call_data_length.expr(),
+                init_code_hash.expr(),
+            );
+            cb.condition(call_data_length_is_zero.expr(), |cb| {
+                cb.require_zero("if initcode is empty, rlc_acc == 0", rlc_acc.expr());
+            });
+        });
+
+        // The entries of the access list (EIP 2930) are warmed up in the
+        // following TxAccessList steps, unless the contract address collides.
+        let access_list_is_empty = IsZeroGadget::construct(
+            cb,
+            tx_access_list_addresses_len.expr() + tx_access_list_storage_keys_len.expr(),
+        );
+        cb.require_equal(
+            "Go to TxAccessList only when the access list is not empty",
+            cb.next
+                .execution_state_selector([ExecutionState::TxAccessList]),
+            (1.expr() - access_list_is_empty.expr()) * (1.expr() - is_collision.expr()),
+        );
+
+        // The precompiled contracts are at the addresses 0x01 to 0x09, whose
+        // execution is done in the next step, or after the TxAccessList steps,
+        // instead of by their code. The code hash of a precompiled contract's
+        // context is its address.
+        let callee_address_is_zero = IsZeroGadget::construct(cb, tx_callee_address.expr());
+        let callee_address_lt_precompiles_end =
+            LtGadget::construct(cb, tx_callee_address.expr(), 10.expr());
+        let is_precompile =
+            callee_address_lt_precompiles_end.expr() * (1.expr() - callee_address_is_zero.expr());
+        cb.require_equal(
+            "Go to PRECOMPILE only when is_precompile and the access list is empty",
+            cb.next
+                .execution_state_selector([ExecutionState::PRECOMPILE]),
+            is_precompile.clone() * access_list_is_empty.expr(),
+        );
+        let callee_code_hash = select::expr(
+            tx_is_create.expr(),
+            init_code_hash.expr(),
+            select::expr(is_precompile, tx_callee_address.expr(), code_hash.expr()),
+        );
+
+        // Setup next call's context, unless the contract address collides.
+        cb.condition(1.expr() - is_collision.expr(), |cb| {
+            for (field_tag, value) in [
+                (CallContextFieldTag::Depth, 1.expr()),
+                (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
+                (CallContextFieldTag::CalleeAddress, callee_address.clone()),
+                (CallContextFieldTag::CallDataOffset, 0.expr()),
+                // The calldata of a creation transaction is its initcode instead.
+                (
+                    CallContextFieldTag::CallDataLength,
+                    (1.expr() - tx_is_create.expr()) * tx_call_data_length.expr(),
+                ),
+                (CallContextFieldTag::Value, tx_value.expr()),
+                (CallContextFieldTag::IsStatic, 0.expr()),
+                (CallContextFieldTag::LastCalleeId, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
+                (CallContextFieldTag::IsRoot, 1.expr()),
+                (CallContextFieldTag::IsCreate, tx_is_create.expr()),
+                (CallContextFieldTag::CodeHash, callee_code_hash.clone()),
+            ] {
+                cb.call_context_lookup(false.expr(), Some(call_id.expr()), field_tag, value);
+            }
+        });
+
+        cb.condition(
+            is_deploying * (1.expr() - call_data_length_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    tx_id.expr(),
+                    CopyDataType::TxCalldata.expr(),
+                    call_id.expr(),
+                    CopyDataType::RlcAcc.expr(),
+                    0.expr(),
+                    tx_call_data_length.expr(),
+                    0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                    tx_call_data_length.expr(),
+                    rlc_acc.expr(),
+                    cb.curr.state.rw_counter.expr() + cb.rw_counter_offset(),
+                    0.expr(),
+                );
+            },
+        );
+
+        // Warm up the coinbase since Shanghai (EIP 3651), which is already
+        // warm when it's the caller or the callee.
+        let coinbase = cb.query_cell();
+        cb.block_lookup(BlockContextFieldTag::Coinbase.expr(), None, coinbase.expr());
+        let is_coinbase_caller =
+            IsEqualGadget::construct(cb, coinbase.expr(), tx_caller_address.expr());
+        let is_coinbase_callee = IsEqualGadget::construct(cb, coinbase.expr(), callee_address);
+        cb.condition(is_shanghai.clone(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                coinbase.expr(),
+                1.expr(),
+                or::expr([is_coinbase_caller.expr(), is_coinbase_callee.expr()]),
+                None,
+            );
+        });
+
+        cb.condition(1.expr() - is_collision.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                // 22 read/write (plus 2 for creation transaction and 1 since
+                // Shanghai) including:
+                //   - Read CallContext TxId
+                //   - Read CallContext RwCounterEndOfReversion
+                //   - Read CallContext IsPersistent
+                //   - Write Account Nonce
+                //   - Write TxAccessListAccount
+                //   - Write TxAccessListAccount
+                //   - Write Account Balance
+                //   - Write Account Balance
+                //   - Read Account CodeHash
+                //   - Read Account Nonce of the new contract for creation transaction
+                //   - Write Account Nonce of the new contract for creation transaction
+                //   - Read CallContext Depth
+                //   - Read CallContext CallerAddress
+                //   - Read CallContext CalleeAddress
+                //   - Read CallContext CallDataOffset
+                //   - Read CallContext CallDataLength
+                //   - Read CallContext Value
+                //   - Read CallContext IsStatic
+                //   - Read CallContext LastCalleeId
+                //   - Read CallContext LastCalleeReturnDataOffset
+                //   - Read CallContext LastCalleeReturnDataLength
+                //   - Read CallContext IsRoot
+                //   - Read CallContext IsCreate
+                //   - Read CallContext CodeHash
+                //   - Write TxAccessListAccount of the coinbase since Shanghai
+                rw_counter: Delta(22.expr() + 2.expr() * tx_is_create.expr() + is_shanghai.clone()),
+                call_id: To(call_id.expr()),
+                is_root: To(true.expr()),
+                is_create: To(tx_is_create.expr()),
+                code_hash: To(callee_code_hash),
+                gas_left: To(gas_left),
+                reversible_write_counter: To(2.expr() + tx_is_create.expr()),
+                log_id: To(0.expr()),
+                ..StepStateTransition::new_context()
+            });
+        });
+
+        // When the contract address collides, the transaction ends right
+        // after this step, where its reversions are done.
+        cb.condition(is_collision.expr(), |cb| {
+            cb.require_next_state(ExecutionState::EndTx);
+            cb.require_step_state_transition(StepStateTransition {
+                // 10 read/write (plus 1 since Shanghai) before the reversions
+                // of the 2 Account Balance writes.
+                rw_counter: Delta(12.expr() + is_shanghai),
+                gas_left: To(0.expr()),
+                ..StepStateTransition::any()
+            });
         });
 
         Self {
@@ -196,17 +477,43 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_nonce,
             tx_gas,
             tx_gas_price,
+            tx_max_fee_per_gas,
+            tx_max_priority_fee_per_gas,
+            sub_max_fee_by_base_fee,
+            max_fee_lt_max_priority_fee,
+            max_priority_fee_lt_max_fee_minus_base_fee,
+            add_effective_tip_to_base_fee,
             mul_gas_fee_by_gas,
+            hardfork,
+            initcode_word_size,
+            max_code_size,
+            initcode_size_exceeded,
             tx_caller_address,
             tx_callee_address,
             tx_is_create,
             tx_value,
             tx_call_data_length,
             tx_call_data_gas_cost,
+            tx_access_list_addresses_len,
+            tx_access_list_storage_keys_len,
             reversion_info,
             sufficient_gas_left,
             transfer_with_gas_fee,
             code_hash,
+            callee_nonce,
+            is_callee_nonce_zero,
+            is_empty_code_hash,
+            is_collision,
+            contract_address,
+            init_code_hash,
+            call_data_length_is_zero,
+            rlc_acc,
+            callee_address_is_zero,
+            callee_address_lt_precompiles_end,
+            access_list_is_empty,
+            coinbase,
+            is_coinbase_caller,
+            is_coinbase_callee,
         }
     }
 
@@ -223,6 +530,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let [caller_balance_pair, callee_balance_pair, (callee_code_hash, _)] =
             [step.rw_indices[6], step.rw_indices[7], step.rw_indices[8]]
                 .map(|idx| block.rws[idx].account_value_pair());
+        let callee_nonce = if tx.is_create {
+            block.rws[step.rw_indices[9]].account_value_pair().0
+        } else {
+            U256::zero()
+        };
 
         self.tx_id
             .assign(region, offset, Some(F::from(tx.id as u64)))?;
@@ -231,8 +543,54 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         self.tx_gas.assign(region, offset, Some(F::from(tx.gas)))?;
         self.tx_gas_price
             .assign(region, offset, Some(tx.gas_price.to_le_bytes()))?;
+        self.tx_max_fee_per_gas
+            .assign(region, offset, Some(tx.max_fee_per_gas.to_le_bytes()))?;
+        self.tx_max_priority_fee_per_gas.assign(
+            region,
+            offset,
+            Some(tx.max_priority_fee_per_gas.to_le_bytes()),
+        )?;
+        let base_fee = block.context.base_fee;
+        let max_fee_minus_base_fee = tx.max_fee_per_gas - base_fee;
+        self.sub_max_fee_by_base_fee.assign(
+            region,
+            offset,
+            [max_fee_minus_base_fee, base_fee],
+            tx.max_fee_per_gas,
+        )?;
+        self.max_fee_lt_max_priority_fee.assign(
+            region,
+            offset,
+            tx.max_fee_per_gas,
+            tx.max_priority_fee_per_gas,
+        )?;
+        self.max_priority_fee_lt_max_fee_minus_base_fee.assign(
+            region,
+            offset,
+            tx.max_priority_fee_per_gas,
+            max_fee_minus_base_fee,
+        )?;
+        self.add_effective_tip_to_base_fee.assign(
+            region,
+            offset,
+            [tx.gas_price - base_fee, base_fee],
+            tx.gas_price,
+        )?;
         self.mul_gas_fee_by_gas
             .assign(region, offset, tx.gas_price, tx.gas, gas_fee)?;
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+        self.initcode_word_size
+            .assign(region, offset, tx.call_data_length as u64)?;
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.initcode_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_INITCODE_SIZE_MULTIPLIER) * max_code_size,
+            F::from(tx.call_data_length as u64),
+        )?;
         self.tx_caller_address
             .assign(region, offset, tx.caller_address.to_scalar())?;
         self.tx_callee_address
@@ -246,14 +604,34 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         )?;
         self.tx_call_data_gas_cost
             .assign(region, offset, Some(F::from(tx.call_data_gas_cost)))?;
+        self.tx_access_list_addresses_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_addresses_len)),
+        )?;
+        self.tx_access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_storage_keys_len)),
+        )?;
         self.reversion_info.assign(
             region,
             offset,
             call.rw_counter_end_of_reversion,
             call.is_persistent,
         )?;
+        // The gas cost of the step is all the gas of the transaction when the
+        // contract address collides, so the intrinsic gas is computed again.
+        let intrinsic_gas_cost = if tx.is_create {
+            GasCost::CREATION_TX.as_u64()
+                + initcode_word_gas_cost(block.context.hardfork, tx.call_data_length as u64)
+        } else {
+            GasCost::TX.as_u64()
+        } + tx.call_data_gas_cost
+            + tx.access_list_addresses_len * GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+            + tx.access_list_storage_keys_len * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64();
         self.sufficient_gas_left
-            .assign(region, offset, F::from(tx.gas - step.gas_cost))?;
+            .assign(region, offset, F::from(tx.gas - intrinsic_gas_cost))?;
         self.transfer_with_gas_fee.assign(
             region,
             offset,
@@ -262,13 +640,78 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx.value,
             gas_fee,
         )?;
-        self.code_hash.assign(
+        let callee_code_hash_rlc = RandomLinearCombination::random_linear_combine(
+            callee_code_hash.to_le_bytes(),
+            block.randomness,
+        );
+        self.code_hash
+            .assign(region, offset, Some(callee_code_hash_rlc))?;
+        self.callee_nonce
+            .assign(region, offset, Some(F::from(callee_nonce.low_u64())))?;
+        self.is_callee_nonce_zero
+            .assign(region, offset, F::from(callee_nonce.low_u64()))?;
+        self.is_empty_code_hash.assign(
             region,
             offset,
-            Some(RandomLinearCombination::random_linear_combine(
-                callee_code_hash.to_le_bytes(),
-                block.randomness,
-            )),
+            callee_code_hash_rlc,
+            RandomLinearCombination::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+        let is_collision = tx.is_create
+            && (!callee_nonce.is_zero() || callee_code_hash.to_le_bytes() != *EMPTY_HASH_LE);
+        self.is_collision
+            .assign(region, offset, Some(F::from(is_collision as u64)))?;
+        self.contract_address.assign(
+            region,
+            offset,
+            tx.caller_address,
+            false,
+            tx.nonce,
+            U256::zero(),
+            U256::zero(),
+        )?;
+        let (init_code_hash, rlc_acc) = if tx.is_create {
+            let rlc_acc = tx.call_data.iter().fold(F::zero(), |acc, byte| {
+                acc * block.randomness + F::from(*byte as u64)
+            });
+            (U256::from_big_endian(&keccak256(&tx.call_data)), rlc_acc)
+        } else {
+            (U256::zero(), F::zero())
+        };
+        self.init_code_hash
+            .assign(region, offset, Some(init_code_hash.to_le_bytes()))?;
+        self.call_data_length_is_zero.assign(
+            region,
+            offset,
+            F::from(tx.call_data_length as u64),
+        )?;
+        self.rlc_acc.assign(region, offset, Some(rlc_acc))?;
+        let callee_address = tx.callee_address.to_scalar().unwrap();
+        self.callee_address_is_zero
+            .assign(region, offset, callee_address)?;
+        self.callee_address_lt_precompiles_end.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(10),
+        )?;
+        self.access_list_is_empty.assign(
+            region,
+            offset,
+            F::from(tx.access_list_addresses_len + tx.access_list_storage_keys_len),
+        )?;
+        let coinbase = block.context.coinbase.to_scalar().unwrap();
+        self.coinbase.assign(region, offset, Some(coinbase))?;
+        self.is_coinbase_caller.assign(
+            region,
+            offset,
+            coinbase,
+            tx.caller_address.to_scalar().unwrap(),
+        )?;
+        self.is_coinbase_callee.assign(
+            region,
+            offset,
+            coinbase,
+            call.callee_address.to_scalar().unwrap(),
         )?;
         Ok(())
     }
@@ -281,7 +724,11 @@ mod test {
         witness::block_convert,
     };
     use bus_mapping::{evm::OpcodeId, mock::BlockData};
-    use eth_types::{self, bytecode, evm_types::GasCost, geth_types::GethData, Word};
+    use eth_types::{
+        self, bytecode, evm_types::GasCost, geth_types::GethData, AccessList, AccessListItem,
+        Hardfork, Word, H256,
+    };
+    use ethers_core::utils::get_contract_address;
     use mock::{
         eth, gwei, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
     };
@@ -405,6 +852,228 @@ mod test {
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
 
+    #[test]
+    fn begin_tx_gadget_eip1559() {
+        // The effective tip is the max priority fee in the first case, and is
+        // capped by the max fee in the second one.
+        for (max_fee_per_gas, max_priority_fee_per_gas) in [(gwei(5), gwei(1)), (gwei(3), gwei(2))]
+        {
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(bytecode! { STOP }),
+                |mut txs, accs| {
+                    txs[0]
+                        .to(accs[0].address)
+                        .from(accs[1].address)
+                        .value(eth(1))
+                        .transaction_type(2)
+                        .max_fee_per_gas(max_fee_per_gas)
+                        .max_priority_fee_per_gas(max_priority_fee_per_gas);
+                },
+                |block, _tx| block.number(0xcafeu64).base_fee_per_gas(gwei(2)),
+            )
+            .unwrap()
+            .into();
+
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            assert_eq!(
+                builder.block.txs()[0].gas_price,
+                max_fee_per_gas.min(gwei(2) + max_priority_fee_per_gas)
+            );
+            let block = block_convert(&builder.block, &builder.code_db);
+
+            assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+        }
+    }
+
+    #[test]
+    fn begin_tx_gadget_access_list() {
+        // The storage slot read by the callee is warmed up by the access list.
+        let code = bytecode! {
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .transaction_type(1)
+                    .access_list(AccessList(vec![
+                        AccessListItem {
+                            address: accs[0].address,
+                            storage_keys: vec![H256::zero(), H256::from_low_u64_be(1)],
+                        },
+                        AccessListItem {
+                            address: MOCK_ACCOUNTS[2],
+                            storage_keys: vec![],
+                        },
+                    ]));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(
+            builder.block.txs()[0].steps()[0].gas_cost.as_u64(),
+            GasCost::TX.as_u64()
+                + 2 * GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+                + 2 * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64()
+        );
+        let block = block_convert(&builder.block, &builder.code_db);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn begin_tx_gadget_create() {
+        // The initcode RETURNs or REVERTs the 4 bytes 0x6001600a, which are
+        // stored at the end of the first memory word.
+        for is_success in [true, false] {
+            let mut initcode = bytecode! {
+                PUSH4(0x6001600a)
+                PUSH1(0x00)
+                MSTORE
+                PUSH1(0x04)
+                PUSH1(0x1c)
+            };
+            initcode.write_op(if is_success {
+                OpcodeId::RETURN
+            } else {
+                OpcodeId::REVERT
+            });
+
+            let block: GethData = TestContext::<1, 1>::new(
+                None,
+                |accs| {
+                    accs[0].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .value(eth(1))
+                        .input(initcode.to_vec().into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            assert_eq!(
+                builder.block.txs()[0].steps()[0].gas_cost.as_u64(),
+                GasCost::CREATION_TX.as_u64()
+                    + initcode
+                        .to_vec()
+                        .iter()
+                        .map(|&x| if x == 0 { 4 } else { 16 })
+                        .sum::<u64>()
+            );
+            let block = block_convert(&builder.block, &builder.code_db);
+
+            assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+        }
+    }
+
+    #[test]
+    fn begin_tx_gadget_create_address_collision() {
+        // The contract address derived from the caller's address and nonce
+        // already has either code or a nonce.
+        let contract_address = get_contract_address(MOCK_ACCOUNTS[1], Word::zero());
+        for has_code in [true, false] {
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                    accs[1].address(contract_address);
+                    if has_code {
+                        accs[1].code(bytecode! { STOP });
+                    } else {
+                        accs[1].nonce(Word::one());
+                    }
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .value(eth(1))
+                        .input(bytecode! { STOP }.to_vec().into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            // The transaction ends right after BeginTx.
+            assert_eq!(builder.block.txs()[0].steps().len(), 2);
+            let block = block_convert(&builder.block, &builder.code_db);
+
+            assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+        }
+    }
+
+    #[test]
+    fn begin_tx_gadget_create_shanghai() {
+        // The initcode is charged per word, and the coinbase is warmed up.
+        let initcode = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            RETURN
+        };
+
+        let block: GethData = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(initcode.to_vec().into());
+            },
+            |block, _tx| block.number(0xcafeu64).hardfork(Hardfork::Shanghai),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(
+            builder.block.txs()[0].steps()[0].gas_cost.as_u64(),
+            GasCost::CREATION_TX.as_u64()
+                + GasCost::INITCODE_WORD_COST.as_u64()
+                + initcode
+                    .to_vec()
+                    .iter()
+                    .map(|&x| if x == 0 { 4 } else { 16 })
+                    .sum::<u64>()
+        );
+        let block = block_convert(&builder.block, &builder.code_db);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
     #[test]
     fn begin_tx_gadget_rand() {
         let random_amount = Word::from_little_endian(&rand_bytes(32)) % eth(1);
@@ -5,9 +5,9 @@ use crate::{
         step::ExecutionState,
         table::BlockContextFieldTag,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{HardforkGadget, SameContextGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            from_bytes, CachedRegion, RandomLinearCombination,
+            from_bytes, CachedRegion, Cell, RandomLinearCombination,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -15,13 +15,14 @@ use crate::{
 };
 use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
-use eth_types::ToLittleEndian;
+use eth_types::{Hardfork, ToLittleEndian};
 use halo2_proofs::plonk::Error;
 use std::convert::{TryFrom, TryInto};
 
 #[derive(Clone, Debug)]
 pub(crate) struct BlockCtxGadget<F, const N_BYTES: usize> {
     same_context: SameContextGadget<F>,
+    opcode: Cell<F>,
     value: RandomLinearCombination<F, N_BYTES>,
 }
 
@@ -54,10 +55,11 @@ impl<F: Field, const N_BYTES: usize> BlockCtxGadget<F, N_BYTES> {
             gas_left: Delta(-OpcodeId::TIMESTAMP.constant_gas_cost().expr()),
             ..Default::default()
         };
-        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+        let same_context = SameContextGadget::construct(cb, opcode.clone(), step_state_transition);
 
         Self {
             same_context,
+            opcode,
             value,
         }
     }
@@ -152,6 +154,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU160Gadget<F> {
 #[derive(Clone, Debug)]
 pub(crate) struct BlockCtxU256Gadget<F> {
     value_u256: BlockCtxGadget<F, N_BYTES_WORD>,
+    hardfork: HardforkGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
@@ -162,7 +165,21 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let value_u256 = BlockCtxGadget::construct(cb);
 
-        Self { value_u256 }
+        // BASEFEE is only available since London, before which it's handled
+        // as an invalid opcode.
+        let hardfork = HardforkGadget::construct(cb);
+        cb.condition(1.expr() - hardfork.is_activated(Hardfork::London), |cb| {
+            cb.require_equal(
+                "opcode == DIFFICULTY before London",
+                value_u256.opcode.expr(),
+                OpcodeId::DIFFICULTY.expr(),
+            );
+        });
+
+        Self {
+            value_u256,
+            hardfork,
+        }
     }
 
     fn assign_exec_step(
@@ -177,6 +194,8 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
         self.value_u256
             .same_context
             .assign_exec_step(region, offset, step)?;
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
 
@@ -191,8 +210,8 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::run_test_circuits;
-    use eth_types::bytecode;
-    use mock::TestContext;
+    use eth_types::{bytecode, Hardfork, H256};
+    use mock::{test_ctx::helpers::*, TestContext};
 
     fn test_ok(bytecode: bytecode::Bytecode) {
         assert_eq!(
@@ -235,4 +254,26 @@ mod test {
         };
         test_ok(bytecode);
     }
+
+    #[test]
+    fn blockcxt_u256_gadget_prevrandao() {
+        // Since the Merge, DIFFICULTY returns the mix hash of the block.
+        let bytecode = bytecode! {
+            PREVRANDAO
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _tx| {
+                block
+                    .hardfork(Hardfork::Merge)
+                    .mix_hash(H256::from_low_u64_be(0xdeadbeef))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
 }
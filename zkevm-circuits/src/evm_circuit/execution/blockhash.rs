@@ -0,0 +1,187 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{NUM_PREV_BLOCK_ALLOWED, N_BYTES_U64},
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlockHashGadget<F> {
+    same_context: SameContextGadget<F>,
+    block_number: Word<F>,
+    current_block_number: Cell<F>,
+    block_hash: Word<F>,
+    is_u64: IsZeroGadget<F>,
+    is_before_current: LtGadget<F, N_BYTES_U64>,
+    // The block number plus NUM_PREV_BLOCK_ALLOWED + 1 might exceed u64.
+    is_within_history: LtGadget<F, { N_BYTES_U64 + 1 }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
+    const NAME: &'static str = "BLOCKHASH";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOCKHASH;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let block_number = cb.query_word();
+        cb.stack_pop(block_number.expr());
+
+        let current_block_number = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::Number.expr(),
+            None,
+            current_block_number.expr(),
+        );
+
+        // Only the hashes of the previous NUM_PREV_BLOCK_ALLOWED blocks are
+        // available, otherwise 0 is pushed.
+        let is_u64 = IsZeroGadget::construct(cb, sum::expr(&block_number.cells[N_BYTES_U64..]));
+        let block_number_u64 = from_bytes::expr(&block_number.cells[..N_BYTES_U64]);
+        let is_before_current =
+            LtGadget::construct(cb, block_number_u64.clone(), current_block_number.expr());
+        let is_within_history = LtGadget::construct(
+            cb,
+            current_block_number.expr(),
+            block_number_u64.clone() + (NUM_PREV_BLOCK_ALLOWED + 1).expr(),
+        );
+        let is_valid = is_u64.expr() * is_before_current.expr() * is_within_history.expr();
+
+        let block_hash = cb.query_word();
+        cb.condition(is_valid.clone(), |cb| {
+            cb.block_lookup(
+                BlockContextFieldTag::BlockHash.expr(),
+                Some(block_number_u64),
+                block_hash.expr(),
+            );
+        });
+        cb.condition(1.expr() - is_valid, |cb| {
+            cb.require_zero(
+                "Invalid block number for block hash lookup",
+                block_hash.expr(),
+            );
+        });
+
+        cb.stack_push(block_hash.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::BLOCKHASH.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            block_number,
+            current_block_number,
+            block_hash,
+            is_u64,
+            is_before_current,
+            is_within_history,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [block_number, block_hash] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let current_block_number = block.context.number.low_u64();
+
+        self.block_number
+            .assign(region, offset, Some(block_number.to_le_bytes()))?;
+        self.current_block_number
+            .assign(region, offset, Some(F::from(current_block_number)))?;
+        self.block_hash
+            .assign(region, offset, Some(block_hash.to_le_bytes()))?;
+        self.is_u64.assign(
+            region,
+            offset,
+            sum::value(&block_number.to_le_bytes()[N_BYTES_U64..]),
+        )?;
+        let block_number_u64 = F::from(block_number.low_u64());
+        self.is_before_current.assign(
+            region,
+            offset,
+            block_number_u64,
+            F::from(current_block_number),
+        )?;
+        self.is_within_history.assign(
+            region,
+            offset,
+            F::from(current_block_number),
+            block_number_u64 + F::from(NUM_PREV_BLOCK_ALLOWED + 1),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    fn test_ok(block_number: Word, current_block_number: u64) {
+        let bytecode = bytecode! {
+            PUSH32(block_number)
+            #[start]
+            BLOCKHASH
+            STOP
+        };
+        let history_hashes = (current_block_number.saturating_sub(256)..current_block_number)
+            .map(|num| Word::from(0xcafe0000u64 + num))
+            .collect();
+        let ctx = TestContext::<2, 1>::new(
+            Some(history_hashes),
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _tx| block.number(current_block_number),
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn blockhash_gadget_simple() {
+        test_ok(0xcafe.into(), 0xcafe + 1);
+        test_ok(0xcafe.into(), 0xcafe + 256);
+    }
+
+    #[test]
+    fn blockhash_gadget_out_of_range() {
+        // Current and future blocks
+        test_ok(0xcafe.into(), 0xcafe);
+        test_ok(0xcafe.into(), 0xcafe - 1);
+        // Blocks older than the previous 256 blocks
+        test_ok(0xcafe.into(), 0xcafe + 257);
+        test_ok(0.into(), 0xcafe);
+        // Block number exceeding u64
+        test_ok(Word::MAX, 0xcafe);
+    }
+}
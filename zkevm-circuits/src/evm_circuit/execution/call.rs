@@ -12,7 +12,7 @@ use crate::{
             },
             from_bytes,
             math_gadget::{
-                BatchedIsZeroGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget,
+                BatchedIsZeroGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget,
                 MinMaxGadget,
             },
             memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
@@ -25,19 +25,28 @@ use crate::{
 use bus_mapping::evm::OpcodeId;
 use eth_types::{
     evm_types::{GasCost, GAS_STIPEND_CALL_WITH_VALUE},
-    Field, ToLittleEndian, ToScalar,
+    Field, ToAddress, ToLittleEndian, ToScalar, U256,
 };
 use halo2_proofs::plonk::Error;
 use keccak256::EMPTY_HASH_LE;
+use std::convert::TryInto;
 
+/// Gadget for CALL, CALLCODE, DELEGATECALL and STATICCALL, which only differ
+/// in the caller, callee, value and static-ness of the new call context.
 #[derive(Clone, Debug)]
 pub(crate) struct CallGadget<F> {
     opcode: Cell<F>,
+    is_call: Cell<F>,
+    is_callcode: Cell<F>,
+    is_delegatecall: Cell<F>,
+    is_staticcall: Cell<F>,
     tx_id: Cell<F>,
     reversion_info: ReversionInfo<F>,
     current_address: Cell<F>,
     is_static: Cell<F>,
     depth: Cell<F>,
+    current_caller_address: Cell<F>,
+    current_value: Cell<F>,
     gas: Word<F>,
     callee_address: Word<F>,
     value: Word<F>,
@@ -57,6 +66,8 @@ pub(crate) struct CallGadget<F> {
     is_empty_code_hash: IsEqualGadget<F>,
     one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
     capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    callee_address_is_zero: IsZeroGadget<F>,
+    callee_address_lt_precompiles_end: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
@@ -70,11 +81,23 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
 
         // We do the responsible opcode check explicitly here because we're not using
         // the `SameContextGadget` for `CALL`.
+        let [is_call, is_callcode, is_delegatecall, is_staticcall] =
+            [(); 4].map(|_| cb.query_bool());
         cb.require_equal(
-            "Opcode should be CALL",
+            "Exactly one of CALL, CALLCODE, DELEGATECALL or STATICCALL is enabled",
+            sum::expr([&is_call, &is_callcode, &is_delegatecall, &is_staticcall]),
+            1.expr(),
+        );
+        cb.require_equal(
+            "Opcode should be CALL, CALLCODE, DELEGATECALL or STATICCALL",
             opcode.expr(),
-            OpcodeId::CALL.expr(),
+            is_call.expr() * OpcodeId::CALL.expr()
+                + is_callcode.expr() * OpcodeId::CALLCODE.expr()
+                + is_delegatecall.expr() * OpcodeId::DELEGATECALL.expr()
+                + is_staticcall.expr() * OpcodeId::STATICCALL.expr(),
         );
+        // Only CALL and CALLCODE have the value on stack, and transfer it.
+        let has_value_on_stack = is_call.expr() + is_callcode.expr();
 
         let gas_word = cb.query_word();
         let callee_address_word = cb.query_word();
@@ -97,17 +120,52 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         ]
         .map(|field_tag| cb.call_context(None, field_tag));
 
-        cb.range_lookup(depth.expr(), 1024);
+        // The depth is at most 1024, otherwise it fails with ErrorDepth.
+        cb.range_lookup(depth.expr() - 1.expr(), 1024);
+
+        // DELEGATECALL keeps the caller and value of the current call.
+        let [current_caller_address, current_value] = [(); 2].map(|_| cb.query_cell());
+        cb.condition(is_delegatecall.expr(), |cb| {
+            for (field_tag, value) in [
+                (CallContextFieldTag::CallerAddress, &current_caller_address),
+                (CallContextFieldTag::Value, &current_value),
+            ] {
+                cb.call_context_lookup(false.expr(), None, field_tag, value.expr());
+            }
+        });
 
         // Lookup values from stack
         cb.stack_pop(gas_word.expr());
         cb.stack_pop(callee_address_word.expr());
-        cb.stack_pop(value.expr());
-        cb.stack_pop(cd_offset.expr());
-        cb.stack_pop(cd_length.expr());
-        cb.stack_pop(rd_offset.expr());
-        cb.stack_pop(rd_length.expr());
-        cb.stack_push(is_success.expr());
+        cb.condition(has_value_on_stack.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        cb.condition(1.expr() - has_value_on_stack.clone(), |cb| {
+            cb.require_zero(
+                "DELEGATECALL and STATICCALL have no value on stack",
+                sum::expr(&value.cells),
+            );
+        });
+        for (idx, word) in [
+            cd_offset.expr(),
+            cd_length.expr(),
+            rd_offset.expr(),
+            rd_length.expr(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            cb.stack_lookup(
+                false.expr(),
+                (2 + idx).expr() + has_value_on_stack.clone(),
+                word,
+            );
+        }
+        cb.stack_lookup(
+            true.expr(),
+            5.expr() + has_value_on_stack.clone(),
+            is_success.expr(),
+        );
 
         // Recomposition of random linear combination to integer
         let callee_address =
@@ -122,7 +180,8 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             [cd_address.address(), rd_address.address()],
         );
 
-        // Add callee to access list
+        // Add callee to access list. For CALLCODE and DELEGATECALL it's the
+        // account whose code is executed.
         let is_warm = cb.query_bool();
         let is_warm_prev = cb.query_bool();
         cb.account_access_list_write(
@@ -148,22 +207,40 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             );
         });
 
+        // The new call context is executed within the current account for
+        // CALLCODE and DELEGATECALL.
+        let callee_context_address = select::expr(
+            is_callcode.expr() + is_delegatecall.expr(),
+            current_address.expr(),
+            callee_address.clone(),
+        );
+        let callee_caller_address = select::expr(
+            is_delegatecall.expr(),
+            current_caller_address.expr(),
+            current_address.expr(),
+        );
+        let callee_value = select::expr(is_delegatecall.expr(), current_value.expr(), value.expr());
+        let callee_is_static =
+            is_static.expr() + is_staticcall.expr() - is_static.expr() * is_staticcall.expr();
+
         // Verify transfer
         let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
         let has_value = 1.expr() - value_is_zero.expr();
-        cb.condition(has_value.clone(), |cb| {
+        cb.condition(is_call.expr() * has_value.clone(), |cb| {
             cb.require_zero(
                 "CALL with value must not be in static call stack",
                 is_static.expr(),
             );
         });
-        let transfer = TransferGadget::construct(
-            cb,
-            current_address.expr(),
-            callee_address.clone(),
-            value.clone(),
-            &mut callee_reversion_info,
-        );
+        let transfer = cb.condition(has_value_on_stack.clone(), |cb| {
+            TransferGadget::construct(
+                cb,
+                current_address.expr(),
+                callee_context_address.clone(),
+                value.clone(),
+                &mut callee_reversion_info,
+            )
+        });
 
         // Verify gas cost
         let [callee_nonce, callee_code_hash] = [AccountFieldTag::Nonce, AccountFieldTag::CodeHash]
@@ -187,14 +264,15 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
                 cb.power_of_randomness(),
             ),
         );
-        // Sum up gas cost
+        // Sum up gas cost, where only CALL might create a new account.
         let gas_cost = select::expr(
             is_warm_prev.expr(),
             GasCost::WARM_ACCESS.expr(),
             GasCost::COLD_ACCOUNT_ACCESS.expr(),
         ) + has_value.clone()
             * (GasCost::CALL_WITH_VALUE.expr()
-                + is_account_empty.expr()
+                + is_call.expr()
+                    * is_account_empty.expr()
                     * is_empty_code_hash.expr()
                     * GasCost::NEW_ACCOUNT.expr())
             + memory_expansion.gas_cost();
@@ -210,9 +288,19 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             all_but_one_64th_gas,
         );
 
-        // TODO: Handle precompiled
+        // The precompiled contracts are at the addresses 0x01 to 0x09, whose
+        // execution is done in the next step instead of by their code.
+        let callee_address_is_zero = IsZeroGadget::construct(cb, callee_address.clone());
+        let callee_address_lt_precompiles_end =
+            LtGadget::construct(cb, callee_address.clone(), 10.expr());
+        let is_precompile =
+            callee_address_lt_precompiles_end.expr() * (1.expr() - callee_address_is_zero.expr());
+        let is_empty_code = is_empty_code_hash.expr() * (1.expr() - is_precompile.clone());
+
+        // The callee only has the reversible writes of the transfer.
+        let callee_reversible_write_counter = 2.expr() * has_value_on_stack.clone();
 
-        cb.condition(is_empty_code_hash.expr(), |cb| {
+        cb.condition(is_empty_code.clone(), |cb| {
             // Save caller's call state
             for field_tag in [
                 CallContextFieldTag::LastCalleeId,
@@ -223,19 +311,19 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                rw_counter: Delta(24.expr()),
+                rw_counter: Delta(cb.rw_counter_offset()),
                 program_counter: Delta(1.expr()),
-                stack_pointer: Delta(6.expr()),
+                stack_pointer: Delta(5.expr() + has_value_on_stack.clone()),
                 gas_left: Delta(
                     has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr() - gas_cost.clone(),
                 ),
                 memory_word_size: To(memory_expansion.next_memory_word_size()),
-                reversible_write_counter: Delta(3.expr()),
+                reversible_write_counter: Delta(1.expr() + callee_reversible_write_counter.clone()),
                 ..StepStateTransition::default()
             });
         });
 
-        cb.condition(1.expr() - is_empty_code_hash.expr(), |cb| {
+        cb.condition(1.expr() - is_empty_code, |cb| {
             // Save caller's call state
             for (field_tag, value) in [
                 (
@@ -244,7 +332,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
                 ),
                 (
                     CallContextFieldTag::StackPointer,
-                    cb.curr.state.stack_pointer.expr() + 6.expr(),
+                    cb.curr.state.stack_pointer.expr() + 5.expr() + has_value_on_stack.clone(),
                 ),
                 (
                     CallContextFieldTag::GasLeft,
@@ -262,52 +350,74 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
                 cb.call_context_lookup(true.expr(), None, field_tag, value);
             }
 
+            // The code hash of a precompiled contract's context is its
+            // address, which identifies the precompiled contract.
+            let callee_code_hash = select::expr(
+                is_precompile.clone(),
+                callee_address.clone(),
+                callee_code_hash.expr(),
+            );
+
             // Setup next call's context.
             for (field_tag, value) in [
                 (CallContextFieldTag::CallerId, cb.curr.state.call_id.expr()),
                 (CallContextFieldTag::TxId, tx_id.expr()),
                 (CallContextFieldTag::Depth, depth.expr() + 1.expr()),
-                (CallContextFieldTag::CallerAddress, current_address.expr()),
-                (CallContextFieldTag::CalleeAddress, callee_address),
+                (CallContextFieldTag::CallerAddress, callee_caller_address),
+                (CallContextFieldTag::CalleeAddress, callee_context_address),
                 (CallContextFieldTag::CallDataOffset, cd_address.offset()),
                 (CallContextFieldTag::CallDataLength, cd_address.length()),
                 (CallContextFieldTag::ReturnDataOffset, rd_address.offset()),
                 (CallContextFieldTag::ReturnDataLength, rd_address.length()),
-                (CallContextFieldTag::Value, value.expr()),
+                (CallContextFieldTag::Value, callee_value),
                 (CallContextFieldTag::IsSuccess, is_success.expr()),
-                (CallContextFieldTag::IsStatic, is_static.expr()),
+                (CallContextFieldTag::IsStatic, callee_is_static),
                 (CallContextFieldTag::LastCalleeId, 0.expr()),
                 (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
                 (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
                 (CallContextFieldTag::IsRoot, 0.expr()),
                 (CallContextFieldTag::IsCreate, 0.expr()),
-                (CallContextFieldTag::CodeHash, callee_code_hash.expr()),
+                (CallContextFieldTag::CodeHash, callee_code_hash.clone()),
             ] {
                 cb.call_context_lookup(false.expr(), Some(callee_call_id.expr()), field_tag, value);
             }
 
+            // The call to a precompiled contract is followed by its execution.
+            cb.require_equal(
+                "Go to PRECOMPILE only when is_precompile",
+                cb.next
+                    .execution_state_selector([ExecutionState::PRECOMPILE]),
+                is_precompile.clone(),
+            );
+
             // Give gas stipend if value is not zero
             let callee_gas_left = callee_gas_left + has_value * GAS_STIPEND_CALL_WITH_VALUE.expr();
 
             cb.require_step_state_transition(StepStateTransition {
-                rw_counter: Delta(44.expr()),
+                rw_counter: Delta(cb.rw_counter_offset()),
                 call_id: To(callee_call_id.expr()),
                 is_root: To(false.expr()),
                 is_create: To(false.expr()),
-                code_hash: To(callee_code_hash.expr()),
+                code_hash: To(callee_code_hash),
                 gas_left: To(callee_gas_left),
-                reversible_write_counter: To(2.expr()),
+                reversible_write_counter: To(callee_reversible_write_counter),
                 ..StepStateTransition::new_context()
             });
         });
 
         Self {
             opcode,
+            is_call,
+            is_callcode,
+            is_delegatecall,
+            is_staticcall,
             tx_id,
             reversion_info,
             current_address,
             is_static,
             depth,
+            current_caller_address,
+            current_value,
             gas: gas_word,
             callee_address: callee_address_word,
             value,
@@ -327,6 +437,8 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             is_empty_code_hash,
             one_64th_gas,
             capped_callee_gas_left,
+            callee_address_is_zero,
+            callee_address_lt_precompiles_end,
         }
     }
 
@@ -339,41 +451,77 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        let [tx_id, current_address, is_static, depth, callee_rw_counter_end_of_reversion, callee_is_persistent] =
-            [
-                step.rw_indices[0],
-                step.rw_indices[3],
-                step.rw_indices[4],
-                step.rw_indices[5],
-                step.rw_indices[15],
-                step.rw_indices[16],
-            ]
-            .map(|idx| block.rws[idx].call_context_value());
-        let [gas, callee_address, value, cd_offset, cd_length, rd_offset, rd_length, is_success] =
-            [
-                step.rw_indices[6],
-                step.rw_indices[7],
-                step.rw_indices[8],
-                step.rw_indices[9],
-                step.rw_indices[10],
-                step.rw_indices[11],
-                step.rw_indices[12],
-                step.rw_indices[13],
-            ]
-            .map(|idx| block.rws[idx].stack_value());
-        let (is_warm, is_warm_prev) = block.rws[step.rw_indices[14]].tx_access_list_value_pair();
-        let [caller_balance_pair, callee_balance_pair, (callee_nonce, _), (callee_code_hash, _)] =
-            [
-                step.rw_indices[17],
-                step.rw_indices[18],
-                step.rw_indices[19],
-                step.rw_indices[20],
-            ]
-            .map(|idx| block.rws[idx].account_value_pair());
-
         let opcode = step.opcode.unwrap();
+        let is_call = opcode == OpcodeId::CALL;
+        let is_callcode = opcode == OpcodeId::CALLCODE;
+        let is_delegatecall = opcode == OpcodeId::DELEGATECALL;
+        let is_staticcall = opcode == OpcodeId::STATICCALL;
+        let has_value_on_stack = is_call || is_callcode;
+
+        // The indices of rw lookups after the ones of the current call
+        // context, which might include the caller address and value for
+        // DELEGATECALL.
+        let mut rw_idx = if is_delegatecall { 8 } else { 6 };
+        let mut next_rw_indices = |n: usize| {
+            let indices = (rw_idx..rw_idx + n)
+                .map(|idx| step.rw_indices[idx])
+                .collect::<Vec<_>>();
+            rw_idx += n;
+            indices
+        };
+
+        let [tx_id, current_address, is_static, depth] = [
+            step.rw_indices[0],
+            step.rw_indices[3],
+            step.rw_indices[4],
+            step.rw_indices[5],
+        ]
+        .map(|idx| block.rws[idx].call_context_value());
+        let [current_caller_address, current_value] = if is_delegatecall {
+            [step.rw_indices[6], step.rw_indices[7]].map(|idx| block.rws[idx].call_context_value())
+        } else {
+            [U256::zero(), U256::zero()]
+        };
+        let mut stack_values = next_rw_indices(if has_value_on_stack { 8 } else { 7 })
+            .into_iter()
+            .map(|idx| block.rws[idx].stack_value())
+            .collect::<Vec<_>>();
+        if !has_value_on_stack {
+            stack_values.insert(2, U256::zero());
+        }
+        let [gas, callee_address, value, cd_offset, cd_length, rd_offset, rd_length, is_success]: [U256; 8] =
+            stack_values.try_into().unwrap();
+        let (is_warm, is_warm_prev) = block.rws[next_rw_indices(1)[0]].tx_access_list_value_pair();
+        let [callee_rw_counter_end_of_reversion, callee_is_persistent]: [U256; 2] =
+            next_rw_indices(2)
+                .into_iter()
+                .map(|idx| block.rws[idx].call_context_value())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+        let [caller_balance_pair, callee_balance_pair] = if has_value_on_stack {
+            let indices = next_rw_indices(2);
+            [indices[0], indices[1]].map(|idx| block.rws[idx].account_value_pair())
+        } else {
+            [(U256::zero(), U256::zero()); 2]
+        };
+        let [(callee_nonce, _), (callee_code_hash, _)]: [(U256, U256); 2] = next_rw_indices(2)
+            .into_iter()
+            .map(|idx| block.rws[idx].account_value_pair())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
         self.opcode
             .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        for (cell, value) in [
+            (&self.is_call, is_call),
+            (&self.is_callcode, is_callcode),
+            (&self.is_delegatecall, is_delegatecall),
+            (&self.is_staticcall, is_staticcall),
+        ] {
+            cell.assign(region, offset, Some(F::from(value as u64)))?;
+        }
 
         self.tx_id
             .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
@@ -389,6 +537,16 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             .assign(region, offset, Some(F::from(is_static.low_u64())))?;
         self.depth
             .assign(region, offset, Some(F::from(depth.low_u64())))?;
+        self.current_caller_address
+            .assign(region, offset, current_caller_address.to_scalar())?;
+        self.current_value.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                current_value.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
 
         self.gas.assign(region, offset, Some(gas.to_le_bytes()))?;
         self.callee_address
@@ -464,7 +622,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             GasCost::COLD_ACCOUNT_ACCESS.as_u64()
         } + if has_value {
             GasCost::CALL_WITH_VALUE.as_u64()
-                + if is_account_empty == F::one() {
+                + if is_call && is_account_empty == F::one() {
                     GasCost::NEW_ACCOUNT.as_u64()
                 } else {
                     0
@@ -481,6 +639,15 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             F::from(gas.low_u64()),
             F::from(gas_available - gas_available / 64),
         )?;
+        let callee_address = callee_address.to_address().to_scalar().unwrap();
+        self.callee_address_is_zero
+            .assign(region, offset, callee_address)?;
+        self.callee_address_lt_precompiles_end.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(10),
+        )?;
         Ok(())
     }
 }
@@ -508,7 +675,8 @@ mod test {
         rd_length: u64,
     }
 
-    fn caller(stack: Stack, caller_is_success: bool) -> Account {
+    fn caller(opcode: OpcodeId, stack: Stack, caller_is_success: bool) -> Account {
+        let is_call_or_callcode = opcode == OpcodeId::CALL || opcode == OpcodeId::CALLCODE;
         let terminator = if caller_is_success {
             OpcodeId::RETURN
         } else {
@@ -516,27 +684,28 @@ mod test {
         };
 
         // Call twice for testing both cold and warm access
-        let bytecode = bytecode! {
-            PUSH32(Word::from(stack.rd_length))
-            PUSH32(Word::from(stack.rd_offset))
-            PUSH32(Word::from(stack.cd_length))
-            PUSH32(Word::from(stack.cd_offset))
-            PUSH32(stack.value)
-            PUSH32(Address::repeat_byte(0xff).to_word())
-            PUSH32(Word::from(stack.gas))
-            CALL
-            PUSH32(Word::from(stack.rd_length))
-            PUSH32(Word::from(stack.rd_offset))
-            PUSH32(Word::from(stack.cd_length))
-            PUSH32(Word::from(stack.cd_offset))
-            PUSH32(stack.value)
-            PUSH32(Address::repeat_byte(0xff).to_word())
-            PUSH32(Word::from(stack.gas))
-            CALL
+        let mut bytecode = Bytecode::default();
+        for _ in 0..2 {
+            bytecode.append(&bytecode! {
+                PUSH32(Word::from(stack.rd_length))
+                PUSH32(Word::from(stack.rd_offset))
+                PUSH32(Word::from(stack.cd_length))
+                PUSH32(Word::from(stack.cd_offset))
+            });
+            if is_call_or_callcode {
+                bytecode.push(32, stack.value);
+            }
+            bytecode.append(&bytecode! {
+                PUSH32(Address::repeat_byte(0xff).to_word())
+                PUSH32(Word::from(stack.gas))
+                .write_op(opcode)
+            });
+        }
+        bytecode.append(&bytecode! {
             PUSH1(0)
             PUSH1(0)
             .write_op(terminator)
-        };
+        });
 
         Account {
             address: Address::repeat_byte(0xfe),
@@ -646,7 +815,7 @@ mod test {
         ];
         let callees = vec![callee(bytecode! {}), callee(bytecode! { STOP })];
         for (stack, callee) in stacks.into_iter().cartesian_product(callees.into_iter()) {
-            test_ok(caller(stack, true), callee, false);
+            test_ok(caller(OpcodeId::CALL, stack, true), callee, false);
         }
     }
 
@@ -654,6 +823,7 @@ mod test {
     fn call_gadget_nested() {
         let callers = vec![
             caller(
+                OpcodeId::CALL,
                 Stack {
                     gas: 100000,
                     ..Default::default()
@@ -661,6 +831,7 @@ mod test {
                 true,
             ),
             caller(
+                OpcodeId::CALL,
                 Stack {
                     gas: 100000,
                     ..Default::default()
@@ -680,6 +851,64 @@ mod test {
         }
     }
 
+    #[test]
+    fn callcode_delegatecall_staticcall_gadget_simple() {
+        let stacks = vec![
+            // With nothing
+            Stack::default(),
+            // With gas and memory expansion
+            Stack {
+                gas: 100000,
+                cd_offset: 64,
+                cd_length: 320,
+                rd_offset: 0,
+                rd_length: 32,
+                ..Default::default()
+            },
+        ];
+        let callees = vec![
+            callee(bytecode! {}),
+            // Reads the storage, caller and value of the new call context
+            callee(bytecode! {
+                PUSH1(0)
+                SLOAD
+                CALLER
+                CALLVALUE
+                ADD
+                ADD
+                POP
+                STOP
+            }),
+        ];
+        for ((opcode, stack), callee) in [
+            OpcodeId::CALLCODE,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::STATICCALL,
+        ]
+        .into_iter()
+        .cartesian_product(stacks.into_iter())
+        .cartesian_product(callees.into_iter())
+        {
+            test_ok(caller(opcode, stack, true), callee, false);
+        }
+    }
+
+    #[test]
+    fn callcode_gadget_with_value() {
+        let stack = Stack {
+            value: Word::from(10).pow(18.into()),
+            gas: 100000,
+            ..Default::default()
+        };
+        let callees = vec![
+            callee(bytecode! {}),
+            callee(bytecode! { PUSH1(0) PUSH1(0) REVERT }),
+        ];
+        for callee in callees {
+            test_ok(caller(OpcodeId::CALLCODE, stack, true), callee, false);
+        }
+    }
+
     #[test]
     fn call_gadget_recursive() {
         test_ok(
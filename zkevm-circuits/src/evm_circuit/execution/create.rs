@@ -0,0 +1,689 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::{ContractAddressGadget, HardforkGadget, TransferGadget},
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{ConstantDivisionGadget, LtGadget},
+            memory_gadget::{
+                MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget,
+                MemoryWordSizeGadget,
+            },
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{
+    evm_types::{gas_utils::initcode_word_gas_cost, GasCost, MAX_INITCODE_SIZE_MULTIPLIER},
+    Field, Hardfork, ToAddress, ToLittleEndian, U256,
+};
+use ethers_core::utils::keccak256;
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CREATE and CREATE2, which derive the address of the new contract,
+/// transfer the value to it and execute its initcode in a new call context.
+#[derive(Clone, Debug)]
+pub(crate) struct CreateGadget<F, const IS_CREATE2: bool> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    depth: Cell<F>,
+    value: Word<F>,
+    salt: Word<F>,
+    is_success: Cell<F>,
+    is_warm_prev: Cell<F>,
+    callee_reversion_info: ReversionInfo<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    hardfork: HardforkGadget<F>,
+    initcode_word_size: MemoryWordSizeGadget<F>,
+    max_code_size: Cell<F>,
+    initcode_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    transfer: TransferGadget<F>,
+    init_code_hash: Word<F>,
+    contract_address: ContractAddressGadget<F>,
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+    /// Random linear combination of the initcode, accumulated in the copy
+    /// table.
+    rlc_acc: Cell<F>,
+}
+
+impl<F: Field, const IS_CREATE2: bool> ExecutionGadget<F> for CreateGadget<F, IS_CREATE2> {
+    const NAME: &'static str = if IS_CREATE2 { "CREATE2" } else { "CREATE" };
+
+    const EXECUTION_STATE: ExecutionState = if IS_CREATE2 {
+        ExecutionState::CREATE2
+    } else {
+        ExecutionState::CREATE
+    };
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `CREATE` and `CREATE2`.
+        cb.require_equal(
+            "Opcode should be CREATE or CREATE2",
+            opcode.expr(),
+            if IS_CREATE2 {
+                OpcodeId::CREATE2
+            } else {
+                OpcodeId::CREATE
+            }
+            .expr(),
+        );
+
+        let value = cb.query_word();
+        let memory_offset = cb.query_cell();
+        let memory_length = cb.query_rlc::<N_BYTES_MEMORY_ADDRESS>();
+        let salt = cb.query_word();
+        let is_success = cb.query_bool();
+        let init_code_hash = cb.query_word();
+
+        let contract_address = ContractAddressGadget::construct(
+            cb,
+            IS_CREATE2.expr(),
+            salt.expr(),
+            init_code_hash.expr(),
+        );
+
+        // Use rw_counter of the step which triggers next call as its call_id.
+        let callee_call_id = cb.curr.state.rw_counter.clone();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info(None);
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::CalleeAddress,
+            contract_address.caller_address(),
+        );
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 0.expr());
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        // The depth is at most 1024, otherwise it fails with ErrorDepth.
+        cb.range_lookup(depth.expr() - 1.expr(), 1024);
+
+        // Lookup values from stack
+        cb.stack_pop(value.expr());
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(memory_length.expr());
+        if IS_CREATE2 {
+            cb.stack_pop(salt.expr());
+        } else {
+            cb.require_zero("salt is unused by CREATE", salt.expr());
+        }
+        cb.stack_push(is_success.expr() * contract_address.address_rlc());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, memory_length);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // The initcode is charged per word, and its size is limited to twice
+        // the maximum code size since Shanghai (EIP 3860). An oversized
+        // initcode fails with out of gas instead.
+        let hardfork = HardforkGadget::construct(cb);
+        let is_shanghai = hardfork.is_activated(Hardfork::Shanghai);
+        let initcode_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let initcode_size_exceeded = LtGadget::construct(
+            cb,
+            MAX_INITCODE_SIZE_MULTIPLIER.expr() * max_code_size.expr(),
+            memory_address.length(),
+        );
+        cb.require_zero(
+            "initcode size <= max_initcode_size since Shanghai",
+            is_shanghai.clone() * initcode_size_exceeded.expr(),
+        );
+
+        // Increase caller's nonce
+        cb.account_write(
+            contract_address.caller_address(),
+            AccountFieldTag::Nonce,
+            contract_address.nonce() + 1.expr(),
+            contract_address.nonce(),
+            Some(&mut reversion_info),
+        );
+
+        // Add callee to access list
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            contract_address.address(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Propagate rw_counter_end_of_reversion and is_persistent
+        let mut callee_reversion_info = cb.reversion_info(Some(callee_call_id.expr()));
+        cb.require_equal(
+            "callee_is_persistent == is_persistent ⋅ is_success",
+            callee_reversion_info.is_persistent(),
+            reversion_info.is_persistent() * is_success.expr(),
+        );
+        cb.condition(is_success.expr() * (1.expr() - reversion_info.is_persistent()), |cb| {
+            cb.require_equal(
+                "callee_rw_counter_end_of_reversion == rw_counter_end_of_reversion - (reversible_write_counter + 2)",
+                callee_reversion_info.rw_counter_end_of_reversion(),
+                reversion_info.rw_counter_of_reversion(),
+            );
+        });
+
+        // The callee must have neither code nor nonce, otherwise the address
+        // collides with an existing contract.
+        let empty_code_hash = Word::random_linear_combine_expr(
+            (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        cb.account_read(
+            contract_address.address(),
+            AccountFieldTag::CodeHash,
+            empty_code_hash,
+        );
+        cb.account_write(
+            contract_address.address(),
+            AccountFieldTag::Nonce,
+            1.expr(),
+            0.expr(),
+            Some(&mut callee_reversion_info),
+        );
+
+        let transfer = TransferGadget::construct(
+            cb,
+            contract_address.caller_address(),
+            contract_address.address(),
+            value.clone(),
+            &mut callee_reversion_info,
+        );
+
+        // CREATE2 additionally pays for hashing the initcode.
+        let gas_cost = GasCost::CREATE.expr()
+            + if IS_CREATE2 {
+                memory_copier_gas.gas_cost()
+            } else {
+                memory_expansion.gas_cost()
+            }
+            + is_shanghai * GasCost::INITCODE_WORD_COST.expr() * initcode_word_size.expr();
+
+        // Apply EIP 150
+        let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let callee_gas_left = gas_available - one_64th_gas.quotient();
+
+        let copy_rwc_inc = cb.query_cell();
+        let rlc_acc = cb.query_cell();
+        cb.keccak_table_lookup(
+            rlc_acc.expr(),
+            memory_address.length(),
+            init_code_hash.expr(),
+        );
+
+        cb.condition(not::expr(memory_address.has_length()), |cb| {
+            cb.require_zero(
+                "if initcode is empty, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+            cb.require_zero("if initcode is empty, rlc_acc == 0", rlc_acc.expr());
+            cb.require_equal(
+                "CREATE with empty initcode always succeeds",
+                is_success.expr(),
+                1.expr(),
+            );
+
+            // Save caller's call state
+            for field_tag in [
+                CallContextFieldTag::LastCalleeId,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+            ] {
+                cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+            }
+
+            // The callee's nonce and balance writes are accumulated into the
+            // caller's, since the callee halts right away.
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Delta(cb.rw_counter_offset()),
+                program_counter: Delta(1.expr()),
+                stack_pointer: Delta((IS_CREATE2 as u64 + 2).expr()),
+                gas_left: Delta(-gas_cost.clone()),
+                memory_word_size: To(memory_expansion.next_memory_word_size()),
+                reversible_write_counter: Delta(5.expr()),
+                ..StepStateTransition::default()
+            });
+        });
+
+        cb.condition(memory_address.has_length(), |cb| {
+            // Save caller's call state
+            for (field_tag, value) in [
+                (
+                    CallContextFieldTag::ProgramCounter,
+                    cb.curr.state.program_counter.expr() + 1.expr(),
+                ),
+                (
+                    CallContextFieldTag::StackPointer,
+                    cb.curr.state.stack_pointer.expr() + (IS_CREATE2 as u64 + 2).expr(),
+                ),
+                (
+                    CallContextFieldTag::GasLeft,
+                    cb.curr.state.gas_left.expr() - gas_cost - callee_gas_left.clone(),
+                ),
+                (
+                    CallContextFieldTag::MemorySize,
+                    memory_expansion.next_memory_word_size(),
+                ),
+                (
+                    CallContextFieldTag::ReversibleWriteCounter,
+                    cb.curr.state.reversible_write_counter.expr() + 2.expr(),
+                ),
+            ] {
+                cb.call_context_lookup(true.expr(), None, field_tag, value);
+            }
+
+            // Setup next call's context.
+            for (field_tag, value) in [
+                (CallContextFieldTag::CallerId, cb.curr.state.call_id.expr()),
+                (CallContextFieldTag::TxId, tx_id.expr()),
+                (CallContextFieldTag::Depth, depth.expr() + 1.expr()),
+                (
+                    CallContextFieldTag::CallerAddress,
+                    contract_address.caller_address(),
+                ),
+                (
+                    CallContextFieldTag::CalleeAddress,
+                    contract_address.address(),
+                ),
+                (CallContextFieldTag::CallDataOffset, 0.expr()),
+                (CallContextFieldTag::CallDataLength, 0.expr()),
+                (CallContextFieldTag::ReturnDataOffset, 0.expr()),
+                (CallContextFieldTag::ReturnDataLength, 0.expr()),
+                (CallContextFieldTag::Value, value.expr()),
+                (CallContextFieldTag::IsSuccess, is_success.expr()),
+                (CallContextFieldTag::IsStatic, 0.expr()),
+                (CallContextFieldTag::LastCalleeId, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
+                (CallContextFieldTag::IsRoot, 0.expr()),
+                (CallContextFieldTag::IsCreate, 1.expr()),
+                (CallContextFieldTag::CodeHash, init_code_hash.expr()),
+            ] {
+                cb.call_context_lookup(false.expr(), Some(callee_call_id.expr()), field_tag, value);
+            }
+
+            // Hash the initcode copied from caller's memory.
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                memory_address.offset(),
+                memory_address.address(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                memory_address.length(),
+                rlc_acc.expr(),
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset(),
+                copy_rwc_inc.expr(),
+            );
+
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Delta(cb.rw_counter_offset() + copy_rwc_inc.expr()),
+                call_id: To(callee_call_id.expr()),
+                is_root: To(false.expr()),
+                is_create: To(true.expr()),
+                code_hash: To(init_code_hash.expr()),
+                gas_left: To(callee_gas_left),
+                reversible_write_counter: To(3.expr()),
+                ..StepStateTransition::new_context()
+            });
+        });
+
+        Self {
+            opcode,
+            tx_id,
+            reversion_info,
+            depth,
+            value,
+            salt,
+            is_success,
+            is_warm_prev,
+            callee_reversion_info,
+            memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            hardfork,
+            initcode_word_size,
+            max_code_size,
+            initcode_size_exceeded,
+            transfer,
+            init_code_hash,
+            contract_address,
+            one_64th_gas,
+            copy_rwc_inc,
+            rlc_acc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let n_pop = if IS_CREATE2 { 4 } else { 3 };
+        let [tx_id, caller_address, depth] =
+            [0, 3, 5].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        let [value, memory_offset, memory_length] =
+            [6, 7, 8].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let salt = if IS_CREATE2 {
+            block.rws[step.rw_indices[9]].stack_value()
+        } else {
+            U256::zero()
+        };
+        let address = block.rws[step.rw_indices[6 + n_pop]].stack_value();
+        let (_, nonce) = block.rws[step.rw_indices[7 + n_pop]].account_value_pair();
+        let (_, is_warm_prev) = block.rws[step.rw_indices[8 + n_pop]].tx_access_list_value_pair();
+        let [callee_rw_counter_end_of_reversion, callee_is_persistent] =
+            [9 + n_pop, 10 + n_pop].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        let [caller_balance_pair, callee_balance_pair] = [13 + n_pop, 14 + n_pop]
+            .map(|idx| block.rws[step.rw_indices[idx]].account_value_pair());
+
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.depth
+            .assign(region, offset, Some(F::from(depth.low_u64())))?;
+
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.salt.assign(region, offset, Some(salt.to_le_bytes()))?;
+        // A created contract never has the zero address.
+        self.is_success
+            .assign(region, offset, Some(F::from(!address.is_zero() as u64)))?;
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+        self.callee_reversion_info.assign(
+            region,
+            offset,
+            callee_rw_counter_end_of_reversion.low_u64() as usize,
+            callee_is_persistent.low_u64() != 0,
+        )?;
+
+        let memory_address = self.memory_address.assign(
+            region,
+            offset,
+            memory_offset,
+            memory_length,
+            block.randomness,
+        )?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            memory_length.as_u64(),
+            memory_expansion_gas_cost,
+        )?;
+
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+        self.initcode_word_size
+            .assign(region, offset, memory_length.as_u64())?;
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.initcode_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_INITCODE_SIZE_MULTIPLIER) * max_code_size,
+            F::from(memory_length.as_u64()),
+        )?;
+
+        self.transfer.assign(
+            region,
+            offset,
+            caller_balance_pair,
+            callee_balance_pair,
+            value,
+        )?;
+
+        let (init_code, copy_rwc_inc, rlc_acc) = if memory_length.is_zero() {
+            (vec![], F::zero(), F::zero())
+        } else {
+            let copy_event = block
+                .copy_event((tx.id, call.id, step.program_counter as usize))
+                .unwrap();
+            let copy_rwc_inc = copy_event
+                .steps
+                .first()
+                .map_or(F::zero(), |cs| F::from(cs.rwc_inc_left));
+            let init_code = copy_event
+                .steps
+                .iter()
+                .filter(|cs| cs.rw.is_write())
+                .map(|cs| cs.value)
+                .collect::<Vec<_>>();
+            let rlc_acc = init_code.iter().fold(F::zero(), |acc, byte| {
+                acc * block.randomness + F::from(*byte as u64)
+            });
+            (init_code, copy_rwc_inc, rlc_acc)
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Some(copy_rwc_inc))?;
+        self.rlc_acc.assign(region, offset, Some(rlc_acc))?;
+
+        let init_code_hash = U256::from_big_endian(&keccak256(&init_code));
+        self.init_code_hash
+            .assign(region, offset, Some(init_code_hash.to_le_bytes()))?;
+        self.contract_address.assign(
+            region,
+            offset,
+            caller_address.to_address(),
+            IS_CREATE2,
+            nonce.low_u64(),
+            salt,
+            init_code_hash,
+        )?;
+
+        let gas_cost = GasCost::CREATE.as_u64()
+            + if IS_CREATE2 {
+                memory_copier_gas_cost
+            } else {
+                memory_expansion_gas_cost
+            }
+            + initcode_word_gas_cost(block.context.hardfork, memory_length.as_u64());
+        self.one_64th_gas
+            .assign(region, offset, (step.gas_left - gas_cost) as u128)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::witness::block_convert,
+        test_util::{test_circuits_using_witness_block, BytecodeTestConfig},
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode, Word};
+    use mock::TestContext;
+
+    // RETURN the 4 bytes 0x6001600a of the deployed code, which are stored at
+    // the end of the first memory word.
+    fn initcode(is_success: bool) -> Vec<u8> {
+        let mut code = bytecode! {
+            PUSH4(0x6001600a)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04)
+            PUSH1(0x1c)
+        };
+        code.write_op(if is_success {
+            OpcodeId::RETURN
+        } else {
+            OpcodeId::REVERT
+        });
+        code.to_vec()
+    }
+
+    fn creator_code(initcode: &[u8], is_create2: bool) -> Bytecode {
+        // Store the initcode at the start of the memory, left-padded within
+        // 32-byte words.
+        let mut code = Bytecode::default();
+        let words = (initcode.len() + 31) / 32;
+        let mut padded = vec![0; words * 32 - initcode.len()];
+        padded.extend_from_slice(initcode);
+        for (idx, word) in padded.chunks(32).enumerate() {
+            code.append(&bytecode! {
+                PUSH32(Word::from_big_endian(word))
+                PUSH2(idx * 32)
+                MSTORE
+            });
+        }
+        if is_create2 {
+            code.append(&bytecode! { PUSH2(0xcafe) });
+        }
+        code.append(&bytecode! {
+            PUSH2(initcode.len())
+            PUSH2(padded.len() - initcode.len())
+            PUSH2(0x10)
+        });
+        code.write_op(if is_create2 {
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        });
+        code.append(&bytecode! { STOP });
+        code
+    }
+
+    fn test_ok(initcode: Vec<u8>, is_create2: bool) {
+        test_code(creator_code(&initcode, is_create2));
+    }
+
+    fn test_code(code: Bytecode) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .expect("could not handle block tx");
+
+        test_circuits_using_witness_block(
+            block_convert(&builder.block, &builder.code_db),
+            BytecodeTestConfig::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn create_gadget() {
+        test_ok(initcode(true), false);
+    }
+
+    #[test]
+    fn create2_gadget() {
+        test_ok(initcode(true), true);
+    }
+
+    #[test]
+    fn create_gadget_reverted() {
+        test_ok(initcode(false), false);
+    }
+
+    #[test]
+    fn create2_gadget_reverted() {
+        test_ok(initcode(false), true);
+    }
+
+    #[test]
+    fn create_gadget_empty_initcode() {
+        test_ok(vec![], false);
+    }
+
+    #[test]
+    fn create2_gadget_empty_initcode() {
+        test_ok(vec![], true);
+    }
+
+    #[test]
+    fn create_gadget_empty_initcode_with_large_offset() {
+        // The offset is ignored when the initcode is empty, so it could be
+        // beyond u64.
+        test_code(bytecode! {
+            PUSH1(0x00) // length
+            PUSH32(Word::MAX) // offset
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        });
+        test_code(bytecode! {
+            PUSH2(0xcafe) // salt
+            PUSH1(0x00) // length
+            PUSH32(Word::MAX) // offset
+            PUSH1(0x00) // value
+            CREATE2
+            STOP
+        });
+    }
+}
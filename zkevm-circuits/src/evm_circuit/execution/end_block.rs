@@ -1,17 +1,26 @@
-use crate::evm_circuit::{
-    execution::ExecutionGadget,
-    step::ExecutionState,
-    util::{constraint_builder::ConstraintBuilder, CachedRegion},
-    witness::{Block, Call, ExecStep, Transaction},
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{RwTableTag, TxReceiptFieldTag},
+        util::{
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Same},
+            math_gadget::IsZeroGadget,
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Rw, Transaction},
+    },
+    util::Expr,
 };
 use eth_types::Field;
-use halo2_proofs::plonk::Error;
-
-use std::marker::PhantomData;
+use halo2_proofs::{circuit::AssignedCell, plonk::Error};
 
 #[derive(Clone, Debug)]
 pub(crate) struct EndBlockGadget<F> {
-    _marker: PhantomData<F>,
+    total_rws: Cell<F>,
+    num_txs: Cell<F>,
+    is_empty_block: IsZeroGadget<F>,
+    cumulative_gas_used: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
@@ -19,27 +28,167 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::EndBlock;
 
-    fn configure(_: &mut ConstraintBuilder<F>) -> Self {
-        // TODO: For last step, constrain:
-        //         - tx_id is equal to total_tx
-        //         - rw_counter is equal to public input one
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        // A block without any transaction starts with EndBlock directly.
+        cb.add_constraint_first_step(
+            "rw_counter is initialized to be 1",
+            1.expr() - cb.curr.state.rw_counter.expr(),
+        );
+
+        // rw_counter starts at 1 and EndBlock does no read/write, so the total
+        // number of read/write in the block is rw_counter - 1. It's copy
+        // constrained to the public input at the last step.
+        let total_rws = cb.query_copy_cell();
+        cb.require_equal(
+            "total_rws == rw_counter - 1",
+            total_rws.expr(),
+            cb.curr.state.rw_counter.expr() - 1.expr(),
+        );
+
+        // The last read/write before EndBlock is the write of the cumulative
+        // gas used into the receipt of the last tx, whose tx_id must be the
+        // total number of txs. It's copy constrained to the public input at
+        // the last step like total_rws.
+        let num_txs = cb.query_copy_cell();
+        let is_empty_block = IsZeroGadget::construct(cb, total_rws.expr());
+        let cumulative_gas_used = cb.query_cell();
+        cb.condition(is_empty_block.expr(), |cb| {
+            cb.require_zero("num_txs == 0 in an empty block", num_txs.expr());
+        });
+        cb.condition(1.expr() - is_empty_block.expr(), |cb| {
+            cb.tx_receipt_lookup_with_counter(
+                total_rws.expr(),
+                1.expr(),
+                num_txs.expr(),
+                TxReceiptFieldTag::CumulativeGasUsed,
+                cumulative_gas_used.expr(),
+            );
+        });
 
-        // TODO: For the rest steps, propagate the rw_counter and call_id to next step.
+        // Every step after EndBlock is a padding EndBlock, which inherits
+        // rw_counter and call_id. The last step has no next step.
+        cb.not_last_step(|cb| {
+            cb.require_next_state(ExecutionState::EndBlock);
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Same,
+                call_id: Same,
+                ..StepStateTransition::any()
+            });
+        });
 
         Self {
-            _marker: PhantomData,
+            total_rws,
+            num_txs,
+            is_empty_block,
+            cumulative_gas_used,
         }
     }
 
     fn assign_exec_step(
         &self,
-        _region: &mut CachedRegion<'_, '_, F>,
-        _offset: usize,
-        _: &Block<F>,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
         _: &Transaction,
         _: &Call,
-        _step: &ExecStep,
+        step: &ExecStep,
     ) -> Result<(), Error> {
+        self.assign_total_rws(region, offset, step)?;
+        self.assign_num_txs(region, offset, block)?;
+        self.is_empty_block
+            .assign(region, offset, F::from((step.rw_counter - 1) as u64))?;
+        let cumulative_gas_used = block
+            .rws
+            .0
+            .get(&RwTableTag::TxReceipt)
+            .and_then(|rws| {
+                rws.iter().rev().find_map(|rw| match rw {
+                    Rw::TxReceipt {
+                        is_write: true,
+                        tx_id,
+                        field_tag: TxReceiptFieldTag::CumulativeGasUsed,
+                        value,
+                        ..
+                    } if *tx_id == block.txs.len() => Some(*value),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+        self.cumulative_gas_used
+            .assign(region, offset, Some(F::from(cumulative_gas_used)))?;
         Ok(())
     }
 }
+
+impl<F: Field> EndBlockGadget<F> {
+    /// Assign the total number of read/write in the block, and return its cell
+    /// for the last step to copy constrain it to the public input.
+    pub(crate) fn assign_total_rws(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        step: &ExecStep,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.total_rws
+            .assign(region, offset, Some(F::from((step.rw_counter - 1) as u64)))
+    }
+
+    /// Assign the total number of txs in the block, and return its cell for
+    /// the last step to copy constrain it to the public input.
+    pub(crate) fn assign_num_txs(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.num_txs
+            .assign(region, offset, Some(F::from(block.txs.len() as u64)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        test::run_test_circuit_incomplete_fixed_table,
+        witness::{block_convert, Block},
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::TestContext;
+
+    fn test_ok(evm_circuit_pad_to: usize) {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let mut block = block_convert(&builder.block, &builder.code_db);
+        block.evm_circuit_pad_to = evm_circuit_pad_to;
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn end_block_exact() {
+        test_ok(0);
+    }
+
+    #[test]
+    fn end_block_padding() {
+        test_ok(200);
+        // The leftover rows which can't fit an EndBlock step are unused.
+        test_ok(201);
+    }
+
+    #[test]
+    fn end_block_empty_block() {
+        let block = Block {
+            evm_circuit_pad_to: 200,
+            ..Default::default()
+        };
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+}
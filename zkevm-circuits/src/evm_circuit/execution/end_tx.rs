@@ -8,19 +8,22 @@ use crate::{
             TxReceiptFieldTag,
         },
         util::{
-            common_gadget::UpdateBalanceGadget,
+            common_gadget::{HardforkGadget, UpdateBalanceGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
             math_gadget::{
                 AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, MinMaxGadget,
                 MulWordByU64Gadget,
             },
-            CachedRegion, Cell,
+            select, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
-use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToScalar};
+use eth_types::{
+    evm_types::{MAX_REFUND_QUOTIENT_OF_GAS_USED, MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_LONDON},
+    Field, Hardfork, ToScalar,
+};
 use halo2_proofs::plonk::Error;
 use strum::EnumCount;
 
@@ -28,7 +31,9 @@ use strum::EnumCount;
 pub(crate) struct EndTxGadget<F> {
     tx_id: Cell<F>,
     tx_gas: Cell<F>,
+    hardfork: HardforkGadget<F>,
     max_refund: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    max_refund_pre_london: ConstantDivisionGadget<F, N_BYTES_GAS>,
     refund: Cell<F>,
     effective_refund: MinMaxGadget<F, N_BYTES_GAS>,
     mul_gas_price_by_refund: MulWordByU64Gadget<F>,
@@ -57,16 +62,31 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                 .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
         let tx_gas_price = cb.tx_context_as_word(tx_id.expr(), TxContextFieldTag::GasPrice, None);
 
-        // Calculate effective gas to refund
+        // Calculate effective gas to refund, which is capped at gas_used / 5
+        // since London (EIP-3529), and at gas_used / 2 before.
         let gas_used = tx_gas.expr() - cb.curr.state.gas_left.expr();
+        let hardfork = HardforkGadget::construct(cb);
         let max_refund = ConstantDivisionGadget::construct(
             cb,
             gas_used.clone(),
             MAX_REFUND_QUOTIENT_OF_GAS_USED as u64,
         );
+        let max_refund_pre_london = ConstantDivisionGadget::construct(
+            cb,
+            gas_used.clone(),
+            MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_LONDON as u64,
+        );
         let refund = cb.query_cell();
         cb.tx_refund_read(tx_id.expr(), refund.expr());
-        let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
+        let effective_refund = MinMaxGadget::construct(
+            cb,
+            select::expr(
+                hardfork.is_activated(Hardfork::London),
+                max_refund.quotient(),
+                max_refund_pre_london.quotient(),
+            ),
+            refund.expr(),
+        );
 
         // Add effective_refund * tx_gas_price back to caller's balance
         let mul_gas_price_by_refund = MulWordByU64Gadget::construct(
@@ -81,7 +101,8 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             None,
         );
 
-        // Add gas_used * effective_tip to coinbase's balance
+        // Add gas_used * effective_tip to coinbase's balance, where the
+        // effective tip is the gas price minus the base fee, which is burnt.
         let coinbase = cb.query_cell();
         let base_fee = cb.query_word();
         for (tag, value) in [
@@ -172,7 +193,9 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         Self {
             tx_id,
             tx_gas,
+            hardfork,
             max_refund,
+            max_refund_pre_london,
             refund,
             effective_refund,
             mul_gas_price_by_refund,
@@ -205,7 +228,17 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         self.tx_id
             .assign(region, offset, Some(F::from(tx.id as u64)))?;
         self.tx_gas.assign(region, offset, Some(F::from(tx.gas)))?;
-        let (max_refund, _) = self.max_refund.assign(region, offset, gas_used as u128)?;
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+        let (max_refund_london, _) = self.max_refund.assign(region, offset, gas_used as u128)?;
+        let (max_refund_pre_london, _) =
+            self.max_refund_pre_london
+                .assign(region, offset, gas_used as u128)?;
+        let max_refund = if block.context.hardfork >= Hardfork::London {
+            max_refund_london
+        } else {
+            max_refund_pre_london
+        };
         self.refund.assign(region, offset, Some(F::from(refund)))?;
         self.effective_refund.assign(
             region,
@@ -286,8 +319,12 @@ mod test {
     use crate::evm_circuit::{
         test::run_test_circuit_incomplete_fixed_table, witness::block_convert,
     };
-    use eth_types::{self, bytecode, geth_types::GethData};
-    use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+    use eth_types::{
+        self, bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode, Hardfork, Word,
+    };
+    use mock::{
+        eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+    };
 
     fn test_ok(block: GethData) {
         let block_data = bus_mapping::mock::BlockData::new_from_geth_data(block);
@@ -300,21 +337,42 @@ mod test {
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
 
+    /// A block with a tx which clears `num_slots` non-zero storage slots, each
+    /// of them costs 5006 gas and gets 4800 gas refunded since London.
+    fn clear_storage_block(num_slots: u64, hardfork: Hardfork) -> GethData {
+        let mut code = Bytecode::default();
+        for slot in 0..num_slots {
+            code.push(1, Word::zero())
+                .push(1, Word::from(slot))
+                .write_op(OpcodeId::SSTORE);
+        }
+        code.write_op(OpcodeId::STOP);
+
+        TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code)
+                    .storage((0..num_slots).map(|slot| (Word::from(slot), Word::one())));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64).hardfork(hardfork),
+        )
+        .unwrap()
+        .into()
+    }
+
     #[test]
     fn end_tx_gadget_simple() {
-        // TODO: Enable this with respective code when SSTORE is implemented.
-        // Tx with non-capped refund
-        // test_ok(vec![mock_tx(
-        //     address!("0x00000000000000000000000000000000000000fe"),
-        //     Some(27000),
-        //     None,
-        // )]);
-        // Tx with capped refund
-        // test_ok(vec![mock_tx(
-        //     address!("0x00000000000000000000000000000000000000fe"),
-        //     Some(65000),
-        //     None,
-        // )]);
+        // Tx with non-capped refund, 4800 < (21000 + 5006) / 5
+        test_ok(clear_storage_block(1, Hardfork::London));
+        // Tx with capped refund, 9600 > (21000 + 5006 * 2) / 5
+        test_ok(clear_storage_block(2, Hardfork::London));
 
         // Multiple txs
         test_ok(
@@ -342,4 +400,11 @@ mod test {
             .into(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_pre_london() {
+        // Before London, clearing a slot gets 15000 gas refunded, which is
+        // capped at gas_used / 2 instead, 15000 > (21000 + 5006) / 2
+        test_ok(clear_storage_block(1, Hardfork::Berlin));
+    }
 }
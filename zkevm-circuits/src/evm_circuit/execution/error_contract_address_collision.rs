@@ -0,0 +1,530 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::{ContractAddressGadget, HardforkGadget},
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget},
+            memory_gadget::{
+                MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget,
+                MemoryWordSizeGadget,
+            },
+            select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{
+    evm_types::{gas_utils::initcode_word_gas_cost, GasCost, MAX_INITCODE_SIZE_MULTIPLIER},
+    Field, Hardfork, ToAddress, ToLittleEndian, U256,
+};
+use ethers_core::utils::keccak256;
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CREATE and CREATE2 deriving the address of an account which
+/// already has a nonce or code. The creation fails within the caller's
+/// context, which gets 0 pushed and loses the gas given to the callee.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorContractAddressCollisionGadget<F> {
+    opcode: Cell<F>,
+    is_create2: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    value: Word<F>,
+    salt: Word<F>,
+    is_warm_prev: Cell<F>,
+    callee_nonce: Cell<F>,
+    callee_code_hash: Cell<F>,
+    is_callee_nonce_zero: IsZeroGadget<F>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    hardfork: HardforkGadget<F>,
+    initcode_word_size: MemoryWordSizeGadget<F>,
+    max_code_size: Cell<F>,
+    initcode_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    init_code_hash: Word<F>,
+    contract_address: ContractAddressGadget<F>,
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    copy_rwc_inc: Cell<F>,
+    rlc_acc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorContractAddressCollisionGadget<F> {
+    const NAME: &'static str = "ErrorContractAddressCollision";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorContractAddressCollision;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        let is_create2 = cb.query_bool();
+        cb.require_equal(
+            "ErrorContractAddressCollision opcode must be CREATE or CREATE2",
+            opcode.expr(),
+            select::expr(
+                is_create2.expr(),
+                OpcodeId::CREATE2.expr(),
+                OpcodeId::CREATE.expr(),
+            ),
+        );
+
+        let value = cb.query_word();
+        let memory_offset = cb.query_cell();
+        let memory_length = cb.query_rlc::<N_BYTES_MEMORY_ADDRESS>();
+        let salt = cb.query_word();
+        let init_code_hash = cb.query_word();
+
+        let contract_address = ContractAddressGadget::construct(
+            cb,
+            is_create2.expr(),
+            salt.expr(),
+            init_code_hash.expr(),
+        );
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info(None);
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::CalleeAddress,
+            contract_address.caller_address(),
+        );
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 0.expr());
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        // The depth is at most 1024, otherwise it fails with ErrorDepth.
+        cb.range_lookup(depth.expr() - 1.expr(), 1024);
+
+        // Pop value, memory_offset, length, and salt for CREATE2, then push 0
+        // as the result.
+        cb.stack_pop(value.expr());
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(memory_length.expr());
+        cb.condition(is_create2.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 3.expr(), salt.expr());
+        });
+        cb.condition(1.expr() - is_create2.expr(), |cb| {
+            cb.require_zero("salt is unused by CREATE", salt.expr());
+        });
+        cb.stack_lookup(true.expr(), 2.expr() + is_create2.expr(), 0.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, memory_length);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // The initcode is charged per word, and its size is limited to twice
+        // the maximum code size since Shanghai (EIP 3860), which is checked
+        // before the address collision.
+        let hardfork = HardforkGadget::construct(cb);
+        let is_shanghai = hardfork.is_activated(Hardfork::Shanghai);
+        let initcode_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let initcode_size_exceeded = LtGadget::construct(
+            cb,
+            MAX_INITCODE_SIZE_MULTIPLIER.expr() * max_code_size.expr(),
+            memory_address.length(),
+        );
+        cb.require_zero(
+            "initcode size <= max_initcode_size since Shanghai",
+            is_shanghai.clone() * initcode_size_exceeded.expr(),
+        );
+
+        // The caller's nonce is increased and the colliding address is added
+        // into access list before the collision is detected.
+        cb.account_write(
+            contract_address.caller_address(),
+            AccountFieldTag::Nonce,
+            contract_address.nonce() + 1.expr(),
+            contract_address.nonce(),
+            Some(&mut reversion_info),
+        );
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            contract_address.address(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Check that the callee has a nonce or code.
+        let [callee_nonce, callee_code_hash] = [AccountFieldTag::Nonce, AccountFieldTag::CodeHash]
+            .map(|field_tag| {
+                let value = cb.query_cell();
+                cb.account_read(contract_address.address(), field_tag, value.expr());
+                value
+            });
+        let is_callee_nonce_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            callee_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+        cb.require_zero(
+            "callee_nonce != 0 or callee_code_hash != EMPTY_HASH",
+            is_callee_nonce_zero.expr() * is_empty_code_hash.expr(),
+        );
+
+        // Save caller's call state
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        // The initcode is still hashed to derive the address of CREATE2.
+        let copy_rwc_inc = cb.query_cell();
+        let rlc_acc = cb.query_cell();
+        cb.condition(is_create2.expr() * memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                memory_address.offset(),
+                memory_address.address(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                memory_address.length(),
+                rlc_acc.expr(),
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset(),
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(
+            1.expr() - is_create2.expr() * memory_address.has_length(),
+            |cb| {
+                cb.require_zero(
+                    "if initcode is not hashed, copy table rwc inc == 0",
+                    copy_rwc_inc.expr(),
+                );
+                cb.require_zero("if initcode is not hashed, rlc_acc == 0", rlc_acc.expr());
+            },
+        );
+        cb.condition(is_create2.expr(), |cb| {
+            cb.keccak_table_lookup(
+                rlc_acc.expr(),
+                memory_address.length(),
+                init_code_hash.expr(),
+            );
+        });
+
+        // Besides the gas cost, the gas given to the callee is consumed, so
+        // only 1/64 of the available gas is left.
+        let gas_cost = GasCost::CREATE.expr()
+            + select::expr(
+                is_create2.expr(),
+                memory_copier_gas.gas_cost(),
+                memory_expansion.gas_cost(),
+            )
+            + is_shanghai * GasCost::INITCODE_WORD_COST.expr() * initcode_word_size.expr();
+        let one_64th_gas =
+            ConstantDivisionGadget::construct(cb, cb.curr.state.gas_left.expr() - gas_cost, 64);
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset() + copy_rwc_inc.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr() + is_create2.expr()),
+            gas_left: To(one_64th_gas.quotient()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            reversible_write_counter: Delta(2.expr()),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            opcode,
+            is_create2,
+            tx_id,
+            reversion_info,
+            value,
+            salt,
+            is_warm_prev,
+            callee_nonce,
+            callee_code_hash,
+            is_callee_nonce_zero,
+            is_empty_code_hash,
+            memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            hardfork,
+            initcode_word_size,
+            max_code_size,
+            initcode_size_exceeded,
+            init_code_hash,
+            contract_address,
+            one_64th_gas,
+            copy_rwc_inc,
+            rlc_acc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_create2 = opcode == OpcodeId::CREATE2;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_create2
+            .assign(region, offset, Some(F::from(is_create2 as u64)))?;
+
+        let n_pop = if is_create2 { 4 } else { 3 };
+        let [tx_id, caller_address] =
+            [0, 3].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        let [value, memory_offset, memory_length] =
+            [6, 7, 8].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let salt = if is_create2 {
+            block.rws[step.rw_indices[9]].stack_value()
+        } else {
+            U256::zero()
+        };
+        let (_, nonce) = block.rws[step.rw_indices[7 + n_pop]].account_value_pair();
+        let (_, is_warm_prev) = block.rws[step.rw_indices[8 + n_pop]].tx_access_list_value_pair();
+        let [(callee_nonce, _), (callee_code_hash, _)] =
+            [9 + n_pop, 10 + n_pop].map(|idx| block.rws[step.rw_indices[idx]].account_value_pair());
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.salt.assign(region, offset, Some(salt.to_le_bytes()))?;
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+
+        self.callee_nonce
+            .assign(region, offset, Some(F::from(callee_nonce.low_u64())))?;
+        let callee_code_hash =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness);
+        self.callee_code_hash
+            .assign(region, offset, Some(callee_code_hash))?;
+        self.is_callee_nonce_zero
+            .assign(region, offset, F::from(callee_nonce.low_u64()))?;
+        self.is_empty_code_hash.assign(
+            region,
+            offset,
+            callee_code_hash,
+            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+
+        let memory_address = self.memory_address.assign(
+            region,
+            offset,
+            memory_offset,
+            memory_length,
+            block.randomness,
+        )?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            memory_length.as_u64(),
+            memory_expansion_gas_cost,
+        )?;
+
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+        self.initcode_word_size
+            .assign(region, offset, memory_length.as_u64())?;
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.initcode_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_INITCODE_SIZE_MULTIPLIER) * max_code_size,
+            F::from(memory_length.as_u64()),
+        )?;
+
+        let (init_code_hash, copy_rwc_inc, rlc_acc) = if is_create2 && !memory_length.is_zero() {
+            let copy_event = block
+                .copy_event((tx.id, call.id, step.program_counter as usize))
+                .unwrap();
+            let copy_rwc_inc = copy_event
+                .steps
+                .first()
+                .map_or(F::zero(), |cs| F::from(cs.rwc_inc_left));
+            let init_code = copy_event
+                .steps
+                .iter()
+                .filter(|cs| cs.rw.is_write())
+                .map(|cs| cs.value)
+                .collect::<Vec<_>>();
+            let rlc_acc = init_code.iter().fold(F::zero(), |acc, byte| {
+                acc * block.randomness + F::from(*byte as u64)
+            });
+            (
+                U256::from_big_endian(&keccak256(&init_code)),
+                copy_rwc_inc,
+                rlc_acc,
+            )
+        } else if is_create2 {
+            (U256::from_big_endian(&keccak256(&[])), F::zero(), F::zero())
+        } else {
+            (U256::zero(), F::zero(), F::zero())
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Some(copy_rwc_inc))?;
+        self.rlc_acc.assign(region, offset, Some(rlc_acc))?;
+        self.init_code_hash
+            .assign(region, offset, Some(init_code_hash.to_le_bytes()))?;
+        self.contract_address.assign(
+            region,
+            offset,
+            caller_address.to_address(),
+            is_create2,
+            nonce.low_u64(),
+            salt,
+            init_code_hash,
+        )?;
+
+        let gas_cost = GasCost::CREATE.as_u64()
+            + if is_create2 {
+                memory_copier_gas_cost
+            } else {
+                memory_expansion_gas_cost
+            }
+            + initcode_word_gas_cost(block.context.hardfork, memory_length.as_u64());
+        self.one_64th_gas
+            .assign(region, offset, (step.gas_left - gas_cost) as u128)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, Bytecode, Word};
+    use ethers_core::utils::get_contract_address;
+    use mock::TestContext;
+
+    fn test_ok(code: Bytecode, existing: Option<(eth_types::Address, Bytecode)>) {
+        let creator = address!("0x0000000000000000000000000000000000000010");
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(creator)
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+                if let Some((address, code)) = existing {
+                    accs[2].address(address).code(code);
+                } else {
+                    accs[2].address(address!("0x0000000000000000000000000000000000cafe02"));
+                }
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn create2_twice(initcode: &[u8]) -> Bytecode {
+        // Creating twice the same contract with the same salt makes the
+        // second CREATE2 collide with the first one.
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(initcode))
+            PUSH1(0x00)
+            MSTORE
+        };
+        for _ in 0..2 {
+            code.append(&bytecode! {
+                PUSH2(0xcafe)
+                PUSH1(initcode.len())
+                PUSH1(32 - initcode.len())
+                PUSH1(0x00)
+                CREATE2
+                POP
+            });
+        }
+        code.append(&bytecode! { STOP });
+        code
+    }
+
+    #[test]
+    fn error_contract_address_collision_create2() {
+        let initcode = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            RETURN
+        };
+        test_ok(create2_twice(&initcode.to_vec()), None);
+    }
+
+    #[test]
+    fn error_contract_address_collision_create2_empty_initcode() {
+        test_ok(create2_twice(&[]), None);
+    }
+
+    #[test]
+    fn error_contract_address_collision_create() {
+        // The address derived from the creator's nonce already has code.
+        let creator = address!("0x0000000000000000000000000000000000000010");
+        let code = bytecode! {
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        };
+        test_ok(
+            code,
+            Some((
+                get_contract_address(creator, Word::zero()),
+                bytecode! { STOP },
+            )),
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_ADDRESS,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, from_bytes,
+            math_gadget::IsZeroGadget, sum, CachedRegion, Cell, MemoryAddress, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidCreationCodeGadget<F> {
+    opcode: Cell<F>,
+    memory_offset: MemoryAddress<F>,
+    length: Word<F>,
+    length_is_zero: IsZeroGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidCreationCodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidCreationCode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidCreationCode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorInvalidCreationCode opcode must be RETURN",
+            opcode.expr(),
+            OpcodeId::RETURN.expr(),
+        );
+
+        // Only a create deploys the returned code.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsCreate, 1.expr());
+
+        let memory_offset = cb.query_rlc();
+        let length = cb.query_word();
+
+        // Pop memory_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Check that the returned code is not empty and starts with 0xef
+        // (EIP-3541).
+        let length_is_zero = IsZeroGadget::construct(cb, sum::expr(&length.cells));
+        cb.require_zero("returned code is not empty", length_is_zero.expr());
+        cb.memory_lookup(
+            false.expr(),
+            from_bytes::expr(&memory_offset.cells),
+            0xef.expr(),
+            None,
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            memory_offset,
+            length,
+            length_is_zero,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, length] =
+            [1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(
+                memory_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+        self.length
+            .assign(region, offset, Some(length.to_le_bytes()))?;
+        self.length_is_zero
+            .assign(region, offset, sum::value(&length.to_le_bytes()))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, Bytecode, Word};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    // The initcode returns the code 0xef, which is rejected by EIP-3541.
+    fn initcode() -> Bytecode {
+        bytecode! {
+            PUSH1(0xef)
+            PUSH1(0x00)
+            MSTORE8
+            PUSH1(0x01)
+            PUSH1(0x00)
+            RETURN
+        }
+    }
+
+    #[test]
+    fn error_invalid_creation_code_root() {
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .input(initcode().to_vec().into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_invalid_creation_code_internal() {
+        let initcode = initcode().to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(initcode.len())
+            PUSH1(32 - initcode.len())
+            PUSH1(0x00)
+            CREATE
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidJumpGadget<F> {
+    opcode: Cell<F>,
+    is_jumpi: IsEqualGadget<F>,
+    destination: Word<F>,
+    condition: Cell<F>,
+    is_condition_zero: IsZeroGadget<F>,
+    code_length: Cell<F>,
+    is_destination_within_u64: IsZeroGadget<F>,
+    is_destination_lt_code_length: LtGadget<F, N_BYTES_U64>,
+    is_code: Cell<F>,
+    value: Cell<F>,
+    is_jumpdest: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidJumpGadget<F> {
+    const NAME: &'static str = "ErrorInvalidJump";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidJump;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_zero(
+            "ErrorInvalidJump opcode must be JUMP or JUMPI",
+            (opcode.expr() - OpcodeId::JUMP.expr()) * (opcode.expr() - OpcodeId::JUMPI.expr()),
+        );
+        let is_jumpi = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::JUMPI.expr());
+
+        // Pop the destination, and the condition for JUMPI
+        let destination = cb.query_word();
+        let condition = cb.query_cell();
+        cb.stack_pop(destination.expr());
+        cb.condition(is_jumpi.expr(), |cb| {
+            cb.stack_pop(condition.expr());
+        });
+
+        // JUMPI only fails when the jump would be taken
+        let is_condition_zero = IsZeroGadget::construct(cb, condition.expr());
+        cb.require_zero(
+            "JUMPI condition is non-zero",
+            is_jumpi.expr() * is_condition_zero.expr(),
+        );
+
+        // The destination is within the code when it fits in 8 bytes and is
+        // less than the code length.
+        let code_length = cb.bytecode_length(cb.curr.state.code_hash.expr());
+        let is_destination_within_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&destination.cells[N_BYTES_U64..]));
+        let is_destination_lt_code_length = LtGadget::construct(
+            cb,
+            from_bytes::expr(&destination.cells[..N_BYTES_U64]),
+            code_length.expr(),
+        );
+        let is_within_range =
+            is_destination_within_u64.expr() * is_destination_lt_code_length.expr();
+
+        // When within range, the byte at the destination must not be a
+        // JUMPDEST used as code.
+        let is_code = cb.query_bool();
+        let value = cb.query_cell();
+        let is_jumpdest = IsEqualGadget::construct(cb, value.expr(), OpcodeId::JUMPDEST.expr());
+        cb.condition(is_within_range.clone(), |cb| {
+            cb.bytecode_lookup(
+                cb.curr.state.code_hash.expr(),
+                from_bytes::expr(&destination.cells[..N_BYTES_U64]),
+                is_code.expr(),
+                value.expr(),
+            );
+        });
+        cb.require_zero(
+            "Destination is not a JUMPDEST in code",
+            is_within_range * is_code.expr() * is_jumpdest.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            is_jumpi,
+            destination,
+            condition,
+            is_condition_zero,
+            code_length,
+            is_destination_within_u64,
+            is_destination_lt_code_length,
+            is_code,
+            value,
+            is_jumpdest,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_jumpi = opcode == OpcodeId::JUMPI;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_jumpi.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::JUMPI.as_u64()),
+        )?;
+
+        let destination = block.rws[step.rw_indices[0]].stack_value();
+        self.destination
+            .assign(region, offset, Some(destination.to_le_bytes()))?;
+
+        let condition = if is_jumpi {
+            let condition = block.rws[step.rw_indices[1]].stack_value();
+            Word::random_linear_combine(condition.to_le_bytes(), block.randomness)
+        } else {
+            F::zero()
+        };
+        self.condition.assign(region, offset, Some(condition))?;
+        self.is_condition_zero.assign(region, offset, condition)?;
+
+        let code = block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode");
+        let code_length = code.bytes.len() as u64;
+        self.code_length
+            .assign(region, offset, Some(F::from(code_length)))?;
+
+        let destination_bytes = destination.to_le_bytes();
+        self.is_destination_within_u64.assign(
+            region,
+            offset,
+            sum::value(&destination_bytes[N_BYTES_U64..]),
+        )?;
+        let destination_lo = from_bytes::value::<F>(&destination_bytes[..N_BYTES_U64]);
+        let (is_lt, _) = self.is_destination_lt_code_length.assign(
+            region,
+            offset,
+            destination_lo,
+            F::from(code_length),
+        )?;
+
+        // Only witness the byte at the destination when it is within the code
+        let (is_code, value) = if destination.bits() <= 64 && is_lt == F::one() {
+            let index = destination.as_usize();
+            let is_code = code
+                .table_assignments(block.randomness)
+                .get(1 + index)
+                .map(|row| row[3])
+                .unwrap();
+            (is_code, F::from(code.bytes[index] as u64))
+        } else {
+            (F::zero(), F::zero())
+        };
+        self.is_code.assign(region, offset, Some(is_code))?;
+        self.value.assign(region, offset, Some(value))?;
+        self.is_jumpdest
+            .assign(region, offset, value, F::from(OpcodeId::JUMPDEST.as_u64()))?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            1 + is_jumpi as usize,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(30_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn invalid_jump_codes() -> Vec<Bytecode> {
+        vec![
+            // Destination is a STOP
+            bytecode! {
+                PUSH1(0x03)
+                JUMP
+                STOP
+            },
+            // Destination is a JUMPDEST byte inside push data
+            bytecode! {
+                PUSH1(0x04)
+                JUMP
+                PUSH1(0x5b)
+                STOP
+            },
+            // Destination is beyond the code length
+            bytecode! {
+                PUSH2(0xffff)
+                JUMP
+            },
+            // Destination does not fit in 64 bits
+            bytecode! {
+                PUSH32(Word::MAX)
+                JUMP
+            },
+            // JUMPI with a non-zero condition
+            bytecode! {
+                PUSH1(0x01)
+                PUSH1(0x05)
+                JUMPI
+                STOP
+                STOP
+            },
+        ]
+    }
+
+    #[test]
+    fn error_invalid_jump_root() {
+        for code in invalid_jump_codes() {
+            test_root_ok(code);
+        }
+    }
+
+    #[test]
+    fn error_invalid_jump_internal() {
+        for code in invalid_jump_codes() {
+            test_internal_ok(code);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{BlockContextFieldTag, FixedTableTag, Lookup},
+        util::{
+            common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, CachedRegion,
+            Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidOpcodeGadget<F> {
+    opcode: Cell<F>,
+    hardfork: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidOpcode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidOpcode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        // Whether an opcode is available depends on the hard fork of the block.
+        let hardfork = cb.query_cell();
+        cb.block_lookup(BlockContextFieldTag::Hardfork.expr(), None, hardfork.expr());
+        cb.add_lookup(
+            "Invalid opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::InvalidOpcode.expr(),
+                values: [opcode.expr(), hardfork.expr(), 0.expr()],
+            },
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            hardfork,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.hardfork
+            .assign(region, offset, Some(F::from(block.context.hardfork as u64)))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 0)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, Hardfork, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn invalid_code(byte: u8) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+        };
+        code.write(byte, true);
+        code
+    }
+
+    fn test_root_ok(code: Bytecode) {
+        test_root_ok_with_hardfork(code, Hardfork::London);
+    }
+
+    fn test_root_ok_with_hardfork(code: Bytecode, hardfork: Hardfork) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(30_000));
+            },
+            |block, _tx| block.number(0xcafeu64).hardfork(hardfork),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_invalid_opcode_root() {
+        // An undefined opcode and the designated invalid instruction
+        test_root_ok(invalid_code(0x0f));
+        test_root_ok(invalid_code(0xfe));
+    }
+
+    #[test]
+    fn error_invalid_opcode_internal() {
+        test_internal_ok(invalid_code(0x0f));
+        test_internal_ok(invalid_code(0xfe));
+    }
+
+    #[test]
+    fn error_invalid_opcode_before_london() {
+        // BASEFEE is only available since London.
+        test_root_ok_with_hardfork(invalid_code(0x48), Hardfork::Berlin);
+    }
+
+    #[test]
+    fn error_invalid_opcode_before_shanghai() {
+        // PUSH0 is only available since Shanghai.
+        test_root_ok(invalid_code(0x5f));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::{BlockContextFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            not, or, sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorMaxCodeSizeExceededGadget<F> {
+    opcode: Cell<F>,
+    memory_offset: Cell<F>,
+    length: Word<F>,
+    max_code_size: Cell<F>,
+    // Either `length` doesn't fit in 8 bytes, or it exceeds the maximum code
+    // size.
+    is_length_within_u64: IsZeroGadget<F>,
+    is_length_over_max_code_size: LtGadget<F, N_BYTES_U64>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorMaxCodeSizeExceededGadget<F> {
+    const NAME: &'static str = "ErrorMaxCodeSizeExceeded";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorMaxCodeSizeExceeded;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorMaxCodeSizeExceeded opcode must be RETURN",
+            opcode.expr(),
+            OpcodeId::RETURN.expr(),
+        );
+
+        // Only a create deploys the returned code.
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsCreate, 1.expr());
+
+        let memory_offset = cb.query_cell();
+        let length = cb.query_word();
+
+        // Pop memory_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Check that `length > max_code_size`, where the maximum code size is
+        // specified by the chain.
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let is_length_within_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&length.cells[N_BYTES_U64..]));
+        let is_length_over_max_code_size = LtGadget::construct(
+            cb,
+            max_code_size.expr(),
+            from_bytes::expr(&length.cells[..N_BYTES_U64]),
+        );
+        cb.require_equal(
+            "length > max_code_size",
+            or::expr([
+                not::expr(is_length_within_u64.expr()),
+                is_length_over_max_code_size.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            memory_offset,
+            length,
+            max_code_size,
+            is_length_within_u64,
+            is_length_over_max_code_size,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, length] =
+            [1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.length
+            .assign(region, offset, Some(length.to_le_bytes()))?;
+
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+
+        let length_bytes = length.to_le_bytes();
+        self.is_length_within_u64.assign(
+            region,
+            offset,
+            sum::value(&length_bytes[N_BYTES_U64..]),
+        )?;
+        self.is_length_over_max_code_size.assign(
+            region,
+            offset,
+            max_code_size,
+            from_bytes::value(&length_bytes[..N_BYTES_U64]),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, evm_types::MAX_CODE_SIZE, Bytecode, Word};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    // The initcode returns an empty code of one byte more than allowed.
+    fn initcode() -> Bytecode {
+        bytecode! {
+            PUSH2(MAX_CODE_SIZE + 1)
+            PUSH1(0x00)
+            RETURN
+        }
+    }
+
+    #[test]
+    fn error_max_code_size_root() {
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .input(initcode().to_vec().into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_max_code_size_internal() {
+        let initcode = initcode().to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(initcode.len())
+            PUSH1(32 - initcode.len())
+            PUSH1(0x00)
+            CREATE
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+}
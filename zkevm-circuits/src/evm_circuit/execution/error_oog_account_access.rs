@@ -0,0 +1,225 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        table::{CallContextFieldTag, FixedTableTag, Lookup},
+        util::{
+            common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, from_bytes,
+            math_gadget::LtGadget, select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGAccountAccessGadget<F> {
+    opcode: Cell<F>,
+    address: Word<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGAccountAccessGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasAccountAccess";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasAccountAccess;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        let address = cb.query_word();
+        cb.stack_pop(address.expr());
+
+        // The access list is only read, since the account is not touched
+        // when running out of gas.
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(
+            tx_id.expr(),
+            from_bytes::expr(&address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+            is_warm.expr(),
+        );
+
+        // BALANCE, EXTCODESIZE and EXTCODEHASH have the same gas cost
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        );
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            address,
+            tx_id,
+            is_warm,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let address = block.rws[step.rw_indices[0]].stack_value();
+        self.address
+            .assign(region, offset, Some(address.to_le_bytes()))?;
+
+        let tx_id = block.rws[step.rw_indices[1]].call_context_value();
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+
+        let (is_warm, _) = block.rws[step.rw_indices[2]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let gas_cost = if is_warm {
+            GasCost::WARM_ACCESS
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS
+        };
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(gas_cost.as_u64()),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn oog_account_access_codes() -> Vec<Bytecode> {
+        let mut codes = Vec::new();
+        for opcode in [
+            OpcodeId::BALANCE,
+            OpcodeId::EXTCODESIZE,
+            OpcodeId::EXTCODEHASH,
+        ] {
+            // A cold account
+            let mut code = bytecode! {
+                PUSH20(address!("0x0000000000000000000000000000000000c01d01").to_word())
+            };
+            code.write_op(opcode);
+            codes.push(code);
+
+            // The warm account of the current call
+            let mut code = bytecode! {
+                ADDRESS
+            };
+            code.write_op(opcode);
+            codes.push(code);
+        }
+        codes
+    }
+
+    #[test]
+    fn error_oog_account_access_root() {
+        for code in oog_account_access_codes() {
+            // Enough for ADDRESS or PUSH20, but not for a warm access
+            test_root_ok(code, 21_000 + 3 + 97);
+        }
+    }
+
+    #[test]
+    fn error_oog_account_access_internal() {
+        for code in oog_account_access_codes() {
+            test_internal_ok(code, 3 + 97);
+        }
+    }
+}
@@ -0,0 +1,459 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{BatchedIsZeroGadget, IsEqualGadget, IsZeroGadget, LtGadget},
+            memory_gadget::{MemoryExpandedAddressGadget, MemoryExpansionGadget},
+            or, select, sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar, U256,
+};
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CALL, CALLCODE, DELEGATECALL and STATICCALL running out of gas,
+/// which happens when the gas left can't afford the gas cost before the
+/// callee's gas is deducted.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCallGadget<F, const S: ExecutionState> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    callee_address: Word<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    cd_address: MemoryExpandedAddressGadget<F>,
+    rd_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    is_warm: Cell<F>,
+    callee_nonce: Cell<F>,
+    callee_balance: Cell<F>,
+    callee_code_hash: Cell<F>,
+    is_account_empty: BatchedIsZeroGadget<F, 2>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F, const S: ExecutionState> ErrorOOGCallGadget<F, S> {
+    const OPCODE: OpcodeId = match S {
+        ExecutionState::ErrorOutOfGasCALL => OpcodeId::CALL,
+        ExecutionState::ErrorOutOfGasCALLCODE => OpcodeId::CALLCODE,
+        ExecutionState::ErrorOutOfGasDELEGATECALL => OpcodeId::DELEGATECALL,
+        ExecutionState::ErrorOutOfGasSTATICCALL => OpcodeId::STATICCALL,
+        _ => unreachable!(),
+    };
+
+    // Only CALL and CALLCODE have the value on stack.
+    const HAS_VALUE_ON_STACK: bool = matches!(
+        S,
+        ExecutionState::ErrorOutOfGasCALL | ExecutionState::ErrorOutOfGasCALLCODE
+    );
+
+    // Only CALL might create a new account.
+    const IS_CALL: bool = matches!(S, ExecutionState::ErrorOutOfGasCALL);
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorOOGCallGadget<F, S> {
+    const NAME: &'static str = match S {
+        ExecutionState::ErrorOutOfGasCALL => "ErrorOutOfGasCALL",
+        ExecutionState::ErrorOutOfGasCALLCODE => "ErrorOutOfGasCALLCODE",
+        ExecutionState::ErrorOutOfGasDELEGATECALL => "ErrorOutOfGasDELEGATECALL",
+        ExecutionState::ErrorOutOfGasSTATICCALL => "ErrorOutOfGasSTATICCALL",
+        _ => unreachable!(),
+    };
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasCALL* opcode must match the execution state",
+            opcode.expr(),
+            Self::OPCODE.expr(),
+        );
+        let has_value_on_stack = Self::HAS_VALUE_ON_STACK.expr();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        // The gas to forward doesn't affect the gas cost, so it's not read.
+        let callee_address = cb.query_word();
+        let value = cb.query_word();
+        let cd_address = MemoryExpandedAddressGadget::construct_self(cb);
+        let rd_address = MemoryExpandedAddressGadget::construct_self(cb);
+        cb.stack_lookup(false.expr(), 1.expr(), callee_address.expr());
+        cb.condition(has_value_on_stack.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        cb.condition(1.expr() - has_value_on_stack.clone(), |cb| {
+            cb.require_zero(
+                "DELEGATECALL and STATICCALL have no value on stack",
+                sum::expr(&value.cells),
+            );
+        });
+        for (idx, word) in [
+            cd_address.offset_rlc(),
+            cd_address.length_rlc(),
+            rd_address.offset_rlc(),
+            rd_address.length_rlc(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            cb.stack_lookup(
+                false.expr(),
+                (2 + idx).expr() + has_value_on_stack.clone(),
+                word,
+            );
+        }
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [cd_address.address(), rd_address.address()],
+        );
+
+        let callee_address_expr =
+            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), callee_address_expr.clone(), is_warm.expr());
+
+        let [callee_nonce, callee_balance, callee_code_hash] = [(); 3].map(|_| cb.query_cell());
+        cb.condition(Self::IS_CALL.expr(), |cb| {
+            for (field_tag, value) in [
+                (AccountFieldTag::Nonce, &callee_nonce),
+                (AccountFieldTag::Balance, &callee_balance),
+                (AccountFieldTag::CodeHash, &callee_code_hash),
+            ] {
+                cb.account_read(callee_address_expr.clone(), field_tag, value.expr());
+            }
+        });
+        let is_account_empty =
+            BatchedIsZeroGadget::construct(cb, [callee_nonce.expr(), callee_balance.expr()]);
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            callee_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+
+        // The same gas cost as the successful CALL*
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let has_value = 1.expr() - value_is_zero.expr();
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + has_value
+            * (GasCost::CALL_WITH_VALUE.expr()
+                + Self::IS_CALL.expr()
+                    * is_account_empty.expr()
+                    * is_empty_code_hash.expr()
+                    * GasCost::NEW_ACCOUNT.expr())
+            + memory_expansion.gas_cost();
+
+        // The callee gets at most all but one 64th of the gas left after the
+        // gas cost, so only the gas cost itself can be unaffordable.
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Memory address is overflow or gas left is less than gas cost",
+            or::expr([
+                cd_address.overflow(),
+                rd_address.overflow(),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            tx_id,
+            callee_address,
+            value,
+            value_is_zero,
+            cd_address,
+            rd_address,
+            memory_expansion,
+            is_warm,
+            callee_nonce,
+            callee_balance,
+            callee_code_hash,
+            is_account_empty,
+            is_empty_code_hash,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+
+        let mut rw_index = 1;
+        let mut next_stack_value = || {
+            let value = block.rws[step.rw_indices[rw_index]].stack_value();
+            rw_index += 1;
+            value
+        };
+        let callee_address = next_stack_value();
+        let value = if Self::HAS_VALUE_ON_STACK {
+            next_stack_value()
+        } else {
+            U256::zero()
+        };
+        let [cd_offset, cd_length, rd_offset, rd_length] = [(); 4].map(|_| next_stack_value());
+
+        self.callee_address
+            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        let cd_address = self
+            .cd_address
+            .assign(region, offset, cd_offset, cd_length)?;
+        let rd_address = self
+            .rd_address
+            .assign(region, offset, rd_offset, rd_length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [cd_address, rd_address],
+        )?;
+
+        let (is_warm, _) = block.rws[step.rw_indices[rw_index]].tx_access_list_value_pair();
+        rw_index += 1;
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let [callee_nonce, callee_balance, callee_code_hash] = if Self::IS_CALL {
+            let values = [0, 1, 2].map(|idx| {
+                block.rws[step.rw_indices[rw_index + idx]]
+                    .account_value_pair()
+                    .0
+            });
+            rw_index += 3;
+            values
+        } else {
+            [U256::zero(); 3]
+        };
+        let callee_code_hash_rlc =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness);
+        self.callee_nonce
+            .assign(region, offset, callee_nonce.to_scalar())?;
+        self.callee_balance.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                callee_balance.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.callee_code_hash
+            .assign(region, offset, Some(callee_code_hash_rlc))?;
+        self.is_account_empty.assign(
+            region,
+            offset,
+            [
+                F::from(callee_nonce.low_u64()),
+                Word::random_linear_combine(callee_balance.to_le_bytes(), block.randomness),
+            ],
+        )?;
+        let is_empty_code_hash = self.is_empty_code_hash.assign(
+            region,
+            offset,
+            callee_code_hash_rlc,
+            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+
+        let has_value = !value.is_zero();
+        let is_account_empty = callee_nonce.is_zero() && callee_balance.is_zero();
+        let gas_cost = if is_warm {
+            GasCost::WARM_ACCESS.as_u64()
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+        } + if has_value {
+            GasCost::CALL_WITH_VALUE.as_u64()
+                + if Self::IS_CALL && is_account_empty && is_empty_code_hash == F::one() {
+                    GasCost::NEW_ACCOUNT.as_u64()
+                } else {
+                    0
+                }
+        } else {
+            0
+        } + memory_expansion_gas_cost;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, rw_index)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1]
+                    .address(callee)
+                    .code(code)
+                    .balance(Word::from(1u64 << 20));
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    /// Returns the codes along with the gas cost of the pushes before CALL*.
+    fn oog_call_codes() -> Vec<(Bytecode, u64)> {
+        let mut codes = Vec::new();
+        for opcode in [
+            OpcodeId::CALL,
+            OpcodeId::CALLCODE,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::STATICCALL,
+        ] {
+            let has_value_on_stack = matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE);
+            for (value, cd_offset, cd_length, rd_offset, rd_length) in [
+                // Only the cold account access is unaffordable
+                (0, Word::zero(), Word::zero(), Word::zero(), Word::zero()),
+                // Transferring value to a new account
+                (1, Word::zero(), Word::zero(), Word::zero(), Word::zero()),
+                (
+                    0,
+                    Word::from(0xffff),
+                    Word::from(0xff),
+                    Word::zero(),
+                    Word::zero(),
+                ),
+                (
+                    0,
+                    Word::zero(),
+                    Word::zero(),
+                    Word::from(0xff),
+                    Word::from(0xffff),
+                ),
+                // The access is beyond 2^32 bytes
+                (
+                    0,
+                    Word::from(1u64 << 40),
+                    Word::one(),
+                    Word::zero(),
+                    Word::zero(),
+                ),
+                (0, Word::zero(), Word::zero(), Word::one(), Word::MAX),
+            ] {
+                if value != 0 && !has_value_on_stack {
+                    continue;
+                }
+                let mut code = bytecode! {
+                    PUSH32(rd_length)
+                    PUSH32(rd_offset)
+                    PUSH32(cd_length)
+                    PUSH32(cd_offset)
+                };
+                if has_value_on_stack {
+                    code.push(1, Word::from(value));
+                }
+                code.append(&bytecode! {
+                    PUSH20(address!("0x0000000000000000000000000000000000c01d01").to_word())
+                    PUSH2(0xffff) // gas
+                });
+                code.write_op(opcode);
+                codes.push((code, 3 * (6 + has_value_on_stack as u64)));
+            }
+        }
+        codes
+    }
+
+    #[test]
+    fn error_oog_call_root() {
+        for (code, push_gas) in oog_call_codes() {
+            // Enough for the pushes, but not for the cold account access
+            test_root_ok(code, 21_000 + push_gas + 2_599);
+        }
+    }
+
+    #[test]
+    fn error_oog_call_internal() {
+        for (code, push_gas) in oog_call_codes() {
+            test_internal_ok(code, push_gas + 2_599);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, LtGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCodeStoreGadget<F> {
+    opcode: Cell<F>,
+    memory_address: MemoryAddressGadget<F>,
+    max_code_size: Cell<F>,
+    is_max_code_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    first_byte: Cell<F>,
+    is_first_byte_invalid: IsEqualGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCodeStoreGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCodeStore";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCodeStore;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasCodeStore opcode must be RETURN",
+            opcode.expr(),
+            OpcodeId::RETURN.expr(),
+        );
+
+        // Only a create deploys the returned code.
+        cb.require_equal(
+            "ErrorOutOfGasCodeStore only happens in a create",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        let memory_offset = cb.query_cell();
+        let memory_length = cb.query_rlc();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(memory_length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, memory_length);
+
+        // The code size and the first byte are checked before the code is
+        // stored, so neither of the other errors could have happened.
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let is_max_code_size_exceeded =
+            LtGadget::construct(cb, max_code_size.expr(), memory_address.length());
+        cb.require_zero(
+            "Returned code does not exceed the maximum code size",
+            is_max_code_size_exceeded.expr(),
+        );
+        let first_byte = cb.query_cell();
+        let is_first_byte_invalid = IsEqualGadget::construct(cb, first_byte.expr(), 0xef.expr());
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.memory_lookup(
+                false.expr(),
+                memory_address.offset(),
+                first_byte.expr(),
+                None,
+            );
+            cb.require_zero(
+                "Returned code does not start with 0xef (EIP-3541)",
+                is_first_byte_invalid.expr(),
+            );
+        });
+
+        // The memory expansion of RETURN and the code deposit are charged
+        // together.
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            memory_expansion.gas_cost()
+                + GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length(),
+        );
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            memory_address,
+            max_code_size,
+            is_max_code_size_exceeded,
+            first_byte,
+            is_first_byte_invalid,
+            memory_expansion,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, memory_length] =
+            [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let address = self.memory_address.assign(
+            region,
+            offset,
+            memory_offset,
+            memory_length,
+            block.randomness,
+        )?;
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.is_max_code_size_exceeded.assign(
+            region,
+            offset,
+            max_code_size,
+            F::from(memory_length.as_u64()),
+        )?;
+
+        let has_length = !memory_length.is_zero();
+        let first_byte = if has_length {
+            block.rws[step.rw_indices[2]].memory_value()
+        } else {
+            0
+        };
+        self.first_byte
+            .assign(region, offset, Some(F::from(first_byte as u64)))?;
+        self.is_first_byte_invalid.assign(
+            region,
+            offset,
+            F::from(first_byte as u64),
+            F::from(0xef),
+        )?;
+
+        let (_, memory_expansion_gas_cost) =
+            self.memory_expansion
+                .assign(region, offset, step.memory_word_size(), [address])?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(
+                memory_expansion_gas_cost
+                    + GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * memory_length.as_u64(),
+            ),
+        )?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            2 + has_length as usize,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(create_gas: u64) {
+        // The initcode returns 0x100 zero bytes as the code to deploy, which
+        // costs 51200 gas to store.
+        let initcode = bytecode! {
+            PUSH2(0x100)
+            PUSH1(0x00)
+            RETURN
+        }
+        .to_vec();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&initcode))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(initcode.len())
+            PUSH1(32 - initcode.len())
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(create_gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_code_store() {
+        // Enough for the CREATE, but the creation gets less than the code
+        // deposit cost.
+        test_ok(100_000);
+    }
+}
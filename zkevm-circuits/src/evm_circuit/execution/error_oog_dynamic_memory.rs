@@ -0,0 +1,326 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{BlockContextFieldTag, FixedTableTag, Lookup},
+        util::{
+            common_gadget::{CommonErrorGadget, HardforkGadget},
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, LtGadget},
+            memory_gadget::{
+                MemoryExpandedAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget,
+            },
+            or, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{
+        gas_utils::initcode_word_gas_cost, GasCost, OpcodeId, MAX_INITCODE_SIZE_MULTIPLIER,
+    },
+    Field, Hardfork,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGDynamicMemoryGadget<F> {
+    opcode: Cell<F>,
+    is_create: IsEqualGadget<F>,
+    memory_address: MemoryExpandedAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    hardfork: HardforkGadget<F>,
+    initcode_word_size: MemoryWordSizeGadget<F>,
+    max_code_size: Cell<F>,
+    initcode_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGDynamicMemoryGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasDynamicMemoryExpansion";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasDynamicMemoryExpansion;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        // CREATE has the value on top of the offset and size
+        let is_create = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CREATE.expr());
+        let memory_address = MemoryExpandedAddressGadget::construct_self(cb);
+        cb.stack_lookup(false.expr(), is_create.expr(), memory_address.offset_rlc());
+        cb.stack_lookup(
+            false.expr(),
+            is_create.expr() + 1.expr(),
+            memory_address.length_rlc(),
+        );
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+
+        // Since Shanghai (EIP 3860), CREATE is charged for every word of the
+        // initcode, and runs out of gas when the initcode is larger than twice
+        // the maximum code size.
+        let hardfork = HardforkGadget::construct(cb);
+        let is_create_since_shanghai = is_create.expr() * hardfork.is_activated(Hardfork::Shanghai);
+        let initcode_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let initcode_size_exceeded = LtGadget::construct(
+            cb,
+            MAX_INITCODE_SIZE_MULTIPLIER.expr() * max_code_size.expr(),
+            memory_address.length(),
+        );
+
+        // Only CREATE has a non-zero constant gas cost
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            is_create.expr() * OpcodeId::CREATE.constant_gas_cost().expr()
+                + is_create_since_shanghai.clone()
+                    * GasCost::INITCODE_WORD_COST.expr()
+                    * initcode_word_size.expr()
+                + memory_expansion.gas_cost(),
+        );
+        cb.require_equal(
+            "Memory address is overflow, initcode size is exceeded or gas left is less than gas cost",
+            or::expr([
+                memory_address.overflow(),
+                is_create_since_shanghai * initcode_size_exceeded.expr(),
+                insufficient_gas.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            is_create,
+            memory_address,
+            memory_expansion,
+            hardfork,
+            initcode_word_size,
+            max_code_size,
+            initcode_size_exceeded,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_create = opcode == OpcodeId::CREATE;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_create.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CREATE.as_u64()),
+        )?;
+
+        let [memory_offset, memory_length] =
+            [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let address = self
+            .memory_address
+            .assign(region, offset, memory_offset, memory_length)?;
+        let (_, memory_expansion_gas_cost) =
+            self.memory_expansion
+                .assign(region, offset, step.memory_word_size(), [address])?;
+
+        let hardfork = block.context.hardfork;
+        let initcode_size = memory_length.low_u32() as u64;
+        self.hardfork.assign(region, offset, hardfork)?;
+        self.initcode_word_size
+            .assign(region, offset, initcode_size)?;
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.initcode_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_INITCODE_SIZE_MULTIPLIER) * max_code_size,
+            F::from(initcode_size),
+        )?;
+
+        let constant_gas_cost = if is_create {
+            OpcodeId::CREATE.constant_gas_cost().as_u64()
+                + initcode_word_gas_cost(hardfork, initcode_size)
+        } else {
+            0
+        };
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(constant_gas_cost + memory_expansion_gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{
+        address, bytecode,
+        bytecode::Bytecode,
+        evm_types::{OpcodeId, MAX_CODE_SIZE, MAX_INITCODE_SIZE_MULTIPLIER},
+        Hardfork, ToWord, Word,
+    };
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        test_root_ok_with_hardfork(code, gas, Hardfork::default());
+    }
+
+    fn test_root_ok_with_hardfork(code: Bytecode, gas: u64, hardfork: Hardfork) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64).hardfork(hardfork),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1]
+                    .address(callee)
+                    .code(code)
+                    .balance(Word::from(1u64 << 20));
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn oog_dynamic_memory_codes() -> Vec<Bytecode> {
+        let mut codes = Vec::new();
+        for (offset, length) in [
+            (Word::from(0xffff), Word::from(0xff)),
+            (Word::from(0xff), Word::from(0xffff)),
+            // The offset is ignored when the length is zero, so only the
+            // constant gas cost of CREATE is insufficient
+            (Word::MAX, Word::zero()),
+            // The access is beyond 2^32 bytes
+            (Word::from(1u64 << 40), Word::one()),
+            (Word::one(), Word::MAX),
+        ] {
+            // RETURN and REVERT have no constant gas cost to run out of
+            if !length.is_zero() {
+                for opcode in [OpcodeId::RETURN, OpcodeId::REVERT] {
+                    let mut code = bytecode! {
+                        PUSH32(length)
+                        PUSH32(offset)
+                    };
+                    code.write_op(opcode);
+                    codes.push(code);
+                }
+            }
+            codes.push(bytecode! {
+                PUSH32(length)
+                PUSH32(offset)
+                PUSH1(0x00) // value
+                CREATE
+                STOP
+            });
+        }
+        codes
+    }
+
+    #[test]
+    fn error_oog_dynamic_memory_root() {
+        for code in oog_dynamic_memory_codes() {
+            test_root_ok(code, 21_000 + 3_000);
+        }
+    }
+
+    #[test]
+    fn error_oog_dynamic_memory_internal() {
+        for code in oog_dynamic_memory_codes() {
+            test_internal_ok(code, 3_000);
+        }
+    }
+
+    #[test]
+    fn error_oog_dynamic_memory_create_initcode_size_exceeded() {
+        // Since Shanghai, CREATE fails with an initcode larger than the limit
+        // even if there is enough gas.
+        let code = bytecode! {
+            PUSH32(Word::from(MAX_INITCODE_SIZE_MULTIPLIER * MAX_CODE_SIZE + 1)) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        };
+        test_root_ok_with_hardfork(code, 21_000 + 100_000, Hardfork::Shanghai);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_GAS,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{ByteSizeGadget, LtGadget},
+            CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGExpGadget<F> {
+    opcode: Cell<F>,
+    base: Word<F>,
+    exponent: Word<F>,
+    exponent_byte_size: ByteSizeGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGExpGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasEXP";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasEXP;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasEXP opcode must be EXP",
+            opcode.expr(),
+            OpcodeId::EXP.expr(),
+        );
+
+        let base = cb.query_word();
+        let exponent = cb.query_word();
+
+        // Pop the base and the exponent from the stack
+        // We still have to do this to verify the correctness of `exponent`
+        cb.stack_pop(base.expr());
+        cb.stack_pop(exponent.expr());
+
+        // Check if the amount of gas available is less than the amount of gas
+        // required
+        let exponent_byte_size = ByteSizeGadget::construct(cb, &exponent);
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::EXP.constant_gas_cost().expr()
+                + GasCost::EXP_BYTE_COST.expr() * exponent_byte_size.byte_size(),
+        );
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            base,
+            exponent,
+            exponent_byte_size,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        // Inputs
+        let [base, exponent] = [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.base.assign(region, offset, Some(base.to_le_bytes()))?;
+        self.exponent
+            .assign(region, offset, Some(exponent.to_le_bytes()))?;
+        self.exponent_byte_size.assign(region, offset, exponent)?;
+
+        // Gas insufficient check
+        let exponent_byte_size = (exponent.bits() as u64 + 7) / 8;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(
+                OpcodeId::EXP.constant_gas_cost().as_u64()
+                    + GasCost::EXP_BYTE_COST.as_u64() * exponent_byte_size,
+            ),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_oog_exp(exponent: Word, gas: u64) {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(Word::from(2))
+            EXP
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(gas.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_exp() {
+        // 21000 (intrinsic) + 3 * 2 (PUSH32) leaves less than the static gas
+        test_oog_exp(Word::one(), 21_006 + 5);
+        // enough for the static gas but not for the dynamic gas
+        test_oog_exp(0xff.into(), 21_006 + 50);
+        test_oog_exp(Word::MAX, 21_006 + 1_000);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::LtGadget,
+            memory_gadget::{
+                MemoryCopierGasGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+            },
+            or, select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGExtCodeCopyGadget<F> {
+    opcode: Cell<F>,
+    external_address: Word<F>,
+    memory_address: MemoryExpandedAddressGadget<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGExtCodeCopyGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasEXTCODECOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasEXTCODECOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasEXTCODECOPY opcode must be EXTCODECOPY",
+            opcode.expr(),
+            OpcodeId::EXTCODECOPY.expr(),
+        );
+
+        // The code offset doesn't affect the gas cost, so only the address,
+        // the memory offset and the size are read.
+        let external_address = cb.query_word();
+        let memory_address = MemoryExpandedAddressGadget::construct_self(cb);
+        cb.stack_lookup(false.expr(), 0.expr(), external_address.expr());
+        cb.stack_lookup(false.expr(), 1.expr(), memory_address.offset_rlc());
+        cb.stack_lookup(false.expr(), 3.expr(), memory_address.length_rlc());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(
+            tx_id.expr(),
+            from_bytes::expr(&external_address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+            is_warm.expr(),
+        );
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + memory_copier_gas.gas_cost();
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Memory address is overflow or gas left is less than gas cost",
+            or::expr([memory_address.overflow(), insufficient_gas.expr()]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            external_address,
+            memory_address,
+            tx_id,
+            is_warm,
+            memory_expansion,
+            memory_copier_gas,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [external_address, memory_offset, memory_length] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.external_address
+            .assign(region, offset, Some(external_address.to_le_bytes()))?;
+        let address = self
+            .memory_address
+            .assign(region, offset, memory_offset, memory_length)?;
+
+        let tx_id = block.rws[step.rw_indices[3]].call_context_value();
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+        let (is_warm, _) = block.rws[step.rw_indices[4]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let (_, memory_expansion_gas_cost) =
+            self.memory_expansion
+                .assign(region, offset, step.memory_word_size(), [address])?;
+        let memory_copier_gas_cost = self.memory_copier_gas.assign(
+            region,
+            offset,
+            memory_length.low_u32() as u64,
+            memory_expansion_gas_cost,
+        )?;
+
+        let access_gas_cost = if is_warm {
+            GasCost::WARM_ACCESS
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS
+        };
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(access_gas_cost.as_u64() + memory_copier_gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 5)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, Address, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn oog_ext_code_copy_codes() -> Vec<Bytecode> {
+        let mut codes = Vec::new();
+        for external_address in [
+            // A cold account
+            address!("0x0000000000000000000000000000000000c01d01"),
+            // The warm account of the current call is read with ADDRESS below
+            Address::zero(),
+        ] {
+            for (memory_offset, size) in [
+                // Only the account access is unaffordable
+                (Word::zero(), Word::zero()),
+                (Word::from(0xffff), Word::from(0xff)),
+                // The access is beyond 2^32 bytes
+                (Word::from(1u64 << 40), Word::one()),
+                (Word::one(), Word::MAX),
+            ] {
+                let mut code = bytecode! {
+                    PUSH32(size)
+                    PUSH1(0x00) // code offset
+                    PUSH32(memory_offset)
+                };
+                if external_address.is_zero() {
+                    code.append(&bytecode! { ADDRESS });
+                } else {
+                    code.append(&bytecode! { PUSH20(external_address.to_word()) });
+                }
+                code.append(&bytecode! { EXTCODECOPY });
+                codes.push(code);
+            }
+        }
+        codes
+    }
+
+    #[test]
+    fn error_oog_ext_code_copy_root() {
+        for code in oog_ext_code_copy_codes() {
+            // Enough for the pushes, but not for a warm access
+            test_root_ok(code, 21_000 + 3 * 4 + 97);
+        }
+    }
+
+    #[test]
+    fn error_oog_ext_code_copy_internal() {
+        for code in oog_ext_code_copy_codes() {
+            test_internal_ok(code, 3 * 4 + 97);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{BatchedIsZeroGadget, IsEqualGadget, IsZeroGadget, LtGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSelfDestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    is_warm: Cell<F>,
+    beneficiary_nonce: Cell<F>,
+    beneficiary_balance: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_beneficiary_empty: BatchedIsZeroGadget<F, 2>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSelfDestructGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSELFDESTRUCT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSELFDESTRUCT opcode must be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary = cb.query_word();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let [tx_id, callee_address] = [
+            CallContextFieldTag::TxId,
+            CallContextFieldTag::CalleeAddress,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), beneficiary_address.clone(), is_warm.expr());
+
+        // Creating the beneficiary costs NEW_ACCOUNT when there is value to
+        // transfer, the same as the successful SELFDESTRUCT.
+        let [beneficiary_nonce, beneficiary_balance, beneficiary_code_hash] = [
+            AccountFieldTag::Nonce,
+            AccountFieldTag::Balance,
+            AccountFieldTag::CodeHash,
+        ]
+        .map(|field_tag| {
+            let value = cb.query_cell();
+            cb.account_read(beneficiary_address.clone(), field_tag, value.expr());
+            value
+        });
+        let value = cb.query_word();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            value.expr(),
+        );
+
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let is_beneficiary_empty = BatchedIsZeroGadget::construct(
+            cb,
+            [beneficiary_nonce.expr(), beneficiary_balance.expr()],
+        );
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            beneficiary_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + (1.expr() - is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + (1.expr() - value_is_zero.expr())
+                * is_beneficiary_empty.expr()
+                * is_empty_code_hash.expr()
+                * GasCost::NEW_ACCOUNT.expr();
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            beneficiary,
+            tx_id,
+            callee_address,
+            is_warm,
+            beneficiary_nonce,
+            beneficiary_balance,
+            beneficiary_code_hash,
+            value,
+            value_is_zero,
+            is_beneficiary_empty,
+            is_empty_code_hash,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.callee_address
+            .assign(region, offset, call.callee_address.to_scalar())?;
+
+        let (is_warm, _) = block.rws[step.rw_indices[3]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let [beneficiary_nonce, beneficiary_balance, beneficiary_code_hash, value] =
+            [4, 5, 6, 7].map(|idx| block.rws[step.rw_indices[idx]].account_value_pair().0);
+        let beneficiary_balance_rlc =
+            Word::random_linear_combine(beneficiary_balance.to_le_bytes(), block.randomness);
+        let beneficiary_code_hash_rlc =
+            Word::random_linear_combine(beneficiary_code_hash.to_le_bytes(), block.randomness);
+        self.beneficiary_nonce
+            .assign(region, offset, beneficiary_nonce.to_scalar())?;
+        self.beneficiary_balance
+            .assign(region, offset, Some(beneficiary_balance_rlc))?;
+        self.beneficiary_code_hash
+            .assign(region, offset, Some(beneficiary_code_hash_rlc))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        let is_beneficiary_empty = self.is_beneficiary_empty.assign(
+            region,
+            offset,
+            [
+                F::from(beneficiary_nonce.low_u64()),
+                beneficiary_balance_rlc,
+            ],
+        )?;
+        let is_empty_code_hash = self.is_empty_code_hash.assign(
+            region,
+            offset,
+            beneficiary_code_hash_rlc,
+            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+
+        let gas_cost = GasCost::SELFDESTRUCT.as_u64()
+            + if is_warm {
+                0
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            }
+            + if !value.is_zero()
+                && is_beneficiary_empty == F::one()
+                && is_empty_code_hash == F::one()
+            {
+                GasCost::NEW_ACCOUNT.as_u64()
+            } else {
+                0
+            };
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 8)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::TestContext;
+
+    fn test_ok(code: Bytecode, balance: Word, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(balance)
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_self_destruct_cold() {
+        // Enough for SELFDESTRUCT to a warm account, but not a cold one
+        let code = bytecode! {
+            PUSH20(address!("0x0000000000000000000000000000000000c01d01").to_word())
+            SELFDESTRUCT
+        };
+        test_ok(code, Word::zero(), 21_000 + 3 + 5_000);
+    }
+
+    #[test]
+    fn error_oog_self_destruct_warm() {
+        let code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+        test_ok(code, Word::zero(), 21_000 + 2 + 4_999);
+    }
+
+    #[test]
+    fn error_oog_self_destruct_new_account() {
+        // Enough for SELFDESTRUCT to a cold account, but not for creating it
+        // with the transferred balance
+        let code = bytecode! {
+            PUSH20(address!("0x0000000000000000000000000000000000c01d01").to_word())
+            SELFDESTRUCT
+        };
+        test_ok(code, Word::from(1u64 << 20), 21_000 + 3 + 5_000 + 2_600);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            sstore::{calc_expected_gas_cost, SstoreGasGadget},
+            ExecutionGadget,
+        },
+        param::N_BYTES_GAS,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget, or, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSstoreGadget<F> {
+    opcode: Cell<F>,
+    key: Cell<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    gas_cost: SstoreGasGadget<F>,
+    insufficient_gas_sentry: LtGadget<F, N_BYTES_GAS>,
+    insufficient_gas_cost: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSstoreGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSSTORE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSSTORE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSSTORE opcode must be SSTORE",
+            opcode.expr(),
+            OpcodeId::SSTORE.expr(),
+        );
+
+        let key = cb.query_cell();
+        let value = cb.query_cell();
+        cb.stack_pop(key.expr());
+        cb.stack_pop(value.expr());
+
+        // The storage and its access list are only read, since nothing is
+        // written when running out of gas.
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let value_prev = cb.query_cell();
+        let original_value = cb.query_cell();
+        cb.account_storage_read(
+            callee_address.expr(),
+            key.expr(),
+            value_prev.expr(),
+            tx_id.expr(),
+            original_value.expr(),
+        );
+        let is_warm = cb.query_bool();
+        cb.account_storage_access_list_read(
+            tx_id.expr(),
+            callee_address.expr(),
+            key.expr(),
+            is_warm.expr(),
+        );
+
+        let gas_cost = SstoreGasGadget::construct(cb, value, value_prev, original_value, is_warm);
+
+        // SSTORE fails when the gas left doesn't exceed the sentry, even if
+        // it could afford the gas cost.
+        let insufficient_gas_sentry = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            (GasCost::SSTORE_SENTRY.as_u64() + 1).expr(),
+        );
+        let insufficient_gas_cost =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost.expr());
+        cb.require_equal(
+            "Gas left is less than gas sentry or gas cost",
+            or::expr([insufficient_gas_sentry.expr(), insufficient_gas_cost.expr()]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            key,
+            tx_id,
+            callee_address,
+            gas_cost,
+            insufficient_gas_sentry,
+            insufficient_gas_cost,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [key, value] = [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.key.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                key.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.callee_address
+            .assign(region, offset, call.callee_address.to_scalar())?;
+
+        let (value_prev, _, _, original_value) = block.rws[step.rw_indices[4]].storage_value_aux();
+        let (is_warm, _) = block.rws[step.rw_indices[5]].tx_access_list_value_pair();
+        let gas_cost = calc_expected_gas_cost(value, value_prev, original_value, is_warm);
+        self.gas_cost.assign(
+            region,
+            offset,
+            gas_cost,
+            value,
+            value_prev,
+            original_value,
+            is_warm,
+            block.randomness,
+        )?;
+
+        self.insufficient_gas_sentry.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(GasCost::SSTORE_SENTRY.as_u64() + 1),
+        )?;
+        self.insufficient_gas_cost.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 6)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_sstore_sentry() {
+        // A cold no-op write of 2200 could be afforded, but not above the
+        // sentry of 2300
+        let code = bytecode! {
+            PUSH1(0x00) // value
+            PUSH1(0x00) // key
+            SSTORE
+        };
+        test_root_ok(code.clone(), 21_000 + 6 + 2_300);
+        test_internal_ok(code, 6 + 2_300);
+    }
+
+    #[test]
+    fn error_oog_sstore_cost() {
+        // Setting a new slot costs 22100 when cold
+        let code = bytecode! {
+            PUSH1(0x01) // value
+            PUSH1(0x00) // key
+            SSTORE
+        };
+        test_root_ok(code.clone(), 21_000 + 6 + 22_099);
+        test_internal_ok(code, 6 + 22_099);
+    }
+}
@@ -3,11 +3,14 @@ use crate::{
         execution::ExecutionGadget,
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
+        table::{FixedTableTag, Lookup},
         util::{
+            common_gadget::CommonErrorGadget,
             constraint_builder::ConstraintBuilder,
-            math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
-            memory_gadget::{address_high, address_low, MemoryExpansionGadget},
-            CachedRegion, Cell, Word,
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            memory_gadget::MemoryExpansionGadget,
+            or, sum, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -15,24 +18,19 @@ use crate::{
 };
 use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
 use halo2_proofs::plonk::Error;
+use std::convert::TryInto;
 
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGStaticMemoryGadget<F> {
     opcode: Cell<F>,
     address: Word<F>,
+    // Accessing beyond 2^32 bytes costs more than 2^45 gas, so the address is
+    // only used for memory expansion when it's within 4 bytes.
     address_in_range: IsZeroGadget<F>,
-    // Allow memory size to expand to 5 bytes, because memory address could be
-    // at most 2^40 - 1, after constant division by 32, the memory word size
-    // then could be at most 2^35 - 1.
-    // So generic N_BYTES_MEMORY_WORD_SIZE for MemoryExpansionGadget needs to
-    // be larger by 1 than normal usage (to be 5), to be able to contain
-    // number up to 2^35 - 1.
-    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
-    // Even memory size at most could be 2^35 - 1, the qudratic part of memory
-    // expansion gas cost could be at most 2^61 - 2^27, due to the constant
-    // division by 512, which still fits in 8 bytes.
-    insufficient_gas: RangeCheckGadget<F, N_BYTES_GAS>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     is_mstore8: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
@@ -40,42 +38,54 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasStaticMemoryExpansion;
 
-    // Support other OOG due to pure memory including CREATE, RETURN and REVERT
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
 
-        // Query address by a full word
+        // Pop the address from the stack
         let address = cb.query_word();
+        cb.stack_pop(address.expr());
 
         // Check if this is an MSTORE8
         let is_mstore8 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MSTORE8.expr());
         let is_not_mstore8 = 1.expr() - is_mstore8.expr();
 
         // Get the next memory size and the gas cost for this memory access
+        let address_in_range =
+            IsZeroGadget::construct(cb, sum::expr(&address.cells[N_BYTES_MEMORY_WORD_SIZE..]));
         let memory_expansion = MemoryExpansionGadget::construct(
             cb,
             cb.curr.state.memory_word_size.expr(),
-            [address_low::expr(&address) + 1.expr() + (is_not_mstore8 * 31.expr())],
+            [from_bytes::expr(&address.cells[..N_BYTES_MEMORY_WORD_SIZE])
+                + 1.expr()
+                + (is_not_mstore8 * 31.expr())],
         );
 
-        // Check if the memory address is too large
-        let address_in_range = IsZeroGadget::construct(cb, address_high::expr(&address));
         // Check if the amount of gas available is less than the amount of gas
-        // required
-        let insufficient_gas = cb.condition(address_in_range.expr(), |cb| {
-            RangeCheckGadget::construct(
-                cb,
-                OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost()
-                    - cb.curr.state.gas_left.expr(),
-            )
-        });
-
-        // Pop the address from the stack
-        // We still have to do this to verify the correctness of `address`
-        cb.stack_pop(address.expr());
+        // required, where MLOAD, MSTORE and MSTORE8 have the same constant gas
+        // cost.
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost(),
+        );
+        cb.require_equal(
+            "Memory address is out of range or gas left is less than gas cost",
+            or::expr([1.expr() - address_in_range.expr(), insufficient_gas.expr()]),
+            1.expr(),
+        );
 
-        // TODO: Use ContextSwitchGadget to switch call context to caller's and
-        // consume all gas_left.
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
 
         Self {
             opcode,
@@ -84,6 +94,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
             memory_expansion,
             insufficient_gas,
             is_mstore8,
+            common_error_gadget,
         }
     }
 
@@ -93,15 +104,17 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
 
-        // Inputs/Outputs
+        // Inputs
         let address = block.rws[step.rw_indices[0]].stack_value();
-        self.address
-            .assign(region, offset, Some(address.to_le_bytes()))?;
+        let address_bytes = address.to_le_bytes();
+        self.address.assign(region, offset, Some(address_bytes))?;
 
         // Check if this is an MSTORE8
         let is_mstore8 = self.is_mstore8.assign(
@@ -115,23 +128,139 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
         self.address_in_range.assign(
             region,
             offset,
-            address_high::value::<F>(address.to_le_bytes()),
+            sum::value(&address_bytes[N_BYTES_MEMORY_WORD_SIZE..]),
         )?;
 
         // Memory expansion
-        self.memory_expansion.assign(
+        let address_low = u32::from_le_bytes(
+            address_bytes[..N_BYTES_MEMORY_WORD_SIZE]
+                .try_into()
+                .unwrap(),
+        ) as u64;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
             region,
             offset,
             step.memory_word_size(),
-            [address_low::value(address.to_le_bytes())
-                + if is_mstore8 == F::one() { 1 } else { 32 }],
+            [address_low + if is_mstore8 == F::one() { 1 } else { 32 }],
         )?;
 
         // Gas insufficient check
-        // Get `gas_available` variable here once it's available
-        self.insufficient_gas
-            .assign(region, offset, F::from(step.gas_cost - step.gas_left))?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(OpcodeId::MLOAD.constant_gas_cost().as_u64() + memory_expansion_gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 1)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode, gas: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode, gas: u64) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH32(Word::from(gas))
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn oog_static_memory_codes() -> Vec<Bytecode> {
+        vec![
+            bytecode! {
+                PUSH32(Word::from(0xffff))
+                MLOAD
+                STOP
+            },
+            bytecode! {
+                PUSH32(Word::from(0xff))
+                PUSH32(Word::from(0xffff))
+                MSTORE
+                STOP
+            },
+            bytecode! {
+                PUSH32(Word::from(0xff))
+                PUSH32(Word::from(0xffff))
+                MSTORE8
+                STOP
+            },
+            // The address is beyond 2^32 bytes
+            bytecode! {
+                PUSH32(Word::from(1u64 << 40))
+                MLOAD
+                STOP
+            },
+            bytecode! {
+                PUSH32(Word::MAX)
+                MLOAD
+                STOP
+            },
+        ]
+    }
+
+    #[test]
+    fn error_oog_static_memory_root() {
+        for code in oog_static_memory_codes() {
+            test_root_ok(code, 21_000 + 3_000);
+        }
+    }
+
+    #[test]
+    fn error_oog_static_memory_internal() {
+        for code in oog_static_memory_codes() {
+            test_internal_ok(code, 3_000);
+        }
+    }
+}
@@ -0,0 +1,659 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::{BatchedIsZeroGadget, IsEqualGadget, IsZeroGadget, LtWordGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            select, sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{
+    evm_types::{GasCost, GAS_STIPEND_CALL_WITH_VALUE},
+    Field, ToLittleEndian, ToScalar, U256,
+};
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE and CREATE2
+/// failing before the callee is entered, which is by exceeding the maximum
+/// call depth when `IS_DEPTH` and by transferring more than the caller's
+/// balance otherwise. The failure happens within the caller's context, which
+/// gets 0 pushed and all the gas given to the callee back.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorPrecheckFailedGadget<F, const IS_DEPTH: bool> {
+    opcode: Cell<F>,
+    is_call: Cell<F>,
+    is_callcode: Cell<F>,
+    is_delegatecall: Cell<F>,
+    is_staticcall: Cell<F>,
+    is_create: Cell<F>,
+    is_create2: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    current_address: Cell<F>,
+    is_static: Cell<F>,
+    depth: Cell<F>,
+    gas: Cell<F>,
+    callee_address: Word<F>,
+    value: Word<F>,
+    salt: Cell<F>,
+    value_is_zero: IsZeroGadget<F>,
+    cd_address: MemoryAddressGadget<F>,
+    rd_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    caller_balance: Word<F>,
+    is_insufficient_balance: LtWordGadget<F>,
+    is_warm_prev: Cell<F>,
+    callee_nonce: Cell<F>,
+    callee_balance: Cell<F>,
+    callee_code_hash: Cell<F>,
+    is_account_empty: BatchedIsZeroGadget<F, 2>,
+    is_empty_code_hash: IsEqualGadget<F>,
+}
+
+impl<F: Field, const IS_DEPTH: bool> ExecutionGadget<F> for ErrorPrecheckFailedGadget<F, IS_DEPTH> {
+    const NAME: &'static str = if IS_DEPTH {
+        "ErrorDepth"
+    } else {
+        "ErrorInsufficientBalance"
+    };
+
+    const EXECUTION_STATE: ExecutionState = if IS_DEPTH {
+        ExecutionState::ErrorDepth
+    } else {
+        ExecutionState::ErrorInsufficientBalance
+    };
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        let [is_call, is_callcode, is_delegatecall, is_staticcall, is_create, is_create2] =
+            [(); 6].map(|_| cb.query_bool());
+        cb.require_equal(
+            "Exactly one of CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE or CREATE2 is enabled",
+            sum::expr([
+                &is_call,
+                &is_callcode,
+                &is_delegatecall,
+                &is_staticcall,
+                &is_create,
+                &is_create2,
+            ]),
+            1.expr(),
+        );
+        cb.require_equal(
+            "Opcode should be CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE or CREATE2",
+            opcode.expr(),
+            is_call.expr() * OpcodeId::CALL.expr()
+                + is_callcode.expr() * OpcodeId::CALLCODE.expr()
+                + is_delegatecall.expr() * OpcodeId::DELEGATECALL.expr()
+                + is_staticcall.expr() * OpcodeId::STATICCALL.expr()
+                + is_create.expr() * OpcodeId::CREATE.expr()
+                + is_create2.expr() * OpcodeId::CREATE2.expr(),
+        );
+        let is_create_family = is_create.expr() + is_create2.expr();
+        let is_call_family = 1.expr() - is_create_family.clone();
+        // Only CALL and CALLCODE have the value on stack among the calls.
+        let has_value_on_stack = is_call.expr() + is_callcode.expr();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info(None);
+        let [current_address, is_static, depth] = [
+            CallContextFieldTag::CalleeAddress,
+            CallContextFieldTag::IsStatic,
+            CallContextFieldTag::Depth,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+
+        // The depth is checked before the balance, so both failures are
+        // exclusive.
+        if IS_DEPTH {
+            cb.require_equal("depth == 1025", depth.expr(), 1025.expr());
+        } else {
+            cb.range_lookup(depth.expr() - 1.expr(), 1024);
+        }
+
+        let gas = cb.query_cell();
+        let callee_address = cb.query_word();
+        let value = cb.query_word();
+        let salt = cb.query_cell();
+        let cd_offset = cb.query_cell();
+        let cd_length = cb.query_rlc();
+        let rd_offset = cb.query_cell();
+        let rd_length = cb.query_rlc();
+
+        // Lookup values from stack, where the memory of CREATE and CREATE2
+        // is the initcode, and then push 0 as the result.
+        cb.condition(is_call_family.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 0.expr(), gas.expr());
+            cb.stack_lookup(false.expr(), 1.expr(), callee_address.expr());
+        });
+        cb.condition(has_value_on_stack.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        cb.condition(is_call_family.clone(), |cb| {
+            for (idx, word) in [
+                cd_offset.expr(),
+                cd_length.expr(),
+                rd_offset.expr(),
+                rd_length.expr(),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                cb.stack_lookup(
+                    false.expr(),
+                    (2 + idx).expr() + has_value_on_stack.clone(),
+                    word,
+                );
+            }
+            cb.stack_lookup(true.expr(), 5.expr() + has_value_on_stack.clone(), 0.expr());
+        });
+        cb.condition(is_create_family.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 0.expr(), value.expr());
+            cb.stack_lookup(false.expr(), 1.expr(), cd_offset.expr());
+            cb.stack_lookup(false.expr(), 2.expr(), cd_length.expr());
+        });
+        cb.condition(is_create2.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 3.expr(), salt.expr());
+        });
+        cb.condition(is_create_family.clone(), |cb| {
+            cb.stack_lookup(true.expr(), 2.expr() + is_create2.expr(), 0.expr());
+        });
+        cb.condition(is_delegatecall.expr() + is_staticcall.expr(), |cb| {
+            cb.require_zero(
+                "DELEGATECALL and STATICCALL have no value on stack",
+                sum::expr(&value.cells),
+            );
+        });
+
+        let cd_address = MemoryAddressGadget::construct(cb, cd_offset, cd_length);
+        let rd_address = MemoryAddressGadget::construct(cb, rd_offset, rd_length);
+        cb.condition(is_create_family.clone(), |cb| {
+            cb.require_zero(
+                "CREATE and CREATE2 have no return data memory",
+                rd_address.has_length(),
+            );
+        });
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [cd_address.address(), rd_address.address()],
+        );
+        let memory_copier_gas =
+            MemoryCopierGasGadget::construct(cb, cd_address.length(), memory_expansion.gas_cost());
+
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let has_value = 1.expr() - value_is_zero.expr();
+
+        // The write protection is checked before the call depth and balance.
+        cb.require_zero(
+            "CALL with value and CREATE must not be in static call stack",
+            is_static.expr() * (is_call.expr() * has_value.clone() + is_create_family.clone()),
+        );
+
+        // The caller's balance is read in both cases, for the layout of both
+        // failures to be the same.
+        let caller_balance = cb.query_word();
+        cb.account_read(
+            current_address.expr(),
+            AccountFieldTag::Balance,
+            caller_balance.expr(),
+        );
+        let is_insufficient_balance = LtWordGadget::construct(cb, &caller_balance, &value);
+        if !IS_DEPTH {
+            cb.require_equal(
+                "caller_balance < value",
+                is_insufficient_balance.expr(),
+                1.expr(),
+            );
+        }
+
+        // The callee is added into access list when the gas cost of the call
+        // is charged, which is before the failure. For CALLCODE and
+        // DELEGATECALL it's the account whose code would be executed.
+        let callee_address_expr =
+            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let is_warm_prev = cb.query_bool();
+        cb.condition(is_call_family.clone(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                callee_address_expr.clone(),
+                1.expr(),
+                is_warm_prev.expr(),
+                Some(&mut reversion_info),
+            );
+        });
+
+        // Only CALL might create a new account, which is charged when the
+        // callee is empty.
+        let [callee_nonce, callee_balance, callee_code_hash] = [(); 3].map(|_| cb.query_cell());
+        cb.condition(is_call.expr(), |cb| {
+            for (field_tag, value) in [
+                (AccountFieldTag::Nonce, &callee_nonce),
+                (AccountFieldTag::Balance, &callee_balance),
+                (AccountFieldTag::CodeHash, &callee_code_hash),
+            ] {
+                cb.account_read(callee_address_expr.clone(), field_tag, value.expr());
+            }
+        });
+        let is_account_empty =
+            BatchedIsZeroGadget::construct(cb, [callee_nonce.expr(), callee_balance.expr()]);
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            callee_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+
+        // Save caller's call state, which has no return data.
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        // The gas cost is the same as the one of a successful call or create,
+        // while the gas given to the callee is returned, along with the
+        // stipend of a call with value.
+        let call_gas_cost = select::expr(
+            is_warm_prev.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + has_value.clone()
+            * (GasCost::CALL_WITH_VALUE.expr()
+                + is_call.expr()
+                    * is_account_empty.expr()
+                    * is_empty_code_hash.expr()
+                    * GasCost::NEW_ACCOUNT.expr())
+            + memory_expansion.gas_cost();
+        let create_gas_cost = GasCost::CREATE.expr()
+            + select::expr(
+                is_create2.expr(),
+                memory_copier_gas.gas_cost(),
+                memory_expansion.gas_cost(),
+            );
+        let gas_cost = select::expr(is_create_family.clone(), create_gas_cost, call_gas_cost);
+        let stipend = has_value_on_stack.clone() * has_value * GAS_STIPEND_CALL_WITH_VALUE.expr();
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(
+                5.expr() * is_call_family.clone()
+                    + has_value_on_stack
+                    + 2.expr() * is_create_family
+                    + is_create2.expr(),
+            ),
+            gas_left: Delta(stipend - gas_cost),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            reversible_write_counter: Delta(is_call_family),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            opcode,
+            is_call,
+            is_callcode,
+            is_delegatecall,
+            is_staticcall,
+            is_create,
+            is_create2,
+            tx_id,
+            reversion_info,
+            current_address,
+            is_static,
+            depth,
+            gas,
+            callee_address,
+            value,
+            salt,
+            value_is_zero,
+            cd_address,
+            rd_address,
+            memory_expansion,
+            memory_copier_gas,
+            caller_balance,
+            is_insufficient_balance,
+            is_warm_prev,
+            callee_nonce,
+            callee_balance,
+            callee_code_hash,
+            is_account_empty,
+            is_empty_code_hash,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_create_family = matches!(opcode, OpcodeId::CREATE | OpcodeId::CREATE2);
+        let has_value_on_stack = matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE);
+
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        for (cell, value) in [
+            (&self.is_call, opcode == OpcodeId::CALL),
+            (&self.is_callcode, opcode == OpcodeId::CALLCODE),
+            (&self.is_delegatecall, opcode == OpcodeId::DELEGATECALL),
+            (&self.is_staticcall, opcode == OpcodeId::STATICCALL),
+            (&self.is_create, opcode == OpcodeId::CREATE),
+            (&self.is_create2, opcode == OpcodeId::CREATE2),
+        ] {
+            cell.assign(region, offset, Some(F::from(value as u64)))?;
+        }
+
+        let [tx_id, current_address, is_static, depth] =
+            [0, 3, 4, 5].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.current_address
+            .assign(region, offset, current_address.to_scalar())?;
+        self.is_static
+            .assign(region, offset, Some(F::from(is_static.low_u64())))?;
+        self.depth
+            .assign(region, offset, Some(F::from(depth.low_u64())))?;
+
+        // The stack values are read after the 6 call context values, and
+        // followed by the push of the result.
+        let n_pop = match opcode {
+            OpcodeId::CALL | OpcodeId::CALLCODE => 7,
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => 6,
+            OpcodeId::CREATE => 3,
+            OpcodeId::CREATE2 => 4,
+            _ => unreachable!(),
+        };
+        let stack_values = (6..6 + n_pop)
+            .map(|idx| block.rws[step.rw_indices[idx]].stack_value())
+            .collect::<Vec<_>>();
+        let (gas, callee_address, value, salt, [cd_offset, cd_length, rd_offset, rd_length]) =
+            if is_create_family {
+                (
+                    U256::zero(),
+                    U256::zero(),
+                    stack_values[0],
+                    stack_values.get(3).copied().unwrap_or_default(),
+                    [stack_values[1], stack_values[2], U256::zero(), U256::zero()],
+                )
+            } else {
+                let n_value = has_value_on_stack as usize;
+                (
+                    stack_values[0],
+                    stack_values[1],
+                    if has_value_on_stack {
+                        stack_values[2]
+                    } else {
+                        U256::zero()
+                    },
+                    U256::zero(),
+                    [0, 1, 2, 3].map(|idx| stack_values[2 + n_value + idx]),
+                )
+            };
+        let mut rw_index = 6 + n_pop + 1;
+
+        self.gas.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                gas.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.callee_address
+            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.salt.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                salt.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+
+        let cd_address =
+            self.cd_address
+                .assign(region, offset, cd_offset, cd_length, block.randomness)?;
+        let rd_address =
+            self.rd_address
+                .assign(region, offset, rd_offset, rd_length, block.randomness)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [cd_address, rd_address],
+        )?;
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            cd_length.as_u64(),
+            memory_expansion_gas_cost,
+        )?;
+
+        let (caller_balance, _) = block.rws[step.rw_indices[rw_index]].account_value_pair();
+        rw_index += 1;
+        self.caller_balance
+            .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
+        self.is_insufficient_balance
+            .assign(region, offset, caller_balance, value)?;
+
+        let is_warm_prev = if is_create_family {
+            false
+        } else {
+            let (_, is_warm_prev) =
+                block.rws[step.rw_indices[rw_index]].tx_access_list_value_pair();
+            rw_index += 1;
+            is_warm_prev
+        };
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+
+        let [callee_nonce, callee_balance, callee_code_hash] = if opcode == OpcodeId::CALL {
+            [rw_index, rw_index + 1, rw_index + 2]
+                .map(|idx| block.rws[step.rw_indices[idx]].account_value_pair().0)
+        } else {
+            [U256::zero(); 3]
+        };
+        let callee_balance =
+            Word::random_linear_combine(callee_balance.to_le_bytes(), block.randomness);
+        let callee_code_hash =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness);
+        self.callee_nonce
+            .assign(region, offset, callee_nonce.to_scalar())?;
+        self.callee_balance
+            .assign(region, offset, Some(callee_balance))?;
+        self.callee_code_hash
+            .assign(region, offset, Some(callee_code_hash))?;
+        self.is_account_empty.assign(
+            region,
+            offset,
+            [F::from(callee_nonce.low_u64()), callee_balance],
+        )?;
+        self.is_empty_code_hash.assign(
+            region,
+            offset,
+            callee_code_hash,
+            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{run_test_circuits, BytecodeTestConfig};
+    use eth_types::{
+        address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, Address, ToWord, Word,
+    };
+    use mock::TestContext;
+
+    const CALLER_BALANCE: u64 = 0x100;
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(CALLER_BALANCE))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x00000000000000000000000000000000000000fe"))
+                    .code(bytecode! { STOP });
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn call_code(opcode: OpcodeId, callee: Address, value: Word) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(0x20) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x20) // call data length
+            PUSH1(0x00) // call data offset
+        };
+        if matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) {
+            code.push(32, value);
+        }
+        code.append(&bytecode! {
+            PUSH20(callee.to_word())
+            PUSH32(0x8_0000_0000_0000_u64) // gas
+        });
+        code.write_op(opcode);
+        code.append(&bytecode! {
+            // The result and the return data size are both 0.
+            RETURNDATASIZE
+            STOP
+        });
+        code
+    }
+
+    fn create_code(opcode: OpcodeId, value: Word) -> Bytecode {
+        let mut code = Bytecode::default();
+        if opcode == OpcodeId::CREATE2 {
+            code.push(2, Word::from(0xcafe)); // salt
+        }
+        code.append(&bytecode! {
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            PUSH32(value)
+        });
+        code.write_op(opcode);
+        code.append(&bytecode! {
+            RETURNDATASIZE
+            STOP
+        });
+        code
+    }
+
+    #[test]
+    fn error_insufficient_balance_call() {
+        for opcode in [OpcodeId::CALL, OpcodeId::CALLCODE] {
+            for callee in [
+                address!("0x00000000000000000000000000000000000000fe"),
+                // An empty account, which would be created by CALL
+                address!("0x0000000000000000000000000000000000c0ffee"),
+            ] {
+                test_ok(call_code(opcode, callee, Word::from(CALLER_BALANCE + 1)));
+            }
+        }
+    }
+
+    #[test]
+    fn error_insufficient_balance_create() {
+        for opcode in [OpcodeId::CREATE, OpcodeId::CREATE2] {
+            for value in [Word::from(CALLER_BALANCE + 1), Word::MAX] {
+                test_ok(create_code(opcode, value));
+            }
+        }
+    }
+
+    #[test]
+    fn error_depth() {
+        // The code calls itself recursively with all the gas it's allowed to
+        // give, until the call at depth 1025 fails. As each callee gets 63/64
+        // of the remaining gas, the transaction gas is set just above what is
+        // needed for the 119 gas spent per frame.
+        let caller = address!("0x0000000000000000000000000000000000000010");
+        let code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            ADDRESS
+            GAS
+            CALL
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(caller).code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .gas(Word::from(10u64.pow(11)));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        // The read/writes of the 1025 frames exceed the rows of the state
+        // circuit under test, so only the EVM circuit is run.
+        let test_config = BytecodeTestConfig {
+            enable_state_circuit_test: false,
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuits(ctx, Some(test_config)), Ok(()));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            not, or, sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorReturnDataOutOfBoundGadget<F> {
+    opcode: Cell<F>,
+    memory_offset: Cell<F>,
+    data_offset: Word<F>,
+    size: Word<F>,
+    return_data_size: Cell<F>,
+    // Either `data_offset` or `size` doesn't fit in 8 bytes, or the end of the
+    // copied range (which then fits in 9 bytes) exceeds the return data size.
+    is_data_offset_within_u64: IsZeroGadget<F>,
+    is_size_within_u64: IsZeroGadget<F>,
+    is_end_over_return_data: LtGadget<F, { N_BYTES_U64 + 1 }>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
+    const NAME: &'static str = "ErrorReturnDataOutOfBound";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorReturnDataOutOfBound;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorReturnDataOutOfBound opcode must be RETURNDATACOPY",
+            opcode.expr(),
+            OpcodeId::RETURNDATACOPY.expr(),
+        );
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_word();
+        let size = cb.query_word();
+
+        // Pop memory_offset, data_offset, size from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(size.expr());
+
+        let return_data_size = cb.query_cell();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+            return_data_size.expr(),
+        );
+
+        // Check that `data_offset + size > return_data_size`
+        let is_data_offset_within_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&data_offset.cells[N_BYTES_U64..]));
+        let is_size_within_u64 = IsZeroGadget::construct(cb, sum::expr(&size.cells[N_BYTES_U64..]));
+        let is_end_over_return_data = LtGadget::construct(
+            cb,
+            return_data_size.expr(),
+            from_bytes::expr(&data_offset.cells[..N_BYTES_U64])
+                + from_bytes::expr(&size.cells[..N_BYTES_U64]),
+        );
+        cb.require_equal(
+            "data_offset + size > return_data_size",
+            or::expr([
+                not::expr(is_data_offset_within_u64.expr()),
+                not::expr(is_size_within_u64.expr()),
+                is_end_over_return_data.expr(),
+            ]),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            memory_offset,
+            data_offset,
+            size,
+            return_data_size,
+            is_data_offset_within_u64,
+            is_size_within_u64,
+            is_end_over_return_data,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, data_offset, size] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.data_offset
+            .assign(region, offset, Some(data_offset.to_le_bytes()))?;
+        self.size.assign(region, offset, Some(size.to_le_bytes()))?;
+
+        let return_data_size = block.rws[step.rw_indices[3]].call_context_value().as_u64();
+        self.return_data_size
+            .assign(region, offset, Some(F::from(return_data_size)))?;
+
+        let [data_offset_lo, size_lo] = [data_offset, size].map(|word| {
+            let bytes = word.to_le_bytes();
+            (
+                from_bytes::value::<F>(&bytes[..N_BYTES_U64]),
+                sum::value::<F>(&bytes[N_BYTES_U64..]),
+            )
+        });
+        self.is_data_offset_within_u64
+            .assign(region, offset, data_offset_lo.1)?;
+        self.is_size_within_u64.assign(region, offset, size_lo.1)?;
+        self.is_end_over_return_data.assign(
+            region,
+            offset,
+            F::from(return_data_size),
+            data_offset_lo.0 + size_lo.0,
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root(data_offset: Word, size: Word) {
+        let code = bytecode! {
+            PUSH32(size)
+            PUSH32(data_offset)
+            PUSH32(Word::zero()) // dst_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal(return_data_size: usize, data_offset: Word, size: Word) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A and returns part of its memory.
+        let code_b = bytecode! {
+            PUSH32(return_data_size)
+            PUSH1(0x00) // offset
+            RETURN
+        };
+
+        // code A calls code B and copies out of bound of the returned data.
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(size)
+            PUSH32(data_offset)
+            PUSH32(Word::zero()) // dst_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_return_data_oob_in_internal_call() {
+        // The callee fails and the caller's context is restored.
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        let code_b = bytecode! {
+            PUSH1(0x01) // size
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // dst_offset
+            RETURNDATACOPY
+            STOP
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_return_data_oob_root() {
+        // There is no return data before any call
+        test_root(Word::zero(), Word::one());
+        test_root(Word::one(), Word::zero());
+    }
+
+    #[test]
+    fn error_return_data_oob_internal() {
+        test_internal(0x20, Word::from(0x10), Word::from(0x11));
+        test_internal(0x20, Word::from(0x21), Word::zero());
+        test_internal(0x20, Word::MAX, Word::one());
+        test_internal(0x20, Word::zero(), Word::from(u64::MAX) + 1);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, CachedRegion,
+            Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the execution of an opcode with a stack pointer out of its valid
+/// range, which is a stack overflow when `IS_OVERFLOW` and a stack underflow
+/// otherwise.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorStackGadget<F, const IS_OVERFLOW: bool> {
+    opcode: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field, const IS_OVERFLOW: bool> ExecutionGadget<F> for ErrorStackGadget<F, IS_OVERFLOW> {
+    const NAME: &'static str = if IS_OVERFLOW {
+        "ErrorStackOverflow"
+    } else {
+        "ErrorStackUnderflow"
+    };
+
+    const EXECUTION_STATE: ExecutionState = if IS_OVERFLOW {
+        ExecutionState::ErrorStackOverflow
+    } else {
+        ExecutionState::ErrorStackUnderflow
+    };
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Check that the opcode is executed with a stack pointer which causes
+        // the error.
+        cb.add_lookup(
+            "Responsible opcode with invalid stack pointer lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    cb.curr.state.stack_pointer.expr(),
+                ],
+            },
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 0)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn test_root_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(1_000_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn test_internal_ok(code: Bytecode) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH20(callee.to_word())
+            PUSH3(0xffffff) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1].address(callee).code(code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[2].address)
+                    .gas(Word::from(1_000_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn stack_overflow_code() -> Bytecode {
+        let mut code = Bytecode::default();
+        for i in 0u64..1025 {
+            code.push(1, Word::from(i & 0xff));
+        }
+        code
+    }
+
+    fn stack_underflow_code() -> Bytecode {
+        bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADDMOD
+        }
+    }
+
+    #[test]
+    fn error_stack_overflow() {
+        test_root_ok(stack_overflow_code());
+        test_internal_ok(stack_overflow_code());
+    }
+
+    #[test]
+    fn error_stack_underflow() {
+        test_root_ok(stack_underflow_code());
+        test_internal_ok(stack_underflow_code());
+        test_root_ok(bytecode! { SWAP5 });
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{CallContextFieldTag, FixedTableTag, Lookup},
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorWriteProtectionGadget<F> {
+    opcode: Cell<F>,
+    is_call: IsEqualGadget<F>,
+    value: Cell<F>,
+    is_value_zero: IsZeroGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
+    const NAME: &'static str = "ErrorWriteProtection";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorWriteProtection;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        // The current call must be static
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 1.expr());
+
+        // CALL only breaks write protection when transferring a non-zero value
+        let is_call = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CALL.expr());
+        let value = cb.query_cell();
+        cb.condition(is_call.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        let is_value_zero = IsZeroGadget::construct(cb, value.expr());
+        cb.require_zero(
+            "CALL value is non-zero",
+            is_call.expr() * is_value_zero.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr());
+
+        Self {
+            opcode,
+            is_call,
+            value,
+            is_value_zero,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_call = opcode == OpcodeId::CALL;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CALL.as_u64()),
+        )?;
+
+        let value = if is_call {
+            let value = block.rws[step.rw_indices[1]].stack_value();
+            Word::random_linear_combine(value.to_le_bytes(), block.randomness)
+        } else {
+            F::zero()
+        };
+        self.value.assign(region, offset, Some(value))?;
+        self.is_value_zero.assign(region, offset, value)?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 1 + is_call as usize)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, bytecode::Bytecode, ToWord, Word};
+    use mock::TestContext;
+
+    fn test_ok(callee_code: Bytecode) {
+        let callee = address!("0x00000000000000000000000000000000000000fe");
+        let caller_code = bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            STATICCALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .code(caller_code);
+                accs[1]
+                    .address(callee)
+                    .code(callee_code)
+                    .balance(Word::from(1u64 << 20));
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn error_write_protection_sstore() {
+        test_ok(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+        });
+    }
+
+    #[test]
+    fn error_write_protection_log() {
+        test_ok(bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            LOG0
+        });
+    }
+
+    #[test]
+    fn error_write_protection_create() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+        });
+    }
+
+    #[test]
+    fn error_write_protection_call_with_value() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // return data length
+            PUSH1(0x00) // return data offset
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x01) // value
+            PUSH1(0x00) // address
+            PUSH2(0xffff) // gas
+            CALL
+        });
+    }
+
+    #[test]
+    fn error_write_protection_selfdestruct() {
+        test_ok(bytecode! {
+            PUSH1(0x00) // beneficiary
+            SELFDESTRUCT
+        });
+    }
+}
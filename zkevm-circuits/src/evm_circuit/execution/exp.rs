@@ -0,0 +1,235 @@
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::plonk::Error;
+
+use crate::{
+    evm_circuit::{
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition},
+            from_bytes,
+            math_gadget::{ByteSizeGadget, IsEqualGadget, IsZeroGadget},
+            sum, CachedRegion, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+
+use super::ExecutionGadget;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ExponentiationGadget<F> {
+    /// Gadget to check that we stay within the same context.
+    same_context: SameContextGadget<F>,
+    /// RLC-encoded integer base that will be exponentiated.
+    base: Word<F>,
+    /// RLC-encoded exponent for the exponentiation operation.
+    exponent: Word<F>,
+    /// RLC-encoded result of the exponentiation.
+    exponentiation: Word<F>,
+    /// Whether the exponent is zero, in which case the result is 1.
+    exponent_is_zero: IsZeroGadget<F>,
+    /// Whether the low 128 bits of the exponent are one.
+    exponent_lo_is_one: IsEqualGadget<F>,
+    /// Whether the high 128 bits of the exponent are zero.
+    exponent_hi_is_zero: IsZeroGadget<F>,
+    /// Gadget to check the byte size of the exponent, which determines the
+    /// dynamic gas cost.
+    exponent_byte_size: ByteSizeGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ExponentiationGadget<F> {
+    const NAME: &'static str = "EXP";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EXP;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Query RLC-encoded values for base, exponent and exponentiation, where:
+        // base^exponent == exponentiation (mod 2^256).
+        let base = cb.query_word();
+        let exponent = cb.query_word();
+        let exponentiation = cb.query_word();
+
+        // Pop RLC-encoded base and exponent from the stack.
+        cb.stack_pop(base.expr());
+        cb.stack_pop(exponent.expr());
+
+        // Push RLC-encoded exponentiation to the stack.
+        cb.stack_push(exponentiation.expr());
+
+        // Exponentiation by 0 and 1 is trivial, and done without a lookup to
+        // the exponentiation table.
+        let exponent_is_zero = IsZeroGadget::construct(cb, sum::expr(&exponent.cells));
+        let exponent_lo_is_one =
+            IsEqualGadget::construct(cb, from_bytes::expr(&exponent.cells[..16]), 1.expr());
+        let exponent_hi_is_zero =
+            IsZeroGadget::construct(cb, from_bytes::expr(&exponent.cells[16..]));
+        let exponent_is_one = exponent_lo_is_one.expr() * exponent_hi_is_zero.expr();
+
+        cb.condition(exponent_is_zero.expr(), |cb| {
+            cb.require_equal(
+                "if exponent == 0, exponentiation == 1",
+                exponentiation.expr(),
+                1.expr(),
+            );
+        });
+        cb.condition(exponent_is_one.clone(), |cb| {
+            cb.require_equal(
+                "if exponent == 1, exponentiation == base",
+                exponentiation.expr(),
+                base.expr(),
+            );
+        });
+        cb.condition(
+            (1.expr() - exponent_is_zero.expr()) * (1.expr() - exponent_is_one),
+            |cb| {
+                cb.exp_table_lookup(
+                    cb.curr.state.rw_counter.expr(),
+                    from_bytes::expr(&base.cells[..16]),
+                    from_bytes::expr(&base.cells[16..]),
+                    from_bytes::expr(&exponent.cells[..16]),
+                    from_bytes::expr(&exponent.cells[16..]),
+                    from_bytes::expr(&exponentiation.cells[..16]),
+                    from_bytes::expr(&exponentiation.cells[16..]),
+                );
+            },
+        );
+
+        // Opcode EXP has a dynamic gas cost:
+        // gas_cost = static_gas + 50 * exponent_byte_size
+        let exponent_byte_size = ByteSizeGadget::construct(cb, &exponent);
+        let dynamic_gas_cost = GasCost::EXP_BYTE_COST.expr() * exponent_byte_size.byte_size();
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Transition::Delta(3.expr()), // 2 stack pops, 1 stack push
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta(1.expr()),
+            gas_left: Transition::Delta(
+                -OpcodeId::EXP.constant_gas_cost().expr() - dynamic_gas_cost,
+            ),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            base,
+            exponent,
+            exponentiation,
+            exponent_is_zero,
+            exponent_lo_is_one,
+            exponent_hi_is_zero,
+            exponent_byte_size,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [base, exponent, exponentiation] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+
+        self.base.assign(region, offset, Some(base.to_le_bytes()))?;
+        self.exponent
+            .assign(region, offset, Some(exponent.to_le_bytes()))?;
+        self.exponentiation
+            .assign(region, offset, Some(exponentiation.to_le_bytes()))?;
+
+        let exponent_bytes = exponent.to_le_bytes();
+        self.exponent_is_zero.assign(
+            region,
+            offset,
+            exponent_bytes
+                .iter()
+                .fold(F::zero(), |acc, byte| acc + F::from(*byte as u64)),
+        )?;
+        let exponent_lo = U256::from_little_endian(&exponent_bytes[..16]);
+        let exponent_hi = U256::from_little_endian(&exponent_bytes[16..]);
+        self.exponent_lo_is_one.assign(
+            region,
+            offset,
+            exponent_lo.to_scalar().unwrap(),
+            F::one(),
+        )?;
+        self.exponent_hi_is_zero
+            .assign(region, offset, exponent_hi.to_scalar().unwrap())?;
+        self.exponent_byte_size.assign(region, offset, exponent)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evm_circuit::test::rand_word;
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(base: Word, exponent: Word) {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(base)
+            EXP
+            STOP
+        };
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn exp_gadget_zero() {
+        test_ok(Word::zero(), Word::zero());
+        test_ok(Word::zero(), Word::one());
+        test_ok(Word::zero(), 1023.into());
+        test_ok(Word::one(), Word::zero());
+        test_ok(0xcafeu64.into(), Word::zero());
+        test_ok(Word::MAX, Word::zero());
+    }
+
+    #[test]
+    fn exp_gadget_one() {
+        test_ok(Word::one(), Word::one());
+        test_ok(0xcafeu64.into(), Word::one());
+        test_ok(Word::MAX, Word::one());
+    }
+
+    #[test]
+    fn exp_gadget_simple() {
+        test_ok(2.into(), 5.into());
+        test_ok(3.into(), 101.into());
+        test_ok(5.into(), 259.into());
+        test_ok(7.into(), 1023.into());
+        test_ok(Word::MAX, 2.into());
+        test_ok(Word::MAX, 3.into());
+    }
+
+    #[test]
+    fn exp_gadget_overflow() {
+        test_ok(2.into(), 256.into());
+        test_ok(Word::MAX, Word::MAX);
+        test_ok(2.into(), Word::one() << 128);
+    }
+
+    #[test]
+    fn exp_gadget_rand() {
+        test_ok(rand_word(), rand_word());
+    }
+}
@@ -0,0 +1,441 @@
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::plonk::Error;
+
+use crate::{
+    evm_circuit::{
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition, Transition,
+            },
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            not, select, sum, CachedRegion, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+
+use super::ExecutionGadget;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ExtcodecopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    external_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    /// Holds the offset in code from where we read, which could be any word.
+    code_offset: Word<F>,
+    /// Whether the code offset fits in u64.
+    code_offset_is_u64: IsZeroGadget<F>,
+    /// Whether the code offset is less than the code size, otherwise only
+    /// zeros are copied from the end of the code.
+    code_offset_lt_code_size: LtGadget<F, N_BYTES_U64>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_warm: Cell<F>,
+    /// Holds the code hash of the external account, which is the empty code
+    /// hash for a non-existing account.
+    code_hash: Cell<F>,
+    /// Holds the size of the external account's bytecode.
+    code_size: Cell<F>,
+    /// The code of the external account is copied to memory. To verify this
+    /// copy operation we need the MemoryAddressGadget.
+    dst_memory_addr: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    /// Opcode EXTCODECOPY needs to copy code bytes into memory. We account for
+    /// the copying costs using the memory copier gas gadget.
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
+    const NAME: &'static str = "EXTCODECOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EXTCODECOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Query elements to be popped from the stack.
+        let external_address = cb.query_rlc();
+        let dst_memory_offset = cb.query_cell();
+        let code_offset = cb.query_rlc();
+        let size = cb.query_rlc();
+
+        // Pop items from stack.
+        cb.stack_pop(external_address.expr());
+        cb.stack_pop(dst_memory_offset.expr());
+        cb.stack_pop(code_offset.expr());
+        cb.stack_pop(size.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info(None);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            from_bytes::expr(&external_address.cells),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let code_hash = cb.query_cell();
+        cb.account_read(
+            from_bytes::expr(&external_address.cells),
+            AccountFieldTag::CodeHash,
+            code_hash.expr(),
+        );
+
+        // Fetch the bytecode length from the bytecode table.
+        let code_size = cb.bytecode_length(code_hash.expr());
+
+        // The code offset is capped to the code size, since only zeros are
+        // copied from beyond the code.
+        let code_offset_is_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&code_offset.cells[N_BYTES_U64..]));
+        let code_offset_lt_code_size = LtGadget::construct(
+            cb,
+            from_bytes::expr(&code_offset.cells[..N_BYTES_U64]),
+            code_size.expr(),
+        );
+        let src_addr = select::expr(
+            code_offset_is_u64.expr() * code_offset_lt_code_size.expr(),
+            from_bytes::expr(&code_offset.cells[..N_BYTES_U64]),
+            code_size.expr(),
+        );
+
+        // Construct memory address in the destionation (memory) to which we copy code.
+        let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_memory_offset, size);
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access. This also accounts for the dynamic gas required to copy bytes to
+        // memory.
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [dst_memory_addr.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_addr.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_memory_addr.has_length(), |cb| {
+            cb.copy_table_lookup(
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_addr,
+                code_size.expr(),
+                dst_memory_addr.offset(),
+                dst_memory_addr.length(),
+                0.expr(), // for EXTCODECOPY, rlc_acc is 0
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr(),
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_memory_addr.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let gas_cost = is_warm.expr() * GasCost::WARM_ACCESS.expr()
+            + (1.expr() - is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + memory_copier_gas.gas_cost();
+
+        // Expected state transition.
+        let step_state_transition = StepStateTransition {
+            rw_counter: Transition::Delta(cb.rw_counter_offset() + copy_rwc_inc.expr()),
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta(4.expr()),
+            memory_word_size: Transition::To(memory_expansion.next_memory_word_size()),
+            gas_left: Transition::Delta(-gas_cost),
+            reversible_write_counter: Transition::Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            external_address,
+            code_offset,
+            code_offset_is_u64,
+            code_offset_lt_code_size,
+            tx_id,
+            reversion_info,
+            is_warm,
+            code_hash,
+            code_size,
+            dst_memory_addr,
+            memory_expansion,
+            memory_copier_gas,
+            copy_rwc_inc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [external_address, dest_offset, code_offset, size] =
+            [0, 1, 2, 3].map(|i| block.rws[step.rw_indices[i]].stack_value());
+
+        let mut le_bytes = external_address.to_address().0;
+        le_bytes.reverse();
+        self.external_address
+            .assign(region, offset, Some(le_bytes))?;
+
+        let code_offset_bytes = code_offset.to_le_bytes();
+        self.code_offset
+            .assign(region, offset, Some(code_offset_bytes))?;
+        self.code_offset_is_u64.assign(
+            region,
+            offset,
+            sum::value(&code_offset_bytes[N_BYTES_U64..]),
+        )?;
+
+        self.tx_id
+            .assign(region, offset, U256::from(tx.id).to_scalar())?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[7]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let code_hash_rw = &block.rws[step.rw_indices[8]];
+        self.code_hash.assign(
+            region,
+            offset,
+            Some(code_hash_rw.table_assignment(block.randomness).value),
+        )?;
+
+        let code_hash = code_hash_rw.account_value_pair().0;
+
+        let code = block
+            .bytecodes
+            .get(&code_hash)
+            .expect("could not find external bytecode");
+        let code_size = code.bytes.len() as u64;
+        self.code_size
+            .assign(region, offset, Some(F::from(code_size)))?;
+        self.code_offset_lt_code_size.assign(
+            region,
+            offset,
+            F::from(code_offset.low_u64()),
+            F::from(code_size),
+        )?;
+
+        // assign the destination memory offset.
+        let memory_address =
+            self.dst_memory_addr
+                .assign(region, offset, dest_offset, size, block.randomness)?;
+
+        // assign to gadgets handling memory expansion cost and copying cost.
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
+
+        let key = (tx.id, call.id, step.program_counter as usize);
+        let copy_rwc_inc = block
+            .copy_event(key)
+            .and_then(|copy_event| copy_event.steps.first())
+            .map_or(F::zero(), |cs| F::from(cs.rwc_inc_left));
+        self.copy_rwc_inc
+            .assign(region, offset, Some(copy_rwc_inc))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::witness::block_convert,
+        test_util::{test_circuits_using_witness_block, BytecodeTestConfig},
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{
+        address, bytecode,
+        evm_types::OpcodeId,
+        geth_types::{Account, GethData},
+        Address, Bytecode, ToWord, Word,
+    };
+    use lazy_static::lazy_static;
+    use mock::TestContext;
+
+    lazy_static! {
+        static ref EXTERNAL_ADDRESS: Address =
+            address!("0xaabbccddee000000000000000000000000000000");
+    }
+
+    fn test_ok(
+        account: Option<Account>,
+        is_warm: bool,
+        memory_offset: usize,
+        code_offset: Word,
+        size: usize,
+    ) {
+        let external_address = account
+            .as_ref()
+            .map(|a| a.address)
+            .unwrap_or(*EXTERNAL_ADDRESS);
+
+        // Make the external account warm, if needed, by first getting its code size.
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                PUSH20(external_address.to_word())
+                EXTCODESIZE
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH32(Word::from(size))
+            PUSH32(code_offset)
+            PUSH32(Word::from(memory_offset))
+            PUSH20(external_address.to_word())
+            #[start]
+            EXTCODECOPY
+            STOP
+        });
+
+        // Execute the bytecode and get trace
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+
+                accs[1].address(external_address);
+                if let Some(account) = account {
+                    accs[1].balance(account.balance).code(account.code);
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .expect("could not handle block tx");
+
+        test_circuits_using_witness_block(
+            block_convert(&builder.block, &builder.code_db),
+            BytecodeTestConfig::default(),
+        )
+        .unwrap();
+    }
+
+    fn existing_account() -> Account {
+        let mut code = Bytecode::default();
+        for _ in 0..0x40 {
+            code.push(1, Word::from(123));
+        }
+        code.write_op(OpcodeId::STOP);
+        Account {
+            address: *EXTERNAL_ADDRESS,
+            balance: Word::from(900),
+            code: code.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn extcodecopy_empty_account() {
+        test_ok(None, false, 0x00, Word::from(0x00), 0x20);
+        test_ok(None, true, 0x40, Word::from(0x00), 0x20);
+    }
+
+    #[test]
+    fn extcodecopy_existing_account() {
+        test_ok(
+            Some(existing_account()),
+            false,
+            0x00,
+            Word::from(0x00),
+            0x20,
+        );
+        test_ok(Some(existing_account()), true, 0x20, Word::from(0x10), 0x40);
+    }
+
+    #[test]
+    fn extcodecopy_out_of_bound() {
+        test_ok(
+            Some(existing_account()),
+            false,
+            0x00,
+            Word::from(0x70),
+            0x40,
+        );
+        test_ok(
+            Some(existing_account()),
+            true,
+            0x00,
+            Word::from(0x100),
+            0x20,
+        );
+    }
+
+    #[test]
+    fn extcodecopy_code_offset_overflow() {
+        test_ok(Some(existing_account()), false, 0x00, Word::MAX, 0x20);
+        test_ok(
+            Some(existing_account()),
+            true,
+            0x00,
+            Word::from(u64::MAX),
+            0x20,
+        );
+    }
+
+    #[test]
+    fn extcodecopy_zero_size() {
+        test_ok(
+            Some(existing_account()),
+            false,
+            0x00,
+            Word::from(0x00),
+            0x00,
+        );
+    }
+}
@@ -201,13 +201,12 @@ mod test {
             .map(|a| a.address)
             .unwrap_or(*EXTERNAL_ADDRESS);
 
-        // Make the external account warm, if needed, by first getting its external code
-        // hash.
+        // Make the external account warm, if needed, by first getting its balance.
         let mut code = Bytecode::default();
         if is_warm {
             code.append(&bytecode! {
                 PUSH20(external_address.to_word())
-                EXTCODEHASH // TODO: Change this to BALANCE once is implemented
+                BALANCE
                 POP
             });
         }
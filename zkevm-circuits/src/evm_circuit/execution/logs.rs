@@ -139,6 +139,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                 memory_address.address(),
                 dst_addr,
                 memory_address.length(),
+                0.expr(), // for LOGN, rlc_acc is 0
                 cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr(),
                 copy_rwc_inc.expr(),
             );
@@ -243,8 +244,7 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
 
         let key = (tx.id, call.id, step.program_counter as usize);
         let copy_rwc_inc = block
-            .copy_events
-            .get(&key)
+            .copy_event(key)
             .unwrap()
             .steps
             .first()
@@ -0,0 +1,561 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            math_gadget::{
+                ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget,
+            },
+            not, select, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::PrecompileCalls};
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToScalar};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the execution of a precompiled contract, which follows the call
+/// to it. The input is copied from the call data into the accumulator of its
+/// random linear combination, and the output is copied from its accumulator
+/// into the precompiled contract's memory and then into the caller's memory as
+/// return data. The relation between the input and the output is verified by a
+/// lookup into the precompile table.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileGadget<F> {
+    is_ecrecover: IsEqualGadget<F>,
+    is_identity: IsEqualGadget<F>,
+    tx_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    is_success: Cell<F>,
+    input_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    input_bytes_read: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    input_rlc: Cell<F>,
+    input_length_is_zero: IsZeroGadget<F>,
+    output_rlc: Cell<F>,
+    output_length: Cell<F>,
+    output_length_is_zero: IsZeroGadget<F>,
+    // Copy of the return data into caller's memory
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for PrecompileGadget<F> {
+    const NAME: &'static str = "PRECOMPILE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PRECOMPILE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        // The code hash of a precompiled contract's context is its address.
+        let address = cb.curr.state.code_hash.expr();
+        let [is_ecrecover, is_identity] = [PrecompileCalls::ECRecover, PrecompileCalls::Identity]
+            .map(|precompile| {
+                IsEqualGadget::construct(cb, address.clone(), (precompile as u64).expr())
+            });
+        // TODO: Support sha256, ripemd160, modexp, bn128 add, bn128 mul, bn128
+        // pairing and blake2f
+        cb.require_equal(
+            "Precompiled contract is ecrecover or identity",
+            is_ecrecover.expr() + is_identity.expr(),
+            1.expr(),
+        );
+
+        let [tx_id, call_data_offset, call_data_length, is_success] = [
+            CallContextFieldTag::TxId,
+            CallContextFieldTag::CallDataOffset,
+            CallContextFieldTag::CallDataLength,
+            CallContextFieldTag::IsSuccess,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+        cb.require_boolean("is_success is boolean", is_success.expr());
+
+        // The call data is padded with zeros or truncated to the length of the
+        // input the precompiled contract operates on.
+        let input_length = select::expr(is_ecrecover.expr(), 128.expr(), call_data_length.expr());
+
+        // The gas cost of all the supported precompiled contracts is linear in
+        // the number of words of the call data.
+        let input_word_size =
+            ConstantDivisionGadget::construct(cb, call_data_length.expr() + 31.expr(), 32);
+        let base_gas_cost = is_ecrecover.expr() * GasCost::PRECOMPILE_ECRECOVER.expr()
+            + is_identity.expr() * GasCost::PRECOMPILE_IDENTITY_BASE.expr();
+        let gas_cost_per_word = is_identity.expr() * GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr();
+        let required_gas = base_gas_cost + gas_cost_per_word * input_word_size.quotient();
+
+        // The call fails only when it runs out of gas, which consumes all the
+        // gas given to it.
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), required_gas.clone());
+        cb.require_equal(
+            "is_success == 1 - insufficient_gas",
+            is_success.expr(),
+            not::expr(insufficient_gas.expr()),
+        );
+        let gas_cost = select::expr(
+            is_success.expr(),
+            required_gas,
+            cb.curr.state.gas_left.expr(),
+        );
+
+        // Only the bytes of the call data in the caller's memory are read,
+        // while the call data of a root call is read from the tx table.
+        let input_bytes_read =
+            MinMaxGadget::construct(cb, call_data_length.expr(), input_length.clone());
+        let input_rlc = cb.query_cell();
+        let input_length_is_zero = IsZeroGadget::construct(cb, input_length.clone());
+        let output_rlc = cb.query_cell();
+        let output_length = cb.query_cell();
+        let output_length_is_zero = IsZeroGadget::construct(cb, output_length.expr());
+        cb.condition(is_success.expr(), |cb| {
+            cb.precompile_table_lookup(
+                address.clone(),
+                input_rlc.expr(),
+                input_length.clone(),
+                output_rlc.expr(),
+                output_length.expr(),
+            );
+        });
+        cb.condition(not::expr(is_success.expr()), |cb| {
+            cb.require_zero("Failed call has no output", output_length.expr());
+        });
+        cb.condition(input_length_is_zero.expr(), |cb| {
+            cb.require_zero("if input is empty, input_rlc == 0", input_rlc.expr());
+        });
+        cb.condition(output_length_is_zero.expr(), |cb| {
+            cb.require_zero("if output is empty, output_rlc == 0", output_rlc.expr());
+        });
+
+        let is_root = cb.curr.state.is_root.expr();
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            is_root.clone(),
+            is_to_end_tx,
+        );
+
+        // When it's an internal call, the return data is copied into the
+        // caller's memory, up to the size requested by the caller.
+        let (return_data_offset, return_data_length, copy_length) =
+            cb.condition(not::expr(is_root.clone()), |cb| {
+                let [return_data_offset, return_data_length] = [
+                    CallContextFieldTag::ReturnDataOffset,
+                    CallContextFieldTag::ReturnDataLength,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag));
+                let copy_length =
+                    MinMaxGadget::construct(cb, return_data_length.expr(), output_length.expr());
+
+                (return_data_offset, return_data_length, copy_length)
+            });
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+
+        let input_is_copied = is_success.expr() * not::expr(input_length_is_zero.expr());
+        let input_copy_rwc_inc =
+            input_is_copied.clone() * not::expr(is_root.clone()) * input_bytes_read.min();
+        let output_is_copied = not::expr(output_length_is_zero.expr());
+        let output_copy_rwc_inc = output_length.expr();
+        let return_is_copied = not::expr(is_root.clone()) * not::expr(copy_length_is_zero.expr());
+        let return_copy_rwc_inc = not::expr(is_root.clone()) * 2.expr() * copy_length.min();
+
+        // When it's a root call
+        cb.condition(is_root.clone(), |cb| {
+            // Do step state transition, where the reversions of a failed
+            // transaction are done right after this step.
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(
+                    cb.rw_counter_offset()
+                        + input_copy_rwc_inc.clone()
+                        + output_copy_rwc_inc.clone()
+                        + not::expr(is_success.expr())
+                            * cb.curr.state.reversible_write_counter.expr(),
+                ),
+                gas_left: Delta(-gas_cost.clone()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(is_root.clone()), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                is_success.expr(),
+                cb.rw_counter_offset()
+                    + input_copy_rwc_inc.clone()
+                    + output_copy_rwc_inc.clone()
+                    + return_copy_rwc_inc.clone(),
+                0.expr(),
+                output_length.expr(),
+                gas_cost,
+                0.expr(),
+            )
+        });
+
+        // The copy steps are done after all the other rw lookups of this step.
+        let input_copy_rw_counter = cb.curr.state.rw_counter.expr() + cb.rw_counter_offset();
+        cb.condition(input_is_copied, |cb| {
+            cb.copy_table_lookup(
+                select::expr(is_root.clone(), tx_id.expr(), restore_context.caller_id()),
+                select::expr(
+                    is_root.clone(),
+                    CopyDataType::TxCalldata.expr(),
+                    CopyDataType::Memory.expr(),
+                ),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                call_data_offset.expr(),
+                call_data_offset.expr() + call_data_length.expr(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                input_length.clone(),
+                input_rlc.expr(),
+                input_copy_rw_counter.clone(),
+                input_copy_rwc_inc.clone(),
+            );
+        });
+        let output_copy_rw_counter = input_copy_rw_counter + input_copy_rwc_inc;
+        cb.condition(output_is_copied, |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                0.expr(), // src_addr for CopyDataType::RlcAcc is 0.
+                output_length.expr(),
+                0.expr(),
+                output_length.expr(),
+                output_rlc.expr(),
+                output_copy_rw_counter.clone(),
+                output_copy_rwc_inc.clone(),
+            );
+        });
+        let return_copy_rw_counter = output_copy_rw_counter + output_copy_rwc_inc;
+        cb.condition(return_is_copied, |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                restore_context.caller_id(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                copy_length.min(),
+                return_data_offset.expr(),
+                copy_length.min(),
+                0.expr(), // for return data copy, rlc_acc is 0
+                return_copy_rw_counter,
+                return_copy_rwc_inc,
+            );
+        });
+
+        Self {
+            is_ecrecover,
+            is_identity,
+            tx_id,
+            call_data_offset,
+            call_data_length,
+            is_success,
+            input_word_size,
+            insufficient_gas,
+            input_bytes_read,
+            input_rlc,
+            input_length_is_zero,
+            output_rlc,
+            output_length,
+            output_length_is_zero,
+            return_data_offset,
+            return_data_length,
+            copy_length,
+            copy_length_is_zero,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let address = call.code_hash.to_address();
+        let precompile = PrecompileCalls::from_address(&address)
+            .expect("code hash of precompiled contract's context should be its address");
+        let address = F::from(precompile as u64);
+        for (gadget, precompile) in [
+            (&self.is_ecrecover, PrecompileCalls::ECRecover),
+            (&self.is_identity, PrecompileCalls::Identity),
+        ] {
+            gadget.assign(region, offset, address, F::from(precompile as u64))?;
+        }
+
+        let [tx_id, call_data_offset, call_data_length, is_success] =
+            [0, 1, 2, 3].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        for (cell, value) in [
+            (&self.tx_id, tx_id),
+            (&self.call_data_offset, call_data_offset),
+            (&self.call_data_length, call_data_length),
+            (&self.is_success, is_success),
+        ] {
+            cell.assign(region, offset, value.to_scalar())?;
+        }
+        let call_data_length = call_data_length.low_u64();
+        let input_length = precompile.input_length(call_data_length);
+
+        let (input_word_size, _) =
+            self.input_word_size
+                .assign(region, offset, (call_data_length + 31) as u128)?;
+        let required_gas = match precompile {
+            PrecompileCalls::ECRecover => GasCost::PRECOMPILE_ECRECOVER.as_u64(),
+            PrecompileCalls::Identity => {
+                GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                    + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * input_word_size as u64
+            }
+            _ => 0,
+        };
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(required_gas),
+        )?;
+
+        self.input_bytes_read.assign(
+            region,
+            offset,
+            F::from(call_data_length),
+            F::from(input_length),
+        )?;
+        self.input_length_is_zero
+            .assign(region, offset, F::from(input_length))?;
+
+        // The input and output are taken from the copy events into and from
+        // the accumulators of their random linear combination.
+        let (mut input_rlc, mut output_rlc, mut output_length) = (F::zero(), F::zero(), 0);
+        for copy_event in block
+            .copy_events
+            .iter()
+            .filter(|copy_event| copy_event.tx_id == tx.id && copy_event.call_id == call.id)
+        {
+            let rlc = copy_event
+                .steps
+                .iter()
+                .filter(|cs| cs.rw.is_write())
+                .fold(F::zero(), |acc, cs| {
+                    acc * block.randomness + F::from(cs.value as u64)
+                });
+            if copy_event.dst_type == CopyDataType::RlcAcc {
+                input_rlc = rlc;
+            } else if copy_event.src_type == CopyDataType::RlcAcc {
+                output_rlc = rlc;
+                output_length = copy_event.length;
+            }
+        }
+        self.input_rlc.assign(region, offset, Some(input_rlc))?;
+        self.output_rlc.assign(region, offset, Some(output_rlc))?;
+        self.output_length
+            .assign(region, offset, Some(F::from(output_length)))?;
+        self.output_length_is_zero
+            .assign(region, offset, F::from(output_length))?;
+
+        let copy_length = if call.is_root {
+            0
+        } else {
+            let [return_data_offset, return_data_length] =
+                [4, 5].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+            self.return_data_offset
+                .assign(region, offset, return_data_offset.to_scalar())?;
+            self.return_data_length
+                .assign(region, offset, return_data_length.to_scalar())?;
+            self.copy_length.assign(
+                region,
+                offset,
+                F::from(return_data_length.low_u64()),
+                F::from(output_length),
+            )?;
+            return_data_length.low_u64().min(output_length)
+        };
+        self.copy_length_is_zero
+            .assign(region, offset, F::from(copy_length))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step, 6)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{
+        address, bytecode, evm_types::OpcodeId, Address, Bytecode, ToBigEndian, ToWord, Word, H256,
+    };
+    use ethers_signers::{LocalWallet, Signer};
+    use itertools::Itertools;
+    use mock::TestContext;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const CALLER_ADDRESS: Address = Address::repeat_byte(0x34);
+
+    fn caller_bytecode(
+        precompile: PrecompileCalls,
+        input: &[u8],
+        call_data_length: u64,
+        return_data_length: u64,
+        gas: u64,
+    ) -> Bytecode {
+        let mut code = Bytecode::default();
+        // Store the input at the start of the memory
+        for (idx, chunk) in input.chunks(32).enumerate() {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            code.push(32, Word::from_big_endian(&word));
+            code.push(32, Word::from(idx * 32));
+            code.write_op(OpcodeId::MSTORE);
+        }
+        code.append(&bytecode! {
+            PUSH32(return_data_length)
+            PUSH2(0x200) // return data offset
+            PUSH32(call_data_length)
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH32(precompile.address().to_word())
+            PUSH32(gas)
+            CALL
+            STOP
+        });
+        code
+    }
+
+    fn test_ok(precompile: PrecompileCalls, caller_code: Option<Bytecode>, call_data: Vec<u8>) {
+        let to = if caller_code.is_some() {
+            CALLER_ADDRESS
+        } else {
+            precompile.address()
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 30));
+                accs[1].address(precompile.address());
+                accs[2]
+                    .address(CALLER_ADDRESS)
+                    .code(caller_code.unwrap_or_default());
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(to)
+                    .input(call_data.into())
+                    .gas(Word::from(100_000u64));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    fn ecrecover_input() -> Vec<u8> {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let wallet = LocalWallet::new(&mut rng);
+        let msg_hash = H256::from_low_u64_be(0xcafe);
+        let sig = wallet.sign_hash(msg_hash, false);
+
+        let mut input = msg_hash.0.to_vec();
+        for value in [Word::from(sig.v), sig.r, sig.s] {
+            input.extend_from_slice(&value.to_be_bytes());
+        }
+        input
+    }
+
+    #[test]
+    fn precompile_identity_gadget_root() {
+        for call_data in [vec![], vec![0xff; 10], vec![0x12; 70]] {
+            test_ok(PrecompileCalls::Identity, None, call_data);
+        }
+    }
+
+    #[test]
+    fn precompile_identity_gadget_nonroot() {
+        let input = (1..=70).collect::<Vec<u8>>();
+        let test_parameters = [(0, 0), (10, 10), (10, 4), (4, 10), (70, 70)];
+        for ((call_data_length, return_data_length), gas) in
+            test_parameters.iter().cartesian_product(&[10_000, 20])
+        {
+            test_ok(
+                PrecompileCalls::Identity,
+                Some(caller_bytecode(
+                    PrecompileCalls::Identity,
+                    &input,
+                    *call_data_length,
+                    *return_data_length,
+                    *gas,
+                )),
+                vec![],
+            );
+        }
+    }
+
+    #[test]
+    fn precompile_ecrecover_gadget_root() {
+        test_ok(PrecompileCalls::ECRecover, None, ecrecover_input());
+    }
+
+    #[test]
+    fn precompile_ecrecover_gadget_nonroot() {
+        let input = ecrecover_input();
+        // The call data is padded with zeros to 128 bytes, which makes the
+        // signature invalid if truncated.
+        for (call_data_length, gas) in [(128, 10_000), (100, 10_000), (128, 2000)] {
+            test_ok(
+                PrecompileCalls::ECRecover,
+                Some(caller_bytecode(
+                    PrecompileCalls::ECRecover,
+                    &input,
+                    call_data_length,
+                    32,
+                    gas,
+                )),
+                vec![],
+            );
+        }
+    }
+
+    #[test]
+    fn precompile_ecrecover_gadget_invalid_signature() {
+        let mut input = ecrecover_input();
+        // v is neither 27 nor 28
+        input[63] = 0x1d;
+        test_ok(PrecompileCalls::ECRecover, None, input.clone());
+        test_ok(
+            PrecompileCalls::ECRecover,
+            Some(caller_bytecode(
+                PrecompileCalls::ECRecover,
+                &input,
+                128,
+                32,
+                10_000,
+            )),
+            vec![],
+        );
+    }
+}
@@ -3,23 +3,24 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{HardforkGadget, SameContextGadget},
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            sum, CachedRegion, Cell, Word,
+            not, select, sum, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
 use array_init::array_init;
-use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use eth_types::{evm_types::OpcodeId, Field, Hardfork, ToLittleEndian};
 use halo2_proofs::plonk::Error;
 
 #[derive(Clone, Debug)]
 pub(crate) struct PushGadget<F> {
     same_context: SameContextGadget<F>,
     value: Word<F>,
-    selectors: [Cell<F>; 31],
+    selectors: [Cell<F>; 32],
+    hardfork: HardforkGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
@@ -41,7 +42,8 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         // condiionally by selectors.
         // For PUSH2 as an example, we lookup from byte0, byte1, ..., byte31,
         // where the byte2 is actually the PUSH2 itself, and lookup are only
-        // enabled for byte0 and byte1.
+        // enabled for byte0 and byte1. PUSH0 has no lookup enabled and pushes
+        // 0.
         //
         //                    program_counter    program_counter + num_pushed(2)
         //                           ▼                     ▼
@@ -50,19 +52,15 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         for idx in 0..32 {
             let byte = &value.cells[idx];
             let index = cb.curr.state.program_counter.expr() + opcode.expr()
-                - (OpcodeId::PUSH1.as_u8() - 1 + idx as u8).expr();
-            if idx == 0 {
+                - (OpcodeId::PUSH0.as_u8() + idx as u8).expr();
+            cb.condition(selectors[idx].expr(), |cb| {
                 cb.opcode_lookup_at(index, byte.expr(), 0.expr())
-            } else {
-                cb.condition(selectors[idx - 1].expr(), |cb| {
-                    cb.opcode_lookup_at(index, byte.expr(), 0.expr())
-                });
-            }
+            });
         }
 
-        for idx in 0..31 {
+        for idx in 0..32 {
             let selector_prev = if idx == 0 {
-                // First selector will always be 1
+                // The selector before the first one is viewed as 1
                 1.expr()
             } else {
                 selectors[idx - 1].expr()
@@ -76,21 +74,29 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
             // byte should be 0 when selector is 0
             cb.require_zero(
                 "Constrain byte == 0 when selector == 0",
-                value.cells[idx + 1].expr() * (1.expr() - selectors[idx].expr()),
+                value.cells[idx].expr() * (1.expr() - selectors[idx].expr()),
             );
         }
 
-        // Deduce the number of additional bytes to push than PUSH1. Note that
-        // num_additional_pushed = n - 1 where n is the suffix number of PUSH*.
-        let num_additional_pushed = opcode.expr() - OpcodeId::PUSH1.as_u64().expr();
-        // Sum of selectors needs to be exactly the number of additional bytes
-        // that needs to be pushed.
+        // Deduce the number of bytes to push, which is the suffix number of
+        // PUSH*.
+        let num_pushed = opcode.expr() - OpcodeId::PUSH0.as_u64().expr();
+        // Sum of selectors needs to be exactly the number of bytes that needs
+        // to be pushed.
         cb.require_equal(
-            "Constrain sum of selectors equal to num_additional_pushed",
+            "Constrain sum of selectors equal to num_pushed",
             sum::expr(&selectors),
-            num_additional_pushed,
+            num_pushed,
         );
 
+        // PUSH0 is only available since Shanghai (EIP-3855), before which it's
+        // handled as an invalid opcode.
+        let is_push0 = not::expr(selectors[0].expr());
+        let hardfork = HardforkGadget::construct(cb);
+        cb.condition(not::expr(hardfork.is_activated(Hardfork::Shanghai)), |cb| {
+            cb.require_zero("opcode != PUSH0 before Shanghai", is_push0.clone());
+        });
+
         // Push the value on the stack
         cb.stack_push(value.expr());
 
@@ -98,9 +104,13 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         // `program_counter` needs to be increased by number of bytes pushed + 1
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(1.expr()),
-            program_counter: Delta(opcode.expr() - (OpcodeId::PUSH1.as_u64() - 2).expr()),
+            program_counter: Delta(opcode.expr() - (OpcodeId::PUSH0.as_u64() - 1).expr()),
             stack_pointer: Delta((-1).expr()),
-            gas_left: Delta(-OpcodeId::PUSH1.constant_gas_cost().expr()),
+            gas_left: Delta(-select::expr(
+                is_push0,
+                OpcodeId::PUSH0.constant_gas_cost().expr(),
+                OpcodeId::PUSH1.constant_gas_cost().expr(),
+            )),
             ..Default::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
@@ -109,6 +119,7 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
             same_context,
             value,
             selectors,
+            hardfork,
         }
     }
 
@@ -129,15 +140,14 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         self.value
             .assign(region, offset, Some(value.to_le_bytes()))?;
 
-        let num_additional_pushed = (opcode.as_u8() - OpcodeId::PUSH1.as_u8()) as usize;
+        let num_pushed = opcode.push_size();
         for (idx, selector) in self.selectors.iter().enumerate() {
-            selector.assign(
-                region,
-                offset,
-                Some(F::from((idx < num_additional_pushed) as u64)),
-            )?;
+            selector.assign(region, offset, Some(F::from((idx < num_pushed) as u64)))?;
         }
 
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+
         Ok(())
     }
 }
@@ -147,10 +157,15 @@ mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::run_test_circuits};
     use eth_types::bytecode;
     use eth_types::evm_types::OpcodeId;
-    use mock::TestContext;
+    use eth_types::Hardfork;
+    use mock::{test_ctx::helpers::*, TestContext};
 
     fn test_ok(opcode: OpcodeId, bytes: &[u8]) {
-        assert!(bytes.len() as u8 == opcode.as_u8() - OpcodeId::PUSH1.as_u8() + 1,);
+        test_ok_with_hardfork(opcode, bytes, Hardfork::London);
+    }
+
+    fn test_ok_with_hardfork(opcode: OpcodeId, bytes: &[u8], hardfork: Hardfork) {
+        assert_eq!(bytes.len(), opcode.push_size());
 
         let mut bytecode = bytecode! {
             .write_op(opcode)
@@ -160,13 +175,15 @@ mod test {
         }
         bytecode.write_op(OpcodeId::STOP);
 
-        assert_eq!(
-            run_test_circuits(
-                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
-                None
-            ),
-            Ok(())
-        );
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _tx| block.hardfork(hardfork),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
     }
 
     #[test]
@@ -189,6 +206,11 @@ mod test {
         );
     }
 
+    #[test]
+    fn push_gadget_push0() {
+        test_ok_with_hardfork(OpcodeId::PUSH0, &[], Hardfork::Shanghai);
+    }
+
     #[test]
     #[ignore]
     fn push_gadget_rand() {
@@ -0,0 +1,548 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+
+/// Gadget for RETURN and REVERT, which halt the current call with the return
+/// data in memory. Depending on the call, the return data is either deployed
+/// as the code of a successful create, or copied into the caller's memory.
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnRevertGadget<F> {
+    opcode: Cell<F>,
+    range: MemoryAddressGadget<F>,
+    is_success: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    // Deployment of the returned code in a successful create
+    address: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    code_hash: Cell<F>,
+    max_code_size: Cell<F>,
+    is_length_over_max_code_size: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    first_byte: Cell<F>,
+    is_first_byte_invalid: IsEqualGadget<F>,
+    // Copy of the return data into caller's memory
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    restore_context: RestoreContextGadget<F>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+    copy_rwc_inc_is_zero: IsZeroGadget<F>,
+    /// Random linear combination of the deployed code, accumulated in the
+    /// copy table.
+    rlc_acc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
+    const NAME: &'static str = "RETURN_REVERT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURN_REVERT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        let memory_offset = cb.query_cell();
+        let memory_length = cb.query_rlc();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(memory_length.expr());
+        let range = MemoryAddressGadget::construct(cb, memory_offset, memory_length);
+
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `RETURN` and `REVERT`.
+        let is_success = cb.call_context(None, CallContextFieldTag::IsSuccess);
+        cb.require_boolean("is_success is boolean", is_success.expr());
+        cb.require_equal(
+            "Opcode should be RETURN if is_success, otherwise REVERT",
+            opcode.expr(),
+            is_success.expr() * OpcodeId::RETURN.expr()
+                + not::expr(is_success.expr()) * OpcodeId::REVERT.expr(),
+        );
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [range.address()],
+        );
+
+        let is_root = cb.curr.state.is_root.expr();
+        let is_create = cb.curr.state.is_create.expr();
+        let is_contract_deployment = is_create.clone() * is_success.expr();
+        let is_return_data_copy = not::expr(is_create) * not::expr(is_root.clone());
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            is_root.clone(),
+            is_to_end_tx,
+        );
+
+        // When it's a successful create, the returned bytes are deployed as the
+        // code of the callee.
+        let copy_rwc_inc = cb.query_cell();
+        let copy_rwc_inc_is_zero = IsZeroGadget::construct(cb, copy_rwc_inc.expr());
+        let code_hash = cb.query_cell();
+        let rlc_acc = cb.query_cell();
+        let max_code_size = cb.query_cell();
+        cb.block_lookup(
+            BlockContextFieldTag::MaxCodeSize.expr(),
+            None,
+            max_code_size.expr(),
+        );
+        let is_length_over_max_code_size =
+            LtGadget::construct(cb, max_code_size.expr(), range.length());
+        let (address, reversion_info) = cb.condition(is_contract_deployment.clone(), |cb| {
+            cb.require_zero(
+                "Deployed code size <= max_code_size",
+                is_length_over_max_code_size.expr(),
+            );
+            cb.require_equal(
+                "Memory reads of the deployed code",
+                copy_rwc_inc.expr(),
+                range.length(),
+            );
+            cb.keccak_table_lookup(rlc_acc.expr(), range.length(), code_hash.expr());
+
+            let address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+            let mut reversion_info = cb.reversion_info(None);
+            let empty_code_hash = Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            );
+            cb.account_write(
+                address.expr(),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+                empty_code_hash,
+                Some(&mut reversion_info),
+            );
+
+            (address, reversion_info)
+        });
+        cb.condition(
+            is_contract_deployment.clone() * copy_rwc_inc_is_zero.expr(),
+            |cb| {
+                cb.require_zero("if deployed code is empty, rlc_acc == 0", rlc_acc.expr());
+            },
+        );
+
+        // The deployed code must not start with 0xef (EIP-3541).
+        let first_byte = cb.query_cell();
+        let is_first_byte_invalid = IsEqualGadget::construct(cb, first_byte.expr(), 0xef.expr());
+        cb.condition(is_contract_deployment.clone() * range.has_length(), |cb| {
+            cb.memory_lookup(false.expr(), range.offset(), first_byte.expr(), None);
+            cb.require_zero(
+                "Deployed code does not start with 0xef",
+                is_first_byte_invalid.expr(),
+            );
+        });
+
+        // When it's an internal call, the return data is copied into the
+        // caller's memory, up to the size requested by the caller.
+        let (return_data_offset, return_data_length, copy_length) =
+            cb.condition(is_return_data_copy.clone(), |cb| {
+                let [return_data_offset, return_data_length] = [
+                    CallContextFieldTag::ReturnDataOffset,
+                    CallContextFieldTag::ReturnDataLength,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag));
+                let copy_length =
+                    MinMaxGadget::construct(cb, return_data_length.expr(), range.length());
+                cb.require_equal(
+                    "Memory reads and writes of the copied return data",
+                    copy_rwc_inc.expr(),
+                    copy_length.min() + copy_length.min(),
+                );
+
+                (return_data_offset, return_data_length, copy_length)
+            });
+        cb.condition(
+            not::expr(is_contract_deployment.clone()) * not::expr(is_return_data_copy.clone()),
+            |cb| {
+                cb.require_zero(
+                    "no bytes to copy, copy table rwc inc == 0",
+                    copy_rwc_inc.expr(),
+                );
+            },
+        );
+
+        let gas_cost = memory_expansion.gas_cost()
+            + is_contract_deployment.clone()
+                * GasCost::CODE_DEPOSIT_BYTE_COST.expr()
+                * range.length();
+
+        // When it's a root call
+        cb.condition(is_root.clone(), |cb| {
+            // Do step state transition, where the reversions of a failed
+            // transaction are done right after this step.
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(
+                    cb.rw_counter_offset()
+                        + copy_rwc_inc.expr()
+                        + not::expr(is_success.expr())
+                            * cb.curr.state.reversible_write_counter.expr(),
+                ),
+                gas_left: Delta(-gas_cost.clone()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(is_root), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                is_success.expr(),
+                cb.rw_counter_offset() + copy_rwc_inc.expr(),
+                not::expr(is_contract_deployment.clone()) * range.offset(),
+                not::expr(is_contract_deployment.clone()) * range.length(),
+                gas_cost,
+                is_contract_deployment.clone(),
+            )
+        });
+
+        // The copy steps are done after all the other rw lookups of this step.
+        cb.condition(
+            is_contract_deployment * not::expr(copy_rwc_inc_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::RlcAcc.expr(),
+                    range.offset(),
+                    range.address(),
+                    0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                    range.length(),
+                    rlc_acc.expr(),
+                    cb.curr.state.rw_counter.expr() + cb.rw_counter_offset(),
+                    copy_rwc_inc.expr(),
+                );
+            },
+        );
+        cb.condition(
+            is_return_data_copy * not::expr(copy_rwc_inc_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    restore_context.caller_id(),
+                    CopyDataType::Memory.expr(),
+                    range.offset(),
+                    range.offset() + copy_length.min(),
+                    return_data_offset.expr(),
+                    copy_length.min(),
+                    0.expr(), // for return data copy, rlc_acc is 0
+                    cb.curr.state.rw_counter.expr() + cb.rw_counter_offset(),
+                    copy_rwc_inc.expr(),
+                );
+            },
+        );
+
+        Self {
+            opcode,
+            range,
+            is_success,
+            memory_expansion,
+            address,
+            reversion_info,
+            code_hash,
+            max_code_size,
+            is_length_over_max_code_size,
+            first_byte,
+            is_first_byte_invalid,
+            return_data_offset,
+            return_data_length,
+            copy_length,
+            restore_context,
+            copy_rwc_inc,
+            copy_rwc_inc_is_zero,
+            rlc_acc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, length] =
+            [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let range = self
+            .range
+            .assign(region, offset, memory_offset, length, block.randomness)?;
+        let length = length.low_u64();
+
+        self.is_success
+            .assign(region, offset, Some(F::from(call.is_success as u64)))?;
+
+        self.memory_expansion
+            .assign(region, offset, step.memory_word_size(), [range])?;
+
+        let is_contract_deployment = call.is_create && call.is_success;
+        let is_return_data_copy = !call.is_create && !call.is_root;
+
+        let max_code_size = F::from(block.context.max_code_size);
+        self.max_code_size
+            .assign(region, offset, Some(max_code_size))?;
+        self.is_length_over_max_code_size
+            .assign(region, offset, max_code_size, F::from(length))?;
+
+        let mut rw_offset = 3;
+        if is_contract_deployment {
+            let address = block.rws[step.rw_indices[3]].call_context_value();
+            self.address.assign(region, offset, address.to_scalar())?;
+            self.reversion_info.assign(
+                region,
+                offset,
+                call.rw_counter_end_of_reversion,
+                call.is_persistent,
+            )?;
+            let (code_hash, _) = block.rws[step.rw_indices[6]].account_value_pair();
+            self.code_hash.assign(
+                region,
+                offset,
+                Some(Word::random_linear_combine(
+                    code_hash.to_le_bytes(),
+                    block.randomness,
+                )),
+            )?;
+            rw_offset += 4;
+        }
+
+        let first_byte = if is_contract_deployment && length > 0 {
+            let first_byte = block.rws[step.rw_indices[rw_offset]].memory_value();
+            rw_offset += 1;
+            first_byte
+        } else {
+            0
+        };
+        self.first_byte
+            .assign(region, offset, Some(F::from(first_byte as u64)))?;
+        self.is_first_byte_invalid.assign(
+            region,
+            offset,
+            F::from(first_byte as u64),
+            F::from(0xef),
+        )?;
+
+        let copy_length = if is_return_data_copy {
+            let [return_data_offset, return_data_length] =
+                [3, 4].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+            self.return_data_offset
+                .assign(region, offset, return_data_offset.to_scalar())?;
+            self.return_data_length
+                .assign(region, offset, return_data_length.to_scalar())?;
+            self.copy_length.assign(
+                region,
+                offset,
+                F::from(return_data_length.low_u64()),
+                F::from(length),
+            )?;
+            rw_offset += 2;
+            return_data_length.low_u64().min(length)
+        } else {
+            0
+        };
+
+        self.restore_context
+            .assign(region, offset, block, call, step, rw_offset)?;
+
+        let (copy_rwc_inc, rlc_acc) = if is_contract_deployment {
+            let rlc_acc = block
+                .copy_event((tx.id, call.id, step.program_counter as usize))
+                .map_or(F::zero(), |copy_event| {
+                    copy_event
+                        .steps
+                        .iter()
+                        .filter(|cs| cs.rw.is_write())
+                        .fold(F::zero(), |acc, cs| {
+                            acc * block.randomness + F::from(cs.value as u64)
+                        })
+                });
+            (length, rlc_acc)
+        } else {
+            (2 * copy_length, F::zero())
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Some(F::from(copy_rwc_inc)))?;
+        self.copy_rwc_inc_is_zero
+            .assign(region, offset, F::from(copy_rwc_inc))?;
+        self.rlc_acc.assign(region, offset, Some(rlc_acc))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{address, bytecode, evm_types::OpcodeId, Address, Bytecode, ToWord, Word};
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    const CALLEE_ADDRESS: Address = Address::repeat_byte(0xff);
+    const CALLER_ADDRESS: Address = Address::repeat_byte(0x34);
+
+    fn callee_bytecode(is_return: bool, offset: u64, length: u64) -> Bytecode {
+        let mut code = bytecode! {
+            // Make a reversible write, which is reverted by REVERT
+            PUSH1(0xff)
+            PUSH1(0x00)
+            SSTORE
+            // Store 10 bytes at the end of the first memory word
+            PUSH10(Word::from_big_endian(&[0x60; 10]))
+            PUSH1(0x00)
+            MSTORE
+            PUSH2(length)
+            PUSH2(22 + offset)
+        };
+        code.write_op(if is_return {
+            OpcodeId::RETURN
+        } else {
+            OpcodeId::REVERT
+        });
+        code
+    }
+
+    fn caller_bytecode(return_data_offset: u64, return_data_length: u64) -> Bytecode {
+        bytecode! {
+            PUSH32(return_data_length)
+            PUSH32(return_data_offset)
+            PUSH1(0x00) // call data length
+            PUSH1(0x00) // call data offset
+            PUSH1(0x00) // value
+            PUSH32(CALLEE_ADDRESS.to_word())
+            PUSH32(40000) // gas
+            CALL
+            STOP
+        }
+    }
+
+    fn test_ok(callee_code: Bytecode, caller_code: Option<Bytecode>) {
+        let (to, caller_code) = match caller_code {
+            Some(caller_code) => (CALLER_ADDRESS, caller_code),
+            None => (CALLEE_ADDRESS, Bytecode::default()),
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 30));
+                accs[1].address(CALLEE_ADDRESS).code(callee_code);
+                accs[2].address(CALLER_ADDRESS).code(caller_code);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(to)
+                    .gas(Word::from(100_000u64));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn return_revert_gadget_root() {
+        let test_parameters = [(0, 0), (0, 10), (300, 20), (1000, 0)];
+        for ((offset, length), is_return) in
+            test_parameters.iter().cartesian_product(&[true, false])
+        {
+            test_ok(callee_bytecode(*is_return, *offset, *length), None);
+        }
+    }
+
+    #[test]
+    fn return_revert_gadget_nonroot() {
+        let test_parameters = [
+            ((0, 0), (0, 0)),
+            ((0, 10), (0, 10)),
+            ((0, 10), (0, 20)),
+            ((0, 20), (0, 10)),
+            ((64, 1), (0, 10)), // Expands memory in RETURN/REVERT opcode
+            ((0, 10), (1000, 0)),
+            ((1000, 0), (0, 10)),
+            ((1000, 0), (1000, 0)),
+        ];
+        for (((callee_offset, callee_length), (caller_offset, caller_length)), is_return) in
+            test_parameters.iter().cartesian_product(&[true, false])
+        {
+            test_ok(
+                callee_bytecode(*is_return, *callee_offset, *callee_length),
+                Some(caller_bytecode(*caller_offset, *caller_length)),
+            );
+        }
+    }
+
+    #[test]
+    fn return_revert_gadget_deploy_code() {
+        // RETURN the 4 bytes 0x6001600a of the deployed code, which are stored
+        // at the end of the first memory word.
+        let initcode = bytecode! {
+            PUSH4(0x6001600a)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04)
+            PUSH1(0x1c)
+            RETURN
+        }
+        .to_vec();
+
+        // Store the initcode at the start of the memory, left-padded within
+        // 32-byte words.
+        let mut caller_code = Bytecode::default();
+        let words = (initcode.len() + 31) / 32;
+        let mut padded = vec![0; words * 32 - initcode.len()];
+        padded.extend_from_slice(&initcode);
+        for (idx, word) in padded.chunks(32).enumerate() {
+            caller_code.append(&bytecode! {
+                PUSH32(Word::from_big_endian(word))
+                PUSH2(idx * 32)
+                MSTORE
+            });
+        }
+        caller_code.append(&bytecode! {
+            PUSH2(initcode.len())
+            PUSH2(padded.len() - initcode.len())
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        });
+
+        test_ok(Bytecode::default(), Some(caller_code));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::RangeCheckGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            not, CachedRegion, Cell, MemoryAddress,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnDataCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    data_offset: MemoryAddress<F>,
+    last_callee_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_size: Cell<F>,
+    // Out of bound reads of the return data are handled by
+    // ErrorReturnDataOutOfBoundGadget, so here we require
+    // `data_offset + length <= return_data_size`.
+    in_bound_check: RangeCheckGadget<F, N_BYTES_U64>,
+    copy_rwc_inc: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
+    const NAME: &'static str = "RETURNDATACOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURNDATACOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_rlc();
+        let length = cb.query_rlc();
+
+        // Pop memory_offset, data_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Lookup the last callee and its return data in the call context
+        let last_callee_id = cb.query_cell();
+        let return_data_offset = cb.query_cell();
+        let return_data_size = cb.query_cell();
+        for (field_tag, value) in [
+            (CallContextFieldTag::LastCalleeId, last_callee_id.expr()),
+            (
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                return_data_offset.expr(),
+            ),
+            (
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                return_data_size.expr(),
+            ),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, value);
+        }
+
+        // Check that the copied range is within the return data
+        let in_bound_check = RangeCheckGadget::construct(
+            cb,
+            return_data_size.expr()
+                - (from_bytes::expr(&data_offset.cells) + memory_address.length()),
+        );
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Copy the return data from the last callee's memory
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                last_callee_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                return_data_offset.expr() + from_bytes::expr(&data_offset.cells),
+                return_data_offset.expr() + return_data_size.expr(),
+                memory_address.offset(),
+                memory_address.length(),
+                0.expr(), // for RETURNDATACOPY, rlc_acc is 0
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr(),
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(memory_address.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 3 stack pop + 3 call context lookup + memory reads and writes
+            rw_counter: Delta(cb.rw_counter_offset() + copy_rwc_inc.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::RETURNDATACOPY.constant_gas_cost().expr()
+                    + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            memory_address,
+            data_offset,
+            last_callee_id,
+            return_data_offset,
+            return_data_size,
+            in_bound_check,
+            copy_rwc_inc,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, data_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        self.data_offset.assign(
+            region,
+            offset,
+            Some(
+                data_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let [last_callee_id, return_data_offset, return_data_size] =
+            [step.rw_indices[3], step.rw_indices[4], step.rw_indices[5]]
+                .map(|idx| block.rws[idx].call_context_value());
+        for (cell, value) in [
+            (&self.last_callee_id, last_callee_id),
+            (&self.return_data_offset, return_data_offset),
+            (&self.return_data_size, return_data_size),
+        ] {
+            cell.assign(region, offset, Some(F::from(value.as_u64())))?;
+        }
+
+        self.in_bound_check.assign(
+            region,
+            offset,
+            F::from(return_data_size.as_u64() - (data_offset.as_u64() + length.as_u64())),
+        )?;
+
+        let key = (tx.id, call.id, step.program_counter as usize);
+        let copy_rwc_inc = block
+            .copy_event(key)
+            .and_then(|copy_event| copy_event.steps.first())
+            .map_or(F::zero(), |cs| F::from(cs.rwc_inc_left));
+        self.copy_rwc_inc
+            .assign(region, offset, Some(copy_rwc_inc))?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{evm_circuit::test::rand_bytes, test_util::run_test_circuits};
+    use eth_types::{bytecode, ToWord, Word};
+    use mock::test_ctx::TestContext;
+
+    fn test_ok_internal(
+        return_data_offset: usize,
+        return_data_size: usize,
+        dst_offset: usize,
+        offset: usize,
+        size: usize,
+    ) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A and returns part of its memory.
+        let pushdata = rand_bytes(32);
+        let code_b = bytecode! {
+            PUSH32(Word::from_big_endian(&pushdata))
+            PUSH1(0x00) // offset
+            MSTORE
+            PUSH32(return_data_size)
+            PUSH32(return_data_offset)
+            RETURN
+        };
+
+        // code A calls code B and copies the returned data.
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(size) // size
+            PUSH32(offset) // offset
+            PUSH32(dst_offset) // dst_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn returndatacopy_gadget_simple() {
+        test_ok_internal(0x00, 0x20, 0x00, 0x00, 0x20);
+        test_ok_internal(0x10, 0x20, 0x40, 0x08, 0x10);
+    }
+
+    #[test]
+    fn returndatacopy_gadget_large() {
+        test_ok_internal(0x00, 0x204, 0x103, 0x102, 0x101);
+    }
+
+    #[test]
+    fn returndatacopy_gadget_zero_length() {
+        test_ok_internal(0x00, 0x20, 0x00, 0x00, 0x00);
+        test_ok_internal(0x00, 0x00, 0x40, 0x00, 0x00);
+    }
+}
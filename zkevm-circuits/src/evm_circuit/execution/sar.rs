@@ -0,0 +1,185 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            math_gadget::{LtGadget, ShrWordsGadget},
+            CachedRegion, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian, U256};
+use halo2_proofs::plonk::Error;
+
+/// SarGadget verifies the arithmetic shift right `a >> shift == b` by doing a
+/// logical shift right on the ones' complement of `a` when `a` is negative,
+/// since `(!a) >> shift == !(a >> shift)` holds for the arithmetic shift.
+#[derive(Clone, Debug)]
+pub(crate) struct SarGadget<F> {
+    same_context: SameContextGadget<F>,
+    a: Word<F>,
+    b: Word<F>,
+    // `a` when it's non-negative, otherwise its ones' complement.
+    a_complement: Word<F>,
+    // Whether `a` is negative, by checking the most significant byte is
+    // greater than 127.
+    is_neg: LtGadget<F, 1>,
+    shr_words: ShrWordsGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
+    const NAME: &'static str = "SAR";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SAR;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let a = cb.query_word();
+        let shift = cb.query_word();
+        let b = cb.query_word();
+        let a_complement = cb.query_word();
+
+        cb.stack_pop(shift.expr());
+        cb.stack_pop(a.expr());
+
+        let is_neg = LtGadget::construct(cb, 127.expr(), a.cells[31].expr());
+        for (a, a_complement) in a.cells.iter().zip(a_complement.cells.iter()) {
+            cb.require_equal(
+                "a_complement == is_neg ? 255 - a : a",
+                a_complement.expr(),
+                a.expr() + is_neg.expr() * (255.expr() - 2.expr() * a.expr()),
+            );
+        }
+
+        // When `shift >= 256`, the logical shift results in 0, whose ones'
+        // complement 2^256 - 1 is the expected result for a negative `a`.
+        let shr_words = ShrWordsGadget::construct(cb, a_complement.clone(), shift);
+        for (b, shifted) in b.cells.iter().zip(shr_words.b().cells.iter()) {
+            cb.require_equal(
+                "b == is_neg ? 255 - shifted : shifted",
+                b.expr(),
+                shifted.expr() + is_neg.expr() * (255.expr() - 2.expr() * shifted.expr()),
+            );
+        }
+
+        cb.stack_push(b.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::SAR.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            a,
+            b,
+            a_complement,
+            is_neg,
+            shr_words,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        let indices = [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]];
+        let [shift, a, b] = indices.map(|idx| block.rws[idx].stack_value());
+
+        self.a.assign(region, offset, Some(a.to_le_bytes()))?;
+        self.b.assign(region, offset, Some(b.to_le_bytes()))?;
+
+        let (is_neg, _) = self.is_neg.assign(
+            region,
+            offset,
+            127.into(),
+            u64::from(a.to_le_bytes()[31]).into(),
+        )?;
+        let a_complement = if is_neg == F::one() { !a } else { a };
+        self.a_complement
+            .assign(region, offset, Some(a_complement.to_le_bytes()))?;
+
+        let shifted = if shift < U256::from(256) {
+            a_complement >> shift.as_usize()
+        } else {
+            U256::zero()
+        };
+        self.shr_words
+            .assign(region, offset, a_complement, shift, shifted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::test::rand_word;
+    use crate::test_util::run_test_circuits;
+    use eth_types::evm_types::OpcodeId;
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+    use rand::Rng;
+
+    fn test_ok(opcode: OpcodeId, a: Word, shift: Word) {
+        let bytecode = bytecode! {
+            PUSH32(a)
+            PUSH32(shift)
+            #[start]
+            .write_op(opcode)
+            STOP
+        };
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn sar_gadget_simple() {
+        test_ok(OpcodeId::SAR, 0xABCD.into(), 8.into());
+        test_ok(OpcodeId::SAR, 0x1234.into(), 7.into());
+        test_ok(OpcodeId::SAR, 0x8765.into(), 17.into());
+        test_ok(OpcodeId::SAR, 0x4321.into(), 0.into());
+        let rand_shift = rand::thread_rng().gen_range(0..=255);
+        test_ok(OpcodeId::SAR, rand_word(), rand_shift.into());
+    }
+
+    #[test]
+    fn sar_gadget_negative() {
+        test_ok(OpcodeId::SAR, Word::MAX, 1.into());
+        test_ok(OpcodeId::SAR, Word::MAX - 0xABCD, 8.into());
+        test_ok(OpcodeId::SAR, Word::one() << 255, 63.into());
+        test_ok(OpcodeId::SAR, Word::one() << 255, 64.into());
+        test_ok(OpcodeId::SAR, Word::one() << 255, 255.into());
+        test_ok(OpcodeId::SAR, Word::MAX - 0x8765, 129.into());
+    }
+
+    #[test]
+    fn sar_gadget_rand_overflow_shift() {
+        test_ok(OpcodeId::SAR, rand_word(), 256.into());
+        test_ok(OpcodeId::SAR, Word::MAX - 0x1234, 256.into());
+        test_ok(OpcodeId::SAR, 0x1234.into(), 0x1234.into());
+        test_ok(
+            OpcodeId::SAR,
+            Word::one() << 255,
+            Word::from_big_endian(&[255_u8; 32]),
+        );
+    }
+}
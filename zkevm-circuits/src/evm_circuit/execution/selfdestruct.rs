@@ -0,0 +1,497 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::{HardforkGadget, RestoreContextGadget, UpdateBalanceGadget},
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            from_bytes,
+            math_gadget::{BatchedIsZeroGadget, IsEqualGadget, IsZeroGadget},
+            select, sum, CachedRegion, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::GasCost, Field, Hardfork, ToAddress, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+use keccak256::EMPTY_HASH_LE;
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SelfdestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    callee_address: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_warm_prev: Cell<F>,
+    beneficiary_nonce: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    value: Word<F>,
+    beneficiary_balance: UpdateBalanceGadget<F, 2, true>,
+    is_beneficiary_self: IsEqualGadget<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_beneficiary_empty: BatchedIsZeroGadget<F, 2>,
+    is_empty_code_hash: IsEqualGadget<F>,
+    is_destructed_prev: Cell<F>,
+    hardfork: HardforkGadget<F>,
+    refund_prev: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfdestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `SELFDESTRUCT`.
+        cb.require_equal(
+            "Opcode should be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary = cb.query_rlc();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells);
+
+        let [tx_id, is_static, callee_address] = [
+            CallContextFieldTag::TxId,
+            CallContextFieldTag::IsStatic,
+            CallContextFieldTag::CalleeAddress,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+        let mut reversion_info = cb.reversion_info(None);
+
+        // Write protection in static call is handled by ErrorWriteProtection.
+        cb.require_zero("SELFDESTRUCT is not in static call", is_static.expr());
+
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+
+        // Add beneficiary into access list
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary_address.clone(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let [beneficiary_nonce, beneficiary_code_hash] =
+            [AccountFieldTag::Nonce, AccountFieldTag::CodeHash].map(|field_tag| {
+                let value = cb.query_cell();
+                cb.account_read(beneficiary_address.clone(), field_tag, value.expr());
+                value
+            });
+
+        // Transfer the whole balance to beneficiary, which is credited before
+        // the balance of the destructed account is cleared, so the balance is
+        // burnt when the beneficiary is the account itself.
+        let value = cb.query_word();
+        let beneficiary_balance = UpdateBalanceGadget::construct(
+            cb,
+            beneficiary_address.clone(),
+            vec![value.clone()],
+            Some(&mut reversion_info),
+        );
+        let is_beneficiary_self =
+            IsEqualGadget::construct(cb, beneficiary_address, callee_address.expr());
+        cb.condition(is_beneficiary_self.expr(), |cb| {
+            cb.require_equal(
+                "value == beneficiary_balance_prev when beneficiary is the account itself",
+                value.expr(),
+                beneficiary_balance.balance_prev().expr(),
+            );
+        });
+        cb.account_write(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            0.expr(),
+            select::expr(
+                is_beneficiary_self.expr(),
+                beneficiary_balance.balance().expr(),
+                value.expr(),
+            ),
+            Some(&mut reversion_info),
+        );
+
+        // Mark the account as destructed, which takes effect at the end of tx
+        let is_destructed_prev = cb.query_bool();
+        cb.account_destructed_write(
+            tx_id.expr(),
+            callee_address.expr(),
+            1.expr(),
+            is_destructed_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Refund is only given for the first destruction of the account, and
+        // EIP-3529 removed it since London.
+        let hardfork = HardforkGadget::construct(cb);
+        let refund = select::expr(
+            hardfork.is_activated(Hardfork::London),
+            GasCost::SELFDESTRUCT_REFUND.expr(),
+            GasCost::SELFDESTRUCT_REFUND_PRE_LONDON.expr(),
+        );
+        let refund_prev = cb.query_cell();
+        cb.tx_refund_write(
+            tx_id.expr(),
+            refund_prev.expr() + (1.expr() - is_destructed_prev.expr()) * refund,
+            refund_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Sum up gas cost, where creating the beneficiary costs NEW_ACCOUNT
+        // when there is value to transfer.
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let is_beneficiary_empty = BatchedIsZeroGadget::construct(
+            cb,
+            [
+                beneficiary_nonce.expr(),
+                beneficiary_balance.balance_prev().expr(),
+            ],
+        );
+        let is_empty_code_hash = IsEqualGadget::construct(
+            cb,
+            beneficiary_code_hash.expr(),
+            Word::random_linear_combine_expr(
+                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
+                cb.power_of_randomness(),
+            ),
+        );
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + (1.expr() - is_warm_prev.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + (1.expr() - value_is_zero.expr())
+                * is_beneficiary_empty.expr()
+                * is_empty_code_hash.expr()
+                * GasCost::NEW_ACCOUNT.expr();
+
+        // The 5 reversible writes above
+        let reversible_write_counter_increase = 5.expr();
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            // When a transaction ends with SELFDESTRUCT, this call must be persistent
+            cb.require_equal(
+                "is_persistent == 1 for root call",
+                reversion_info.is_persistent(),
+                1.expr(),
+            );
+
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.clone()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                1.expr(),
+                cb.rw_counter_offset(),
+                0.expr(),
+                0.expr(),
+                gas_cost,
+                reversible_write_counter_increase,
+            )
+        });
+
+        Self {
+            opcode,
+            beneficiary,
+            tx_id,
+            is_static,
+            callee_address,
+            reversion_info,
+            is_warm_prev,
+            beneficiary_nonce,
+            beneficiary_code_hash,
+            value,
+            beneficiary_balance,
+            is_beneficiary_self,
+            value_is_zero,
+            is_beneficiary_empty,
+            is_empty_code_hash,
+            is_destructed_prev,
+            hardfork,
+            refund_prev,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary.assign(
+            region,
+            offset,
+            Some(
+                beneficiary.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let [tx_id, is_static, callee_address] =
+            [1, 2, 3].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        self.tx_id.assign(region, offset, tx_id.to_scalar())?;
+        self.is_static
+            .assign(region, offset, is_static.to_scalar())?;
+        self.callee_address
+            .assign(region, offset, callee_address.to_scalar())?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+
+        let (_, is_warm_prev) = block.rws[step.rw_indices[7]].tx_access_list_value_pair();
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+
+        let [(beneficiary_nonce, _), (beneficiary_code_hash, _), (beneficiary_balance, beneficiary_balance_prev), (_, balance_prev)] =
+            [8, 9, 10, 11].map(|idx| block.rws[step.rw_indices[idx]].account_value_pair());
+        self.beneficiary_nonce
+            .assign(region, offset, beneficiary_nonce.to_scalar())?;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                beneficiary_code_hash.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        let beneficiary_address = beneficiary.to_address();
+        let is_beneficiary_self = beneficiary_address == callee_address.to_address();
+        let value = if is_beneficiary_self {
+            beneficiary_balance_prev
+        } else {
+            balance_prev
+        };
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.beneficiary_balance.assign(
+            region,
+            offset,
+            beneficiary_balance_prev,
+            vec![value],
+            beneficiary_balance,
+        )?;
+        self.is_beneficiary_self.assign(
+            region,
+            offset,
+            beneficiary_address.to_scalar().unwrap(),
+            callee_address.to_scalar().unwrap(),
+        )?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        self.is_beneficiary_empty.assign(
+            region,
+            offset,
+            [
+                F::from(beneficiary_nonce.low_u64()),
+                Word::random_linear_combine(
+                    beneficiary_balance_prev.to_le_bytes(),
+                    block.randomness,
+                ),
+            ],
+        )?;
+        self.is_empty_code_hash.assign(
+            region,
+            offset,
+            Word::random_linear_combine(beneficiary_code_hash.to_le_bytes(), block.randomness),
+            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+        )?;
+
+        let (_, is_destructed_prev) =
+            block.rws[step.rw_indices[12]].account_destructed_value_pair();
+        self.is_destructed_prev
+            .assign(region, offset, Some(F::from(is_destructed_prev as u64)))?;
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
+        let (_, refund_prev) = block.rws[step.rw_indices[13]].tx_refund_value_pair();
+        self.refund_prev
+            .assign(region, offset, Some(F::from(refund_prev)))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step, 14)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        test::run_test_circuit_incomplete_fixed_table, witness::block_convert,
+    };
+    use eth_types::{
+        address, bytecode, geth_types::Account, Address, Bytecode, Hardfork, ToWord, Word,
+    };
+    use mock::TestContext;
+
+    fn contract(code: Bytecode) -> Account {
+        Account {
+            address: Address::repeat_byte(0xfe),
+            balance: Word::from(10).pow(18.into()),
+            code: code.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    fn test_ok(contract: Account, is_root: bool) {
+        test_ok_with_hardfork(contract, is_root, Hardfork::London);
+    }
+
+    fn test_ok_with_hardfork(contract: Account, is_root: bool, hardfork: Hardfork) {
+        let caller = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH32(contract.address.to_word())
+            PUSH2(50000)
+            CALL
+            STOP
+        };
+        let block = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(contract.address)
+                    .code(contract.code)
+                    .balance(contract.balance);
+                accs[2]
+                    .address(Address::repeat_byte(0xff))
+                    .code(caller)
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(if is_root {
+                        accs[1].address
+                    } else {
+                        accs[2].address
+                    })
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64).hardfork(hardfork),
+        )
+        .unwrap()
+        .into();
+        let block_data = bus_mapping::mock::BlockData::new_from_geth_data(block);
+        let mut builder = block_data.new_circuit_input_builder();
+        builder
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_simple() {
+        let beneficiaries = [
+            // Empty account
+            address!("0x00000000000000000000000000000000000000be"),
+            // Existing account, which is also warm as the sender
+            address!("0x000000000000000000000000000000000000cafe"),
+            // The account itself
+            Address::repeat_byte(0xfe),
+        ];
+        for beneficiary in beneficiaries {
+            for is_root in [true, false] {
+                test_ok(
+                    contract(bytecode! {
+                        PUSH20(beneficiary.to_word())
+                        SELFDESTRUCT
+                    }),
+                    is_root,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn selfdestruct_gadget_twice() {
+        // The account calls itself to SELFDESTRUCT in an internal call first,
+        // and the second SELFDESTRUCT of the same account gets no refund.
+        let code = bytecode! {
+            ADDRESS
+            CALLER
+            EQ
+            PUSH1(22)
+            JUMPI
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            ADDRESS
+            PUSH2(20000)
+            CALL
+            POP
+            JUMPDEST // 22
+            PUSH1(0xbe)
+            SELFDESTRUCT
+        };
+        test_ok(contract(code), true);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_pre_london() {
+        // SELFDESTRUCT gets 24000 gas refunded before London
+        for is_root in [true, false] {
+            test_ok_with_hardfork(
+                contract(bytecode! {
+                    PUSH1(0xbe)
+                    SELFDESTRUCT
+                }),
+                is_root,
+                Hardfork::Berlin,
+            );
+        }
+    }
+}
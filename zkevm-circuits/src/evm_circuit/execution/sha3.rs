@@ -0,0 +1,227 @@
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition},
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+
+use super::ExecutionGadget;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Sha3Gadget<F> {
+    same_context: SameContextGadget<F>,
+    /// The memory region that is hashed.
+    memory_address: MemoryAddressGadget<F>,
+    /// The keccak256 digest of the memory region.
+    sha3_rlc: Word<F>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+    /// Random linear combination of the hashed bytes, accumulated in the copy
+    /// table.
+    rlc_acc: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    /// Opcode SHA3 has a dynamic gas cost:
+    /// gas_cost = static_gas + 6 * minimum_word_size + memory_expansion_cost
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Sha3Gadget<F> {
+    const NAME: &'static str = "SHA3";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SHA3;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let offset = cb.query_cell();
+        let size = cb.query_rlc();
+        let sha3_rlc = cb.query_word();
+
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(size.expr());
+        cb.stack_push(sha3_rlc.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, offset, size);
+
+        let copy_rwc_inc = cb.query_cell();
+        let rlc_acc = cb.query_cell();
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                memory_address.offset(),
+                memory_address.address(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                memory_address.length(),
+                rlc_acc.expr(),
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr(),
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(memory_address.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to hash, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+            cb.require_zero("if no bytes to hash, rlc_acc == 0", rlc_acc.expr());
+        });
+
+        cb.keccak_table_lookup(rlc_acc.expr(), memory_address.length(), sha3_rlc.expr());
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Transition::Delta(cb.rw_counter_offset() + copy_rwc_inc.expr()),
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta(1.expr()),
+            memory_word_size: Transition::To(memory_expansion.next_memory_word_size()),
+            gas_left: Transition::Delta(
+                -OpcodeId::SHA3.constant_gas_cost().expr() - memory_copier_gas.gas_cost(),
+            ),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            memory_address,
+            sha3_rlc,
+            copy_rwc_inc,
+            rlc_acc,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, size, sha3_output] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, size, block.randomness)?;
+        self.sha3_rlc
+            .assign(region, offset, Some(sha3_output.to_le_bytes()))?;
+
+        let (copy_rwc_inc, rlc_acc) = if size.is_zero() {
+            (F::zero(), F::zero())
+        } else {
+            let copy_event = block
+                .copy_event((tx.id, call.id, step.program_counter as usize))
+                .unwrap();
+            let copy_rwc_inc = copy_event
+                .steps
+                .first()
+                .map_or(F::zero(), |cs| F::from(cs.rwc_inc_left));
+            let rlc_acc = copy_event
+                .steps
+                .iter()
+                .filter(|cs| cs.rw.is_write())
+                .fold(F::zero(), |acc, cs| {
+                    acc * block.randomness + F::from(cs.value as u64)
+                });
+            (copy_rwc_inc, rlc_acc)
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Some(copy_rwc_inc))?;
+        self.rlc_acc.assign(region, offset, Some(rlc_acc))?;
+
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    use crate::test_util::run_test_circuits;
+
+    fn test_ok(offset: impl Into<Word>, size: usize) {
+        let offset: Word = offset.into();
+        let value = Word::from_big_endian(&(1..=32).collect::<Vec<u8>>());
+        let code = bytecode! {
+            PUSH32(value)
+            PUSH32(Word::from(0x00))
+            MSTORE
+            PUSH32(value)
+            PUSH32(Word::from(0x20))
+            MSTORE
+            PUSH32(Word::from(size))
+            PUSH32(offset)
+            SHA3
+            STOP
+        };
+
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+                None,
+            ),
+            Ok(()),
+        );
+    }
+
+    #[test]
+    fn sha3_gadget_zero_length() {
+        test_ok(0x20, 0x00);
+        // The offset is ignored when the size is 0, so it could be beyond u64.
+        test_ok(Word::MAX, 0x00);
+    }
+
+    #[test]
+    fn sha3_gadget_simple() {
+        test_ok(0x00, 0x08);
+        test_ok(0x10, 0x20);
+        test_ok(0x18, 0x28);
+    }
+
+    #[test]
+    fn sha3_gadget_memory_expansion() {
+        test_ok(0x30, 0x40);
+        test_ok(0x101, 0x21);
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
         step::ExecutionState,
         table::CallContextFieldTag,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{HardforkGadget, SameContextGadget},
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
             },
@@ -16,7 +16,10 @@ use crate::{
     util::Expr,
 };
 
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use eth_types::{
+    evm_types::{gas_utils::sstore_clears_schedule, GasCost},
+    Field, Hardfork, ToLittleEndian, ToScalar,
+};
 use halo2_proofs::plonk::{Error, Expression};
 
 #[derive(Clone, Debug)]
@@ -33,6 +36,7 @@ pub(crate) struct SstoreGadget<F> {
     is_warm: Cell<F>,
     tx_refund_prev: Cell<F>,
     gas_cost: SstoreGasGadget<F>,
+    hardfork: HardforkGadget<F>,
     tx_refund: SstoreTxRefundGadget<F>,
 }
 
@@ -92,12 +96,18 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         );
 
         let tx_refund_prev = cb.query_cell();
+        let hardfork = HardforkGadget::construct(cb);
         let tx_refund = SstoreTxRefundGadget::construct(
             cb,
             tx_refund_prev.clone(),
             value.clone(),
             value_prev.clone(),
             original_value.clone(),
+            select::expr(
+                hardfork.is_activated(Hardfork::London),
+                GasCost::SSTORE_CLEARS_SCHEDULE.expr(),
+                GasCost::SSTORE_CLEARS_SCHEDULE_PRE_LONDON.expr(),
+            ),
         );
         cb.tx_refund_write(
             tx_id.expr(),
@@ -129,6 +139,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             is_warm,
             tx_refund_prev,
             gas_cost,
+            hardfork,
             tx_refund,
         }
     }
@@ -213,6 +224,8 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             block.randomness,
         )?;
 
+        self.hardfork
+            .assign(region, offset, block.context.hardfork)?;
         self.tx_refund.assign(
             region,
             offset,
@@ -221,6 +234,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             value,
             value_prev,
             original_value,
+            block.context.hardfork,
             block.randomness,
         )?;
         Ok(())
@@ -369,6 +383,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value: Cell<F>,
         value_prev: Cell<F>,
         original_value: Cell<F>,
+        sstore_clears_schedule: Expression<F>,
     ) -> Self {
         let value_prev_is_zero_gadget = IsZeroGadget::construct(cb, value_prev.expr());
         let value_is_zero_gadget = IsZeroGadget::construct(cb, value.expr());
@@ -407,10 +422,10 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
 
         let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
+            + delete_slot * sstore_clears_schedule.clone()
             + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
             + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+            - recreate_slot * sstore_clears_schedule;
 
         Self {
             value,
@@ -442,6 +457,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value: eth_types::Word,
         value_prev: eth_types::Word,
         original_value: eth_types::Word,
+        hardfork: Hardfork,
         randomness: F,
     ) -> Result<(), Error> {
         self.tx_refund_old
@@ -501,14 +517,14 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             Word::random_linear_combine(value_prev.to_le_bytes(), randomness),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
+            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value, hardfork),
             tx_refund
         );
         Ok(())
     }
 }
 
-fn calc_expected_gas_cost(
+pub(crate) fn calc_expected_gas_cost(
     value: eth_types::Word,
     value_prev: eth_types::Word,
     original_value: eth_types::Word,
@@ -537,6 +553,7 @@ fn calc_expected_tx_refund(
     value: eth_types::Word,
     value_prev: eth_types::Word,
     original_value: eth_types::Word,
+    hardfork: Hardfork,
 ) -> u64 {
     // Same clause tags(like "delete slot (2.1.2b)") used as [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27)
     // Control flow of this function try to follow `makeGasSStoreFunc` for better
@@ -563,11 +580,11 @@ fn calc_expected_tx_refund(
         if !original_value.is_zero() {
             if value_prev.is_zero() {
                 // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new -= sstore_clears_schedule(hardfork)
             }
             if value.is_zero() {
                 // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new += sstore_clears_schedule(hardfork)
             }
         }
 
@@ -591,7 +608,7 @@ mod test {
 
     use crate::test_util::{run_test_circuits, BytecodeTestConfig};
 
-    use eth_types::{bytecode, Word};
+    use eth_types::{bytecode, Hardfork, Word};
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
     #[test]
@@ -655,7 +672,36 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_pre_london() {
+        // The refund of clearing a slot is 15000 instead of 4800 before London
+        test_ok_with_hardfork(
+            0x030201.into(),
+            0x0.into(),
+            0x060505.into(),
+            0x060506.into(),
+            Hardfork::Berlin,
+        );
+        test_ok_with_hardfork(
+            0x030201.into(),
+            0x060504.into(),
+            0x0.into(),
+            0x060506.into(),
+            Hardfork::Berlin,
+        );
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
+        test_ok_with_hardfork(key, value, value_prev, original_value, Hardfork::London);
+    }
+
+    fn test_ok_with_hardfork(
+        key: Word,
+        value: Word,
+        value_prev: Word,
+        original_value: Word,
+        hardfork: Hardfork,
+    ) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
         // the first SSTORE is used to test cold,  and the second is used to test warm
@@ -693,7 +739,7 @@ mod test {
                         .balance(Word::from(10u64.pow(19)));
                 },
                 tx_from_1_to_0,
-                |block, _txs| block,
+                |block, _txs| block.hardfork(hardfork),
             )
             .unwrap();
             let test_config = BytecodeTestConfig {
@@ -0,0 +1,286 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64},
+        step::ExecutionState,
+        table::{CallContextFieldTag, TxContextFieldTag},
+        util::{
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            not, select, CachedRegion, Cell, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+
+/// Gadget to warm up an entry of the access list of the transaction (EIP
+/// 2930), which is looked up in the tx table at the index kept in the program
+/// counter. The addresses come first, followed by the storage keys with their
+/// addresses.
+#[derive(Clone, Debug)]
+pub(crate) struct TxAccessListGadget<F> {
+    tx_id: Cell<F>,
+    tx_access_list_addresses_len: Cell<F>,
+    tx_access_list_storage_keys_len: Cell<F>,
+    index_lt_addresses_len: LtGadget<F, N_BYTES_U64>,
+    address: Cell<F>,
+    storage_key: Cell<F>,
+    is_warm_prev: Cell<F>,
+    is_last: IsEqualGadget<F>,
+    tx_callee_address: Cell<F>,
+    callee_address_is_zero: IsZeroGadget<F>,
+    callee_address_lt_precompiles_end: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+}
+
+impl<F: Field> ExecutionGadget<F> for TxAccessListGadget<F> {
+    const NAME: &'static str = "TxAccessList";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::TxAccessList;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let index = cb.curr.state.program_counter.clone();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let [tx_access_list_addresses_len, tx_access_list_storage_keys_len, tx_callee_address] = [
+            TxContextFieldTag::AccessListAddressesLen,
+            TxContextFieldTag::AccessListStorageKeysLen,
+            TxContextFieldTag::CalleeAddress,
+        ]
+        .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+
+        // The entries after the addresses are the storage keys.
+        let index_lt_addresses_len =
+            LtGadget::construct(cb, index.expr(), tx_access_list_addresses_len.expr());
+        let is_storage_key = not::expr(index_lt_addresses_len.expr());
+
+        let address = cb.tx_context(
+            tx_id.expr(),
+            TxContextFieldTag::AccessListAddress,
+            Some(index.expr()),
+        );
+        let storage_key = cb.query_cell();
+        let is_warm_prev = cb.query_bool();
+        cb.condition(is_storage_key, |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::AccessListStorageKey,
+                Some(index.expr()),
+                storage_key.expr(),
+            );
+            cb.account_storage_access_list_write(
+                tx_id.expr(),
+                address.expr(),
+                storage_key.expr(),
+                1.expr(),
+                is_warm_prev.expr(),
+                None,
+            );
+        });
+        cb.condition(index_lt_addresses_len.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                address.expr(),
+                1.expr(),
+                is_warm_prev.expr(),
+                None,
+            );
+        });
+
+        let is_last = IsEqualGadget::construct(
+            cb,
+            index.expr() + 1.expr(),
+            tx_access_list_addresses_len.expr() + tx_access_list_storage_keys_len.expr(),
+        );
+        cb.require_equal(
+            "Go to TxAccessList unless it's the last entry",
+            cb.next
+                .execution_state_selector([ExecutionState::TxAccessList]),
+            not::expr(is_last.expr()),
+        );
+
+        // The precompiled contract is executed right after the last entry.
+        let callee_address_is_zero = IsZeroGadget::construct(cb, tx_callee_address.expr());
+        let callee_address_lt_precompiles_end =
+            LtGadget::construct(cb, tx_callee_address.expr(), 10.expr());
+        cb.condition(is_last.expr(), |cb| {
+            cb.require_equal(
+                "Go to PRECOMPILE only when is_precompile",
+                cb.next
+                    .execution_state_selector([ExecutionState::PRECOMPILE]),
+                callee_address_lt_precompiles_end.expr() * not::expr(callee_address_is_zero.expr()),
+            );
+        });
+
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: To(select::expr(
+                is_last.expr(),
+                0.expr(),
+                index.expr() + 1.expr(),
+            )),
+            ..Default::default()
+        });
+
+        Self {
+            tx_id,
+            tx_access_list_addresses_len,
+            tx_access_list_storage_keys_len,
+            index_lt_addresses_len,
+            address,
+            storage_key,
+            is_warm_prev,
+            is_last,
+            tx_callee_address,
+            callee_address_is_zero,
+            callee_address_lt_precompiles_end,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let index = step.program_counter;
+        let (address, storage_key) = tx.access_list[index as usize];
+        let (_, is_warm_prev) = block.rws[step.rw_indices[1]].tx_access_list_value_pair();
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.tx_access_list_addresses_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_addresses_len)),
+        )?;
+        self.tx_access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_storage_keys_len)),
+        )?;
+        self.index_lt_addresses_len.assign(
+            region,
+            offset,
+            F::from(index),
+            F::from(tx.access_list_addresses_len),
+        )?;
+        self.address.assign(region, offset, address.to_scalar())?;
+        self.storage_key.assign(
+            region,
+            offset,
+            Some(storage_key.map_or(F::zero(), |storage_key| {
+                RandomLinearCombination::random_linear_combine(
+                    storage_key.to_le_bytes(),
+                    block.randomness,
+                )
+            })),
+        )?;
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+        self.is_last.assign(
+            region,
+            offset,
+            F::from(index + 1),
+            F::from(tx.access_list_addresses_len + tx.access_list_storage_keys_len),
+        )?;
+        let callee_address = tx.callee_address.to_scalar().unwrap();
+        self.tx_callee_address
+            .assign(region, offset, Some(callee_address))?;
+        self.callee_address_is_zero
+            .assign(region, offset, callee_address)?;
+        self.callee_address_lt_precompiles_end.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(10),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, AccessList, AccessListItem, Word, H256};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+    #[test]
+    fn tx_access_list_gadget_repeated_entries() {
+        // The callee and the storage key repeated in the access list are
+        // already warm when they are warmed up.
+        let code = bytecode! {
+            PUSH1(0x01)
+            SLOAD
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .transaction_type(1)
+                    .access_list(AccessList(vec![
+                        AccessListItem {
+                            address: MOCK_ACCOUNTS[2],
+                            storage_keys: vec![H256::from_low_u64_be(1)],
+                        },
+                        AccessListItem {
+                            address: accs[0].address,
+                            storage_keys: vec![H256::from_low_u64_be(1)],
+                        },
+                        AccessListItem {
+                            address: MOCK_ACCOUNTS[2],
+                            storage_keys: vec![H256::from_low_u64_be(1)],
+                        },
+                    ]));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn tx_access_list_gadget_precompile() {
+        // The precompiled contract is executed after the access list.
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(1u64 << 30));
+                accs[1].address(PrecompileCalls::Identity.address());
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .input(vec![0xca, 0xfe].into())
+                    .gas(Word::from(100_000u64))
+                    .transaction_type(1)
+                    .access_list(AccessList(vec![AccessListItem {
+                        address: MOCK_ACCOUNTS[2],
+                        storage_keys: vec![H256::zero()],
+                    }]));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+}
@@ -15,8 +15,15 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Block, 1),
     (Table::Byte, 24),
     (Table::Copy, 1),
+    (Table::Keccak, 1),
+    (Table::Exp, 1),
+    (Table::Precompile, 1),
 ];
 
+/// Number of columns with equality enabled, for the cells copy constrained to
+/// public inputs.
+pub(crate) const N_COPY_COLUMNS: usize = 1;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -37,6 +44,9 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 
 pub(crate) const STACK_CAPACITY: usize = 1024;
 
+// Number of previous blocks whose hashes are accessible by BLOCKHASH.
+pub(crate) const NUM_PREV_BLOCK_ALLOWED: u64 = 256;
+
 // Number of bytes that will be used of prorgam counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
 // transaction, which could be covered by 3 bytes, we still support program
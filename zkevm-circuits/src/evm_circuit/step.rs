@@ -21,6 +21,7 @@ use strum_macros::EnumIter;
 pub enum ExecutionState {
     // Internal state
     BeginTx,
+    TxAccessList,
     EndTx,
     EndBlock,
     // Opcode successful cases
@@ -74,19 +75,17 @@ pub enum ExecutionState {
     MSIZE,
     GAS,
     JUMPDEST,
-    PUSH, // PUSH1, PUSH2, ..., PUSH32
+    PUSH, // PUSH0, PUSH1, PUSH2, ..., PUSH32
     DUP,  // DUP1, DUP2, ..., DUP16
     SWAP, // SWAP1, SWAP2, ..., SWAP16
     LOG,  // LOG0, LOG1, ..., LOG4
     CREATE,
-    CALL,
-    CALLCODE,
-    RETURN,
-    DELEGATECALL,
+    CALL,          // CALL, CALLCODE, DELEGATECALL, STATICCALL
+    RETURN_REVERT, // RETURN, REVERT
     CREATE2,
-    STATICCALL,
-    REVERT,
     SELFDESTRUCT,
+    // Precompiled contracts
+    PRECOMPILE,
     // Error cases
     ErrorInvalidOpcode,
     ErrorStackOverflow,
@@ -135,7 +134,7 @@ impl ExecutionState {
     }
 
     pub(crate) fn halts_in_success(&self) -> bool {
-        matches!(self, Self::STOP | Self::RETURN | Self::SELFDESTRUCT)
+        matches!(self, Self::STOP | Self::SELFDESTRUCT)
     }
 
     pub(crate) fn halts_in_exception(&self) -> bool {
@@ -174,7 +173,9 @@ impl ExecutionState {
     }
 
     pub(crate) fn halts(&self) -> bool {
-        self.halts_in_success() || self.halts_in_exception() || matches!(self, Self::REVERT)
+        self.halts_in_success()
+            || self.halts_in_exception()
+            || matches!(self, Self::RETURN_REVERT | Self::PRECOMPILE)
     }
 
     pub(crate) fn responsible_opcodes(&self) -> Vec<OpcodeId> {
@@ -232,6 +233,7 @@ impl ExecutionState {
             Self::GAS => vec![OpcodeId::GAS],
             Self::JUMPDEST => vec![OpcodeId::JUMPDEST],
             Self::PUSH => vec![
+                OpcodeId::PUSH0,
                 OpcodeId::PUSH1,
                 OpcodeId::PUSH2,
                 OpcodeId::PUSH3,
@@ -309,17 +311,116 @@ impl ExecutionState {
                 OpcodeId::LOG4,
             ],
             Self::CREATE => vec![OpcodeId::CREATE],
-            Self::CALL => vec![OpcodeId::CALL],
-            Self::CALLCODE => vec![OpcodeId::CALLCODE],
-            Self::RETURN => vec![OpcodeId::RETURN],
-            Self::DELEGATECALL => vec![OpcodeId::DELEGATECALL],
+            Self::CALL => vec![
+                OpcodeId::CALL,
+                OpcodeId::CALLCODE,
+                OpcodeId::DELEGATECALL,
+                OpcodeId::STATICCALL,
+            ],
+            Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::CREATE2 => vec![OpcodeId::CREATE2],
-            Self::STATICCALL => vec![OpcodeId::STATICCALL],
-            Self::REVERT => vec![OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
+            Self::ErrorWriteProtection => vec![
+                OpcodeId::SSTORE,
+                OpcodeId::LOG0,
+                OpcodeId::LOG1,
+                OpcodeId::LOG2,
+                OpcodeId::LOG3,
+                OpcodeId::LOG4,
+                OpcodeId::CREATE,
+                OpcodeId::CALL,
+                OpcodeId::CREATE2,
+                OpcodeId::SELFDESTRUCT,
+            ],
+            Self::ErrorOutOfGasConstant => {
+                // Every opcode with a non-zero constant gas cost, except the
+                // ones whose running out of gas is handled along with their
+                // dynamic gas cost.
+                let dynamic_gas_states = [
+                    Self::ErrorOutOfGasStaticMemoryExpansion,
+                    Self::ErrorOutOfGasDynamicMemoryExpansion,
+                    Self::ErrorOutOfGasMemoryCopy,
+                    Self::ErrorOutOfGasAccountAccess,
+                    Self::ErrorOutOfGasLOG,
+                    Self::ErrorOutOfGasEXP,
+                    Self::ErrorOutOfGasSHA3,
+                    Self::ErrorOutOfGasEXTCODECOPY,
+                    Self::ErrorOutOfGasSLOAD,
+                    Self::ErrorOutOfGasSSTORE,
+                    Self::ErrorOutOfGasCALL,
+                    Self::ErrorOutOfGasCALLCODE,
+                    Self::ErrorOutOfGasDELEGATECALL,
+                    Self::ErrorOutOfGasCREATE2,
+                    Self::ErrorOutOfGasSTATICCALL,
+                    Self::ErrorOutOfGasSELFDESTRUCT,
+                ]
+                .iter()
+                .flat_map(|state| state.responsible_opcodes())
+                .collect::<Vec<_>>();
+                OpcodeId::valid_opcodes()
+                    .into_iter()
+                    .filter(|opcode| {
+                        opcode.constant_gas_cost().as_u64() > 0
+                            && !dynamic_gas_states.contains(opcode)
+                    })
+                    .collect()
+            }
+            Self::ErrorOutOfGasStaticMemoryExpansion => {
+                vec![OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8]
+            }
+            Self::ErrorOutOfGasDynamicMemoryExpansion => {
+                vec![OpcodeId::CREATE, OpcodeId::RETURN, OpcodeId::REVERT]
+            }
+            Self::ErrorOutOfGasMemoryCopy => vec![
+                OpcodeId::CALLDATACOPY,
+                OpcodeId::CODECOPY,
+                OpcodeId::RETURNDATACOPY,
+            ],
+            Self::ErrorOutOfGasAccountAccess => vec![
+                OpcodeId::BALANCE,
+                OpcodeId::EXTCODESIZE,
+                OpcodeId::EXTCODEHASH,
+            ],
+            Self::ErrorOutOfGasCodeStore => vec![OpcodeId::RETURN],
+            Self::ErrorOutOfGasLOG => vec![
+                OpcodeId::LOG0,
+                OpcodeId::LOG1,
+                OpcodeId::LOG2,
+                OpcodeId::LOG3,
+                OpcodeId::LOG4,
+            ],
+            Self::ErrorOutOfGasEXP => vec![OpcodeId::EXP],
+            Self::ErrorOutOfGasSHA3 => vec![OpcodeId::SHA3],
+            Self::ErrorOutOfGasEXTCODECOPY => vec![OpcodeId::EXTCODECOPY],
+            Self::ErrorOutOfGasSLOAD => vec![OpcodeId::SLOAD],
+            Self::ErrorOutOfGasSSTORE => vec![OpcodeId::SSTORE],
+            Self::ErrorOutOfGasCALL => vec![OpcodeId::CALL],
+            Self::ErrorOutOfGasCALLCODE => vec![OpcodeId::CALLCODE],
+            Self::ErrorOutOfGasDELEGATECALL => vec![OpcodeId::DELEGATECALL],
+            Self::ErrorOutOfGasCREATE2 => vec![OpcodeId::CREATE2],
+            Self::ErrorOutOfGasSTATICCALL => vec![OpcodeId::STATICCALL],
+            Self::ErrorOutOfGasSELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
             _ => vec![],
         }
     }
+
+    /// Returns the opcodes responsible for a stack error, each with every
+    /// stack pointer which causes the error when the opcode is executed.
+    pub(crate) fn responsible_invalid_stack_ptrs(&self) -> Vec<(OpcodeId, u32)> {
+        let invalid_stack_ptrs: fn(&OpcodeId) -> Vec<u32> = match self {
+            Self::ErrorStackOverflow => OpcodeId::stack_overflow_ptrs,
+            Self::ErrorStackUnderflow => OpcodeId::stack_underflow_ptrs,
+            _ => return vec![],
+        };
+        OpcodeId::valid_opcodes()
+            .into_iter()
+            .flat_map(|opcode| {
+                invalid_stack_ptrs(&opcode)
+                    .into_iter()
+                    .map(move |stack_ptr| (opcode, stack_ptr))
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1,4 +1,6 @@
-use crate::{evm_circuit::step::ExecutionState, impl_expr};
+use crate::{evm_circuit::step::ExecutionState, impl_expr, util::Expr};
+use bus_mapping::evm::OpcodeId;
+use eth_types::Hardfork;
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Advice, Column, Expression, Fixed, VirtualCells},
@@ -43,6 +45,7 @@ pub enum FixedTableTag {
     BitwiseXor,
     ResponsibleOpcode,
     Pow2,
+    InvalidOpcode,
 }
 
 impl FixedTableTag {
@@ -90,17 +93,38 @@ impl FixedTableTag {
             })),
             Self::ResponsibleOpcode => {
                 Box::new(ExecutionState::iter().flat_map(move |execution_state| {
-                    execution_state
-                        .responsible_opcodes()
+                    // The out of gas error of constant gas cost is looked up
+                    // along with the constant gas cost.
+                    let opcodes =
+                        execution_state
+                            .responsible_opcodes()
+                            .into_iter()
+                            .map(move |opcode| {
+                                let aux =
+                                    if execution_state == ExecutionState::ErrorOutOfGasConstant {
+                                        opcode.constant_gas_cost().as_u64()
+                                    } else {
+                                        0
+                                    };
+                                (opcode, aux)
+                            });
+                    // The stack errors are looked up along with the stack
+                    // pointer causing them.
+                    let invalid_stack_ptrs = execution_state
+                        .responsible_invalid_stack_ptrs()
                         .into_iter()
-                        .map(move |opcode| {
+                        .map(|(opcode, stack_ptr)| (opcode, stack_ptr as u64));
+                    opcodes
+                        .chain(invalid_stack_ptrs)
+                        .map(move |(opcode, aux)| {
                             [
                                 tag,
                                 F::from(execution_state.as_u64()),
                                 F::from(opcode.as_u64()),
-                                F::zero(),
+                                F::from(aux),
                             ]
                         })
+                        .collect::<Vec<_>>()
                 }))
             }
             Self::Pow2 => Box::new((0..65).map(move |value| {
@@ -111,6 +135,18 @@ impl FixedTableTag {
                     F::zero(),
                 ]
             })),
+            Self::InvalidOpcode => Box::new(Hardfork::iter().flat_map(move |hardfork| {
+                OpcodeId::invalid_opcodes(hardfork)
+                    .into_iter()
+                    .map(move |byte| {
+                        [
+                            tag,
+                            F::from(byte as u64),
+                            F::from(hardfork as u64),
+                            F::zero(),
+                        ]
+                    })
+            })),
         }
     }
 }
@@ -126,7 +162,13 @@ pub enum TxContextFieldTag {
     Value,
     CallDataLength,
     CallDataGasCost,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+    AccessListAddressesLen,
+    AccessListStorageKeysLen,
     CallData,
+    AccessListAddress,
+    AccessListStorageKey,
 }
 
 // Keep the sequence consistent with OpcodeId for scalar
@@ -140,6 +182,8 @@ pub enum BlockContextFieldTag {
     BaseFee = 8,
     BlockHash,
     ChainId,
+    Hardfork,
+    MaxCodeSize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
@@ -256,6 +300,9 @@ pub(crate) enum Table {
     Block,
     Byte,
     Copy,
+    Keccak,
+    Exp,
+    Precompile,
 }
 
 #[derive(Clone, Debug)]
@@ -347,12 +394,56 @@ pub(crate) enum Lookup<F> {
         dst_addr: Expression<F>,
         /// The number of bytes to be copied in this copy event.
         length: Expression<F>,
+        /// The random linear combination of the copied bytes, only used when
+        /// the destination is RlcAcc, otherwise should be set to 0.
+        rlc_acc: Expression<F>,
         /// The RW counter at the start of the copy event.
         rw_counter: Expression<F>,
         /// The RW counter that is incremented by the time all bytes have been
         /// copied specific to this copy event.
         rwc_inc: Expression<F>,
     },
+    /// Lookup to keccak table.
+    KeccakTable {
+        /// Accumulator to the input.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// Output (hash) until this state. This is the RLC representation of
+        /// the final output keccak256 hash of the input.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to exponentiation table.
+    ExpTable {
+        /// Identifier of the exponentiation event, which is the RW counter at
+        /// the EXP step.
+        identifier: Expression<F>,
+        /// The low 128 bits of the base.
+        base_lo: Expression<F>,
+        /// The high 128 bits of the base.
+        base_hi: Expression<F>,
+        /// The low 128 bits of the exponent.
+        exponent_lo: Expression<F>,
+        /// The high 128 bits of the exponent.
+        exponent_hi: Expression<F>,
+        /// The low 128 bits of the exponentiation result.
+        exponentiation_lo: Expression<F>,
+        /// The high 128 bits of the exponentiation result.
+        exponentiation_hi: Expression<F>,
+    },
+    /// Lookup to precompile table.
+    PrecompileTable {
+        /// Address of the precompiled contract.
+        address: Expression<F>,
+        /// Random linear combination of the input.
+        input_rlc: Expression<F>,
+        /// Length of the input.
+        input_len: Expression<F>,
+        /// Random linear combination of the output.
+        output_rlc: Expression<F>,
+        /// Length of the output.
+        output_len: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -371,6 +462,9 @@ impl<F: FieldExt> Lookup<F> {
             Self::Block { .. } => Table::Block,
             Self::Byte { .. } => Table::Byte,
             Self::CopyTable { .. } => Table::Copy,
+            Self::KeccakTable { .. } => Table::Keccak,
+            Self::ExpTable { .. } => Table::Exp,
+            Self::PrecompileTable { .. } => Table::Precompile,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -429,6 +523,7 @@ impl<F: FieldExt> Lookup<F> {
                 src_addr_end,
                 dst_addr,
                 length,
+                rlc_acc,
                 rw_counter,
                 rwc_inc,
             } => vec![
@@ -441,9 +536,51 @@ impl<F: FieldExt> Lookup<F> {
                 src_addr_end.clone(),
                 dst_addr.clone(),
                 length.clone(),
+                rlc_acc.clone(),
                 rw_counter.clone(),
                 rwc_inc.clone(),
             ],
+            Self::KeccakTable {
+                input_rlc,
+                input_len,
+                output_rlc,
+            } => vec![
+                1.expr(), // is_enabled
+                input_rlc.clone(),
+                input_len.clone(),
+                output_rlc.clone(),
+            ],
+            Self::ExpTable {
+                identifier,
+                base_lo,
+                base_hi,
+                exponent_lo,
+                exponent_hi,
+                exponentiation_lo,
+                exponentiation_hi,
+            } => vec![
+                1.expr(), // is_last
+                identifier.clone(),
+                base_lo.clone(),
+                base_hi.clone(),
+                exponent_lo.clone(),
+                exponent_hi.clone(),
+                exponentiation_lo.clone(),
+                exponentiation_hi.clone(),
+            ],
+            Self::PrecompileTable {
+                address,
+                input_rlc,
+                input_len,
+                output_rlc,
+                output_len,
+            } => vec![
+                address.clone(),
+                input_rlc.clone(),
+                input_len.clone(),
+                output_rlc.clone(),
+                output_len.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
@@ -1,6 +1,6 @@
 use crate::{
     evm_circuit::{
-        param::{LOOKUP_CONFIG, N_BYTES_MEMORY_ADDRESS},
+        param::{LOOKUP_CONFIG, N_BYTES_MEMORY_ADDRESS, N_COPY_COLUMNS},
         table::Table,
     },
     util::Expr,
@@ -172,6 +172,7 @@ impl<F: FieldExt> StoredExpression<F> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) enum CellType {
     Storage,
+    StoragePermutation,
     Lookup(Table),
 }
 
@@ -232,6 +233,13 @@ impl<F: FieldExt> CellManager<F> {
             }
         }
 
+        // Mark columns used for copy constraints
+        for _ in 0..N_COPY_COLUMNS {
+            meta.enable_equality(advices[column_idx]);
+            columns[column_idx].cell_type = CellType::StoragePermutation;
+            column_idx += 1;
+        }
+
         Self {
             width,
             height,
@@ -1,23 +1,32 @@
 use super::CachedRegion;
 use crate::{
     evm_circuit::{
-        param::N_BYTES_GAS,
-        table::{AccountFieldTag, CallContextFieldTag, FixedTableTag, Lookup},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64, N_BYTES_WORD},
+        step::ExecutionState,
+        table::{
+            AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, FixedTableTag, Lookup,
+        },
         util::{
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, Same, To},
             },
-            math_gadget::{AddWordsGadget, RangeCheckGadget},
-            Cell, Word,
+            from_bytes,
+            math_gadget::{AddWordsGadget, ByteSizeGadget, LtGadget, RangeCheckGadget},
+            not, rlc, select, sum, Cell, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep},
     },
     util::Expr,
 };
-use eth_types::{Field, ToLittleEndian, ToScalar, U256};
+use bus_mapping::circuit_input_builder::{
+    get_create2_address_preimage, get_create_address_preimage,
+};
+use eth_types::{Address, Field, Hardfork, ToBigEndian, ToLittleEndian, ToScalar, H256, U256};
+use ethers_core::utils::keccak256;
 use halo2_proofs::plonk::{Error, Expression};
 use std::convert::TryInto;
+use strum::{EnumCount, IntoEnumIterator};
 
 /// Construction of execution state that stays in the same call context, which
 /// lookups the opcode and verifies the execution state is responsible for it,
@@ -96,9 +105,12 @@ pub(crate) struct RestoreContextGadget<F> {
 impl<F: Field> RestoreContextGadget<F> {
     pub(crate) fn construct(
         cb: &mut ConstraintBuilder<F>,
+        is_success: Expression<F>,
         rw_counter_delta: Expression<F>,
         return_data_offset: Expression<F>,
         return_data_length: Expression<F>,
+        gas_cost: Expression<F>,
+        reversible_write_counter_increase: Expression<F>,
     ) -> Self {
         // Read caller's context for restore
         let caller_id = cb.call_context(None, CallContextFieldTag::CallerId);
@@ -137,22 +149,27 @@ impl<F: Field> RestoreContextGadget<F> {
         let gas_left = if cb.execution_state().halts_in_exception() {
             caller_gas_left.expr()
         } else {
-            caller_gas_left.expr() + cb.curr.state.gas_left.expr()
+            caller_gas_left.expr() + cb.curr.state.gas_left.expr() - gas_cost
         };
 
         // Accumulate reversible_write_counter in case this call stack reverts in the
         // future even it itself succeeds. Note that when sub-call halts in
         // failure, we don't need to accumulate reversible_write_counter because
         // what happened in the sub-call has been reverted.
-        let reversible_write_counter = if cb.execution_state().halts_in_success() {
-            caller_reversible_write_counter.expr() + cb.curr.state.reversible_write_counter.expr()
-        } else {
-            caller_reversible_write_counter.expr()
-        };
+        let reversible_write_counter = caller_reversible_write_counter.expr()
+            + is_success.clone()
+                * (cb.curr.state.reversible_write_counter.expr()
+                    + reversible_write_counter_increase);
+
+        // When sub-call halts in failure, the reversions of its reversible
+        // writes are done right after this step.
+        let rw_counter_delta = rw_counter_delta
+            + 12.expr()
+            + (1.expr() - is_success) * cb.curr.state.reversible_write_counter.expr();
 
         // Do step state transition
         cb.require_step_state_transition(StepStateTransition {
-            rw_counter: Delta(rw_counter_delta + 12.expr()),
+            rw_counter: Delta(rw_counter_delta),
             call_id: To(caller_id.expr()),
             is_root: To(caller_is_root.expr()),
             is_create: To(caller_is_create.expr()),
@@ -178,6 +195,10 @@ impl<F: Field> RestoreContextGadget<F> {
         }
     }
 
+    pub(crate) fn caller_id(&self) -> Expression<F> {
+        self.caller_id.expr()
+    }
+
     pub(crate) fn assign(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
@@ -185,23 +206,14 @@ impl<F: Field> RestoreContextGadget<F> {
         block: &Block<F>,
         call: &Call,
         step: &ExecStep,
+        rw_offset: usize,
     ) -> Result<(), Error> {
         let [caller_id, caller_is_root, caller_is_create, caller_code_hash, caller_program_counter, caller_stack_pointer, caller_gas_left, caller_memory_word_size, caller_reversible_write_counter] =
             if call.is_root {
                 [U256::zero(); 9]
             } else {
-                [
-                    step.rw_indices[1],
-                    step.rw_indices[2],
-                    step.rw_indices[3],
-                    step.rw_indices[4],
-                    step.rw_indices[5],
-                    step.rw_indices[6],
-                    step.rw_indices[7],
-                    step.rw_indices[8],
-                    step.rw_indices[9],
-                ]
-                .map(|idx| block.rws[idx].call_context_value())
+                [0, 1, 2, 3, 4, 5, 6, 7, 8]
+                    .map(|idx| block.rws[step.rw_indices[idx + rw_offset]].call_context_value())
             };
 
         for (cell, value) in [
@@ -233,6 +245,71 @@ impl<F: Field> RestoreContextGadget<F> {
     }
 }
 
+/// Construction of the step state transition of an execution state which halts
+/// in exception, which looks up the opcode, reads that the call fails, then
+/// either ends the transaction when it's a root call or restores the caller's
+/// context, consuming all the gas left in both cases.
+#[derive(Clone, Debug)]
+pub(crate) struct CommonErrorGadget<F> {
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> CommonErrorGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, opcode: Expression<F>) -> Self {
+        debug_assert!(cb.execution_state().halts_in_exception());
+
+        cb.opcode_lookup(opcode, 1.expr());
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
+
+        let is_root = cb.curr.state.is_root.expr();
+
+        // When it's a root call
+        cb.condition(is_root.clone(), |cb| {
+            // Do step state transition, where the reversions of the failed
+            // transaction are done right after this step.
+            cb.require_next_state(ExecutionState::EndTx);
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(
+                    cb.rw_counter_offset() + cb.curr.state.reversible_write_counter.expr(),
+                ),
+                gas_left: To(0.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(is_root), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                0.expr(),
+                cb.rw_counter_offset(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            )
+        });
+
+        Self { restore_context }
+    }
+
+    /// Assigns the restoration of the caller's context, where `rw_offset` is
+    /// the index of the read of the call failure in the step's rw lookups.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        self.restore_context
+            .assign(region, offset, block, call, step, rw_offset + 1)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct UpdateBalanceGadget<F, const N_ADDENDS: usize, const INCREASE: bool> {
     add_words: AddWordsGadget<F, N_ADDENDS, true>,
@@ -426,3 +503,221 @@ impl<F: Field> TransferGadget<F> {
         Ok(())
     }
 }
+
+/// Derivation of the address of a contract created by CREATE, which is the
+/// lower 20 bytes of `keccak256(rlp([caller_address, nonce]))`, or by CREATE2,
+/// which is the lower 20 bytes of
+/// `keccak256(0xff ++ caller_address ++ salt ++ keccak256(init_code))`.
+#[derive(Clone, Debug)]
+pub(crate) struct ContractAddressGadget<F> {
+    caller_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    nonce: Word<F>,
+    nonce_byte_size: ByteSizeGadget<F>,
+    nonce_lt_0x80: LtGadget<F, N_BYTES_U64>,
+    address_hash: Word<F>,
+    address_rlc: Expression<F>,
+}
+
+impl<F: Field> ContractAddressGadget<F> {
+    /// The `salt` and `init_code_hash` are random linear combinations of their
+    /// words, and only used when `is_create2` is 1.
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        is_create2: Expression<F>,
+        salt: Expression<F>,
+        init_code_hash: Expression<F>,
+    ) -> Self {
+        let caller_address = cb.query_rlc();
+        let nonce = cb.query_word();
+        cb.require_zero(
+            "nonce fits in 8 bytes",
+            sum::expr(&nonce.cells[N_BYTES_U64..]),
+        );
+        let nonce_byte_size = ByteSizeGadget::construct(cb, &nonce);
+        let nonce_lt_0x80 = LtGadget::construct(
+            cb,
+            from_bytes::expr(&nonce.cells[..N_BYTES_U64]),
+            0x80.expr(),
+        );
+        let address_hash = cb.query_word();
+
+        let r = |exponent: usize| cb.power_of_randomness()[exponent - 1].clone();
+
+        // 0xff ++ caller_address ++ salt ++ init_code_hash
+        let r_32 = r(31) * r(1);
+        let r_64 = r_32.clone() * r_32.clone();
+        let create2_preimage_rlc = 0xff.expr() * r_64.clone() * r(20)
+            + caller_address.expr() * r_64
+            + salt * r_32
+            + init_code_hash;
+        let create2_preimage_length = (1 + N_BYTES_ACCOUNT_ADDRESS + 2 * N_BYTES_WORD).expr();
+
+        // A nonce below 0x80 is encoded as a single byte, except 0 which is
+        // encoded as 0x80, otherwise the nonce is prefixed by 0x80 plus its
+        // byte size.
+        let is_nonce_single_byte = nonce_lt_0x80.expr();
+        let r_nonce_byte_size =
+            nonce_byte_size.pow_of_byte_size(&cb.power_of_randomness()[..N_BYTES_U64]);
+        let nonce_rlp_rlc = select::expr(
+            is_nonce_single_byte.clone(),
+            nonce.expr() + nonce_byte_size.is_zero() * 0x80.expr(),
+            (0x80.expr() + nonce_byte_size.byte_size()) * r_nonce_byte_size.clone() + nonce.expr(),
+        );
+        let nonce_rlp_length =
+            1.expr() + (1.expr() - is_nonce_single_byte.clone()) * nonce_byte_size.byte_size();
+        let r_nonce_rlp_length =
+            r(1) * select::expr(is_nonce_single_byte, 1.expr(), r_nonce_byte_size);
+
+        // rlp([caller_address, nonce]) is a short list, whose payload is the
+        // address prefixed by 0x94 and the encoded nonce.
+        let create_preimage_rlc =
+            ((0xc0.expr() + (1 + N_BYTES_ACCOUNT_ADDRESS).expr() + nonce_rlp_length.clone())
+                * r(N_BYTES_ACCOUNT_ADDRESS + 1)
+                + 0x94.expr() * r(N_BYTES_ACCOUNT_ADDRESS)
+                + caller_address.expr())
+                * r_nonce_rlp_length
+                + nonce_rlp_rlc;
+        let create_preimage_length = (2 + N_BYTES_ACCOUNT_ADDRESS).expr() + nonce_rlp_length;
+
+        let preimage_rlc = select::expr(
+            is_create2.clone(),
+            create2_preimage_rlc,
+            create_preimage_rlc,
+        );
+        let preimage_length =
+            select::expr(is_create2, create2_preimage_length, create_preimage_length);
+        cb.keccak_table_lookup(preimage_rlc, preimage_length, address_hash.expr());
+
+        let address_rlc = rlc::expr(
+            &address_hash.cells[..N_BYTES_ACCOUNT_ADDRESS]
+                .iter()
+                .map(|cell| cell.expr())
+                .collect::<Vec<_>>(),
+            cb.power_of_randomness(),
+        );
+
+        Self {
+            caller_address,
+            nonce,
+            nonce_byte_size,
+            nonce_lt_0x80,
+            address_hash,
+            address_rlc,
+        }
+    }
+
+    pub(crate) fn caller_address(&self) -> Expression<F> {
+        from_bytes::expr(&self.caller_address.cells)
+    }
+
+    /// The caller's nonce before the creation.
+    pub(crate) fn nonce(&self) -> Expression<F> {
+        from_bytes::expr(&self.nonce.cells[..N_BYTES_U64])
+    }
+
+    pub(crate) fn address(&self) -> Expression<F> {
+        from_bytes::expr(&self.address_hash.cells[..N_BYTES_ACCOUNT_ADDRESS])
+    }
+
+    /// Random linear combination of the address as a word, which is pushed to
+    /// the stack.
+    pub(crate) fn address_rlc(&self) -> Expression<F> {
+        self.address_rlc.clone()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        caller_address: Address,
+        is_create2: bool,
+        nonce: u64,
+        salt: U256,
+        init_code_hash: U256,
+    ) -> Result<(), Error> {
+        let mut caller_address_le_bytes = caller_address.0;
+        caller_address_le_bytes.reverse();
+        self.caller_address
+            .assign(region, offset, Some(caller_address_le_bytes))?;
+        self.nonce
+            .assign(region, offset, Some(U256::from(nonce).to_le_bytes()))?;
+        self.nonce_byte_size.assign(region, offset, nonce.into())?;
+        self.nonce_lt_0x80
+            .assign(region, offset, F::from(nonce), F::from(0x80))?;
+
+        let preimage = if is_create2 {
+            get_create2_address_preimage(caller_address, salt, H256(init_code_hash.to_be_bytes()))
+        } else {
+            get_create_address_preimage(caller_address, nonce)
+        };
+        self.address_hash.assign(
+            region,
+            offset,
+            Some(U256::from_big_endian(&keccak256(&preimage)).to_le_bytes()),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Lookup of the hard fork whose rules the block is processed under, which is
+/// decomposed into a boolean for each hard fork to tell whether the rules of a
+/// hard fork are in effect.
+#[derive(Clone, Debug)]
+pub(crate) struct HardforkGadget<F> {
+    hardfork: Cell<F>,
+    is_hardfork: [Cell<F>; Hardfork::COUNT],
+}
+
+impl<F: Field> HardforkGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let hardfork = cb.query_cell();
+        cb.block_lookup(BlockContextFieldTag::Hardfork.expr(), None, hardfork.expr());
+
+        let is_hardfork = [(); Hardfork::COUNT].map(|_| cb.query_bool());
+        cb.require_equal(
+            "Exactly one hard fork is the hard fork of the block",
+            sum::expr(&is_hardfork),
+            1.expr(),
+        );
+        cb.require_equal(
+            "hardfork == sum(fork * is_hardfork[fork])",
+            hardfork.expr(),
+            sum::expr(
+                Hardfork::iter()
+                    .zip(is_hardfork.iter())
+                    .map(|(fork, is_fork)| fork.expr() * is_fork.expr()),
+            ),
+        );
+
+        Self {
+            hardfork,
+            is_hardfork,
+        }
+    }
+
+    pub(crate) fn hardfork(&self) -> Expression<F> {
+        self.hardfork.expr()
+    }
+
+    /// Whether the rules of the given hard fork are in effect, which is when
+    /// the block is processed under the hard fork or a later one.
+    pub(crate) fn is_activated(&self, fork: Hardfork) -> Expression<F> {
+        sum::expr(&self.is_hardfork[fork as usize..])
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        hardfork: Hardfork,
+    ) -> Result<(), Error> {
+        self.hardfork
+            .assign(region, offset, Some(F::from(hardfork as u64)))?;
+        for (fork, is_fork) in Hardfork::iter().zip(self.is_hardfork.iter()) {
+            is_fork.assign(region, offset, Some(F::from((fork == hardfork) as u64)))?;
+        }
+        Ok(())
+    }
+}
@@ -247,11 +247,13 @@ pub(crate) struct ConstraintBuilder<'a, F> {
     execution_state: ExecutionState,
     constraints: Vec<(&'static str, Expression<F>)>,
     constraints_first_step: Vec<(&'static str, Expression<F>)>,
+    constraints_not_last_step: Vec<(&'static str, Expression<F>)>,
     rw_counter_offset: Expression<F>,
     program_counter_offset: usize,
     stack_pointer_offset: i32,
     log_id_offset: usize,
     in_next_step: bool,
+    in_not_last_step: bool,
     condition: Option<Expression<F>>,
     stored_expressions: Vec<StoredExpression<F>>,
 }
@@ -271,11 +273,13 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             execution_state,
             constraints: Vec::new(),
             constraints_first_step: Vec::new(),
+            constraints_not_last_step: Vec::new(),
             rw_counter_offset: 0.expr(),
             program_counter_offset: 0,
             stack_pointer_offset: 0,
             log_id_offset: 0,
             in_next_step: false,
+            in_not_last_step: false,
             condition: None,
             stored_expressions: Vec::new(),
         }
@@ -285,6 +289,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
     pub(crate) fn build(
         self,
     ) -> (
+        Vec<(&'static str, Expression<F>)>,
         Vec<(&'static str, Expression<F>)>,
         Vec<(&'static str, Expression<F>)>,
         Vec<StoredExpression<F>>,
@@ -300,6 +305,10 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
                 .into_iter()
                 .map(|(name, constraint)| (name, execution_state_selector.clone() * constraint))
                 .collect(),
+            self.constraints_not_last_step
+                .into_iter()
+                .map(|(name, constraint)| (name, execution_state_selector.clone() * constraint))
+                .collect(),
             self.stored_expressions,
             self.curr.cell_manager.get_height(),
         )
@@ -367,6 +376,11 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         self.query_cell_with_type(CellType::Storage)
     }
 
+    /// Query a cell which can be copy constrained, e.g. to a public input.
+    pub(crate) fn query_copy_cell(&mut self) -> Cell<F> {
+        self.query_cell_with_type(CellType::StoragePermutation)
+    }
+
     pub(crate) fn query_cell_with_type(&mut self, cell_type: CellType) -> Cell<F> {
         self.query_cells(cell_type, 1).first().unwrap().clone()
     }
@@ -700,6 +714,29 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
 
     // Access list
 
+    pub(crate) fn account_access_list_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxAccessListAccount read",
+            false.expr(),
+            RwTableTag::TxAccessListAccount,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
     pub(crate) fn account_access_list_write(
         &mut self,
         tx_id: Expression<F>,
@@ -725,6 +762,30 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn account_storage_access_list_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        storage_key: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxAccessListAccountStorage read",
+            false.expr(),
+            RwTableTag::TxAccessListAccountStorage,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                storage_key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
     pub(crate) fn account_storage_access_list_write(
         &mut self,
         tx_id: Expression<F>,
@@ -845,6 +906,31 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn account_destructed_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "AccountDestructed write",
+            RwTableTag::AccountDestructed,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ],
+            reversion_info,
+        );
+    }
+
     // Account Storage
 
     pub(crate) fn account_storage_read(
@@ -1065,6 +1151,34 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    /// Add a tx receipt lookup at `counter` without increasing the
+    /// rw_counter_offset, which is useful to look up a past read/write.
+    pub(crate) fn tx_receipt_lookup_with_counter(
+        &mut self,
+        counter: Expression<F>,
+        is_write: Expression<F>,
+        tx_id: Expression<F>,
+        tag: TxReceiptFieldTag,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup_with_counter(
+            "tx receipt lookup with counter",
+            counter,
+            is_write,
+            RwTableTag::TxReceipt,
+            [
+                tx_id,
+                0.expr(),
+                tag.expr(),
+                0.expr(),
+                value,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
     // Copy Table
 
     #[allow(clippy::too_many_arguments)]
@@ -1078,6 +1192,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         src_addr_end: Expression<F>,
         dst_addr: Expression<F>,
         length: Expression<F>,
+        rlc_acc: Expression<F>,
         rw_counter: Expression<F>,
         rwc_inc: Expression<F>,
     ) {
@@ -1093,12 +1208,80 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
                 src_addr_end,
                 dst_addr,
                 length,
+                rlc_acc,
                 rw_counter,
                 rwc_inc,
             },
         );
     }
 
+    // Keccak Table
+
+    pub(crate) fn keccak_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "keccak lookup",
+            Lookup::KeccakTable {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // Exp Table
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn exp_table_lookup(
+        &mut self,
+        identifier: Expression<F>,
+        base_lo: Expression<F>,
+        base_hi: Expression<F>,
+        exponent_lo: Expression<F>,
+        exponent_hi: Expression<F>,
+        exponentiation_lo: Expression<F>,
+        exponentiation_hi: Expression<F>,
+    ) {
+        self.add_lookup(
+            "exponentiation lookup",
+            Lookup::ExpTable {
+                identifier,
+                base_lo,
+                base_hi,
+                exponent_lo,
+                exponent_hi,
+                exponentiation_lo,
+                exponentiation_hi,
+            },
+        );
+    }
+
+    // Precompile Table
+
+    pub(crate) fn precompile_table_lookup(
+        &mut self,
+        address: Expression<F>,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+        output_len: Expression<F>,
+    ) {
+        self.add_lookup(
+            "precompile lookup",
+            Lookup::PrecompileTable {
+                address,
+                input_rlc,
+                input_len,
+                output_rlc,
+                output_len,
+            },
+        );
+    }
+
     // Validation
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
@@ -1116,18 +1299,21 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
 
     // General
 
+    /// Apply `condition` to the constraints and lookups added by
+    /// `constraint`. A nested condition is combined with the outer one, as
+    /// it's the case for a reversible write within a condition.
     pub(crate) fn condition<R>(
         &mut self,
         condition: Expression<F>,
         constraint: impl FnOnce(&mut Self) -> R,
     ) -> R {
-        debug_assert!(
-            self.condition.is_none(),
-            "Nested condition is not supported"
-        );
-        self.condition = Some(condition);
+        let outer_condition = self.condition.clone();
+        self.condition = Some(match &outer_condition {
+            Some(outer_condition) => outer_condition.clone() * condition,
+            None => condition,
+        });
         let ret = constraint(self);
-        self.condition = None;
+        self.condition = outer_condition;
         ret
     }
 
@@ -1157,6 +1343,16 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         ret
     }
 
+    /// Enable the constraints added by `constraint` only when the step isn't
+    /// the last one, which has no next step to constrain.
+    pub(crate) fn not_last_step<R>(&mut self, constraint: impl FnOnce(&mut Self) -> R) -> R {
+        assert!(!self.in_not_last_step, "Already not in the last step");
+        self.in_not_last_step = true;
+        let ret = constraint(self);
+        self.in_not_last_step = false;
+        ret
+    }
+
     pub(crate) fn add_constraints(&mut self, constraints: Vec<(&'static str, Expression<F>)>) {
         for (name, constraint) in constraints {
             self.add_constraint(name, constraint);
@@ -1169,10 +1365,17 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             None => constraint,
         };
 
-        let constraint = self.split_expression(name, constraint, MAX_DEGREE - IMPLICIT_DEGREE);
+        // The constraints which aren't for the last step are also multiplied by
+        // the q_step_last selector.
+        let max_degree = MAX_DEGREE - IMPLICIT_DEGREE - self.in_not_last_step as usize;
+        let constraint = self.split_expression(name, constraint, max_degree);
 
         self.validate_degree(constraint.degree(), name);
-        self.constraints.push((name, constraint));
+        if self.in_not_last_step {
+            self.constraints_not_last_step.push((name, constraint));
+        } else {
+            self.constraints.push((name, constraint));
+        }
     }
 
     pub(crate) fn add_constraint_first_step(
@@ -1,7 +1,7 @@
 use super::CachedRegion;
 use crate::{
     evm_circuit::{
-        param::N_BYTES_U64,
+        param::{N_BYTES_U64, N_BYTES_WORD},
         table::{FixedTableTag, Lookup},
         util::{
             self, constraint_builder::ConstraintBuilder, from_bytes, pow_of_two, pow_of_two_expr,
@@ -1141,6 +1141,267 @@ impl<F: Field> ShrWordsGadget<F> {
     }
 }
 
+/// Construction of word shift left for `a << shift == b`.
+#[derive(Clone, Debug)]
+pub(crate) struct ShlWordsGadget<F> {
+    a: util::Word<F>,
+    shift: util::Word<F>,
+    b: util::Word<F>,
+    // four 64-bit limbs of word `a`
+    a64s: [Cell<F>; 4],
+    // four 64-bit limbs of word `b`
+    b64s: [Cell<F>; 4],
+    // Each of the four `a64s` limbs is split into two parts (`a64s_lo` and `a64s_hi`) at
+    // position `64 - shf_mod64`. `a64s_lo` is the lower `64 - shf_mod64` bits.
+    a64s_lo: [Cell<F>; 4],
+    // `a64s_hi` is the higher `shf_mod64` bits.
+    a64s_hi: [Cell<F>; 4],
+    // shift[0] / 64
+    shf_div64: Cell<F>,
+    // shift[0] % 64
+    shf_mod64: Cell<F>,
+    // 1 << shf_mod64
+    p_lo: Cell<F>,
+    // 1 << (64 - shf_mod64)
+    p_hi: Cell<F>,
+    // shift < 256
+    shf_lt256: IsZeroGadget<F>,
+    // shf_div64 == 0
+    shf_div64_eq0: IsZeroGadget<F>,
+    // shf_div64 == 1
+    shf_div64_eq1: IsEqualGadget<F>,
+    // shf_div64 == 2
+    shf_div64_eq2: IsEqualGadget<F>,
+    // a64s_lo[idx] < p_hi
+    a64s_lo_lt_p_hi: [LtGadget<F, 16>; 4],
+}
+
+impl<F: Field> ShlWordsGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        a: util::Word<F>,
+        shift: util::Word<F>,
+    ) -> Self {
+        let b = cb.query_word();
+        let a64s = array_init(|_| cb.query_cell());
+        let b64s = array_init(|_| cb.query_cell());
+        let a64s_lo = array_init(|_| cb.query_cell());
+        let a64s_hi = array_init(|_| cb.query_cell());
+        let shf_div64 = cb.query_cell();
+        let shf_mod64 = cb.query_cell();
+        let p_lo = cb.query_cell();
+        let p_hi = cb.query_cell();
+        let shf_lt256 = IsZeroGadget::construct(cb, sum::expr(&shift.cells[1..32]));
+        for idx in 0..4 {
+            let offset = idx * N_BYTES_U64;
+
+            // a64s constraint
+            cb.require_equal(
+                "a64s[idx] == from_bytes(a[8 * idx..8 * (idx + 1)])",
+                a64s[idx].expr(),
+                from_bytes::expr(&a.cells[offset..offset + N_BYTES_U64]),
+            );
+
+            // b64s constraint
+            cb.require_equal(
+                "b64s[idx] * shf_lt256 == from_bytes(b[8 * idx..8 * (idx + 1)])",
+                b64s[idx].expr() * shf_lt256.expr(),
+                from_bytes::expr(&b.cells[offset..offset + N_BYTES_U64]),
+            );
+
+            cb.require_equal(
+                "a64s[idx] == a64s_lo[idx] + a64s_hi[idx] * p_hi",
+                a64s[idx].expr(),
+                a64s_lo[idx].expr() + a64s_hi[idx].expr() * p_hi.expr(),
+            );
+        }
+
+        // a64s_lo[idx] < p_hi
+        let a64s_lo_lt_p_hi = array_init(|idx| {
+            let lt = LtGadget::construct(cb, a64s_lo[idx].expr(), p_hi.expr());
+            cb.require_equal("a64s_lo[idx] < p_hi", lt.expr(), 1.expr());
+            lt
+        });
+
+        // merge contraints
+        let shf_div64_eq0 = IsZeroGadget::construct(cb, shf_div64.expr());
+        let shf_div64_eq1 = IsEqualGadget::construct(cb, shf_div64.expr(), 1.expr());
+        let shf_div64_eq2 = IsEqualGadget::construct(cb, shf_div64.expr(), 2.expr());
+        let shf_div64_eq3 =
+            1.expr() - shf_div64_eq0.expr() - shf_div64_eq1.expr() - shf_div64_eq2.expr();
+        // The limb of `b` at `idx` takes the lower part of the `a` limb shifted
+        // to it, and the higher part of the limb before.
+        let shifted_limb = |idx: usize| {
+            a64s_lo[idx].expr() * p_lo.expr()
+                + if idx == 0 {
+                    0.expr()
+                } else {
+                    a64s_hi[idx - 1].expr()
+                }
+        };
+        cb.require_equal(
+            "Constrain b64s[0]",
+            b64s[0].expr(),
+            shifted_limb(0) * shf_div64_eq0.expr(),
+        );
+        cb.require_equal(
+            "Constrain b64s[1]",
+            b64s[1].expr(),
+            shifted_limb(1) * shf_div64_eq0.expr() + shifted_limb(0) * shf_div64_eq1.expr(),
+        );
+        cb.require_equal(
+            "Constrain b64s[2]",
+            b64s[2].expr(),
+            shifted_limb(2) * shf_div64_eq0.expr()
+                + shifted_limb(1) * shf_div64_eq1.expr()
+                + shifted_limb(0) * shf_div64_eq2.expr(),
+        );
+        cb.require_equal(
+            "Constrain b64s[3]",
+            b64s[3].expr(),
+            shifted_limb(3) * shf_div64_eq0.expr()
+                + shifted_limb(2) * shf_div64_eq1.expr()
+                + shifted_limb(1) * shf_div64_eq2.expr()
+                + shifted_limb(0) * shf_div64_eq3,
+        );
+
+        // shift constraint
+        cb.require_equal(
+            "shift[0] == shf_mod64 + shf_div64 * 64",
+            shift.cells[0].expr(),
+            shf_mod64.expr() + shf_div64.expr() * 64.expr(),
+        );
+
+        // p_lo == pow(2, shf_mod64)
+        cb.add_lookup(
+            "Pow2 lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::Pow2.expr(),
+                values: [shf_mod64.expr(), p_lo.expr(), 0.expr()],
+            },
+        );
+
+        // p_hi == pow(2, 64 - shf_mod64)
+        cb.add_lookup(
+            "Pow2 lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::Pow2.expr(),
+                values: [64.expr() - shf_mod64.expr(), p_hi.expr(), 0.expr()],
+            },
+        );
+
+        Self {
+            a,
+            shift,
+            b,
+            a64s,
+            b64s,
+            a64s_lo,
+            a64s_hi,
+            shf_div64,
+            shf_mod64,
+            p_lo,
+            p_hi,
+            shf_lt256,
+            shf_div64_eq0,
+            shf_div64_eq1,
+            shf_div64_eq2,
+            a64s_lo_lt_p_hi,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        a: Word,
+        shift: Word,
+        b: Word,
+    ) -> Result<(), Error> {
+        self.assign_witness(region, offset, &a, &shift)?;
+        self.a.assign(region, offset, Some(a.to_le_bytes()))?;
+        self.shift
+            .assign(region, offset, Some(shift.to_le_bytes()))?;
+        self.b.assign(region, offset, Some(b.to_le_bytes()))?;
+        Ok(())
+    }
+
+    pub(crate) fn b(&self) -> &util::Word<F> {
+        &self.b
+    }
+
+    fn assign_witness(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        a: &Word,
+        shift: &Word,
+    ) -> Result<(), Error> {
+        let shf0 = shift.to_le_bytes()[0] as usize;
+        let shf_div64 = shf0 / 64;
+        let shf_mod64 = shf0 % 64;
+        let p_lo: u128 = 1 << shf_mod64;
+        let p_hi: u128 = 1 << (64 - shf_mod64);
+        let shf_lt256 = shift
+            .to_le_bytes()
+            .iter()
+            .fold(0, |acc, val| acc + *val as u128)
+            - shf0 as u128;
+        let a64s = a.0;
+        let mut a64s_lo = [0_u128; 4];
+        let mut a64s_hi = [0_u128; 4];
+        for idx in 0..4 {
+            a64s_lo[idx] = u128::from(a64s[idx]) % p_hi;
+            a64s_hi[idx] = u128::from(a64s[idx]) / p_hi;
+        }
+        let mut b64s = [0_u128; 4];
+        b64s[shf_div64] = a64s_lo[0] * p_lo;
+        for k in shf_div64 + 1..4 {
+            b64s[k] = a64s_lo[k - shf_div64] * p_lo + a64s_hi[k - shf_div64 - 1];
+        }
+        self.a64s
+            .iter()
+            .zip(a64s.iter())
+            .map(|(cell, val)| cell.assign(region, offset, Some(F::from(*val))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.b64s
+            .iter()
+            .zip(b64s.iter())
+            .map(|(cell, val)| cell.assign(region, offset, Some(F::from_u128(*val))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.a64s_lo
+            .iter()
+            .zip(a64s_lo.iter())
+            .map(|(cell, val)| cell.assign(region, offset, Some(F::from_u128(*val))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.a64s_hi
+            .iter()
+            .zip(a64s_hi.iter())
+            .map(|(cell, val)| cell.assign(region, offset, Some(F::from_u128(*val))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.shf_div64
+            .assign(region, offset, Some(F::from(shf_div64 as u64)))?;
+        self.shf_mod64
+            .assign(region, offset, Some(F::from(shf_mod64 as u64)))?;
+        self.p_lo.assign(region, offset, Some(F::from_u128(p_lo)))?;
+        self.p_hi.assign(region, offset, Some(F::from_u128(p_hi)))?;
+        self.shf_lt256
+            .assign(region, offset, F::from_u128(shf_lt256))?;
+        self.shf_div64_eq0
+            .assign(region, offset, F::from(shf_div64 as u64))?;
+        self.shf_div64_eq1
+            .assign(region, offset, F::from(shf_div64 as u64), F::from(1))?;
+        self.shf_div64_eq2
+            .assign(region, offset, F::from(shf_div64 as u64), F::from(2))?;
+        self.a64s_lo_lt_p_hi
+            .iter()
+            .zip(a64s_lo.iter())
+            .map(|(lt, val)| lt.assign(region, offset, F::from_u128(*val), F::from_u128(p_hi)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 /// CmpWordsGadget compares two words, exposing `eq`  and `lt`
 pub(crate) struct CmpWordsGadget<F> {
@@ -1571,3 +1832,115 @@ impl<F: Field> AbsWordGadget<F> {
         &self.is_neg
     }
 }
+
+/// Returns the number of bytes needed to represent a 256-bit word, i.e. the
+/// index of its most significant non-zero byte plus one, or 0 when the word is
+/// 0.
+#[derive(Clone, Debug)]
+pub(crate) struct ByteSizeGadget<F> {
+    // One boolean cell per possible byte size, where only the cell whose index
+    // equals the byte size is 1.
+    most_significant_nonzero_byte_index: [Cell<F>; N_BYTES_WORD + 1],
+    // The most significant non-zero byte, or 0 when the word is 0.
+    most_significant_nonzero_byte: Cell<F>,
+    // The inverse of the most significant non-zero byte, to prove it's non-zero.
+    most_significant_nonzero_byte_inverse: Cell<F>,
+}
+
+impl<F: Field> ByteSizeGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, value: &util::Word<F>) -> Self {
+        let most_significant_nonzero_byte_index = array_init(|_| cb.query_bool());
+        cb.require_equal(
+            "exactly one byte size index is enabled",
+            sum::expr(&most_significant_nonzero_byte_index),
+            1.expr(),
+        );
+
+        for (idx, index) in most_significant_nonzero_byte_index.iter().enumerate() {
+            cb.condition(index.expr(), |cb| {
+                cb.require_zero(
+                    "bytes more significant than the byte size are 0",
+                    sum::expr(&value.cells[idx..]),
+                );
+            });
+        }
+
+        // Select the byte at index `byte_size - 1`, which should be non-zero
+        // unless the byte size is 0.
+        let most_significant_nonzero_byte = cb.copy(sum::expr(
+            most_significant_nonzero_byte_index
+                .iter()
+                .skip(1)
+                .zip(value.cells.iter())
+                .map(|(index, byte)| index.expr() * byte.expr()),
+        ));
+        let most_significant_nonzero_byte_inverse = cb.query_cell();
+        cb.require_equal(
+            "most significant non-zero byte is non-zero unless the byte size is 0",
+            most_significant_nonzero_byte.expr() * most_significant_nonzero_byte_inverse.expr(),
+            1.expr() - most_significant_nonzero_byte_index[0].expr(),
+        );
+
+        Self {
+            most_significant_nonzero_byte_index,
+            most_significant_nonzero_byte,
+            most_significant_nonzero_byte_inverse,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: Word,
+    ) -> Result<(), Error> {
+        let byte_size = (value.bits() + 7) / 8;
+        for (idx, index) in self.most_significant_nonzero_byte_index.iter().enumerate() {
+            index.assign(region, offset, Some(F::from((idx == byte_size) as u64)))?;
+        }
+
+        let most_significant_nonzero_byte = if byte_size > 0 {
+            F::from(value.to_le_bytes()[byte_size - 1] as u64)
+        } else {
+            F::zero()
+        };
+        self.most_significant_nonzero_byte.assign(
+            region,
+            offset,
+            Some(most_significant_nonzero_byte),
+        )?;
+        self.most_significant_nonzero_byte_inverse.assign(
+            region,
+            offset,
+            Some(most_significant_nonzero_byte.invert().unwrap_or(F::zero())),
+        )?;
+
+        Ok(())
+    }
+
+    pub(crate) fn byte_size(&self) -> Expression<F> {
+        sum::expr(
+            self.most_significant_nonzero_byte_index
+                .iter()
+                .enumerate()
+                .map(|(idx, index)| idx.expr() * index.expr()),
+        )
+    }
+
+    /// Returns `1` when the word is 0, and returns `0` otherwise.
+    pub(crate) fn is_zero(&self) -> Expression<F> {
+        self.most_significant_nonzero_byte_index[0].expr()
+    }
+
+    /// Returns `r^byte_size` given the successive powers `r^1, r^2, ...` in
+    /// `powers`, whose length must be at least the byte size.
+    pub(crate) fn pow_of_byte_size(&self, powers: &[Expression<F>]) -> Expression<F> {
+        self.is_zero()
+            + sum::expr(
+                self.most_significant_nonzero_byte_index[1..]
+                    .iter()
+                    .zip(powers.iter())
+                    .map(|(index, power)| index.expr() * power.clone()),
+            )
+    }
+}
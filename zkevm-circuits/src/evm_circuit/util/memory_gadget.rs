@@ -153,6 +153,111 @@ impl<F: Field> MemoryAddressGadget<F> {
     }
 }
 
+/// Decodes the dynamic memory offset and length from full words, for the
+/// out of gas errors where the memory access could be arbitrarily large.
+/// It checks whether the access is within `2^32` bytes, beyond which the
+/// memory expansion costs more than `2^45` gas, so the step must run out of
+/// gas.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryExpandedAddressGadget<F> {
+    memory_offset: Word<F>,
+    memory_length: Word<F>,
+    memory_length_is_zero: IsZeroGadget<F>,
+    within_range: IsZeroGadget<F>,
+}
+
+impl<F: Field> MemoryExpandedAddressGadget<F> {
+    pub(crate) fn construct_self(cb: &mut ConstraintBuilder<F>) -> Self {
+        let memory_offset = cb.query_word();
+        let memory_length = cb.query_word();
+
+        let memory_length_is_zero = IsZeroGadget::construct(cb, sum::expr(&memory_length.cells));
+        let has_length = 1.expr() - memory_length_is_zero.expr();
+
+        // The offset is irrelevant when the length is zero
+        let within_range = IsZeroGadget::construct(
+            cb,
+            has_length * sum::expr(&memory_offset.cells[N_BYTES_MEMORY_WORD_SIZE..])
+                + sum::expr(&memory_length.cells[N_BYTES_MEMORY_WORD_SIZE..]),
+        );
+
+        Self {
+            memory_offset,
+            memory_length,
+            memory_length_is_zero,
+            within_range,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        memory_offset: U256,
+        memory_length: U256,
+    ) -> Result<u64, Error> {
+        let memory_offset_bytes = memory_offset.to_le_bytes();
+        let memory_length_bytes = memory_length.to_le_bytes();
+        self.memory_offset
+            .assign(region, offset, Some(memory_offset_bytes))?;
+        self.memory_length
+            .assign(region, offset, Some(memory_length_bytes))?;
+        self.memory_length_is_zero
+            .assign(region, offset, sum::value(&memory_length_bytes))?;
+
+        let has_length = !memory_length.is_zero();
+        let offset_high = if has_length {
+            sum::value(&memory_offset_bytes[N_BYTES_MEMORY_WORD_SIZE..])
+        } else {
+            F::zero()
+        };
+        self.within_range.assign(
+            region,
+            offset,
+            offset_high + sum::value::<F>(&memory_length_bytes[N_BYTES_MEMORY_WORD_SIZE..]),
+        )?;
+
+        let low_u32 = |bytes: [u8; 32]| {
+            u32::from_le_bytes(bytes[..N_BYTES_MEMORY_WORD_SIZE].try_into().unwrap()) as u64
+        };
+        Ok(if has_length {
+            low_u32(memory_offset_bytes) + low_u32(memory_length_bytes)
+        } else {
+            0
+        })
+    }
+
+    /// The random linear combination of the offset popped from the stack
+    pub(crate) fn offset_rlc(&self) -> Expression<F> {
+        self.memory_offset.expr()
+    }
+
+    /// The random linear combination of the length popped from the stack
+    pub(crate) fn length_rlc(&self) -> Expression<F> {
+        self.memory_length.expr()
+    }
+
+    pub(crate) fn has_length(&self) -> Expression<F> {
+        1.expr() - self.memory_length_is_zero.expr()
+    }
+
+    pub(crate) fn length(&self) -> Expression<F> {
+        from_bytes::expr(&self.memory_length.cells[..N_BYTES_MEMORY_WORD_SIZE])
+    }
+
+    /// The address after the access, which is less than `2^33`
+    pub(crate) fn address(&self) -> Expression<F> {
+        self.has_length()
+            * (from_bytes::expr(&self.memory_offset.cells[..N_BYTES_MEMORY_WORD_SIZE])
+                + self.length())
+    }
+
+    /// Returns `1` when the access is beyond `2^32` bytes
+    pub(crate) fn overflow(&self) -> Expression<F> {
+        1.expr() - self.within_range.expr()
+    }
+}
+
 /// Calculates the memory size in words required for a memory access at the
 /// specified address.
 /// `memory_word_size = ceil(address/32) = floor((address + 31) / 32)`
@@ -334,14 +439,13 @@ impl<F: Field, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize>
 /// This gas cost is the difference between the next and current memory costs:
 /// `memory_cost = Gmem * memory_size + floor(memory_size * memory_size / 512)`
 #[derive(Clone, Debug)]
-pub(crate) struct MemoryCopierGasGadget<F> {
+pub(crate) struct MemoryCopierGasGadget<F, const GAS_COPY: GasCost> {
     word_size: MemoryWordSizeGadget<F>,
     gas_cost: Expression<F>,
     gas_cost_range_check: RangeCheckGadget<F, N_BYTES_GAS>,
 }
 
-impl<F: Field> MemoryCopierGasGadget<F> {
-    pub const GAS_COPY: GasCost = GasCost::COPY;
+impl<F: Field, const GAS_COPY: GasCost> MemoryCopierGasGadget<F, GAS_COPY> {
     pub const WORD_SIZE: u64 = 32u64;
 
     /// Input requirements:
@@ -358,7 +462,7 @@ impl<F: Field> MemoryCopierGasGadget<F> {
     ) -> Self {
         let word_size = MemoryWordSizeGadget::construct(cb, num_bytes);
 
-        let gas_cost = word_size.expr() * Self::GAS_COPY.expr() + memory_expansion_gas_cost;
+        let gas_cost = word_size.expr() * GAS_COPY.expr() + memory_expansion_gas_cost;
         let gas_cost_range_check = RangeCheckGadget::construct(cb, gas_cost.clone());
 
         Self {
@@ -381,7 +485,7 @@ impl<F: Field> MemoryCopierGasGadget<F> {
         memory_expansion_gas_cost: u64,
     ) -> Result<u64, Error> {
         let word_size = self.word_size.assign(region, offset, num_bytes)?;
-        let gas_cost = word_size * Self::GAS_COPY.as_u64() + memory_expansion_gas_cost;
+        let gas_cost = word_size * GAS_COPY.as_u64() + memory_expansion_gas_cost;
         self.gas_cost_range_check
             .assign(region, offset, F::from(gas_cost))?;
         // Return the memory copier gas cost
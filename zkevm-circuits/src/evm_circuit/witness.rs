@@ -10,13 +10,13 @@ use crate::evm_circuit::{
 };
 
 use bus_mapping::{
-    circuit_input_builder::{self, CopyEvent},
+    circuit_input_builder::{self, CopyEvent, ExpEvent, PrecompileEvent},
     error::{ExecError, OogError},
     operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField},
 };
 
 use eth_types::{evm_types::OpcodeId, ToWord};
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use eth_types::{Address, Field, Hardfork, ToBigEndian, ToLittleEndian, ToScalar, Word, H256};
 use eth_types::{ToAddress, U256};
 use halo2_proofs::arithmetic::{BaseExt, FieldExt};
 use halo2_proofs::pairing::bn256::Fr;
@@ -36,9 +36,26 @@ pub struct Block<F> {
     pub bytecodes: HashMap<Word, Bytecode>,
     /// The block context
     pub context: BlockContext,
-    /// Copy events for the EVM circuit's Copy Table, a mapping from (tx_id ||
-    /// call_id || pc) to the corresponding copy event.
-    pub copy_events: HashMap<(usize, usize, usize), CopyEvent>,
+    /// Copy events for the EVM circuit's Copy Table.
+    pub copy_events: Vec<CopyEvent>,
+    /// Inputs to the SHA3 opcode
+    pub sha3_inputs: Vec<Vec<u8>>,
+    /// Exponentiation events for the EVM circuit's Exponentiation Table.
+    pub exp_events: Vec<ExpEvent>,
+    /// Precompile events for the EVM circuit's Precompile Table.
+    pub precompile_events: Vec<PrecompileEvent>,
+    /// Number of rows the EVM circuit pads its steps to
+    pub evm_circuit_pad_to: usize,
+}
+
+impl<F> Block<F> {
+    /// Return the first copy event of the step at `pc` in the call `call_id`
+    /// of the transaction `tx_id`, given as (tx_id || call_id || pc).
+    pub fn copy_event(&self, (tx_id, call_id, pc): (usize, usize, usize)) -> Option<&CopyEvent> {
+        self.copy_events.iter().find(|copy_event| {
+            copy_event.tx_id == tx_id && copy_event.call_id == call_id && copy_event.pc.0 == pc
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -51,10 +68,15 @@ pub struct BlockContext {
     pub number: Word,
     /// The timestamp of the block
     pub timestamp: Word,
-    /// The difficulty of the blcok
+    /// The difficulty of the block, which is the randomness of the beacon
+    /// chain (PREVRANDAO) since the Merge
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The hard fork whose rules the block is processed under
+    pub hardfork: Hardfork,
+    /// The maximum size of the code deployed by a contract creation
+    pub max_code_size: u64,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -109,6 +131,16 @@ impl BlockContext {
                         randomness,
                     ),
                 ],
+                [
+                    F::from(BlockContextFieldTag::Hardfork as u64),
+                    F::zero(),
+                    F::from(self.hardfork as u64),
+                ],
+                [
+                    F::from(BlockContextFieldTag::MaxCodeSize as u64),
+                    F::zero(),
+                    F::from(self.max_code_size),
+                ],
             ],
             self.history_hashes
                 .iter()
@@ -116,7 +148,10 @@ impl BlockContext {
                 .map(|(idx, hash)| {
                     [
                         F::from(BlockContextFieldTag::BlockHash as u64),
-                        (self.number - idx - 1).to_scalar().unwrap(),
+                        // The latest hash is at the end.
+                        (self.number - self.history_hashes.len() + idx)
+                            .to_scalar()
+                            .unwrap(),
                         RandomLinearCombination::random_linear_combine(
                             hash.to_le_bytes(),
                             randomness,
@@ -137,8 +172,12 @@ pub struct Transaction {
     pub nonce: u64,
     /// The gas limit of the transaction
     pub gas: u64,
-    /// The gas price
+    /// The gas price, which is the effective one for EIP-1559 transactions
     pub gas_price: Word,
+    /// The max fee per gas (EIP-1559)
+    pub max_fee_per_gas: Word,
+    /// The max priority fee per gas (EIP-1559)
+    pub max_priority_fee_per_gas: Word,
     /// The caller address
     pub caller_address: Address,
     /// The callee address
@@ -153,6 +192,13 @@ pub struct Transaction {
     pub call_data_length: usize,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
+    /// The number of addresses in the access list (EIP-2930)
+    pub access_list_addresses_len: u64,
+    /// The number of storage keys in the access list (EIP-2930)
+    pub access_list_storage_keys_len: u64,
+    /// The entries of the access list in the order they are warmed up, which
+    /// are the addresses followed by the storage keys with their addresses
+    pub access_list: Vec<(Address, Option<Word>)>,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -223,6 +269,36 @@ impl Transaction {
                     F::zero(),
                     F::from(self.call_data_gas_cost),
                 ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::MaxFeePerGas as u64),
+                    F::zero(),
+                    RandomLinearCombination::random_linear_combine(
+                        self.max_fee_per_gas.to_le_bytes(),
+                        randomness,
+                    ),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::MaxPriorityFeePerGas as u64),
+                    F::zero(),
+                    RandomLinearCombination::random_linear_combine(
+                        self.max_priority_fee_per_gas.to_le_bytes(),
+                        randomness,
+                    ),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::AccessListAddressesLen as u64),
+                    F::zero(),
+                    F::from(self.access_list_addresses_len),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::AccessListStorageKeysLen as u64),
+                    F::zero(),
+                    F::from(self.access_list_storage_keys_len),
+                ],
             ],
             self.call_data
                 .iter()
@@ -236,6 +312,29 @@ impl Transaction {
                     ]
                 })
                 .collect(),
+            self.access_list
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, (address, storage_key))| {
+                    iter::once([
+                        F::from(self.id as u64),
+                        F::from(TxContextFieldTag::AccessListAddress as u64),
+                        F::from(idx as u64),
+                        address.to_scalar().unwrap(),
+                    ])
+                    .chain(storage_key.map(|storage_key| {
+                        [
+                            F::from(self.id as u64),
+                            F::from(TxContextFieldTag::AccessListStorageKey as u64),
+                            F::from(idx as u64),
+                            RandomLinearCombination::random_linear_combine(
+                                storage_key.to_le_bytes(),
+                                randomness,
+                            ),
+                        ]
+                    }))
+                })
+                .collect(),
         ]
         .concat()
     }
@@ -325,6 +424,39 @@ pub struct Bytecode {
     pub bytes: Vec<u8>,
 }
 
+/// Returns the keccak table rows of the given input, in the form of
+/// [is_enabled, input_rlc, input_len, output_rlc].
+pub fn keccak_table_assignments<F: Field>(input: &[u8], randomness: F) -> Vec<[F; 4]> {
+    // The input is RLC encoded with the first byte having the highest power of
+    // randomness, which is the same as the RlcAcc accumulation in copy circuit.
+    let input_rlc = input.iter().fold(F::zero(), |acc, byte| {
+        acc * randomness + F::from(*byte as u64)
+    });
+    let output = Word::from_big_endian(Keccak256::digest(input).as_slice());
+    let output_rlc =
+        RandomLinearCombination::random_linear_combine(output.to_le_bytes(), randomness);
+
+    vec![[F::one(), input_rlc, F::from(input.len() as u64), output_rlc]]
+}
+
+/// Return the row of the precompile table of a precompile event, with the input
+/// and output RLC encoded in the same order as the RlcAcc accumulation in copy
+/// circuit.
+pub fn precompile_table_assignments<F: Field>(event: &PrecompileEvent, randomness: F) -> [F; 5] {
+    let rlc = |bytes: &[u8]| {
+        bytes.iter().fold(F::zero(), |acc, byte| {
+            acc * randomness + F::from(*byte as u64)
+        })
+    };
+    [
+        F::from(event.precompile as u64),
+        rlc(&event.input),
+        F::from(event.input.len() as u64),
+        rlc(&event.output),
+        F::from(event.output.len() as u64),
+    ]
+}
+
 impl Bytecode {
     pub fn new(bytes: Vec<u8>) -> Self {
         let hash = Word::from_big_endian(Keccak256::digest(&bytes).as_slice());
@@ -525,6 +657,17 @@ impl Rw {
         }
     }
 
+    pub fn account_destructed_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::AccountDestructed {
+                is_destructed,
+                is_destructed_prev,
+                ..
+            } => (*is_destructed, *is_destructed_prev),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn tx_refund_value_pair(&self) -> (u64, u64) {
         match self {
             Self::TxRefund {
@@ -681,11 +824,12 @@ impl Rw {
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
-            | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
+            | Self::TxReceipt { tx_id, .. }
+            | Self::AccountDestructed { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
-            Self::Start { .. } | Self::Account { .. } | Self::AccountDestructed { .. } => None,
+            Self::Start { .. } | Self::Account { .. } => None,
         }
     }
 
@@ -872,6 +1016,8 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             timestamp: block.timestamp,
             difficulty: block.difficulty,
             base_fee: block.base_fee,
+            hardfork: block.hardfork,
+            max_code_size: block.max_code_size,
             history_hashes: block.history_hashes.clone(),
             chain_id: block.chain_id,
         }
@@ -1162,13 +1308,6 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     return ExecutionState::LOG;
                 }
 
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        log::warn!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
                 match op {
                     OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
                     OpcodeId::ADDMOD => ExecutionState::ADDMOD,
@@ -1198,6 +1337,8 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::CALLER => ExecutionState::CALLER,
                     OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
                     OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
+                    OpcodeId::EXTCODESIZE => ExecutionState::EXTCODESIZE,
+                    OpcodeId::EXTCODECOPY => ExecutionState::EXTCODECOPY,
                     OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::GASLIMIT => {
                         ExecutionState::BLOCKCTXU64
                     }
@@ -1205,42 +1346,41 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
                     OpcodeId::GAS => ExecutionState::GAS,
                     OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
+                    OpcodeId::SHL => ExecutionState::SHL,
                     OpcodeId::SHR => ExecutionState::SHR,
+                    OpcodeId::SAR => ExecutionState::SAR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
                     OpcodeId::ISZERO => ExecutionState::ISZERO,
-                    OpcodeId::CALL => ExecutionState::CALL,
+                    OpcodeId::CALL
+                    | OpcodeId::CALLCODE
+                    | OpcodeId::DELEGATECALL
+                    | OpcodeId::STATICCALL => ExecutionState::CALL,
                     OpcodeId::ORIGIN => ExecutionState::ORIGIN,
                     OpcodeId::CODECOPY => ExecutionState::CODECOPY,
                     OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
                     OpcodeId::CODESIZE => ExecutionState::CODESIZE,
-                    OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::RETURN,
-                    // dummy ops
-                    OpcodeId::SHA3 => dummy!(ExecutionState::SHA3),
-                    OpcodeId::ADDRESS => dummy!(ExecutionState::ADDRESS),
-                    OpcodeId::BALANCE => dummy!(ExecutionState::BALANCE),
-                    OpcodeId::BLOCKHASH => dummy!(ExecutionState::BLOCKHASH),
-                    OpcodeId::EXP => dummy!(ExecutionState::EXP),
-                    OpcodeId::SHL => dummy!(ExecutionState::SHL),
-                    OpcodeId::SAR => dummy!(ExecutionState::SAR),
-                    OpcodeId::EXTCODESIZE => dummy!(ExecutionState::EXTCODESIZE),
-                    OpcodeId::EXTCODECOPY => dummy!(ExecutionState::EXTCODECOPY),
-                    OpcodeId::RETURNDATASIZE => dummy!(ExecutionState::RETURNDATASIZE),
-                    OpcodeId::RETURNDATACOPY => dummy!(ExecutionState::RETURNDATACOPY),
-                    OpcodeId::CREATE => dummy!(ExecutionState::CREATE),
-                    OpcodeId::CALLCODE => dummy!(ExecutionState::CALLCODE),
-                    OpcodeId::DELEGATECALL => dummy!(ExecutionState::DELEGATECALL),
-                    OpcodeId::CREATE2 => dummy!(ExecutionState::CREATE2),
-                    OpcodeId::STATICCALL => dummy!(ExecutionState::STATICCALL),
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
+                    OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::RETURN_REVERT,
+                    OpcodeId::SHA3 => ExecutionState::SHA3,
+                    OpcodeId::EXP => ExecutionState::EXP,
+                    OpcodeId::ADDRESS => ExecutionState::ADDRESS,
+                    OpcodeId::BALANCE => ExecutionState::BALANCE,
+                    OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
+                    OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
+                    OpcodeId::CREATE => ExecutionState::CREATE,
+                    OpcodeId::CREATE2 => ExecutionState::CREATE2,
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
+                    OpcodeId::BLOCKHASH => ExecutionState::BLOCKHASH,
                     _ => unimplemented!("unimplemented opcode {:?}", op),
                 }
             }
             circuit_input_builder::ExecState::BeginTx => ExecutionState::BeginTx,
+            circuit_input_builder::ExecState::TxAccessList => ExecutionState::TxAccessList,
             circuit_input_builder::ExecState::EndTx => ExecutionState::EndTx,
+            circuit_input_builder::ExecState::Precompile(_) => ExecutionState::PRECOMPILE,
         }
     }
 }
@@ -1298,6 +1438,8 @@ fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize, is_last_tx: bo
         nonce: tx.nonce,
         gas: tx.gas,
         gas_price: tx.gas_price,
+        max_fee_per_gas: tx.max_fee_per_gas,
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
         caller_address: tx.from,
         callee_address: tx.to,
         is_create: tx.is_create(),
@@ -1308,6 +1450,24 @@ fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize, is_last_tx: bo
             .input
             .iter()
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 }),
+        access_list_addresses_len: tx.access_list.0.len() as u64,
+        access_list_storage_keys_len: tx
+            .access_list
+            .0
+            .iter()
+            .map(|item| item.storage_keys.len() as u64)
+            .sum(),
+        access_list: tx
+            .access_list
+            .0
+            .iter()
+            .map(|item| (item.address, None))
+            .chain(tx.access_list.0.iter().flat_map(|item| {
+                item.storage_keys
+                    .iter()
+                    .map(|key| (item.address, Some(key.to_word())))
+            }))
+            .collect(),
         calls: tx
             .calls()
             .iter()
@@ -1315,7 +1475,12 @@ fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize, is_last_tx: bo
                 id: call.call_id,
                 is_root: call.is_root,
                 is_create: call.is_create(),
-                code_hash: call.code_hash.to_word(),
+                // The code hash of a precompiled contract's context is its
+                // address, which identifies the precompiled contract.
+                code_hash: match call.precompile() {
+                    Some(precompile) => precompile.address().to_word(),
+                    None => call.code_hash.to_word(),
+                },
                 rw_counter_end_of_reversion: call.rw_counter_end_of_reversion,
                 caller_id: call.caller_id,
                 depth: call.depth,
@@ -1370,27 +1535,27 @@ pub fn block_convert(
         bytecodes: block
             .txs()
             .iter()
-            .flat_map(|tx| {
-                tx.calls()
+            .flat_map(|tx| tx.calls().iter().map(|call| call.code_hash))
+            .chain(
+                // Code of other accounts read by EXTCODESIZE or EXTCODECOPY
+                block
+                    .container
+                    .account
                     .iter()
-                    .map(|call| call.code_hash)
-                    .unique()
-                    .into_iter()
-                    .map(|code_hash| {
-                        let bytecode = Bytecode::new(code_db.0.get(&code_hash).unwrap().to_vec());
-                        (bytecode.hash, bytecode)
-                    })
-            })
-            .collect(),
-        copy_events: block
-            .copy_events
-            .iter()
-            .map(|copy_event| {
-                (
-                    (copy_event.tx_id, copy_event.call_id, copy_event.pc.0),
-                    copy_event.clone(),
-                )
+                    .filter(|op| op.op().field == AccountField::CodeHash)
+                    .map(|op| H256(op.op().value.to_be_bytes()))
+                    .filter(|code_hash| code_db.0.contains_key(code_hash)),
+            )
+            .unique()
+            .map(|code_hash| {
+                let bytecode = Bytecode::new(code_db.0.get(&code_hash).unwrap().to_vec());
+                (bytecode.hash, bytecode)
             })
             .collect(),
+        copy_events: block.copy_events.clone(),
+        sha3_inputs: block.sha3_inputs.clone(),
+        exp_events: block.exp_events.clone(),
+        precompile_events: block.precompile_events.clone(),
+        evm_circuit_pad_to: 0,
     }
 }
@@ -0,0 +1,688 @@
+//! The Exponentiation circuit implements constraints for the
+//! square-and-multiply steps of the exponentiations done by the EXP opcode, and
+//! exposes a lookup table of `base ^ exponent (mod 2^256)` to the EVM circuit.
+
+use std::marker::PhantomData;
+
+use bus_mapping::circuit_input_builder::{ExpEvent, ExpStep};
+use eth_types::{Field, ToLittleEndian};
+use gadgets::util::{and, not, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::evm_circuit::{
+    table::LookupTable,
+    util::{
+        constraint_builder::BaseConstraintBuilder, from_bytes, pow_of_two_expr, split_u256,
+        split_u256_limb64,
+    },
+    witness::Block,
+};
+
+/// The exponentiation table shared between the EVM circuit and the
+/// Exponentiation circuit. Every row verifies a single step `a * b == d (mod
+/// 2^256)` of an exponentiation event, and the last row of an event holds the
+/// exponentiation result.
+#[derive(Clone, Copy, Debug)]
+pub struct ExpCircuit<F> {
+    /// Whether the row is enabled or not.
+    pub q_enable: Column<Fixed>,
+    /// Enabled on the first row, which needs to be the first step of an
+    /// exponentiation event if enabled.
+    pub q_first: Selector,
+    /// Whether the row is the first step of an exponentiation event.
+    pub is_first: Column<Advice>,
+    /// Whether the row is the last step of an exponentiation event.
+    pub is_last: Column<Advice>,
+    /// Identifier of the exponentiation event, which is the read-write counter
+    /// of the EXP step in the EVM circuit.
+    pub identifier: Column<Advice>,
+    /// The little-endian bytes of the base of the exponentiation.
+    pub base: [Column<Advice>; 32],
+    /// The four 64-bit limbs of the first multiplicand.
+    pub a_limbs: [Column<Advice>; 4],
+    /// The four 64-bit limbs of the second multiplicand.
+    pub b_limbs: [Column<Advice>; 4],
+    /// The little-endian bytes of the product `a * b (mod 2^256)`.
+    pub d: [Column<Advice>; 32],
+    /// The little-endian bytes of the carry of the low 128 bits of the product.
+    pub carry_lo: [Column<Advice>; 9],
+    /// The little-endian bytes of the carry of the high 128 bits of the
+    /// product.
+    pub carry_hi: [Column<Advice>; 9],
+    /// Whether the step squares the intermediate exponentiation, otherwise it
+    /// multiplies it by the base.
+    pub is_square: Column<Advice>,
+    /// The little-endian bytes of the low 128 bits of the exponent accumulated
+    /// after this step.
+    pub exponent_lo: [Column<Advice>; 16],
+    /// The high 128 bits of the exponent accumulated after this step.
+    pub exponent_hi: Column<Advice>,
+    /// The carry from the low 128 bits to the high 128 bits of the exponent
+    /// accumulated after this step.
+    pub exponent_carry: Column<Advice>,
+    /// Fixed table of all the byte values, used to range check the bytes.
+    pub u8_table: Column<Fixed>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LookupTable<F> for ExpCircuit<F> {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        let base = self.query_bytes(meta, &self.base, Rotation::cur());
+        let exponent_lo = self.query_bytes(meta, &self.exponent_lo, Rotation::cur());
+        let d = self.query_bytes(meta, &self.d, Rotation::cur());
+        vec![
+            meta.query_advice(self.is_last, Rotation::cur()),
+            meta.query_advice(self.identifier, Rotation::cur()),
+            from_bytes::expr(&base[..16]),  // base_lo
+            from_bytes::expr(&base[16..]),  // base_hi
+            from_bytes::expr(&exponent_lo), // exponent_lo
+            meta.query_advice(self.exponent_hi, Rotation::cur()), // exponent_hi
+            from_bytes::expr(&d[..16]),     // exponentiation_lo
+            from_bytes::expr(&d[16..]),     // exponentiation_hi
+        ]
+    }
+}
+
+impl<F: Field> ExpCircuit<F> {
+    /// Configure the Exponentiation Circuit constraining the
+    /// square-and-multiply steps of every exponentiation event.
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_first = meta.selector();
+        let is_first = meta.advice_column();
+        let is_last = meta.advice_column();
+        let identifier = meta.advice_column();
+        let base = [(); 32].map(|_| meta.advice_column());
+        let a_limbs = [(); 4].map(|_| meta.advice_column());
+        let b_limbs = [(); 4].map(|_| meta.advice_column());
+        let d = [(); 32].map(|_| meta.advice_column());
+        let carry_lo = [(); 9].map(|_| meta.advice_column());
+        let carry_hi = [(); 9].map(|_| meta.advice_column());
+        let is_square = meta.advice_column();
+        let exponent_lo = [(); 16].map(|_| meta.advice_column());
+        let exponent_hi = meta.advice_column();
+        let exponent_carry = meta.advice_column();
+        let u8_table = meta.fixed_column();
+
+        let config = Self {
+            q_enable,
+            q_first,
+            is_first,
+            is_last,
+            identifier,
+            base,
+            a_limbs,
+            b_limbs,
+            d,
+            carry_lo,
+            carry_hi,
+            is_square,
+            exponent_lo,
+            exponent_hi,
+            exponent_carry,
+            u8_table,
+            _marker: PhantomData,
+        };
+
+        meta.create_gate("verify first row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_first == 1 for the first row",
+                meta.query_advice(is_first, Rotation::cur()),
+                1.expr(),
+            );
+
+            cb.gate(meta.query_selector(q_first) * meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("verify step", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            for (name, column) in [
+                ("is_first is boolean", is_first),
+                ("is_last is boolean", is_last),
+                ("is_square is boolean", is_square),
+                ("exponent_carry is boolean", exponent_carry),
+            ] {
+                cb.require_boolean(name, meta.query_advice(column, Rotation::cur()));
+            }
+
+            let base_limbs = config.query_limbs(meta, &base, Rotation::cur());
+            let a_limbs = a_limbs.map(|column| meta.query_advice(column, Rotation::cur()));
+            let b_limbs = b_limbs.map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_square = meta.query_advice(is_square, Rotation::cur());
+
+            cb.condition(is_square.clone(), |cb| {
+                for (a, b) in a_limbs.iter().zip(b_limbs.iter()) {
+                    cb.require_equal("b == a for a squaring step", b.clone(), a.clone());
+                }
+            });
+            cb.condition(not::expr(is_square), |cb| {
+                for (base, b) in base_limbs.iter().zip(b_limbs.iter()) {
+                    cb.require_equal(
+                        "b == base for a multiplication step",
+                        b.clone(),
+                        base.clone(),
+                    );
+                }
+            });
+
+            // Verify a * b == d (mod 2^256), the same way as MulAddWordsGadget
+            // does in the EVM circuit.
+            let t0 = a_limbs[0].clone() * b_limbs[0].clone();
+            let t1 =
+                a_limbs[0].clone() * b_limbs[1].clone() + a_limbs[1].clone() * b_limbs[0].clone();
+            let t2 = a_limbs[0].clone() * b_limbs[2].clone()
+                + a_limbs[1].clone() * b_limbs[1].clone()
+                + a_limbs[2].clone() * b_limbs[0].clone();
+            let t3 = a_limbs[0].clone() * b_limbs[3].clone()
+                + a_limbs[1].clone() * b_limbs[2].clone()
+                + a_limbs[2].clone() * b_limbs[1].clone()
+                + a_limbs[3].clone() * b_limbs[0].clone();
+            let d = config.query_bytes(meta, &d, Rotation::cur());
+            let carry_lo = from_bytes::expr(&config.query_bytes(meta, &carry_lo, Rotation::cur()));
+            let carry_hi = from_bytes::expr(&config.query_bytes(meta, &carry_hi, Rotation::cur()));
+            cb.require_equal(
+                "(a * b)_lo == d_lo + carry_lo ⋅ 2^128",
+                t0 + t1 * pow_of_two_expr(64),
+                from_bytes::expr(&d[..16]) + carry_lo.clone() * pow_of_two_expr(128),
+            );
+            cb.require_equal(
+                "(a * b)_hi + carry_lo == d_hi + carry_hi ⋅ 2^128",
+                t2 + t3 * pow_of_two_expr(64) + carry_lo,
+                from_bytes::expr(&d[16..]) + carry_hi * pow_of_two_expr(128),
+            );
+
+            // The first step starts from base ^ 1, and both squaring it or
+            // multiplying it by the base results in base ^ 2.
+            cb.condition(meta.query_advice(is_first, Rotation::cur()), |cb| {
+                for (base, a) in base_limbs.iter().zip(a_limbs.iter()) {
+                    cb.require_equal("a == base for the first step", a.clone(), base.clone());
+                }
+                cb.require_equal(
+                    "exponent_lo == 2 for the first step",
+                    from_bytes::expr(&config.query_bytes(meta, &exponent_lo, Rotation::cur())),
+                    2.expr(),
+                );
+                cb.require_zero(
+                    "exponent_hi == 0 for the first step",
+                    meta.query_advice(exponent_hi, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("verify transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_last = meta.query_advice(is_last, Rotation::cur());
+            let q_enable_next = meta.query_fixed(q_enable, Rotation::next());
+            let is_first_next = meta.query_advice(is_first, Rotation::next());
+            let is_square_next = meta.query_advice(is_square, Rotation::next());
+
+            cb.require_zero(
+                "is_last == 1 if the next row is disabled",
+                and::expr([not::expr(is_last.clone()), not::expr(q_enable_next.clone())]),
+            );
+            cb.condition(is_last.clone(), |cb| {
+                cb.require_equal(
+                    "next row is the first step of the next event if enabled",
+                    is_first_next.clone(),
+                    q_enable_next,
+                );
+            });
+
+            cb.condition(not::expr(is_last.clone()), |cb| {
+                cb.require_zero("is_first == 0 for a non-first step", is_first_next);
+                cb.require_equal(
+                    "identifier stays the same in the event",
+                    meta.query_advice(identifier, Rotation::next()),
+                    meta.query_advice(identifier, Rotation::cur()),
+                );
+                for (name, range) in [
+                    ("base_lo stays the same in the event", 0..16),
+                    ("base_hi stays the same in the event", 16..32),
+                ] {
+                    cb.require_equal(
+                        name,
+                        from_bytes::expr(&config.query_bytes(
+                            meta,
+                            &base[range.clone()],
+                            Rotation::next(),
+                        )),
+                        from_bytes::expr(&config.query_bytes(
+                            meta,
+                            &base[range],
+                            Rotation::cur(),
+                        )),
+                    );
+                }
+                let d_limbs = config.query_limbs(meta, &d, Rotation::cur());
+                for (a_next, d) in a_limbs.iter().zip(d_limbs) {
+                    cb.require_equal(
+                        "next step's a == d",
+                        meta.query_advice(*a_next, Rotation::next()),
+                        d,
+                    );
+                }
+            });
+
+            // Keep track of the exponent accumulated after every step, which
+            // is doubled by a squaring step and incremented by a
+            // multiplication step. The low 128 bits are range checked by
+            // bytes, which makes the carry to the high 128 bits unique.
+            let exponent_lo = from_bytes::expr(&config.query_bytes(
+                meta,
+                &exponent_lo,
+                Rotation::cur(),
+            ));
+            let exponent_hi = meta.query_advice(exponent_hi, Rotation::cur());
+            let exponent_lo_next = from_bytes::expr(&config.query_bytes(
+                meta,
+                &config.exponent_lo,
+                Rotation::next(),
+            ));
+            let exponent_hi_next = meta.query_advice(config.exponent_hi, Rotation::next());
+            let exponent_carry_next = meta.query_advice(exponent_carry, Rotation::next());
+            cb.condition(
+                and::expr([not::expr(is_last.clone()), is_square_next.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "exponent_lo_next + exponent_carry_next ⋅ 2^128 == 2 ⋅ exponent_lo for a squaring step",
+                        exponent_lo_next.clone()
+                            + exponent_carry_next.clone() * pow_of_two_expr(128),
+                        2.expr() * exponent_lo.clone(),
+                    );
+                    cb.require_equal(
+                        "exponent_hi_next == 2 ⋅ exponent_hi + exponent_carry_next for a squaring step",
+                        exponent_hi_next.clone(),
+                        2.expr() * exponent_hi.clone() + exponent_carry_next.clone(),
+                    );
+                },
+            );
+            cb.condition(
+                and::expr([not::expr(is_last), not::expr(is_square_next)]),
+                |cb| {
+                    cb.require_equal(
+                        "exponent_lo_next + exponent_carry_next ⋅ 2^128 == exponent_lo + 1 for a multiplication step",
+                        exponent_lo_next + exponent_carry_next.clone() * pow_of_two_expr(128),
+                        exponent_lo + 1.expr(),
+                    );
+                    cb.require_equal(
+                        "exponent_hi_next == exponent_hi + exponent_carry_next for a multiplication step",
+                        exponent_hi_next,
+                        exponent_hi + exponent_carry_next,
+                    );
+                },
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        for (name, column) in base
+            .iter()
+            .map(|column| ("base byte range check", column))
+            .chain(d.iter().map(|column| ("d byte range check", column)))
+            .chain(
+                carry_lo
+                    .iter()
+                    .map(|column| ("carry_lo byte range check", column)),
+            )
+            .chain(
+                carry_hi
+                    .iter()
+                    .map(|column| ("carry_hi byte range check", column)),
+            )
+            .chain(
+                exponent_lo
+                    .iter()
+                    .map(|column| ("exponent_lo byte range check", column)),
+            )
+        {
+            meta.lookup_any(name, |meta| {
+                vec![(
+                    meta.query_fixed(q_enable, Rotation::cur())
+                        * meta.query_advice(*column, Rotation::cur()),
+                    meta.query_fixed(u8_table, Rotation::cur()),
+                )]
+            });
+        }
+
+        config
+    }
+
+    fn query_bytes(
+        &self,
+        meta: &mut VirtualCells<F>,
+        columns: &[Column<Advice>],
+        rotation: Rotation,
+    ) -> Vec<Expression<F>> {
+        columns
+            .iter()
+            .map(|column| meta.query_advice(*column, rotation))
+            .collect()
+    }
+
+    fn query_limbs(
+        &self,
+        meta: &mut VirtualCells<F>,
+        columns: &[Column<Advice>; 32],
+        rotation: Rotation,
+    ) -> Vec<Expression<F>> {
+        columns
+            .chunks(8)
+            .map(|chunk| from_bytes::expr(&self.query_bytes(meta, chunk, rotation)))
+            .collect()
+    }
+
+    /// Assign a witness block to the Exponentiation Circuit.
+    pub fn assign_block(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        block: &Block<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "exponentiation table u8",
+            |mut region| {
+                for value in 0..256 {
+                    region.assign_fixed(
+                        || format!("u8 table row {}", value),
+                        self.u8_table,
+                        value,
+                        || Ok(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "assign exponentiation table",
+            |mut region| {
+                let mut offset = 0;
+                self.q_first.enable(&mut region, offset)?;
+                for exp_event in block.exp_events.iter() {
+                    for (step_idx, exp_step) in exp_event.steps.iter().enumerate() {
+                        self.assign_step(&mut region, offset, exp_event, step_idx, exp_step)?;
+                        offset += 1;
+                    }
+                }
+                // pad two rows in the end to satisfy Halo2 cell assignment check
+                for _ in 0..2 {
+                    self.assign_padding_row(&mut region, offset)?;
+                    offset += 1;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_step(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        exp_event: &ExpEvent,
+        step_idx: usize,
+        exp_step: &ExpStep,
+    ) -> Result<(), Error> {
+        // q_enable
+        region.assign_fixed(|| "q_enable", self.q_enable, offset, || Ok(F::one()))?;
+        // is_first
+        region.assign_advice(
+            || format!("assign is_first {}", offset),
+            self.is_first,
+            offset,
+            || Ok(F::from((step_idx == 0) as u64)),
+        )?;
+        // is_last
+        region.assign_advice(
+            || format!("assign is_last {}", offset),
+            self.is_last,
+            offset,
+            || Ok(F::from((step_idx == exp_event.steps.len() - 1) as u64)),
+        )?;
+        // identifier
+        region.assign_advice(
+            || format!("assign identifier {}", offset),
+            self.identifier,
+            offset,
+            || Ok(F::from(exp_event.identifier as u64)),
+        )?;
+        // is_square
+        region.assign_advice(
+            || format!("assign is_square {}", offset),
+            self.is_square,
+            offset,
+            || Ok(F::from(exp_step.is_square as u64)),
+        )?;
+
+        // multiplication a * b == d (mod 2^256)
+        let a_limbs = split_u256_limb64(&exp_step.a);
+        let b_limbs = split_u256_limb64(&exp_step.b);
+        let (d_lo, d_hi) = split_u256(&exp_step.d);
+        let t0 = a_limbs[0] * b_limbs[0];
+        let t1 = a_limbs[0] * b_limbs[1] + a_limbs[1] * b_limbs[0];
+        let t2 = a_limbs[0] * b_limbs[2] + a_limbs[1] * b_limbs[1] + a_limbs[2] * b_limbs[0];
+        let t3 = a_limbs[0] * b_limbs[3]
+            + a_limbs[1] * b_limbs[2]
+            + a_limbs[2] * b_limbs[1]
+            + a_limbs[3] * b_limbs[0];
+        let carry_lo = (t0 + (t1 << 64) - d_lo) >> 128;
+        let carry_hi = (t2 + (t3 << 64) + carry_lo - d_hi) >> 128;
+        for (name, columns, limbs) in [("a", &self.a_limbs, a_limbs), ("b", &self.b_limbs, b_limbs)]
+        {
+            for (column, limb) in columns.iter().zip(limbs) {
+                region.assign_advice(
+                    || format!("assign {} limb {}", name, offset),
+                    *column,
+                    offset,
+                    || Ok(F::from(limb.as_u64())),
+                )?;
+            }
+        }
+
+        // exponent accumulated after this step
+        let (exponent_lo, exponent_hi) = split_u256(&exp_step.exponent);
+        let exponent_carry = if step_idx == 0 {
+            false
+        } else {
+            let (exponent_lo_prev, _) = split_u256(&exp_event.steps[step_idx - 1].exponent);
+            if exp_step.is_square {
+                exponent_lo_prev.bit(127)
+            } else {
+                exponent_lo_prev.as_u128() == u128::MAX
+            }
+        };
+        region.assign_advice(
+            || format!("assign exponent_hi {}", offset),
+            self.exponent_hi,
+            offset,
+            || Ok(F::from_u128(exponent_hi.as_u128())),
+        )?;
+        region.assign_advice(
+            || format!("assign exponent_carry {}", offset),
+            self.exponent_carry,
+            offset,
+            || Ok(F::from(exponent_carry as u64)),
+        )?;
+
+        for (name, columns, bytes) in [
+            (
+                "base",
+                &self.base[..],
+                exp_event.base.to_le_bytes().to_vec(),
+            ),
+            ("d", &self.d[..], exp_step.d.to_le_bytes().to_vec()),
+            (
+                "carry_lo",
+                &self.carry_lo[..],
+                carry_lo.to_le_bytes().to_vec(),
+            ),
+            (
+                "carry_hi",
+                &self.carry_hi[..],
+                carry_hi.to_le_bytes().to_vec(),
+            ),
+            (
+                "exponent_lo",
+                &self.exponent_lo[..],
+                exponent_lo.to_le_bytes().to_vec(),
+            ),
+        ] {
+            for (column, byte) in columns.iter().zip(bytes) {
+                region.assign_advice(
+                    || format!("assign {} byte {}", name, offset),
+                    *column,
+                    offset,
+                    || Ok(F::from(byte as u64)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assign_padding_row(&self, region: &mut Region<F>, offset: usize) -> Result<(), Error> {
+        // q_enable
+        region.assign_fixed(|| "q_enable", self.q_enable, offset, || Ok(F::zero()))?;
+        for column in [
+            self.is_first,
+            self.is_last,
+            self.identifier,
+            self.is_square,
+            self.exponent_hi,
+            self.exponent_carry,
+        ]
+        .iter()
+        .chain(self.base.iter())
+        .chain(self.a_limbs.iter())
+        .chain(self.b_limbs.iter())
+        .chain(self.d.iter())
+        .chain(self.carry_lo.iter())
+        .chain(self.carry_hi.iter())
+        .chain(self.exponent_lo.iter())
+        {
+            region.assign_advice(
+                || format!("assign padding row {}", offset),
+                *column,
+                offset,
+                || Ok(F::zero()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bus_mapping::{circuit_input_builder::CircuitInputBuilder, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData, Field, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use mock::TestContext;
+    use rand::Rng;
+
+    use crate::evm_circuit::witness::{block_convert, Block};
+
+    use super::ExpCircuit;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        block: Block<F>,
+    }
+
+    impl<F> MyCircuit<F> {
+        pub fn new(block: Block<F>) -> Self {
+            Self { block }
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = ExpCircuit<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            ExpCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign_block(&mut layouter, &self.block)
+        }
+    }
+
+    fn run_circuit<F: Field>(k: u32, block: Block<F>) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = MyCircuit::<F>::new(block);
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        prover.verify()
+    }
+
+    fn gen_exp_data(base: Word, exponent: Word) -> CircuitInputBuilder {
+        let code = bytecode! {
+            PUSH32(exponent)
+            PUSH32(base)
+            EXP
+            STOP
+        };
+        let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+        let block: GethData = test_ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder
+    }
+
+    fn test_ok(base: Word, exponent: Word) {
+        let builder = gen_exp_data(base, exponent);
+        assert_eq!(
+            builder.block.exp_events[0].exponentiation,
+            base.overflowing_pow(exponent).0
+        );
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert_eq!(run_circuit(10, block), Ok(()));
+    }
+
+    #[test]
+    fn exp_circuit_valid() {
+        test_ok(2.into(), 2.into());
+        test_ok(3.into(), 7.into());
+        test_ok(0xcafe.into(), 0xbabe.into());
+        test_ok(Word::MAX, Word::MAX);
+        test_ok(2.into(), (Word::one() << 128) + 1);
+    }
+
+    #[test]
+    fn exp_circuit_invalid() {
+        let mut builder = gen_exp_data(3.into(), 0xbabe.into());
+        let exp_event = &mut builder.block.exp_events[0];
+        let mut rng = rand::thread_rng();
+        // the first step both squares and multiplies the base, so skip it.
+        let step_idx = rng.gen_range(1..exp_event.steps.len());
+        match rng.gen::<f32>() {
+            f if f < 0.33 => exp_event.base = exp_event.base + 1,
+            f if f < 0.66 => {
+                exp_event.steps[step_idx].exponent = exp_event.steps[step_idx].exponent + 1
+            }
+            _ => exp_event.steps[step_idx].is_square = !exp_event.steps[step_idx].is_square,
+        }
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert!(run_circuit(10, block).is_err());
+    }
+}
@@ -19,6 +19,8 @@
 pub mod bytecode_circuit;
 pub mod copy_circuit;
 pub mod evm_circuit;
+pub mod exp_circuit;
+pub mod precompile_circuit;
 pub mod rw_table;
 pub mod state_circuit;
 #[cfg(test)]
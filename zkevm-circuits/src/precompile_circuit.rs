@@ -0,0 +1,534 @@
+//! The Precompile circuit implements the lookup table of the calls to the
+//! precompiled contracts done by the EVM circuit, and verifies the output of
+//! every call from its input.
+//!
+//! Every row of the table holds a call as `(address, input_rlc, input_len,
+//! output_rlc, output_len)`, where the RLCs are accumulated in the order the
+//! bytes are copied, so that the first byte gets the highest power of the
+//! randomness. The following precompiled contracts are supported:
+//! - Identity (0x04), whose output equals its input.
+//! - ECRecover (0x01), whose signature and recovery id are verified by the
+//!   [`SignVerifyChip`] when `v` is 27 or 28, and whose output is empty
+//!   otherwise. The calls with a `v` of 27 or 28 and a signature from which no
+//!   public key can be recovered aren't supported, as their invalidity can't be
+//!   proven.
+
+// Naming notes:
+// - *_be: Big-Endian bytes
+// - *_le: Little-Endian bytes
+
+use crate::{
+    evm_circuit::table::LookupTable,
+    tx_circuit::{
+        biguint_to_32bytes_le, ct_option_ok_or, recover_pk,
+        sign_verify::{SignData, SignVerifyChip, SignVerifyConfig, POW_RAND_SIZE},
+        SECP256K1_Q,
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::PrecompileEvent, precompile::PrecompileCalls};
+use eth_types::{Field, ToLittleEndian, Word};
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
+    poly::Rotation,
+};
+use log::error;
+use num::Integer;
+use num_bigint::BigUint;
+use std::marker::PhantomData;
+
+/// Config for PrecompileCircuit
+#[derive(Clone, Debug)]
+pub struct PrecompileCircuitConfig<F: Field> {
+    q_enable: Selector,
+    // Enabled on the rows reserved to the calls to ecrecover with a valid
+    // signature, which are the only rows copy constrained to the signature
+    // verifications.
+    q_ecrecover: Selector,
+    /// Address of the precompiled contract, or 0 for padding.
+    pub address: Column<Advice>,
+    /// RLC of the input the precompiled contract operates on.
+    pub input_rlc: Column<Advice>,
+    /// Length of the input the precompiled contract operates on.
+    pub input_len: Column<Advice>,
+    /// RLC of the output of the call.
+    pub output_rlc: Column<Advice>,
+    /// Length of the output of the call.
+    pub output_len: Column<Advice>,
+    // RLC of the message hash of an ecrecover input
+    msg_hash_rlc: Column<Advice>,
+    // RLC of the `v` word of an ecrecover input
+    sig_v_rlc: Column<Advice>,
+    // RLC of the `r` and `s` words of an ecrecover input
+    sig_rlc: Column<Advice>,
+    // Whether the signature of an ecrecover input is valid, which is when `v`
+    // is 27 or 28
+    is_valid: Column<Advice>,
+    // Inverse of (v - 27) * (v - 28), when it's not zero
+    sig_v_inv: Column<Advice>,
+    // Recovery id of a valid signature, equal to v - 27
+    recovery_id: Column<Advice>,
+    sign_verify: SignVerifyConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LookupTable<F> for PrecompileCircuitConfig<F> {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_advice(self.address, Rotation::cur()),
+            meta.query_advice(self.input_rlc, Rotation::cur()),
+            meta.query_advice(self.input_len, Rotation::cur()),
+            meta.query_advice(self.output_rlc, Rotation::cur()),
+            meta.query_advice(self.output_len, Rotation::cur()),
+        ]
+    }
+}
+
+impl<F: Field> PrecompileCircuitConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_enable = meta.selector();
+        let q_ecrecover = meta.selector();
+        let [address, input_rlc, input_len, output_rlc, output_len] =
+            [(); 5].map(|_| meta.advice_column());
+        let msg_hash_rlc = meta.advice_column();
+        meta.enable_equality(msg_hash_rlc);
+        meta.enable_equality(output_rlc);
+        let sig_v_rlc = meta.advice_column();
+        let sig_rlc = meta.advice_column();
+        meta.enable_equality(sig_rlc);
+        let is_valid = meta.advice_column();
+        let sig_v_inv = meta.advice_column();
+        let recovery_id = meta.advice_column();
+        meta.enable_equality(recovery_id);
+
+        // This gate is used just to get the array of expressions from the power of
+        // randomness instance column, so that later on we don't need to query
+        // columns everywhere, and can pass the power of randomness array
+        // expression everywhere.  The gate itself doesn't add any constraints.
+        let power_of_randomness = {
+            let columns = [(); POW_RAND_SIZE].map(|_| meta.instance_column());
+            let mut power_of_randomness = None;
+
+            meta.create_gate("power of randomness", |meta| {
+                power_of_randomness =
+                    Some(columns.map(|column| meta.query_instance(column, Rotation::cur())));
+
+                [0.expr()]
+            });
+
+            power_of_randomness.unwrap()
+        };
+
+        meta.create_gate("precompile call", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let q_ecrecover = meta.query_selector(q_ecrecover);
+            let address = meta.query_advice(address, Rotation::cur());
+            let input_rlc = meta.query_advice(input_rlc, Rotation::cur());
+            let input_len = meta.query_advice(input_len, Rotation::cur());
+            let output_rlc = meta.query_advice(output_rlc, Rotation::cur());
+            let output_len = meta.query_advice(output_len, Rotation::cur());
+            let msg_hash_rlc = meta.query_advice(msg_hash_rlc, Rotation::cur());
+            let sig_v_rlc = meta.query_advice(sig_v_rlc, Rotation::cur());
+            let sig_rlc = meta.query_advice(sig_rlc, Rotation::cur());
+            let is_valid = meta.query_advice(is_valid, Rotation::cur());
+            let sig_v_inv = meta.query_advice(sig_v_inv, Rotation::cur());
+            let recovery_id = meta.query_advice(recovery_id, Rotation::cur());
+
+            // TODO: Support the other precompiled contracts.
+            let is_supported =
+                address.clone() * (address.clone() - 1.expr()) * (address.clone() - 4.expr());
+            // Lagrange interpolations over the addresses {0, 1, 4}
+            let is_ecrecover = address.clone()
+                * (4.expr() - address.clone())
+                * Expression::Constant(F::from(3).invert().unwrap());
+            let is_identity = address.clone()
+                * (address.clone() - 1.expr())
+                * Expression::Constant(F::from(12).invert().unwrap());
+
+            // The input is the 32 bytes long message hash followed by the 32
+            // bytes long `v` and the 64 bytes of `r` and `s` of the signature,
+            // where msg_hash_rlc, sig_rlc and recovery_id of a valid signature
+            // are copy constrained to the ones verified by the SignVerifyChip.
+            // A signature is valid when `v` is 27 or 28, as the calls with
+            // another `v` are the only invalid ones supported.
+            // TODO: Support the message hashes greater than the order of
+            // secp256k1.
+            let sig_v_product = (sig_v_rlc.clone() - 27.expr()) * (sig_v_rlc.clone() - 28.expr());
+            let r_pow_64 = power_of_randomness[62].clone() * power_of_randomness[0].clone();
+            let r_pow_96 = power_of_randomness[62].clone() * power_of_randomness[32].clone();
+
+            vec![
+                q_enable.clone() * is_supported,
+                q_enable.clone()
+                    * (1.expr() - q_ecrecover.clone())
+                    * is_ecrecover.clone()
+                    * is_valid.clone(),
+                q_ecrecover.clone() * address.clone() * (address - 1.expr()),
+                q_ecrecover * is_ecrecover.clone() * (1.expr() - is_valid.clone()),
+                q_enable.clone() * is_identity.clone() * (input_rlc.clone() - output_rlc.clone()),
+                q_enable.clone() * is_identity * (input_len.clone() - output_len.clone()),
+                q_enable.clone()
+                    * is_ecrecover.clone()
+                    * (is_valid.clone() - (1.expr() - sig_v_product.clone() * sig_v_inv)),
+                q_enable.clone() * is_ecrecover.clone() * sig_v_product * is_valid.clone(),
+                q_ecrecover.clone()
+                    * is_ecrecover.clone()
+                    * (sig_v_rlc.clone() - 27.expr() - recovery_id),
+                q_enable.clone() * is_ecrecover.clone() * (input_len - 128.expr()),
+                q_enable.clone()
+                    * is_ecrecover.clone()
+                    * (output_len - 32.expr() * is_valid.clone()),
+                q_enable.clone() * is_ecrecover.clone() * (1.expr() - is_valid) * output_rlc,
+                q_enable
+                    * is_ecrecover
+                    * (input_rlc - (msg_hash_rlc * r_pow_96 + sig_v_rlc * r_pow_64 + sig_rlc)),
+            ]
+        });
+
+        let sign_verify = SignVerifyConfig::new(meta, power_of_randomness);
+
+        Self {
+            q_enable,
+            q_ecrecover,
+            address,
+            input_rlc,
+            input_len,
+            output_rlc,
+            output_len,
+            msg_hash_rlc,
+            sig_v_rlc,
+            sig_rlc,
+            is_valid,
+            sig_v_inv,
+            recovery_id,
+            sign_verify,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns a precompile call in the row at offset, or a padding row when
+    /// there is no call, and returns the assigned cells of the message hash
+    /// RLC, the signature RLC, the output RLC and the recovery id in the row.
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        event: Option<&PrecompileEvent>,
+        recovery_id: F,
+        randomness: F,
+    ) -> Result<[AssignedCell<F, F>; 4], Error> {
+        let rlc = |bytes: &[u8]| {
+            bytes.iter().fold(F::zero(), |acc, byte| {
+                acc * randomness + F::from(*byte as u64)
+            })
+        };
+        let (row, [msg_hash_rlc, sig_v_rlc, sig_rlc, is_valid, sig_v_inv]) = match event {
+            Some(event) => {
+                let ecrecover_columns = match event.precompile {
+                    PrecompileCalls::ECRecover => {
+                        let sig_v_rlc = rlc(&event.input[32..64]);
+                        let sig_v_product = (sig_v_rlc - F::from(27)) * (sig_v_rlc - F::from(28));
+                        [
+                            rlc(&event.input[..32]),
+                            sig_v_rlc,
+                            rlc(&event.input[64..]),
+                            F::from(!event.output.is_empty() as u64),
+                            sig_v_product.invert().unwrap_or(F::zero()),
+                        ]
+                    }
+                    _ => [F::zero(); 5],
+                };
+                (
+                    [
+                        F::from(event.precompile as u64),
+                        rlc(&event.input),
+                        F::from(event.input.len() as u64),
+                        rlc(&event.output),
+                        F::from(event.output.len() as u64),
+                    ],
+                    ecrecover_columns,
+                )
+            }
+            None => ([F::zero(); 5], [F::zero(); 5]),
+        };
+
+        self.q_enable.enable(region, offset)?;
+        let mut assigned_cells = Vec::with_capacity(11);
+        for (column, value) in [
+            (self.address, row[0]),
+            (self.input_rlc, row[1]),
+            (self.input_len, row[2]),
+            (self.output_rlc, row[3]),
+            (self.output_len, row[4]),
+            (self.msg_hash_rlc, msg_hash_rlc),
+            (self.sig_v_rlc, sig_v_rlc),
+            (self.sig_rlc, sig_rlc),
+            (self.is_valid, is_valid),
+            (self.sig_v_inv, sig_v_inv),
+            (self.recovery_id, recovery_id),
+        ] {
+            assigned_cells.push(region.assign_advice(
+                || "precompile table",
+                column,
+                offset,
+                || Ok(value),
+            )?);
+        }
+        Ok([
+            assigned_cells[5].clone(),
+            assigned_cells[7].clone(),
+            assigned_cells[3].clone(),
+            assigned_cells[10].clone(),
+        ])
+    }
+}
+
+/// Return the signature verification of a call to ecrecover with a valid
+/// signature, which is the one with a non-empty output.
+fn ecrecover_to_sign_data(event: &PrecompileEvent) -> Result<SignData, Error> {
+    let input = &event.input;
+    let msg_hash: [u8; 32] = input[..32].try_into().unwrap();
+    let v = Word::from_big_endian(&input[32..64]);
+    let r = Word::from_big_endian(&input[64..96]);
+    let s = Word::from_big_endian(&input[96..128]);
+    let sig_r = ct_option_ok_or(secp256k1::Fq::from_repr(r.to_le_bytes()), Error::Synthesis)?;
+    let sig_s = ct_option_ok_or(secp256k1::Fq::from_repr(s.to_le_bytes()), Error::Synthesis)?;
+    let pk = recover_pk((v.low_u64() - 27) as u8, &r, &s, &msg_hash)?;
+    // msg_hash = msg_hash % q
+    let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
+    let msg_hash = msg_hash.mod_floor(&*SECP256K1_Q);
+    let msg_hash = ct_option_ok_or(
+        secp256k1::Fq::from_repr(biguint_to_32bytes_le(msg_hash)),
+        Error::Synthesis,
+    )?;
+    Ok(SignData {
+        signature: (sig_r, sig_s),
+        pk,
+        msg_hash,
+    })
+}
+
+/// Precompile Circuit for verifying the calls to the precompiled contracts
+#[derive(Default)]
+pub struct PrecompileCircuit<F: Field, const MAX_ECRECOVER: usize> {
+    /// SignVerify chip, which verifies the calls to ecrecover
+    pub sign_verify: SignVerifyChip<F, MAX_ECRECOVER>,
+    /// Randomness for RLC encoding
+    pub randomness: F,
+    /// List of calls to the precompiled contracts
+    pub events: Vec<PrecompileEvent>,
+}
+
+impl<F: Field, const MAX_ECRECOVER: usize> Circuit<F> for PrecompileCircuit<F, MAX_ECRECOVER> {
+    type Config = PrecompileCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        PrecompileCircuitConfig::new(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        // The calls to ecrecover with an invalid signature have an empty output,
+        // and are not verified by the SignVerifyChip.
+        let (ecrecover_events, other_events): (Vec<_>, Vec<_>) =
+            self.events.iter().partition(|event| {
+                event.precompile == PrecompileCalls::ECRecover && !event.output.is_empty()
+            });
+        if let Some(event) = other_events.iter().find(|event| {
+            event.precompile == PrecompileCalls::ECRecover
+                && [27, 28]
+                    .map(Word::from)
+                    .contains(&Word::from_big_endian(&event.input[32..64]))
+        }) {
+            error!(
+                "unsupported invalid ecrecover signature with v = 27 or 28 for input {:?}",
+                event.input
+            );
+            return Err(Error::Synthesis);
+        }
+        if let Some(event) = other_events.iter().find(|event| {
+            !matches!(
+                event.precompile,
+                PrecompileCalls::ECRecover | PrecompileCalls::Identity
+            )
+        }) {
+            error!("unsupported precompiled contract {:?}", event.precompile);
+            return Err(Error::Synthesis);
+        }
+        let sign_datas: Vec<SignData> = ecrecover_events
+            .iter()
+            .map(|event| {
+                ecrecover_to_sign_data(event).map_err(|e| {
+                    error!("ecrecover_to_sign_data error for input {:?}", event.input);
+                    e
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let assigned_sig_verifs = self.sign_verify.assign(
+            &config.sign_verify,
+            &mut layouter,
+            self.randomness,
+            &sign_datas,
+        )?;
+
+        layouter.assign_region(
+            || "precompile table",
+            |mut region| {
+                let mut offset = 0;
+                // Empty entry
+                config.assign_row(&mut region, offset, None, F::zero(), self.randomness)?;
+                offset += 1;
+                // The rows of the calls to ecrecover, in the same order as the
+                // signature verifications
+                for (i, assigned_sig_verif) in assigned_sig_verifs.iter().enumerate() {
+                    config.q_ecrecover.enable(&mut region, offset)?;
+                    // The recovery id of a padding row is the one of the padding
+                    // signature verification, which is unconstrained.
+                    let [msg_hash_rlc, sig_rlc, output_rlc, recovery_id] = config.assign_row(
+                        &mut region,
+                        offset,
+                        ecrecover_events.get(i).copied(),
+                        assigned_sig_verif
+                            .recovery_id
+                            .value()
+                            .cloned()
+                            .ok_or(Error::Synthesis)?,
+                        self.randomness,
+                    )?;
+                    offset += 1;
+
+                    // Copy constraints using fixed offsets between the ecrecover
+                    // rows and the SignVerifyChip
+                    region.constrain_equal(
+                        msg_hash_rlc.cell(),
+                        assigned_sig_verif.msg_hash_rlc.cell(),
+                    )?;
+                    region.constrain_equal(sig_rlc.cell(), assigned_sig_verif.sig_rlc.cell())?;
+                    region.constrain_equal(
+                        output_rlc.cell(),
+                        assigned_sig_verif.address_rlc.cell(),
+                    )?;
+                    region.constrain_equal(
+                        recovery_id.cell(),
+                        assigned_sig_verif.recovery_id.cell(),
+                    )?;
+                }
+                // The rows of the calls to the other precompiled contracts
+                for event in other_events.iter() {
+                    config.assign_row(
+                        &mut region,
+                        offset,
+                        Some(*event),
+                        F::zero(),
+                        self.randomness,
+                    )?;
+                    offset += 1;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod precompile_circuit_tests {
+    use super::*;
+    use eth_types::{ToBigEndian, H256};
+    use ethers_signers::{LocalWallet, Signer};
+    use group::{Curve, Group};
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        dev::{MockProver, VerifyFailure},
+        pairing::bn256::Fr,
+    };
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use secp256k1::Secp256k1Affine;
+
+    fn run<F: Field, const MAX_ECRECOVER: usize>(
+        k: u32,
+        events: Vec<PrecompileEvent>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let aux_generator =
+            <Secp256k1Affine as CurveAffine>::CurveExt::random(&mut rng).to_affine();
+
+        let randomness = F::random(&mut rng);
+        let mut instance: Vec<Vec<F>> = (1..POW_RAND_SIZE + 1)
+            .map(|exp| vec![randomness.pow(&[exp as u64, 0, 0, 0]); MAX_ECRECOVER + 1])
+            .collect();
+        // SignVerifyChip -> ECDSAChip -> MainGate instance column
+        instance.push(vec![]);
+        let circuit = PrecompileCircuit::<F, MAX_ECRECOVER> {
+            sign_verify: SignVerifyChip {
+                aux_generator,
+                window_size: 2,
+                _marker: PhantomData,
+            },
+            randomness,
+            events,
+        };
+
+        let prover = match MockProver::run(k, &circuit, instance) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify()
+    }
+
+    fn ecrecover_event() -> PrecompileEvent {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let wallet = LocalWallet::new(&mut rng);
+        let sig = wallet.sign_hash(H256::from_low_u64_be(0xcafe), false);
+
+        let mut input = H256::from_low_u64_be(0xcafe).0.to_vec();
+        input.extend_from_slice(&Word::from(sig.v).to_be_bytes());
+        input.extend_from_slice(&sig.r.to_be_bytes());
+        input.extend_from_slice(&sig.s.to_be_bytes());
+        let output = PrecompileCalls::ECRecover.execute(&input).unwrap();
+        PrecompileEvent {
+            precompile: PrecompileCalls::ECRecover,
+            input,
+            output,
+        }
+    }
+
+    // High memory usage test.  Run in serial with:
+    // `cargo test [...] serial_ -- --ignored --test-threads 1`
+    #[ignore]
+    #[test]
+    fn serial_test_precompile_circuit() {
+        const MAX_ECRECOVER: usize = 2;
+
+        let data = vec![0xab; 40];
+        let events = vec![
+            PrecompileEvent {
+                precompile: PrecompileCalls::Identity,
+                input: data.clone(),
+                output: data,
+            },
+            ecrecover_event(),
+            // Invalid signature, as v is neither 27 nor 28
+            PrecompileEvent {
+                precompile: PrecompileCalls::ECRecover,
+                input: vec![0x1d; 128],
+                output: vec![],
+            },
+        ];
+
+        let k = 19;
+        assert_eq!(run::<Fr, MAX_ECRECOVER>(k, events), Ok(()));
+    }
+}
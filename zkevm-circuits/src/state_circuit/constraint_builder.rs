@@ -239,13 +239,16 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 
     fn build_account_destructed_constraints(&mut self, q: &Queries<F>) {
-        self.require_zero("id is 0 for AccountDestructed", q.id());
         self.require_zero("field_tag is 0 for AccountDestructed", q.field_tag());
         self.require_zero(
             "storage_key is 0 for AccountDestructed",
             q.storage_key.encoded.clone(),
         );
-        // TODO: Missing constraints
+        self.require_boolean("AccountDestructed value is boolean", q.value());
+        self.require_zero(
+            "initial AccountDestructed value is false",
+            q.initial_value(),
+        );
     }
 
     fn build_call_context_constraints(&mut self, q: &Queries<F>) {
@@ -912,6 +912,25 @@ fn bad_initial_tx_access_list_account_value() {
     );
 }
 
+#[test]
+fn bad_initial_account_destructed_value() {
+    let rows = vec![Rw::AccountDestructed {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000004356002"),
+        is_destructed: true,
+        is_destructed_prev: false,
+    }];
+
+    let overrides = HashMap::from([((AdviceColumn::InitialValue, 0), Fr::from(1))]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial AccountDestructed value is false",
+    );
+}
+
 #[test]
 fn bad_initial_tx_refund_value() {
     let rows = vec![Rw::TxRefund {
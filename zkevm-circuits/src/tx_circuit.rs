@@ -35,7 +35,7 @@ use subtle::CtOption;
 lazy_static! {
     // Curve Scalar.  Referece: Section 2.4.1 (parameter `n`) in "SEC 2: Recommended Elliptic Curve
     // Domain Parameters" document at http://www.secg.org/sec2-v2.pdf
-    static ref SECP256K1_Q: BigUint = BigUint::from_slice(&[
+    pub(crate) static ref SECP256K1_Q: BigUint = BigUint::from_slice(&[
         0xd0364141, 0xbfd25e8c,
         0xaf48a03b, 0xbaaedce6,
         0xfffffffe, 0xffffffff,
@@ -43,7 +43,12 @@ lazy_static! {
     ]);
 }
 
-fn recover_pk(v: u8, r: &Word, s: &Word, msg_hash: &[u8; 32]) -> Result<Secp256k1Affine, Error> {
+pub(crate) fn recover_pk(
+    v: u8,
+    r: &Word,
+    s: &Word,
+    msg_hash: &[u8; 32],
+) -> Result<Secp256k1Affine, Error> {
     let mut sig_bytes = [0u8; 64];
     sig_bytes[..32].copy_from_slice(&r.to_be_bytes());
     sig_bytes[32..].copy_from_slice(&s.to_be_bytes());
@@ -74,14 +79,14 @@ fn recover_pk(v: u8, r: &Word, s: &Word, msg_hash: &[u8; 32]) -> Result<Secp256k
     })
 }
 
-fn biguint_to_32bytes_le(v: BigUint) -> [u8; 32] {
+pub(crate) fn biguint_to_32bytes_le(v: BigUint) -> [u8; 32] {
     let mut res = [0u8; 32];
     let v_le = v.to_bytes_le();
     res[..v_le.len()].copy_from_slice(&v_le);
     res
 }
 
-fn ct_option_ok_or<T, E>(v: CtOption<T>, err: E) -> Result<T, E> {
+pub(crate) fn ct_option_ok_or<T, E>(v: CtOption<T>, err: E) -> Result<T, E> {
     Option::<T>::from(v).ok_or(err)
 }
 
@@ -5,11 +5,10 @@
 // - *_le: Little-Endian bytes
 
 use crate::{
-    evm_circuit::util::{not, RandomLinearCombination, Word},
+    evm_circuit::util::{not, rlc, RandomLinearCombination, Word},
     util::Expr,
 };
 use ecc::{EccConfig, GeneralEccChip};
-use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
 use gadgets::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 use group::{ff::Field, prime::PrimeCurveAffine, Curve};
 use halo2_proofs::{
@@ -118,6 +117,15 @@ pub(crate) struct SignVerifyConfig<F: FieldExt> {
     address_is_zero: IsZeroConfig<F>,
     address_inv: Column<Advice>,
     msg_hash_rlc: Column<Advice>,
+    // RLC of the address left padded to 32 bytes, in the byte order of the
+    // output of the ecrecover precompile.
+    address_rlc: Column<Advice>,
+    // RLC of the big-endian `r` followed by the big-endian `s` of the
+    // signature, in the byte order of the input of the ecrecover precompile.
+    sig_rlc: Column<Advice>,
+    // Parity of the y coordinate of the point R of the signature, which is
+    // the recovery id of the public key.
+    recovery_id: Column<Advice>,
 
     // ECDSA
     main_gate_config: MainGateConfig,
@@ -126,6 +134,8 @@ pub(crate) struct SignVerifyConfig<F: FieldExt> {
     // endian.
     pk: [[Column<Advice>; 32]; 2],
     msg_hash: [Column<Advice>; 32],
+    sig_r: [Column<Advice>; 32],
+    sig_s: [Column<Advice>; 32],
     power_of_randomness: [Expression<F>; POW_RAND_SIZE],
 
     // [is_enabled, input_rlc, input_len, output_rlc]
@@ -146,6 +156,12 @@ impl<F: FieldExt> SignVerifyConfig<F> {
         let msg_hash = [(); 32].map(|_| meta.advice_column());
         msg_hash.iter().for_each(|c| meta.enable_equality(*c));
 
+        let [sig_r, sig_s] = [(); 2].map(|_| [(); 32].map(|_| meta.advice_column()));
+        sig_r
+            .iter()
+            .chain(sig_s.iter())
+            .for_each(|c| meta.enable_equality(*c));
+
         let address = meta.advice_column();
         meta.enable_equality(address);
 
@@ -154,6 +170,15 @@ impl<F: FieldExt> SignVerifyConfig<F> {
         let msg_hash_rlc = meta.advice_column();
         meta.enable_equality(msg_hash_rlc);
 
+        let address_rlc = meta.advice_column();
+        meta.enable_equality(address_rlc);
+
+        let sig_rlc = meta.advice_column();
+        meta.enable_equality(sig_rlc);
+
+        let recovery_id = meta.advice_column();
+        meta.enable_equality(recovery_id);
+
         let address_inv = meta.advice_column();
         let address_is_zero = IsZeroChip::configure(
             meta,
@@ -239,6 +264,34 @@ impl<F: FieldExt> SignVerifyConfig<F> {
             vec![q_enable * (msg_hash_rlc - is_not_padding.clone() * expected_msg_hash_rlc)]
         });
 
+        // Verify that address_rlc is the RLC encoding of the address taken from
+        // the pub_key_hash, with the last byte of the address as the least
+        // significant one.
+        meta.create_gate("address_rlc = RLC(pk_hash[-20:])", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let pk_hash = pk_hash.map(|c| meta.query_advice(c, Rotation::cur()));
+            let address_rlc = meta.query_advice(address_rlc, Rotation::cur());
+
+            let address_be = pk_hash[32 - 20..].iter().rev().cloned().collect::<Vec<_>>();
+            let expected_address_rlc = rlc::expr(&address_be, &power_of_randomness[..19]);
+            vec![q_enable * (address_rlc - expected_address_rlc)]
+        });
+
+        // Verify that sig_rlc is the RLC encoding of the `r` and `s` of the
+        // signature verified in the ecdsa_chip.
+        meta.create_gate("sig_rlc = is_not_padding * RLC(r || s)", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let sig_rlc = meta.query_advice(sig_rlc, Rotation::cur());
+
+            let sig_le = sig_s
+                .iter()
+                .chain(sig_r.iter())
+                .map(|c| meta.query_advice(*c, Rotation::cur()))
+                .collect::<Vec<_>>();
+            let expected_sig_rlc = rlc::expr(&sig_le, &power_of_randomness);
+            vec![q_enable * (sig_rlc - is_not_padding.clone() * expected_sig_rlc)]
+        });
+
         // ECDSA config
         let (rns_base, rns_scalar) =
             GeneralEccChip::<Secp256k1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
@@ -253,12 +306,17 @@ impl<F: FieldExt> SignVerifyConfig<F> {
             pk_hash,
             address,
             msg_hash_rlc,
+            address_rlc,
+            sig_rlc,
+            recovery_id,
             address_is_zero,
             address_inv,
             range_config,
             main_gate_config,
             pk,
             msg_hash,
+            sig_r,
+            sig_s,
             power_of_randomness,
             keccak_table,
         }
@@ -354,12 +412,18 @@ pub(crate) struct AssignedECDSA<F: FieldExt> {
     pk_x_le: [AssignedValue<F>; 32],
     pk_y_le: [AssignedValue<F>; 32],
     msg_hash_le: [AssignedValue<F>; 32],
+    sig_r_le: [AssignedValue<F>; 32],
+    sig_s_le: [AssignedValue<F>; 32],
+    recovery_id: AssignedValue<F>,
 }
 
 #[derive(Debug)]
 pub(crate) struct AssignedSignatureVerify<F: FieldExt> {
     pub(crate) address: AssignedCell<F, F>,
     pub(crate) msg_hash_rlc: AssignedCell<F, F>,
+    pub(crate) address_rlc: AssignedCell<F, F>,
+    pub(crate) sig_rlc: AssignedCell<F, F>,
+    pub(crate) recovery_id: AssignedCell<F, F>,
 }
 
 // Returns assigned constants [256^1, 256^2, .., 256^{n-1}]
@@ -417,7 +481,6 @@ struct ChipsRef<'a, F: FieldExt, const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIM
     range_chip: &'a RangeChip<F>,
     ecc_chip: &'a GeneralEccChip<Secp256k1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
     scalar_chip: &'a IntegerChip<secp256k1::Fq, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
-    ecdsa_chip: &'a EcdsaChip<Secp256k1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
 }
 
 impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
@@ -452,36 +515,55 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             range_chip,
             ecc_chip,
             scalar_chip,
-            ecdsa_chip,
         } = chips;
 
         let integer_r = ecc_chip.new_unassigned_scalar(Some(*sig_r));
         let integer_s = ecc_chip.new_unassigned_scalar(Some(*sig_s));
         let msg_hash = ecc_chip.new_unassigned_scalar(Some(*msg_hash));
 
-        let r_assigned = scalar_chip.assign_integer(ctx, integer_r)?;
-        let s_assigned = scalar_chip.assign_integer(ctx, integer_s)?;
-        let sig = AssignedEcdsaSig {
-            r: r_assigned,
-            s: s_assigned,
-        };
-
-        let pk_in_circuit = ecc_chip.assign_point(ctx, Some(*pk))?;
-        let pk_assigned = AssignedPublicKey {
-            point: pk_in_circuit,
-        };
+        let sig_r = scalar_chip.assign_integer(ctx, integer_r)?;
+        let sig_s = scalar_chip.assign_integer(ctx, integer_s)?;
+        let pk = ecc_chip.assign_point(ctx, Some(*pk))?;
         let msg_hash = scalar_chip.assign_integer(ctx, msg_hash)?;
 
-        // Convert (msg_hash, pk_x, pk_y) integers to little endian bytes
+        // Convert (msg_hash, pk_x, pk_y, r, s) integers to little endian bytes
         let pows_256 = assign_pows_256(ctx, main_gate, 9)?;
         let msg_hash_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &msg_hash)?;
-        let pk_x = pk_assigned.point.get_x();
+        let sig_r_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &sig_r)?;
+        let sig_s_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &sig_s)?;
+        let pk_x = pk.get_x();
         let pk_x_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &pk_x)?;
-        let pk_y = pk_assigned.point.get_y();
+        let pk_y = pk.get_y();
         let pk_y_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &pk_y)?;
 
-        // Ref. spec SignVerifyChip 4. Verify the ECDSA signature
-        ecdsa_chip.verify(ctx, &sig, &pk_assigned, &msg_hash)?;
+        // Ref. spec SignVerifyChip 4. Verify the ECDSA signature.  This is the
+        // verification of `EcdsaChip::verify`, except that the point R = u1*G +
+        // u2*pk is kept to get the recovery id from its y coordinate, and that
+        // its x coordinate must equal r instead of r mod n, as the public key
+        // recovery takes r as the x coordinate of R.
+        // 0 < r, s < n
+        scalar_chip.assert_not_zero(ctx, &sig_r)?;
+        scalar_chip.assert_not_zero(ctx, &sig_s)?;
+        // u1 = msg_hash / s, u2 = r / s (mod n)
+        let (s_inv, _) = scalar_chip.invert(ctx, &sig_s)?;
+        let u1 = scalar_chip.mul(ctx, &msg_hash, &s_inv)?;
+        let u2 = scalar_chip.mul(ctx, &sig_r, &s_inv)?;
+        let generator = ecc_chip.assign_point(ctx, Some(Secp256k1Affine::generator()))?;
+        let u1_g = ecc_chip.mul(ctx, &generator, &u1, self.window_size)?;
+        let u2_pk = ecc_chip.mul(ctx, &pk, &u2, self.window_size)?;
+        let point_r = ecc_chip.add(ctx, &u1_g, &u2_pk)?;
+        let base_chip = ecc_chip.base_field_chip();
+        let point_r_x = point_r.get_x();
+        base_chip.assert_in_field(ctx, &point_r_x)?;
+        let point_r_x_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &point_r_x)?;
+        for (x_byte, r_byte) in point_r_x_le.iter().zip(sig_r_le.iter()) {
+            main_gate.assert_equal(ctx, x_byte, r_byte)?;
+        }
+        // The recovery id is the least significant bit of R.y
+        let point_r_y = point_r.get_y();
+        base_chip.assert_in_field(ctx, &point_r_y)?;
+        let point_r_y_le = integer_to_bytes_le(ctx, main_gate, range_chip, &pows_256, &point_r_y)?;
+        let recovery_id: AssignedValue<F> = main_gate.to_bits(ctx, &point_r_y_le[0], 8)?[0].into();
 
         // TODO: Update once halo2wrong suports the following methods:
         // - `IntegerChip::assign_integer_from_bytes_le`
@@ -491,6 +573,9 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             pk_x_le,
             pk_y_le,
             msg_hash_le,
+            sig_r_le,
+            sig_s_le,
+            recovery_id,
         })
     }
 
@@ -510,7 +595,7 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             None => (true, SignData::default()),
         };
         let SignData {
-            signature: _,
+            signature: (sig_r, sig_s),
             pk,
             msg_hash,
         } = sign_data;
@@ -538,6 +623,20 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             &config.msg_hash,
             offset,
         )?;
+        copy_integer_bytes_le(
+            region,
+            "sig_r",
+            &assigned_ecdsa.sig_r_le,
+            &config.sig_r,
+            offset,
+        )?;
+        copy_integer_bytes_le(
+            region,
+            "sig_s",
+            &assigned_ecdsa.sig_s_le,
+            &config.sig_s,
+            offset,
+        )?;
 
         config.q_enable.enable(region, offset)?;
 
@@ -611,6 +710,17 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             region.assign_advice(|| "address", config.address, offset, || Ok(address))?;
         address_is_zero_chip.assign(region, offset, Some(address))?;
 
+        // Assign address_rlc, which is zero for padding as pk_hash is zero
+        let address_rlc = pk_hash[32 - 20..].iter().fold(F::zero(), |acc, byte| {
+            acc * randomness + F::from(*byte as u64)
+        });
+        let address_rlc_assigned = region.assign_advice(
+            || "address_rlc",
+            config.address_rlc,
+            offset,
+            || Ok(address_rlc),
+        )?;
+
         // Assign msg_hash
         for (i, byte) in msg_hash_le.iter().enumerate() {
             region.assign_advice(
@@ -621,10 +731,38 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             )?;
         }
 
+        // Assign sig_rlc
+        let mut sig_le = [0u8; 64];
+        sig_s
+            .write(&mut Cursor::new(&mut sig_le[..32]))
+            .expect("cannot write bytes to array");
+        sig_r
+            .write(&mut Cursor::new(&mut sig_le[32..]))
+            .expect("cannot write bytes to array");
+        let sig_rlc = if !padding {
+            rlc::value(&sig_le, randomness)
+        } else {
+            F::zero()
+        };
+        let sig_rlc_assigned =
+            region.assign_advice(|| "sig_rlc", config.sig_rlc, offset, || Ok(sig_rlc))?;
+
+        // Copy the recovery id from the ECDSA chip
+        let recovery_id = region.assign_advice(
+            || "recovery_id",
+            config.recovery_id,
+            offset,
+            || assigned_ecdsa.recovery_id.value().ok_or(Error::Synthesis),
+        )?;
+        region.constrain_equal(recovery_id.cell(), assigned_ecdsa.recovery_id.cell())?;
+
         Ok((
             AssignedSignatureVerify {
                 address: address_assigned,
                 msg_hash_rlc: msg_hash_rlc_assigned,
+                address_rlc: address_rlc_assigned,
+                sig_rlc: sig_rlc_assigned,
+                recovery_id,
             },
             KeccakAux {
                 input: pk_be,
@@ -662,7 +800,6 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             |mut region| self.assign_aux(&mut region, &mut ecc_chip),
         )?;
 
-        let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
         let address_is_zero_chip = IsZeroChip::construct(config.address_is_zero.clone());
 
         let mut assigned_ecdsas = Vec::new();
@@ -673,7 +810,6 @@ impl<F: FieldExt, const MAX_VERIF: usize> SignVerifyChip<F, MAX_VERIF> {
             range_chip: &range_chip,
             ecc_chip: &ecc_chip,
             scalar_chip: &scalar_chip,
-            ecdsa_chip: &ecdsa_chip,
         };
 
         layouter.assign_region(